@@ -0,0 +1,49 @@
+-- Users and their aggregated cross-chain metrics
+CREATE TABLE IF NOT EXISTS users (
+    id TEXT PRIMARY KEY NOT NULL,
+    aggregated_metrics TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+-- Wallets a user has proven ownership of
+CREATE TABLE IF NOT EXISTS verified_addresses (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    address TEXT NOT NULL,
+    chain TEXT NOT NULL,
+    verification_kind TEXT NOT NULL,
+    verification_method TEXT NOT NULL,
+    verified_at TEXT NOT NULL,
+    nonce TEXT NOT NULL,
+    UNIQUE (user_id, chain, address)
+);
+
+CREATE INDEX IF NOT EXISTS idx_verified_addresses_address
+    ON verified_addresses (chain, address);
+
+-- Per-chain metric snapshots, one row per fetch
+CREATE TABLE IF NOT EXISTS chain_metrics (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    chain TEXT NOT NULL,
+    address TEXT NOT NULL,
+    metrics TEXT NOT NULL,
+    last_updated TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_chain_metrics_user
+    ON chain_metrics (user_id, chain, address, last_updated);
+
+-- Score history, one row per calculation
+CREATE TABLE IF NOT EXISTS scores (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    total_score REAL NOT NULL,
+    tier TEXT NOT NULL,
+    score TEXT NOT NULL,
+    calculated_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_scores_user_time
+    ON scores (user_id, calculated_at);
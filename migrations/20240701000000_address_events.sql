@@ -0,0 +1,15 @@
+-- Audit trail of address ownership across users; replayed to rebuild the registry.
+-- No foreign key, so the trail outlives deleted users.
+CREATE TABLE IF NOT EXISTS address_events (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    namespace TEXT NOT NULL,
+    address TEXT NOT NULL,
+    chain TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    event TEXT NOT NULL,
+    detail TEXT,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_address_events_address
+    ON address_events (namespace, address);
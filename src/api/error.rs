@@ -0,0 +1,58 @@
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::json;
+use thiserror::Error;
+
+use crate::models::DegenScoreError;
+
+/// Errors returned by the HTTP API, mapped onto status codes
+#[derive(Error, Debug)]
+pub enum ApiError {
+    #[error("{0}")]
+    NotFound(String),
+
+    #[error("{0}")]
+    BadRequest(String),
+
+    #[error(transparent)]
+    Engine(#[from] DegenScoreError),
+}
+
+impl ApiError {
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::Engine(e) => match e {
+                DegenScoreError::InvalidAddress(_) |
+                DegenScoreError::ChainNotSupported(_) => StatusCode::BAD_REQUEST,
+                DegenScoreError::SignatureVerificationFailed(_) => StatusCode::UNAUTHORIZED,
+                DegenScoreError::RateLimitExceeded { .. } => StatusCode::TOO_MANY_REQUESTS,
+                DegenScoreError::RpcError { .. } |
+                DegenScoreError::PriceUnavailable { .. } |
+                DegenScoreError::CircuitBreakerOpen(_) => StatusCode::BAD_GATEWAY,
+                DegenScoreError::DataFetchTimeout { .. } => StatusCode::GATEWAY_TIMEOUT,
+                DegenScoreError::ScoreCalculationError(_) => StatusCode::UNPROCESSABLE_ENTITY,
+                DegenScoreError::AddressConflict { .. } => StatusCode::CONFLICT,
+                // Not linked yet, but the claim stands until an operator reviews it
+                DegenScoreError::AddressEscalated { .. } => StatusCode::ACCEPTED,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            },
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = self.status_code();
+        let body = Json(json!({
+            "error": self.to_string(),
+            "status": status.as_u16(),
+        }));
+
+        (status, body).into_response()
+    }
+}
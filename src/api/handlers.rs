@@ -0,0 +1,255 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::{
+    api::{ApiError, AppState},
+    models::{
+        Chain, DegenScore, DegenScoreError, ScoreBreakdown, ScoreCalculationRequest, ScoreTier,
+        UserProfile, VerifiedAddress,
+    },
+    scoring::PercentileEngine,
+    verification::{AddressEvent, AddressKey, EvmSignatureVerifier, SolanaSignatureVerifier, WalletVerifier},
+};
+
+/// How long an issued verification challenge stays valid
+const CHALLENGE_TTL_MINUTES: i64 = 10;
+
+const DEFAULT_LEADERBOARD_LIMIT: usize = 100;
+const MAX_LEADERBOARD_LIMIT: usize = 1000;
+
+#[derive(Debug, Deserialize)]
+pub struct ChallengeRequest {
+    pub chain: Chain,
+    pub address: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChallengeResponse {
+    pub chain: Chain,
+    pub address: String,
+    pub nonce: String,
+    pub message: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct VerifyRequest {
+    pub user_id: String,
+    pub chain: Chain,
+    pub address: String,
+    pub signature: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LeaderboardQuery {
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LeaderboardEntry {
+    pub rank: usize,
+    pub user_id: String,
+    pub total_score: f64,
+    pub percentile: f64,
+    pub tier: ScoreTier,
+    pub breakdown: ScoreBreakdown,
+}
+
+// EVM casing is ignored; Solana keys are case-sensitive and kept as given
+fn challenge_key(chain: &Chain, address: &str) -> Result<String, ApiError> {
+    Ok(format!("{}:{}", chain.as_str(), AddressKey::normalize(chain, address)?.address))
+}
+
+/// POST /verify/challenge - issue a nonce and the message the wallet must sign
+pub async fn create_challenge(
+    State(state): State<AppState>,
+    Json(req): Json<ChallengeRequest>,
+) -> Result<Json<ChallengeResponse>, ApiError> {
+    WalletVerifier::validate_address_format(&req.chain, &req.address)?;
+
+    let request = WalletVerifier::create_verification_request(req.chain.clone(), req.address.clone());
+    let message = if req.chain.is_evm() {
+        EvmSignatureVerifier::generate_message(&request.address, &request.nonce)
+    } else {
+        SolanaSignatureVerifier::generate_message(&request.address, &request.nonce)
+    };
+
+    let response = ChallengeResponse {
+        chain: request.chain.clone(),
+        address: request.address.clone(),
+        nonce: request.nonce.clone(),
+        message,
+    };
+
+    let key = challenge_key(&req.chain, &req.address)?;
+    let mut challenges = state.challenges.write().await;
+    let now = Utc::now();
+    challenges.retain(|_, pending| now - pending.timestamp <= Duration::minutes(CHALLENGE_TTL_MINUTES));
+    if challenges.len() >= state.calculator.settings().api.max_pending_challenges && !challenges.contains_key(&key) {
+        return Err(DegenScoreError::RateLimitExceeded { service: "verification challenges".to_string() }.into());
+    }
+    challenges.insert(key, request);
+
+    Ok(Json(response))
+}
+
+/// POST /verify - check a signed challenge and link the address to the user
+pub async fn verify_address(
+    State(state): State<AppState>,
+    Json(req): Json<VerifyRequest>,
+) -> Result<Json<VerifiedAddress>, ApiError> {
+    let key = challenge_key(&req.chain, &req.address)?;
+
+    // A challenge can only be answered once, successful or not
+    let request = state.challenges.write().await
+        .remove(&key)
+        .ok_or_else(|| ApiError::BadRequest(
+            format!("No pending challenge for {} on {}", req.address, req.chain.as_str())
+        ))?;
+
+    if Utc::now() - request.timestamp > Duration::minutes(CHALLENGE_TTL_MINUTES) {
+        return Err(ApiError::BadRequest("Verification challenge has expired".to_string()));
+    }
+
+    let verified = state.verifier.verify_with_signature(request, req.signature).await?;
+
+    // Lock order: registry, then users (see `AppState`)
+    let mut registry = state.registry.write().await;
+    let mut users = state.users.write().await;
+    let user = users
+        .entry(req.user_id.clone())
+        .or_insert_with(|| UserProfile::new(req.user_id.clone()));
+    state.verifier.add_verified_address_to_profile(&mut registry, user, verified.clone()).await?;
+    
+    if let Some(store) = &state.store {
+        store.upsert_user(user).await?;
+    }
+
+    info!("User {} verified {} address {}", req.user_id, verified.chain.as_str(), verified.address);
+
+    Ok(Json(verified))
+}
+
+/// POST /score/calculate - score a user's verified addresses
+pub async fn calculate_score(
+    State(state): State<AppState>,
+    Json(req): Json<ScoreCalculationRequest>,
+) -> Result<Json<DegenScore>, ApiError> {
+    let mut profile = state.users.read().await
+        .get(&req.user_id)
+        .cloned()
+        .ok_or_else(|| ApiError::NotFound(format!("Unknown user: {}", req.user_id)))?;
+
+    if !req.force_refresh {
+        if let Some(score) = &profile.degen_score {
+            return Ok(Json(score.clone()));
+        }
+    }
+
+    // Only verified addresses may contribute to a score
+    if !req.addresses.is_empty() {
+        for (address, chain) in &req.addresses {
+            if !profile.verified_addresses.iter().any(|a| &a.chain == chain && a.address.eq_ignore_ascii_case(address)) {
+                return Err(ApiError::BadRequest(
+                    format!("Address {} is not verified on {} for this user", address, chain.as_str())
+                ));
+            }
+        }
+
+        profile.verified_addresses.retain(|a| {
+            req.addresses.iter().any(|(address, chain)| &a.chain == chain && a.address.eq_ignore_ascii_case(address))
+        });
+    }
+
+    if profile.verified_addresses.is_empty() {
+        return Err(ApiError::BadRequest(format!("User {} has no verified addresses", req.user_id)));
+    }
+
+    let score = match &state.store {
+        Some(store) => state.calculator.calculate_and_store(&profile, store).await?,
+        None => state.calculator.calculate_user_score(&profile).await?,
+    };
+
+    if let Some(user) = state.users.write().await.get_mut(&req.user_id) {
+        user.degen_score = Some(score.clone());
+        user.updated_at = Utc::now();
+    }
+
+    Ok(Json(score))
+}
+
+/// GET /score/{user_id} - latest calculated score for a user
+pub async fn get_score(
+    State(state): State<AppState>,
+    Path(user_id): Path<String>,
+) -> Result<Json<DegenScore>, ApiError> {
+    let users = state.users.read().await;
+    let user = users
+        .get(&user_id)
+        .ok_or_else(|| ApiError::NotFound(format!("Unknown user: {}", user_id)))?;
+
+    user.degen_score
+        .clone()
+        .map(Json)
+        .ok_or_else(|| ApiError::NotFound(format!("No score calculated for user {}", user_id)))
+}
+
+/// GET /leaderboard - top scored users, highest first
+pub async fn leaderboard(
+    State(state): State<AppState>,
+    Query(query): Query<LeaderboardQuery>,
+) -> Result<Json<Vec<LeaderboardEntry>>, ApiError> {
+    let limit = query.limit
+        .unwrap_or(DEFAULT_LEADERBOARD_LIMIT)
+        .min(MAX_LEADERBOARD_LIMIT);
+
+    let users = state.users.read().await;
+    let mut scored: Vec<(&String, &DegenScore)> = users
+        .values()
+        .filter_map(|u| u.degen_score.as_ref().map(|s| (&u.id, s)))
+        .collect();
+
+    scored.sort_by(|a, b| {
+        b.1.total_score
+            .partial_cmp(&a.1.total_score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.0.cmp(b.0))
+    });
+
+    // Rank against the users currently on the board so percentiles never go stale
+    let cohort = PercentileEngine::from_scores(scored.iter().map(|(id, score)| (id.as_str(), *score)));
+    
+    let entries = scored
+        .into_iter()
+        .take(limit)
+        .enumerate()
+        .map(|(i, (user_id, score))| LeaderboardEntry {
+            rank: i + 1,
+            user_id: user_id.clone(),
+            total_score: score.total_score,
+            percentile: cohort.percentile(score.total_score),
+            tier: score.tier.clone(),
+            breakdown: score.breakdown.clone(),
+        })
+        .collect();
+
+    Ok(Json(entries))
+}
+
+/// GET /addresses/{chain}/{address}/events - ownership audit trail for one address
+pub async fn address_events(
+    State(state): State<AppState>,
+    Path((chain, address)): Path<(Chain, String)>,
+) -> Result<Json<Vec<AddressEvent>>, ApiError> {
+    let registry = state.registry.read().await;
+    let events = registry.history(&chain, &address)?
+        .into_iter()
+        .cloned()
+        .collect();
+
+    Ok(Json(events))
+}
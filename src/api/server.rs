@@ -0,0 +1,393 @@
+use axum::{
+    extract::DefaultBodyLimit,
+    http::{HeaderValue, Method, header},
+    routing::{get, post},
+    Router,
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tower_http::cors::{Any, CorsLayer};
+use tracing::{info, warn};
+
+use crate::{
+    api::handlers,
+    config::ApiSettings,
+    models::{AddressVerificationRequest, DegenScoreError, Result, UserProfile},
+    scoring::ScoreCalculator,
+    storage::ScoreStore,
+    verification::{AddressRegistry, WalletVerifier},
+};
+
+/// Shared state handed to every request handler
+#[derive(Clone)]
+pub struct AppState {
+    pub calculator: Arc<ScoreCalculator>,
+    pub verifier: Arc<WalletVerifier>,
+    pub users: Arc<RwLock<HashMap<String, UserProfile>>>,
+    // chain:address -> outstanding verification challenge
+    pub challenges: Arc<RwLock<HashMap<String, AddressVerificationRequest>>>,
+    // Global address ownership; writes its audit trail through to the store.
+    // A handler holding both locks takes `registry` before `users`.
+    pub registry: Arc<RwLock<AddressRegistry>>,
+    // Write-through persistence; users live only in memory when unset
+    pub store: Option<ScoreStore>,
+}
+
+impl AppState {
+    pub fn new(calculator: ScoreCalculator) -> Self {
+        let registry = AddressRegistry::new(calculator.settings().registry.on_conflict);
+        Self {
+            calculator: Arc::new(calculator),
+            verifier: Arc::new(WalletVerifier::new()),
+            users: Arc::new(RwLock::new(HashMap::new())),
+            challenges: Arc::new(RwLock::new(HashMap::new())),
+            registry: Arc::new(RwLock::new(registry)),
+            store: None,
+        }
+    }
+    
+    /// State backed by a database, preloaded with every stored user
+    pub async fn with_store(calculator: ScoreCalculator, store: ScoreStore) -> Result<Self> {
+        let mut users = store.list_users().await?;
+        let registry = AddressRegistry::load(calculator.settings().registry.on_conflict, store.clone(), &mut users).await?;
+        let users = users
+            .into_iter()
+            .map(|user| (user.id.clone(), user))
+            .collect::<HashMap<_, _>>();
+        
+        info!("Loaded {} users from the database", users.len());
+        calculator.load_percentiles(&store).await?;
+        
+        Ok(Self {
+            users: Arc::new(RwLock::new(users)),
+            registry: Arc::new(RwLock::new(registry)),
+            store: Some(store),
+            ..Self::new(calculator)
+        })
+    }
+}
+
+/// HTTP server exposing the scoring engine
+pub struct ApiServer {
+    settings: ApiSettings,
+    state: AppState,
+}
+
+impl ApiServer {
+    pub fn new(settings: ApiSettings, state: AppState) -> Self {
+        Self { settings, state }
+    }
+
+    /// Build the router with CORS and request-size limits applied
+    pub fn router(&self) -> Router {
+        build_router(self.state.clone(), &self.settings)
+    }
+
+    /// Bind to the configured host/port and serve until the process exits
+    pub async fn run(self) -> Result<()> {
+        let bind_addr = format!("{}:{}", self.settings.host, self.settings.port);
+        let listener = tokio::net::TcpListener::bind(&bind_addr).await
+            .map_err(|e| DegenScoreError::ConfigError(
+                format!("Failed to bind {}: {}", bind_addr, e)
+            ))?;
+
+        info!("API server listening on {}", bind_addr);
+
+        axum::serve(listener, self.router()).await
+            .map_err(|e| DegenScoreError::ConfigError(format!("API server error: {}", e)))
+    }
+}
+
+pub fn build_router(state: AppState, settings: &ApiSettings) -> Router {
+    let body_limit = settings.max_request_size_mb.saturating_mul(1024 * 1024);
+
+    Router::new()
+        .route("/verify/challenge", post(handlers::create_challenge))
+        .route("/verify", post(handlers::verify_address))
+        .route("/score/calculate", post(handlers::calculate_score))
+        .route("/score/:user_id", get(handlers::get_score))
+        .route("/leaderboard", get(handlers::leaderboard))
+        .route("/addresses/:chain/:address/events", get(handlers::address_events))
+        .layer(DefaultBodyLimit::max(body_limit))
+        .layer(cors_layer(&settings.cors_origins))
+        .with_state(state)
+}
+
+fn cors_layer(origins: &[String]) -> CorsLayer {
+    let layer = CorsLayer::new()
+        .allow_methods([Method::GET, Method::POST, Method::OPTIONS])
+        .allow_headers([header::CONTENT_TYPE]);
+
+    if origins.iter().any(|o| o == "*") {
+        return layer.allow_origin(Any);
+    }
+
+    let allowed: Vec<HeaderValue> = origins
+        .iter()
+        .filter_map(|origin| match HeaderValue::from_str(origin) {
+            Ok(value) => Some(value),
+            Err(_) => {
+                warn!("Ignoring invalid CORS origin: {}", origin);
+                None
+            }
+        })
+        .collect();
+
+    layer.allow_origin(allowed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        api::handlers::{ChallengeResponse, LeaderboardEntry},
+        fixtures::mock_calculator,
+        config::{ConflictPolicy, Settings},
+        models::{Chain, DegenScore},
+        verification::{AddressEvent, AddressEventKind},
+    };
+    use axum::{
+        body::{to_bytes, Body},
+        http::{Request, StatusCode},
+    };
+    use ring::{rand::SystemRandom, signature::{Ed25519KeyPair, KeyPair}};
+    use serde_json::json;
+    use tower::ServiceExt;
+
+    fn test_router() -> Router {
+        router_with(Settings::default())
+    }
+
+    fn router_with(settings: Settings) -> Router {
+        build_router(AppState::new(mock_calculator(&settings)), &settings.api)
+    }
+
+    async fn send(router: &Router, method: &str, uri: &str, body: Option<serde_json::Value>) -> (StatusCode, Vec<u8>) {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("content-type", "application/json")
+            .body(match body {
+                Some(b) => Body::from(b.to_string()),
+                None => Body::empty(),
+            })
+            .unwrap();
+
+        let response = router.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, bytes.to_vec())
+    }
+
+    #[tokio::test]
+    async fn test_verify_score_and_leaderboard_flow() {
+        let router = test_router();
+
+        // Fresh Solana wallet to sign the challenge with
+        let rng = SystemRandom::new();
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&rng).unwrap();
+        let keypair = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+        let address = bs58::encode(keypair.public_key().as_ref()).into_string();
+
+        let (status, body) = send(&router, "POST", "/verify/challenge", Some(json!({
+            "chain": "Solana",
+            "address": address,
+        }))).await;
+        assert_eq!(status, StatusCode::OK);
+        let challenge: ChallengeResponse = serde_json::from_slice(&body).unwrap();
+
+        let signature = bs58::encode(keypair.sign(challenge.message.as_bytes()).as_ref()).into_string();
+        let (status, _) = send(&router, "POST", "/verify", Some(json!({
+            "user_id": "alice",
+            "chain": "Solana",
+            "address": address,
+            "signature": signature,
+        }))).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (status, body) = send(&router, "POST", "/score/calculate", Some(json!({
+            "user_id": "alice",
+            "addresses": [],
+            "force_refresh": true,
+        }))).await;
+        assert_eq!(status, StatusCode::OK);
+        let calculated: DegenScore = serde_json::from_slice(&body).unwrap();
+
+        let (status, body) = send(&router, "GET", "/score/alice", None).await;
+        assert_eq!(status, StatusCode::OK);
+        let fetched: DegenScore = serde_json::from_slice(&body).unwrap();
+        assert_eq!(fetched.total_score, calculated.total_score);
+
+        let (status, body) = send(&router, "GET", "/leaderboard?limit=10", None).await;
+        assert_eq!(status, StatusCode::OK);
+        let board: Vec<LeaderboardEntry> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(board.len(), 1);
+        assert_eq!(board[0].rank, 1);
+        assert_eq!(board[0].user_id, "alice");
+    }
+
+    #[tokio::test]
+    async fn test_verify_rejects_bad_signature_and_replay() {
+        let router = test_router();
+        let address = "0x742d35Cc6634C0532925a3b844Bc9e7595f6e842";
+
+        let (status, _) = send(&router, "POST", "/verify/challenge", Some(json!({
+            "chain": "Ethereum",
+            "address": address,
+        }))).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let bad_signature = format!("0x{}", "11".repeat(65));
+        let (status, _) = send(&router, "POST", "/verify", Some(json!({
+            "user_id": "mallory",
+            "chain": "Ethereum",
+            "address": address,
+            "signature": bad_signature,
+        }))).await;
+        assert_ne!(status, StatusCode::OK);
+
+        // The challenge is consumed by the first attempt
+        let (status, _) = send(&router, "POST", "/verify", Some(json!({
+            "user_id": "mallory",
+            "chain": "Ethereum",
+            "address": address,
+            "signature": bad_signature,
+        }))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_pending_challenges_are_capped_and_expire() {
+        let mut settings = Settings::default();
+        settings.api.max_pending_challenges = 2;
+        let state = AppState::new(mock_calculator(&settings));
+        let router = build_router(state.clone(), &settings.api);
+        let challenge = |address: &str| send(&router, "POST", "/verify/challenge", Some(json!({
+            "chain": "Solana",
+            "address": address,
+        })));
+
+        // Two Solana keys that differ only in case are different wallets
+        let first = "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM";
+        let second = "9wzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM";
+        assert_eq!(challenge(first).await.0, StatusCode::OK);
+        assert_eq!(challenge(second).await.0, StatusCode::OK);
+        assert!(state.challenges.read().await.contains_key(&format!("solana:{}", second)));
+
+        let third = "7UVimffxr9ow1uXYxsr4LHAcV58mLzhmwaeKvJ1pjLiE";
+        assert_eq!(challenge(third).await.0, StatusCode::TOO_MANY_REQUESTS);
+
+        // An expired challenge makes room
+        state.challenges.write().await
+            .get_mut(&format!("solana:{}", first)).unwrap()
+            .timestamp -= chrono::Duration::hours(1);
+        assert_eq!(challenge(third).await.0, StatusCode::OK);
+        assert!(!state.challenges.read().await.contains_key(&format!("solana:{}", first)));
+    }
+
+    #[tokio::test]
+    async fn test_address_owned_by_another_user_conflicts() {
+        for (policy, expected, kind) in [
+            (ConflictPolicy::Reject, StatusCode::CONFLICT, AddressEventKind::Rejected),
+            (ConflictPolicy::Escalate, StatusCode::ACCEPTED, AddressEventKind::Escalated),
+        ] {
+            let mut settings = Settings::default();
+            settings.registry.on_conflict = policy;
+            check_second_claim(router_with(settings), expected, kind).await;
+        }
+    }
+
+    async fn check_second_claim(router: Router, expected: StatusCode, kind: AddressEventKind) {
+
+        let rng = SystemRandom::new();
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&rng).unwrap();
+        let keypair = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+        let address = bs58::encode(keypair.public_key().as_ref()).into_string();
+
+        let mut statuses = Vec::new();
+        for user_id in ["alice", "bob"] {
+            let (_, body) = send(&router, "POST", "/verify/challenge", Some(json!({
+                "chain": "Solana",
+                "address": address,
+            }))).await;
+            let challenge: ChallengeResponse = serde_json::from_slice(&body).unwrap();
+            let signature = bs58::encode(keypair.sign(challenge.message.as_bytes()).as_ref()).into_string();
+
+            let (status, _) = send(&router, "POST", "/verify", Some(json!({
+                "user_id": user_id,
+                "chain": "Solana",
+                "address": address,
+                "signature": signature,
+            }))).await;
+            statuses.push(status);
+        }
+        assert_eq!(statuses, vec![StatusCode::OK, expected]);
+
+        let (status, body) = send(&router, "GET", &format!("/addresses/Solana/{}/events", address), None).await;
+        assert_eq!(status, StatusCode::OK);
+        let events: Vec<AddressEvent> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1].user_id, "bob");
+        assert_eq!(events[1].kind, kind);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_user_returns_not_found() {
+        let router = test_router();
+
+        let (status, _) = send(&router, "GET", "/score/nobody", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let (status, _) = send(&router, "POST", "/score/calculate", Some(json!({
+            "user_id": "nobody",
+            "addresses": [],
+            "force_refresh": false,
+        }))).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_store_backed_state_persists_scores() {
+        let store = ScoreStore::in_memory().await.unwrap();
+        let mut user = UserProfile::new("demo-user".to_string());
+        user.add_verified_address(crate::models::VerifiedAddress {
+            address: "7VXNe1r6nTqVw6TKyBzt1TNSSQqPqNcEYizv8TduLWpU".to_string(),
+            chain: Chain::Solana,
+            verification_method: crate::models::VerificationMethod::Signature {
+                message: "msg".to_string(),
+                signature: "sig".to_string(),
+            },
+            verified_at: chrono::Utc::now(),
+            nonce: "1".to_string(),
+        });
+        store.upsert_user(&user).await.unwrap();
+
+        let settings = Settings::default();
+        let state = AppState::with_store(mock_calculator(&settings), store.clone()).await.unwrap();
+        let router = build_router(state, &settings.api);
+
+        let (status, _) = send(&router, "POST", "/score/calculate", Some(json!({
+            "user_id": "demo-user",
+            "addresses": [],
+            "force_refresh": true,
+        }))).await;
+        assert_eq!(status, StatusCode::OK);
+
+        assert_eq!(store.get_score_history("demo-user", 10).await.unwrap().len(), 1);
+        assert_eq!(store.get_latest_chain_metrics("demo-user").await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_request_size_limit() {
+        let mut settings = Settings::default();
+        settings.api.max_request_size_mb = 0;
+        let router = router_with(settings);
+
+        let (status, _) = send(&router, "POST", "/verify/challenge", Some(json!({
+            "chain": Chain::Ethereum,
+            "address": "0x742d35Cc6634C0532925a3b844Bc9e7595f6e842",
+        }))).await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
@@ -0,0 +1,235 @@
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use tracing::warn;
+
+/// Progress of one keyed scan: where it resumes and what it has found so far
+#[derive(Debug, Clone, PartialEq)]
+pub struct Checkpoint<C, T> {
+    pub cursor: C,
+    pub items: Vec<T>,
+}
+
+// Contents of `<key>.json`; `items` counts the lines of `<key>.jsonl` covered by `cursor`
+#[derive(Serialize, Deserialize)]
+struct CursorFile<C> {
+    cursor: C,
+    items: usize,
+}
+
+/// Checkpoints of many keyed scans, in memory and optionally in a directory.
+///
+/// Each key gets a small cursor file, rewritten as its scan advances, and an append-only
+/// JSON-lines file of the items found. Saving progress writes only the new items and
+/// never touches other keys. Items appended after the last cursor write are cut off on
+/// load, so an interrupted save cannot duplicate them or leak into later appends.
+pub struct CheckpointStore<C, T> {
+    dir: Option<PathBuf>,
+    entries: RwLock<HashMap<String, Checkpoint<C, T>>>,
+    // Keys whose last save failed; their items file is rewritten whole on the next advance
+    unsynced: RwLock<HashSet<String>>,
+}
+
+impl<C, T> CheckpointStore<C, T>
+where
+    C: Clone + Serialize + DeserializeOwned,
+    T: Clone + Serialize + DeserializeOwned,
+{
+    pub fn new(dir: Option<PathBuf>) -> Self {
+        if let Some(dir) = &dir {
+            if let Err(e) = fs::create_dir_all(dir) {
+                warn!("Failed to create checkpoint directory {}: {}", dir.display(), e);
+            }
+        }
+
+        Self {
+            dir,
+            entries: RwLock::new(HashMap::new()),
+            unsynced: RwLock::new(HashSet::new()),
+        }
+    }
+
+    /// Checkpoint of `key`, read from disk the first time it is asked for
+    pub fn get(&self, key: &str) -> Option<Checkpoint<C, T>> {
+        if let Some(checkpoint) = self.entries.read().unwrap_or_else(|e| e.into_inner()).get(key) {
+            return Some(checkpoint.clone());
+        }
+
+        let checkpoint = self.load(key)?;
+        self.entries.write().unwrap_or_else(|e| e.into_inner())
+            .insert(key.to_string(), checkpoint.clone());
+        Some(checkpoint)
+    }
+
+    /// Start `key` over at `cursor`, dropping everything found before
+    pub fn reset(&self, key: &str, cursor: C) {
+        self.entries.write().unwrap_or_else(|e| e.into_inner())
+            .insert(key.to_string(), Checkpoint { cursor: cursor.clone(), items: Vec::new() });
+
+        if let Some((cursor_path, items_path)) = self.paths(key) {
+            let written = File::create(&items_path)
+                .map_err(|e| e.to_string())
+                .and_then(|_| write_cursor(&cursor_path, &CursorFile { cursor, items: 0 }));
+            if let Err(e) = written {
+                warn!("Failed to reset checkpoint {}: {}", cursor_path.display(), e);
+            }
+        }
+    }
+
+    /// Record `items` found on the way to `cursor`
+    pub fn advance(&self, key: &str, cursor: C, items: &[T]) {
+        if self.get(key).is_none() {
+            self.reset(key, cursor.clone());
+        }
+
+        let unsynced = self.unsynced.read().unwrap_or_else(|e| e.into_inner()).contains(key);
+        let (count, all_items) = {
+            let mut entries = self.entries.write().unwrap_or_else(|e| e.into_inner());
+            let Some(checkpoint) = entries.get_mut(key) else { return };
+            checkpoint.cursor = cursor.clone();
+            checkpoint.items.extend_from_slice(items);
+            (checkpoint.items.len(), unsynced.then(|| checkpoint.items.clone()))
+        };
+
+        if let Some((cursor_path, items_path)) = self.paths(key) {
+            let written = match &all_items {
+                Some(all_items) => File::create(&items_path)
+                    .map_err(|e| e.to_string())
+                    .and_then(|_| append_items(&items_path, all_items)),
+                None => append_items(&items_path, items),
+            };
+            let written = written.and_then(|_| write_cursor(&cursor_path, &CursorFile { cursor, items: count }));
+
+            let mut unsynced = self.unsynced.write().unwrap_or_else(|e| e.into_inner());
+            match written {
+                Ok(()) => {
+                    unsynced.remove(key);
+                }
+                Err(e) => {
+                    warn!("Failed to persist checkpoint {}: {}", cursor_path.display(), e);
+                    unsynced.insert(key.to_string());
+                }
+            }
+        }
+    }
+
+    fn load(&self, key: &str) -> Option<Checkpoint<C, T>> {
+        let (cursor_path, items_path) = self.paths(key)?;
+        let contents = fs::read_to_string(&cursor_path).ok()?;
+
+        let loaded = serde_json::from_str::<CursorFile<C>>(&contents)
+            .map_err(|e| e.to_string())
+            .and_then(|file| {
+                let mut reader = BufReader::new(File::open(&items_path).map_err(|e| e.to_string())?);
+                let mut items = Vec::with_capacity(file.items);
+                let mut covered = 0;
+                let mut line = String::new();
+                while items.len() < file.items {
+                    line.clear();
+                    let read = reader.read_line(&mut line).map_err(|e| e.to_string())?;
+                    if read == 0 {
+                        return Err(format!("expected {} items, found {}", file.items, items.len()));
+                    }
+                    items.push(serde_json::from_str(line.trim_end()).map_err(|e| e.to_string())?);
+                    covered += read as u64;
+                }
+
+                // Cut off appends the cursor never covered, so the next advance writes after them
+                let items_file = OpenOptions::new().write(true).open(&items_path).map_err(|e| e.to_string())?;
+                if items_file.metadata().map_err(|e| e.to_string())?.len() > covered {
+                    items_file.set_len(covered).map_err(|e| e.to_string())?;
+                }
+                Ok(Checkpoint { cursor: file.cursor, items })
+            });
+
+        match loaded {
+            Ok(checkpoint) => Some(checkpoint),
+            Err(e) => {
+                warn!("Ignoring unreadable checkpoint {}: {}", cursor_path.display(), e);
+                None
+            }
+        }
+    }
+
+    fn paths(&self, key: &str) -> Option<(PathBuf, PathBuf)> {
+        let dir = self.dir.as_ref()?;
+        let name: String = key.chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+            .collect();
+        Some((dir.join(format!("{}.json", name)), dir.join(format!("{}.jsonl", name))))
+    }
+}
+
+fn append_items<T: Serialize>(path: &Path, items: &[T]) -> std::result::Result<(), String> {
+    if items.is_empty() {
+        return Ok(());
+    }
+
+    let mut lines = String::new();
+    for item in items {
+        lines.push_str(&serde_json::to_string(item).map_err(|e| e.to_string())?);
+        lines.push('\n');
+    }
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(lines.as_bytes()))
+        .map_err(|e| e.to_string())
+}
+
+// Written aside and renamed over, so a crash never leaves a half-written cursor
+fn write_cursor<C: Serialize>(path: &Path, file: &CursorFile<C>) -> std::result::Result<(), String> {
+    let json = serde_json::to_string(file).map_err(|e| e.to_string())?;
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, json)
+        .and_then(|_| fs::rename(&tmp, path))
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("checkpoints-{}-{}", name, std::process::id()))
+    }
+
+    #[test]
+    fn test_items_are_appended_and_survive_a_torn_write() {
+        let dir = temp_dir("torn");
+        let store: CheckpointStore<u64, String> = CheckpointStore::new(Some(dir.clone()));
+        store.reset("eth:a", 0);
+        store.advance("eth:a", 10, &["x".to_string(), "y".to_string()]);
+        store.advance("eth:a", 20, &[]);
+        store.advance("eth:a", 30, &["z".to_string()]);
+
+        // Each item is written once, however often the cursor moves
+        let lines = fs::read_to_string(dir.join("eth_a.jsonl")).unwrap();
+        assert_eq!(lines.lines().count(), 3);
+
+        // An append whose cursor never made it to disk is dropped on load
+        append_items(&dir.join("eth_a.jsonl"), &["torn".to_string()]).unwrap();
+        let reloaded: CheckpointStore<u64, String> = CheckpointStore::new(Some(dir.clone()));
+        let checkpoint = reloaded.get("eth:a").unwrap();
+        assert_eq!(checkpoint.cursor, 30);
+        assert_eq!(checkpoint.items, vec!["x", "y", "z"]);
+        assert!(reloaded.get("eth:b").is_none());
+
+        // The torn line is gone, so the next advance and reload pick up only real items
+        reloaded.advance("eth:a", 40, &["w".to_string()]);
+        let checkpoint = CheckpointStore::<u64, String>::new(Some(dir.clone())).get("eth:a").unwrap();
+        assert_eq!(checkpoint.cursor, 40);
+        assert_eq!(checkpoint.items, vec!["x", "y", "z", "w"]);
+
+        // Resetting starts the key over
+        reloaded.reset("eth:a", 5);
+        let checkpoint = CheckpointStore::<u64, String>::new(Some(dir.clone())).get("eth:a").unwrap();
+        assert_eq!((checkpoint.cursor, checkpoint.items.len()), (5, 0));
+
+        fs::remove_dir_all(dir).ok();
+    }
+}
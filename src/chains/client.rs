@@ -1,7 +1,7 @@
 use async_trait::async_trait;
 use crate::models::{
     Chain, DegenMetrics, ChainMetrics, TokenBalance, NFTBalance, 
-    TransactionSummary, Result
+    TransactionSummary, EVMTokenTransfer, Result
 };
 use chrono::{DateTime, Utc};
 
@@ -39,6 +39,11 @@ pub trait ChainClient: Send + Sync {
     
     /// Validate if an address is valid for this chain
     fn validate_address(&self, address: &str) -> Result<()>;
+    
+    /// Token transfers into and out of an address; empty where not supported
+    async fn get_token_transfers(&self, _address: &str) -> Result<Vec<EVMTokenTransfer>> {
+        Ok(Vec::new())
+    }
 }
 
 #[derive(Debug, Clone)]
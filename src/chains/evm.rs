@@ -2,11 +2,10 @@ use async_trait::async_trait;
 use ethers::{
     prelude::*,
     providers::{Provider, Http, Middleware},
-    types::{Address, BlockNumber, Filter, H160, H256, U256, U64},
-    utils::format_units,
+    types::{Address, BlockNumber, Filter, H160, H256, I256, U256, U64},
 };
 use std::sync::Arc;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 use std::str::FromStr;
@@ -19,18 +18,38 @@ use crate::{
         ProtocolInteraction, ProtocolType, EVMTransaction, EVMTokenTransfer,
         chain_data::{ProtocolAddresses, EventSignatures, KnownTokens, TokenInteractionMetrics},
         CasinoInteraction, CasinoPlatform, InteractionType, CasinoMetrics,
-        ScoreCache, CacheKey,
+        ScoreCache, CacheKey, EVMNftTransfer, NftStandard, NFTHolding, TokenHolding,
     },
-    chains::{ChainClient, client::{ProtocolMetrics, ChainClientConfig}, ResilientRpcClient, CircuitBreakerConfig, RetryConfig},
+    chains::{
+        ChainClient, client::{ProtocolMetrics, ChainClientConfig}, ResilientRpcClient, CircuitBreakerConfig, RetryConfig,
+        LogScanner, ExplorerClient, scanner::{filter_key, is_range_error}, nft,
+        nft_valuation::{self, CollectionValuation, NftSale},
+        pricing::{PriceOracle, units_to_decimal},
+        portfolio,
+    },
+    config::{NftSettings, PortfolioSettings, PricingSettings, ScannerSettings},
 };
 
+// Receipts requested per JSON-RPC batch when filling in L1 data fees
+const RECEIPT_BATCH_SIZE: usize = 100;
+
 pub struct EvmClient {
     provider: Arc<Provider<Http>>,
     resilient_client: ResilientRpcClient,
     chain: Chain,
     chain_id: u64,
-    explorer_api: Option<String>,
+    explorer: Option<ExplorerClient>,
     cache: Arc<ScoreCache>,
+    scanner: Arc<LogScanner>,
+    // First block of each hour looked up so far, keyed by hours since the epoch
+    blocks_by_hour: std::sync::Mutex<HashMap<i64, u64>>,
+    // Timestamps of the blocks looked up so far
+    block_timestamps: std::sync::Mutex<HashMap<u64, i64>>,
+    nft: NftSettings,
+    // Fetches NFT metadata
+    http: reqwest::Client,
+    prices: Arc<PriceOracle>,
+    portfolio: PortfolioSettings,
 }
 
 impl EvmClient {
@@ -71,35 +90,60 @@ impl EvmClient {
             }
         }
         
+        let prices = Arc::new(PriceOracle::new(provider.clone(), chain.clone(), &PricingSettings::default()));
+        
         Ok(Self {
             provider,
             resilient_client,
             chain,
             chain_id: chain_id.as_u64(),
-            explorer_api: None,
+            explorer: None,
             cache: Arc::new(ScoreCache::default()),
+            scanner: Arc::new(LogScanner::new(ScannerSettings::default())),
+            blocks_by_hour: std::sync::Mutex::new(HashMap::new()),
+            block_timestamps: std::sync::Mutex::new(HashMap::new()),
+            http: metadata_client(&NftSettings::default()),
+            nft: NftSettings::default(),
+            prices,
+            portfolio: PortfolioSettings::default(),
         })
     }
     
     pub fn with_explorer_api(mut self, api_url: String) -> Self {
-        self.explorer_api = Some(api_url);
+        self.explorer = Some(ExplorerClient::new(self.chain.clone(), api_url));
         self
     }
     
-    /// Clear all cached data for this client
-    pub fn clear_cache(&self) {
-        self.cache.clear_all();
+    /// Prefer `explorer` over log scanning for transaction and transfer history
+    pub fn with_explorer(mut self, explorer: ExplorerClient) -> Self {
+        self.explorer = Some(explorer);
+        self
     }
     
-    /// Get cache statistics
-    pub fn cache_stats(&self) -> crate::models::CacheStats {
-        self.cache.get_stats()
+    pub fn with_nft_settings(mut self, settings: NftSettings) -> Self {
+        self.http = metadata_client(&settings);
+        self.nft = settings;
+        self
     }
     
-    async fn get_transaction_history(&self, address: &Address) -> Result<Vec<EVMTransaction>> {
-        // For now, we'll use event logs to reconstruct activity
-        // In production, we'd use explorer APIs for full history
-        let current_block = self.resilient_client.call(|| {
+    pub fn with_pricing_settings(mut self, settings: &PricingSettings) -> Self {
+        self.prices = Arc::new(PriceOracle::new(self.provider.clone(), self.chain.clone(), settings));
+        self
+    }
+    
+    pub fn with_portfolio_settings(mut self, settings: PortfolioSettings) -> Self {
+        self.portfolio = settings;
+        self
+    }
+    
+    /// Log scanner for this chain's queries, e.g. one persisting its checkpoints
+    pub fn with_log_scanner(mut self, scanner: Arc<LogScanner>) -> Self {
+        self.scanner = scanner;
+        self
+    }
+    
+    async fn current_block(&self) -> Result<u64> {
+        let block = self.resilient_client.call(|| {
             let provider = self.provider.clone();
             let chain_name = self.chain.as_str().to_string();
             async move {
@@ -111,137 +155,668 @@ impl EvmClient {
             }
         }).await?;
         
-        // Get transactions from the last ~2 weeks (to avoid rate limits)
-        let from_block = current_block.saturating_sub(U64::from(8_000));
+        Ok(block.as_u64())
+    }
+    
+    /// Every log matching `filter` from the configured start block to head
+    async fn scan_logs(&self, filter: &Filter, context: &str) -> Result<Vec<Log>> {
+        let head = self.current_block().await?;
+        let start = self.scanner.start_block(&self.chain).min(head);
+        
+        self.scanner.scan(&filter_key(&self.chain, filter), start, head, |from, to| {
+            self.get_logs_between(filter, from, to, context)
+        }).await
+    }
+    
+    /// Logs matching `filter` from the first block at or after `since` to head, without
+    /// checkpointing
+    async fn scan_logs_since(&self, filter: &Filter, since: DateTime<Utc>, context: &str) -> Result<Vec<Log>> {
+        let head = self.current_block().await?;
+        let from = self.first_block_since(since, head).await?;
+        
+        self.scanner.scan_window(from, head, |from, to| {
+            self.get_logs_between(filter, from, to, context)
+        }).await
+    }
+    
+    /// First block stamped at or after `since`, by binary search over block timestamps.
+    /// Lookups are cached to the hour, since block times differ too much across chains
+    /// to convert a duration into blocks.
+    async fn first_block_since(&self, since: DateTime<Utc>, head: u64) -> Result<u64> {
+        let hour = since.timestamp().div_euclid(3600);
+        if let Some(&block) = self.blocks_by_hour.lock().unwrap_or_else(|e| e.into_inner()).get(&hour) {
+            return Ok(block);
+        }
         
-        let filter = Filter::new()
-            .from_block(from_block)
-            .to_block(current_block)
-            .address(vec![*address]);
+        let target = hour * 3600;
+        let (mut low, mut high) = (0, head);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if self.block_timestamp(mid).await? < target {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        
+        self.blocks_by_hour.lock().unwrap_or_else(|e| e.into_inner()).insert(hour, low);
+        Ok(low)
+    }
+    
+    async fn block_timestamp(&self, block_number: u64) -> Result<i64> {
+        if let Some(&timestamp) = self.block_timestamps.lock().unwrap_or_else(|e| e.into_inner()).get(&block_number) {
+            return Ok(timestamp);
+        }
         
-        let logs = self.resilient_client.call(|| {
+        let block = self.resilient_client.call(|| {
             let provider = self.provider.clone();
             let chain_name = self.chain.as_str().to_string();
-            let filter = filter.clone();
             async move {
-                provider.get_logs(&filter).await
+                provider.get_block(block_number).await
                     .map_err(|e| DegenScoreError::RpcError {
                         chain: chain_name,
-                        message: format!("Failed to get logs: {}", e),
+                        message: format!("Failed to get block {}: {}", block_number, e),
                     })
             }
         }).await?;
         
-        // This is a simplified version - in production we'd parse these logs
-        // and potentially use explorer APIs for complete transaction history
-        Ok(vec![])
+        let timestamp = block.map(|block| block.timestamp.as_u64() as i64)
+            .ok_or_else(|| DegenScoreError::RpcError {
+                chain: self.chain.as_str().to_string(),
+                message: format!("Block {} not found", block_number),
+            })?;
+        self.block_timestamps.lock().unwrap_or_else(|e| e.into_inner()).insert(block_number, timestamp);
+        Ok(timestamp)
     }
     
-    async fn get_erc20_transfers(&self, address: &Address) -> Result<Vec<EVMTokenTransfer>> {
+    async fn get_logs_between(&self, filter: &Filter, from: u64, to: u64, context: &str) -> Result<Vec<Log>> {
+        let filter = filter.clone().from_block(from).to_block(to);
+        // A rejected range is answered by shrinking the window, so it is handed back as a
+        // value rather than retried or counted against the circuit breaker
+        self.resilient_client.call(|| {
+            let provider = self.provider.clone();
+            let filter = filter.clone();
+            let chain_name = self.chain.as_str().to_string();
+            let context = context.to_string();
+            async move {
+                match provider.get_logs(&filter).await {
+                    Ok(logs) => Ok(Ok(logs)),
+                    Err(e) => {
+                        let error = DegenScoreError::RpcError {
+                            chain: chain_name,
+                            message: format!("Failed to get {}: {}", context, e),
+                        };
+                        if is_range_error(&error) { Ok(Err(error)) } else { Err(error) }
+                    }
+                }
+            }
+        }).await?
+    }
+    
+    /// Clear all cached data for this client
+    pub fn clear_cache(&self) {
+        self.cache.clear_all();
+    }
+    
+    /// Get cache statistics
+    pub fn cache_stats(&self) -> crate::models::CacheStats {
+        self.cache.get_stats()
+    }
+    
+    /// Normal and internal transactions of `address`, oldest first
+    async fn get_transaction_history(&self, address: &Address) -> Result<Vec<EVMTransaction>> {
+        if let Some(explorer) = &self.explorer {
+            let address = format!("{:?}", address);
+            let mut history = explorer.transactions(&address).await?;
+            self.fill_l1_fees(&address, &mut history).await;
+            history.extend(explorer.internal_transactions(&address).await?);
+            history.sort_by_key(|tx| tx.block_number);
+            return Ok(history);
+        }
+        
+        // Without an explorer, plain transfers are invisible to logs; reconstruct the
+        // transactions in which the address sent tokens
         let transfer_topic = H256::from_slice(
             &ethers::core::utils::keccak256(EventSignatures::ERC20_TRANSFER.as_bytes())
         );
+        let filter = Filter::new()
+            .topic0(transfer_topic)
+            .topic1(*address);
+        let logs = self.scan_logs(&filter, "transfer logs").await?;
         
-        let current_block = self.resilient_client.call(|| {
-            let provider = self.provider.clone();
+        let mut hashes: Vec<(u64, H256)> = logs.iter()
+            .filter_map(|log| Some((log.block_number?.as_u64(), log.transaction_hash?)))
+            .collect();
+        hashes.sort();
+        hashes.dedup();
+        
+        let mut history = Vec::new();
+        for (block_number, hash) in hashes {
+            let rpc_error = |e: ProviderError| DegenScoreError::RpcError {
+                chain: self.chain.as_str().to_string(),
+                message: format!("Failed to get transaction {:?}: {}", hash, e),
+            };
+            let Some(tx) = self.provider.get_transaction(hash).await.map_err(rpc_error)? else { continue };
+            let receipt = self.provider.get_transaction_receipt(hash).await.map_err(rpc_error)?;
+            
+            let timestamp = self.block_timestamp(block_number).await?;
+            
+            history.push(EVMTransaction {
+                hash: format!("{:?}", hash),
+                from: format!("{:?}", tx.from),
+                to: tx.to.map(|to| format!("{:?}", to)),
+                value: tx.value,
+                gas_used: receipt.as_ref().and_then(|r| r.gas_used).unwrap_or_default(),
+                gas_price: receipt.as_ref().and_then(|r| r.effective_gas_price).or(tx.gas_price).unwrap_or_default(),
+                timestamp: DateTime::from_timestamp(timestamp, 0).unwrap_or_default(),
+                block_number,
+                input_data: tx.input.to_vec(),
+                l1_fee: receipt.as_ref().map(|r| l1_data_fee(&self.chain, r)).unwrap_or_default(),
+                status: receipt.and_then(|r| r.status).is_none_or(|status| status.as_u64() == 1),
+            });
+        }
+        
+        Ok(history)
+    }
+    
+    /// Explorers omit the OP Stack L1 data fee; read it from the receipts of sent
+    /// transactions, `RECEIPT_BATCH_SIZE` per JSON-RPC batch. A receipt that cannot be
+    /// read leaves that transaction's L1 fee at zero rather than failing the history.
+    async fn fill_l1_fees(&self, sender: &str, history: &mut [EVMTransaction]) {
+        if !matches!(self.chain, Chain::Optimism | Chain::Blast) {
+            return;
+        }
+        
+        let pending: Vec<usize> = history.iter()
+            .enumerate()
+            .filter(|(_, tx)| tx.from.eq_ignore_ascii_case(sender) && tx.l1_fee.is_zero())
+            .map(|(index, _)| index)
+            .collect();
+        
+        for chunk in pending.chunks(RECEIPT_BATCH_SIZE) {
+            let hashes: Vec<String> = chunk.iter().map(|&index| history[index].hash.clone()).collect();
+            let receipts = match self.get_receipts(&hashes).await {
+                Ok(receipts) => receipts,
+                Err(e) => {
+                    warn!("Skipping L1 fees of {} transactions: {}", hashes.len(), e);
+                    continue;
+                }
+            };
+            for (&index, receipt) in chunk.iter().zip(receipts) {
+                match receipt {
+                    Ok(Some(receipt)) => history[index].l1_fee = l1_data_fee(&self.chain, &receipt),
+                    Ok(None) => debug!("No receipt for {}", history[index].hash),
+                    Err(e) => warn!("Skipping L1 fee of {}: {}", history[index].hash, e),
+                }
+            }
+        }
+    }
+    
+    /// Receipts of `hashes` in one JSON-RPC batch, in order; each entry fails on its own
+    async fn get_receipts(&self, hashes: &[String]) -> Result<Vec<std::result::Result<Option<TransactionReceipt>, String>>> {
+        let requests: Vec<serde_json::Value> = hashes.iter()
+            .enumerate()
+            .map(|(id, hash)| serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "method": "eth_getTransactionReceipt",
+                "params": [hash],
+            }))
+            .collect();
+        
+        let responses: Vec<serde_json::Value> = self.resilient_client.call(|| {
+            let request = self.http.post(self.provider.as_ref().url().clone()).json(&requests);
             let chain_name = self.chain.as_str().to_string();
             async move {
-                provider.get_block_number().await
-                    .map_err(|e| DegenScoreError::RpcError {
-                        chain: chain_name,
-                        message: format!("Failed to get current block: {}", e),
-                    })
+                let rpc_error = |e: reqwest::Error| DegenScoreError::RpcError {
+                    chain: chain_name.clone(),
+                    message: format!("Failed to get receipts: {}", e),
+                };
+                request.send().await
+                    .and_then(|response| response.error_for_status())
+                    .map_err(rpc_error)?
+                    .json().await
+                    .map_err(rpc_error)
             }
         }).await?;
         
-        let from_block = current_block.saturating_sub(U64::from(8_000));
+        // Batch responses may come back in any order
+        let mut receipts: Vec<_> = hashes.iter().map(|_| Err("missing from batch response".to_string())).collect();
+        for response in responses {
+            let Some(slot) = response["id"].as_u64().and_then(|id| receipts.get_mut(id as usize)) else { continue };
+            *slot = match response.get("error") {
+                Some(error) => Err(error.to_string()),
+                None => serde_json::from_value(response["result"].clone()).map_err(|e| e.to_string()),
+            };
+        }
+        Ok(receipts)
+    }
+    
+    /// USD cost of the gas `sender` paid, each day priced at the block of its first
+    /// transaction. Falls back to the current price where the node cannot serve a past
+    /// one; gas on a chain without an ETH price source (Blast unless configured) is 0.
+    async fn gas_spent_usd(&self, sender: &str, history: &[EVMTransaction]) -> Decimal {
+        let mut by_day: BTreeMap<i64, (u64, Decimal)> = BTreeMap::new();
+        let mut seen = HashSet::new();
+        for tx in history.iter().filter(|tx| tx.from.eq_ignore_ascii_case(sender)) {
+            if !seen.insert(tx.hash.as_str()) {
+                continue;
+            }
+            let day = by_day.entry(tx.timestamp.timestamp().div_euclid(86400)).or_insert((tx.block_number, Decimal::ZERO));
+            day.0 = day.0.min(tx.block_number);
+            day.1 += units_to_decimal(tx.fee_paid(), 18);
+        }
+        
+        let mut total = Decimal::ZERO;
+        for (block, gas) in by_day.into_values().filter(|(_, gas)| *gas > Decimal::ZERO) {
+            let price = match self.prices.native_price_usd(Some(block)).await {
+                Ok(price) => price,
+                Err(e) => {
+                    debug!("No ETH price at block {}, using the latest: {}", block, e);
+                    match self.prices.native_price_usd(None).await {
+                        Ok(price) => price,
+                        Err(e) => {
+                            warn!("Failed to price {} ETH of gas: {}", gas, e);
+                            return total;
+                        }
+                    }
+                }
+            };
+            total += gas * price;
+        }
+        total
+    }
+    
+    async fn call_contract(&self, contract: Address, data: Vec<u8>, context: &str) -> Result<Bytes> {
+        let call_req = ethers::types::transaction::eip2718::TypedTransaction::Legacy(
+            ethers::types::TransactionRequest {
+                to: Some(contract.into()),
+                data: Some(data.into()),
+                ..Default::default()
+            }
+        );
+        
+        self.provider.call(&call_req, None).await
+            .map_err(|e| DegenScoreError::RpcError {
+                chain: self.chain.as_str().to_string(),
+                message: format!("Failed to call {}: {}", context, e),
+            })
+    }
+    
+    /// ERC-721 and ERC-1155 transfers into and out of `address`
+    async fn get_nft_transfers(&self, address: &Address) -> Result<Vec<EVMNftTransfer>> {
+        if let Some(explorer) = &self.explorer {
+            let address = format!("{:?}", address);
+            let mut transfers = explorer.erc721_transfers(&address).await?;
+            transfers.extend(explorer.erc1155_transfers(&address).await?);
+            return Ok(transfers);
+        }
+        
+        // ERC-721 Transfer matches the ERC-20 queries, so these scans share their checkpoints
+        let transfer_topic = nft::topic(EventSignatures::ERC721_TRANSFER);
+        let erc1155_topics = vec![
+            nft::topic(EventSignatures::ERC1155_TRANSFER_SINGLE),
+            nft::topic(EventSignatures::ERC1155_TRANSFER_BATCH),
+        ];
+        let filters = [
+            Filter::new().topic0(transfer_topic).topic1(*address),
+            Filter::new().topic0(transfer_topic).topic2(*address),
+            Filter::new().topic0(erc1155_topics.clone()).topic2(*address),
+            Filter::new().topic0(erc1155_topics).topic3(*address),
+        ];
+        
+        let mut logs = Vec::new();
+        for filter in &filters {
+            logs.extend(self.scan_logs(filter, "NFT transfer logs").await?);
+        }
+        
+        Ok(nft::transfers_from_logs(&logs))
+    }
+    
+    /// Current NFT holdings of `address`, rebuilt from its transfers and confirmed on chain.
+    /// With `with_metadata`, token URIs and metadata are fetched for up to
+    /// `max_metadata_fetches` tokens.
+    async fn discover_nfts(&self, address: &Address, with_metadata: bool) -> Result<Vec<NFTBalance>> {
+        let transfers = self.get_nft_transfers(address).await?;
+        let holdings = nft::reconstruct_holdings(address, &transfers);
+        
+        let mut owner_word = [0u8; 32];
+        owner_word[12..].copy_from_slice(address.as_bytes());
+        
+        let mut balances = Vec::new();
+        for holding in holdings {
+            let contract = Address::from_str(&holding.contract)
+                .map_err(|_| DegenScoreError::InvalidAddress(holding.contract.clone()))?;
+            let mut id_word = [0u8; 32];
+            holding.token_id.to_big_endian(&mut id_word);
+            
+            // ownerOf(uint256): 0x6352211e, balanceOf(address,uint256): 0x00fdd58e
+            let check = match holding.standard {
+                NftStandard::Erc1155 => [&[0x00, 0xfd, 0xd5, 0x8e][..], &owner_word, &id_word].concat(),
+                NftStandard::Erc721 => [&[0x63, 0x52, 0x21, 0x1e][..], &id_word].concat(),
+                NftStandard::Metaplex => {
+                    warn!("Skipping Metaplex NFT {} #{} found on {}", holding.contract, holding.token_id, self.chain.as_str());
+                    continue;
+                }
+            };
+            let balance = match self.call_contract(contract, check, "NFT ownership check").await {
+                Ok(result) if result.len() >= 32 => match holding.standard {
+                    NftStandard::Erc1155 => U256::from_big_endian(&result[..32]),
+                    NftStandard::Erc721 if result[..32] == owner_word => U256::one(),
+                    NftStandard::Erc721 => U256::zero(),
+                    NftStandard::Metaplex => unreachable!("Metaplex holdings are skipped above"),
+                },
+                // Contracts that do not answer keep the balance their events imply
+                Ok(_) => holding.balance,
+                Err(e) => {
+                    debug!("Keeping unverified NFT {} #{}: {}", holding.contract, holding.token_id, e);
+                    holding.balance
+                }
+            };
+            if balance.is_zero() {
+                continue;
+            }
+            
+            let mut nft_balance = NFTBalance {
+                contract_address: holding.contract,
+                token_id: holding.token_id.to_string(),
+                token_uri: None,
+                metadata: None,
+                standard: holding.standard,
+                balance,
+            };
+            if with_metadata && balances.len() < self.nft.max_metadata_fetches {
+                // tokenURI(uint256): 0xc87b56dd, uri(uint256): 0x0e89341c
+                let selector = match holding.standard {
+                    NftStandard::Erc1155 => [0x0e, 0x89, 0x34, 0x1c],
+                    NftStandard::Erc721 => [0xc8, 0x7b, 0x56, 0xdd],
+                    NftStandard::Metaplex => unreachable!("Metaplex holdings are skipped above"),
+                };
+                let uri = self.call_contract(contract, [&selector[..], &id_word].concat(), "token URI").await
+                    .ok()
+                    .and_then(|result| nft::decode_string(&result));
+                if let Some(uri) = &uri {
+                    nft_balance.metadata = self.fetch_nft_metadata(uri, holding.token_id).await;
+                }
+                nft_balance.token_uri = uri;
+            }
+            balances.push(nft_balance);
+        }
+        
+        Ok(balances)
+    }
+    
+    async fn fetch_nft_metadata(&self, uri: &str, token_id: U256) -> Option<HashMap<String, serde_json::Value>> {
+        if let Some(metadata) = nft::decode_data_uri(uri) {
+            return Some(metadata);
+        }
+        
+        let url = nft::metadata_url(uri, token_id, &self.nft.ipfs_gateway)?;
+        let response = self.http.get(&url).send().await
+            .and_then(|response| response.error_for_status());
+        match response {
+            Ok(response) => response.json().await
+                .map_err(|e| debug!("Unreadable NFT metadata at {}: {}", url, e))
+                .ok(),
+            Err(e) => {
+                debug!("Failed to fetch NFT metadata from {}: {}", url, e);
+                None
+            }
+        }
+    }
+    
+    /// NFTs held by `address`, each valued from its collection's recent marketplace sales
+    pub async fn get_nft_holdings(&self, address: &str) -> Result<Vec<NFTHolding>> {
+        let addr = Address::from_str(address)
+            .map_err(|_| DegenScoreError::InvalidAddress(address.to_string()))?;
+        
+        let nfts = self.discover_nfts(&addr, false).await?;
+        Ok(self.value_nfts(&nfts).await)
+    }
+    
+    /// Recent marketplace sales of `collection`, found in the transactions that moved its tokens
+    async fn get_collection_sales(&self, collection: Address) -> Result<Vec<NftSale>> {
+        let filter = Filter::new().address(collection).topic0(vec![
+            nft::topic(EventSignatures::ERC721_TRANSFER),
+            nft::topic(EventSignatures::ERC1155_TRANSFER_SINGLE),
+            nft::topic(EventSignatures::ERC1155_TRANSFER_BATCH),
+        ]);
+        let since = Utc::now() - chrono::Duration::days(self.nft.sales_lookback_days as i64);
+        let logs = self.scan_logs_since(&filter, since, "collection transfer logs").await?;
+        
+        // Newest transactions first; mints and plain transfers simply decode to no sales
+        let mut transactions: Vec<(u64, H256)> = logs.iter()
+            .filter_map(|log| Some((log.block_number?.as_u64(), log.transaction_hash?)))
+            .collect();
+        transactions.sort_unstable_by(|a, b| b.cmp(a));
+        transactions.dedup();
+        
+        let mut sales = Vec::new();
+        for (_, hash) in transactions.into_iter().take(self.nft.max_sale_receipts) {
+            let receipt = self.provider.get_transaction_receipt(hash).await
+                .map_err(|e| DegenScoreError::RpcError {
+                    chain: self.chain.as_str().to_string(),
+                    message: format!("Failed to get receipt for {:?}: {}", hash, e),
+                })?;
+            if let Some(receipt) = receipt {
+                sales.extend(nft_valuation::sales_from_logs(&receipt.logs).into_iter()
+                    .filter(|sale| sale.collection == collection));
+            }
+        }
+        
+        Ok(sales)
+    }
+    
+    /// Value `nfts` in USD from their collections' recent sales. Estimates stay empty for
+    /// collections without sales and on chains without an ETH/USD feed.
+    async fn value_nfts(&self, nfts: &[NFTBalance]) -> Vec<NFTHolding> {
+        if nfts.is_empty() {
+            return Vec::new();
+        }
+        let eth_price = match self.prices.native_price_usd(None).await {
+            Ok(price) => Some(price),
+            Err(e) => {
+                warn!("NFTs on {} are left unvalued: {}", self.chain.as_str(), e);
+                None
+            }
+        };
+        
+        let mut collections: HashMap<String, (String, Option<CollectionValuation>)> = HashMap::new();
+        let mut holdings = Vec::new();
+        for nft in nfts {
+            let key = nft.contract_address.to_lowercase();
+            let Ok(contract) = Address::from_str(&key) else { continue };
+            
+            if !collections.contains_key(&key) {
+                let valuation = match self.get_collection_sales(contract).await {
+                    Ok(sales) => nft_valuation::value_collection(&sales, self.nft.floor_sample_sales),
+                    Err(e) => {
+                        warn!("Failed to fetch sales of {}: {}", key, e);
+                        None
+                    }
+                };
+                // name() selector: 0x06fdde03
+                let name = self.call_contract(contract, vec![0x06, 0xfd, 0xde, 0x03], "collection name").await
+                    .ok()
+                    .and_then(|result| nft::decode_string(&result))
+                    .unwrap_or_else(|| key.clone());
+                collections.insert(key.clone(), (name, valuation));
+            }
+            let (name, valuation) = &collections[&key];
+            
+            let token_id = U256::from_dec_str(&nft.token_id).unwrap_or_default();
+            let estimated_value_usd = valuation.as_ref().zip(eth_price).map(|(valuation, price)| {
+                units_to_decimal(valuation.estimate(&token_id).saturating_mul(nft.balance), 18) * price
+            });
+            
+            holdings.push(NFTHolding {
+                collection_address: key.clone(),
+                collection_name: name.clone(),
+                token_id: nft.token_id.clone(),
+                chain: self.chain.as_str().to_string(),
+                estimated_value_usd,
+                rarity_score: None,
+                acquisition_date: None,
+            });
+        }
+        
+        holdings
+    }
+    
+    /// Results of `calls`, batched through Multicall3; `None` where a call reverted.
+    /// Batches Multicall3 cannot serve are retried call by call.
+    async fn multicall(&self, calls: &[(Address, Vec<u8>)]) -> Result<Vec<Option<Bytes>>> {
+        let multicall = Address::from_str(ProtocolAddresses::MULTICALL3)
+            .map_err(|_| DegenScoreError::ConfigError("Invalid Multicall3 address".to_string()))?;
+        
+        let mut results = Vec::with_capacity(calls.len());
+        for batch in calls.chunks(self.portfolio.multicall_batch_size.max(1)) {
+            let batched = self.call_contract(multicall, portfolio::encode_aggregate3(batch), "aggregate3").await
+                .ok()
+                .and_then(|result| portfolio::decode_aggregate3(&result))
+                .filter(|batched| batched.len() == batch.len());
+            match batched {
+                Some(batched) => results.extend(batched),
+                None => {
+                    debug!("Multicall3 unavailable on {}, calling {} contracts directly", self.chain.as_str(), batch.len());
+                    for (contract, data) in batch {
+                        results.push(self.call_contract(*contract, data.clone(), "token call").await.ok());
+                    }
+                }
+            }
+        }
+        
+        Ok(results)
+    }
+    
+    /// Non-zero balances of every ERC-20 in `transfers`, with decimals, symbol and name.
+    /// Contracts that do not answer `decimals()` are not ERC-20s and are dropped.
+    async fn discover_tokens(&self, address: &Address, transfers: &[EVMTokenTransfer]) -> Result<Vec<TokenBalance>> {
+        let mut tokens = portfolio::touched_tokens(transfers);
+        if tokens.len() > self.portfolio.max_tokens {
+            warn!("{:?} has moved {} tokens on {}; checking the first {}",
+                  address, tokens.len(), self.chain.as_str(), self.portfolio.max_tokens);
+            tokens.truncate(self.portfolio.max_tokens);
+        }
+        
+        let balance_calls: Vec<_> = tokens.iter().map(|token| (*token, portfolio::balance_of(address))).collect();
+        let held: Vec<(Address, U256)> = tokens.iter()
+            .zip(self.multicall(&balance_calls).await?)
+            .filter_map(|(token, result)| {
+                let balance = U256::from_big_endian(result?.get(..32)?);
+                (!balance.is_zero()).then_some((*token, balance))
+            })
+            .collect();
+        
+        let metadata_calls: Vec<_> = held.iter()
+            .flat_map(|(token, _)| ["decimals()", "symbol()", "name()"].map(|call| (*token, portfolio::selector(call).to_vec())))
+            .collect();
+        let metadata = self.multicall(&metadata_calls).await?;
+        
+        let mut balances = Vec::new();
+        for ((token, balance), results) in held.into_iter().zip(metadata.chunks(3)) {
+            let Some(decimals) = results[0].as_ref().and_then(|data| portfolio::decode_decimals(data)) else {
+                debug!("Skipping {:?}: no decimals()", token);
+                continue;
+            };
+            let text = |result: &Option<Bytes>| result.as_ref().and_then(|data| portfolio::decode_token_string(data));
+            balances.push(TokenBalance {
+                token_address: format!("{:?}", token),
+                balance,
+                decimals,
+                symbol: text(&results[1]).unwrap_or_else(|| "UNKNOWN".to_string()),
+                name: text(&results[2]).unwrap_or_else(|| "Unknown Token".to_string()),
+            });
+        }
+        
+        Ok(balances)
+    }
+    
+    /// Native balance plus every ERC-20 `address` holds, each valued at head. Tokens
+    /// without a price source are listed with a zero value.
+    pub async fn get_token_holdings(&self, address: &str) -> Result<Vec<TokenHolding>> {
+        let balances = self.get_token_balances(address).await?;
+        Ok(self.value_tokens(&balances).await)
+    }
+    
+    async fn value_tokens(&self, balances: &[TokenBalance]) -> Vec<TokenHolding> {
+        let mut holdings = Vec::new();
+        for token in balances {
+            let Ok(contract) = Address::from_str(&token.token_address) else { continue };
+            // Tokens without a configured source are looked up in Uniswap pools
+            let value_usd = match self.prices.value_usd(contract, token.balance, token.decimals, None).await {
+                Ok(value) => Some(value),
+                Err(e) => {
+                    debug!("{} balance left unpriced: {}", token.symbol, e);
+                    None
+                }
+            };
+            
+            holdings.push(TokenHolding {
+                token_type: portfolio::classify_token(&self.chain, &token.token_address),
+                token_address: token.token_address.to_lowercase(),
+                token_symbol: token.symbol.clone(),
+                token_name: token.name.clone(),
+                balance: units_to_decimal(token.balance, token.decimals as u32),
+                decimals: token.decimals,
+                value_usd,
+                chain: self.chain.as_str().to_string(),
+            });
+        }
+        holdings
+    }
+    
+    async fn get_erc20_transfers(&self, address: &Address) -> Result<Vec<EVMTokenTransfer>> {
+        if let Some(explorer) = &self.explorer {
+            return explorer.erc20_transfers(&format!("{:?}", address)).await;
+        }
+        
+        let transfer_topic = H256::from_slice(
+            &ethers::core::utils::keccak256(EventSignatures::ERC20_TRANSFER.as_bytes())
+        );
         
         // Get transfers FROM the address
         let filter_from = Filter::new()
-            .from_block(from_block)
-            .to_block(current_block)
             .topic0(transfer_topic)
             .topic1(*address);
         
         // Get transfers TO the address
         let filter_to = Filter::new()
-            .from_block(from_block)
-            .to_block(current_block)
             .topic0(transfer_topic)
             .topic2(*address);
         
-        let logs_from = self.resilient_client.call(|| {
-            let provider = self.provider.clone();
-            let chain_name = self.chain.as_str().to_string();
-            let filter = filter_from.clone();
-            async move {
-                provider.get_logs(&filter).await
-                    .map_err(|e| DegenScoreError::RpcError {
-                        chain: chain_name,
-                        message: format!("Failed to get transfer logs: {}", e),
-                    })
-            }
-        }).await?;
-        
-        let logs_to = self.resilient_client.call(|| {
-            let provider = self.provider.clone();
-            let chain_name = self.chain.as_str().to_string();
-            let filter = filter_to.clone();
-            async move {
-                provider.get_logs(&filter).await
-                    .map_err(|e| DegenScoreError::RpcError {
-                        chain: chain_name,
-                        message: format!("Failed to get transfer logs: {}", e),
-                    })
-            }
-        }).await?;
+        let logs_from = self.scan_logs(&filter_from, "transfer logs").await?;
+        let logs_to = self.scan_logs(&filter_to, "transfer logs").await?;
         
         let mut transfers = Vec::new();
         
-        // Collect unique block numbers to fetch timestamps efficiently
-        let mut unique_blocks: std::collections::HashSet<u64> = std::collections::HashSet::new();
-        for log in logs_from.iter().chain(logs_to.iter()) {
-            if let Some(block_number) = log.block_number {
-                unique_blocks.insert(block_number.as_u64());
-            }
-        }
-        
-        // Fetch block timestamps for unique blocks (limit to avoid too many RPC calls)
+        // Every transfer's block timestamp; a transfer whose block can't be read is dropped
+        // rather than dated to now
+        let unique_blocks: BTreeSet<u64> = logs_from.iter().chain(logs_to.iter())
+            .filter_map(|log| log.block_number.map(|block| block.as_u64()))
+            .collect();
         let mut block_timestamps = HashMap::new();
-        for &block_num in unique_blocks.iter().take(20) { // Limit to 20 blocks max
-            let block_result = self.resilient_client.call(|| {
-                let provider = self.provider.clone();
-                async move {
-                    provider.get_block(block_num).await
-                        .map_err(|e| DegenScoreError::RpcError {
-                            chain: "evm".to_string(),
-                            message: format!("Failed to get block {}: {}", block_num, e),
-                        })
+        for block_num in unique_blocks {
+            match self.block_timestamp(block_num).await {
+                Ok(timestamp) => {
+                    block_timestamps.insert(block_num, timestamp);
                 }
-            }).await;
-            
-            if let Ok(Some(block)) = block_result {
-                block_timestamps.insert(block_num, block.timestamp.as_u64());
+                Err(e) => warn!("Dropping transfers in block {}: {}", block_num, e),
             }
         }
         
-        // Parse transfer logs
+        // Parse transfer logs; ERC-721 transfers share the signature but index a fourth topic
         for log in logs_from.iter().chain(logs_to.iter()) {
-            if log.topics.len() >= 3 {
+            if log.topics.len() == 3 {
                 let from = Address::from(H160::from(log.topics[1]));
                 let to = Address::from(H160::from(log.topics[2]));
                 let value = U256::from_big_endian(&log.data);
                 
-                // Get timestamp from block data if available
-                let timestamp = if let Some(block_number) = log.block_number {
-                    let block_num = block_number.as_u64();
-                    if let Some(&block_timestamp) = block_timestamps.get(&block_num) {
-                        DateTime::from_timestamp(block_timestamp as i64, 0).unwrap_or_else(|| Utc::now())
-                    } else {
-                        Utc::now() // Fallback to current time
-                    }
-                } else {
-                    Utc::now()
+                let Some(timestamp) = log.block_number
+                    .and_then(|block| block_timestamps.get(&block.as_u64()))
+                    .and_then(|&timestamp| DateTime::from_timestamp(timestamp, 0))
+                else {
+                    continue;
                 };
                 
                 transfers.push(EVMTokenTransfer {
@@ -282,84 +857,31 @@ impl EvmClient {
             &ethers::core::utils::keccak256(EventSignatures::GMX_DECREASE_POSITION.as_bytes())
         );
         
-        let current_block = self.resilient_client.call(|| {
-            let provider = self.provider.clone();
-            let chain_name = self.chain.as_str().to_string();
-            async move {
-                provider.get_block_number().await
-                    .map_err(|e| DegenScoreError::RpcError {
-                        chain: chain_name,
-                        message: format!("Failed to get current block: {}", e),
-                    })
-            }
-        }).await?;
-        
-        let from_block = current_block.saturating_sub(U64::from(8_000)); // Reduced range for RPC limits
-        
         // Check IncreasePosition events
         let increase_filter = Filter::new()
-            .from_block(from_block)
-            .to_block(current_block)
             .address(gmx_vault)
             .topic0(increase_position_topic)
             .topic2(*address); // account is the second indexed parameter
         
-        let increase_logs = self.resilient_client.call(|| {
-            let provider = self.provider.clone();
-            let chain_name = self.chain.as_str().to_string();
-            let filter = increase_filter.clone();
-            async move {
-                provider.get_logs(&filter).await
-                    .map_err(|e| DegenScoreError::RpcError {
-                        chain: chain_name,
-                        message: format!("Failed to get GMX IncreasePosition logs: {}", e),
-                    })
-            }
-        }).await?;
+        let increase_logs = self.scan_logs(&increase_filter, "GMX IncreasePosition logs").await?;
         
         // Check DecreasePosition events
         let decrease_filter = Filter::new()
-            .from_block(from_block)
-            .to_block(current_block)
             .address(gmx_vault)
             .topic0(decrease_position_topic)
             .topic2(*address); // account is the second indexed parameter
         
-        let decrease_logs = self.resilient_client.call(|| {
-            let provider = self.provider.clone();
-            let chain_name = self.chain.as_str().to_string();
-            let filter = decrease_filter.clone();
-            async move {
-                provider.get_logs(&filter).await
-                    .map_err(|e| DegenScoreError::RpcError {
-                        chain: chain_name,
-                        message: format!("Failed to get GMX DecreasePosition logs: {}", e),
-                    })
-            }
-        }).await?;
+        let decrease_logs = self.scan_logs(&decrease_filter, "GMX DecreasePosition logs").await?;
         
         let mut total_volume = Decimal::ZERO;
         let mut total_interactions = 0u32;
         
-        // Parse IncreasePosition sizes from logs
-        for log in &increase_logs {
-            if log.data.len() >= 256 {
-                // Size is the 5th parameter (uint256) in the event
-                let size_bytes = &log.data[128..160];
-                let size = U256::from_big_endian(size_bytes);
-                let size_decimal = Decimal::from_str(&size.to_string()).unwrap_or(Decimal::ZERO);
-                total_volume += size_decimal / Decimal::from(10u64.pow(30)); // GMX uses 30 decimals for USD
-            }
-        }
-        
-        // Parse DecreasePosition sizes from logs
-        for log in &decrease_logs {
-            if log.data.len() >= 256 {
-                // Size is the 5th parameter (uint256) in the event
-                let size_bytes = &log.data[128..160];
-                let size = U256::from_big_endian(size_bytes);
-                let size_decimal = Decimal::from_str(&size.to_string()).unwrap_or(Decimal::ZERO);
-                total_volume += size_decimal / Decimal::from(10u64.pow(30)); // GMX uses 30 decimals for USD
+        // sizeDelta is the 6th word, after key, account, collateralToken, indexToken and
+        // collateralDelta; GMX denominates it in USD with 30 decimals
+        for log in increase_logs.iter().chain(&decrease_logs) {
+            if log.data.len() >= 192 {
+                let size_delta = U256::from_big_endian(&log.data[160..192]);
+                total_volume += units_to_decimal(size_delta, 30);
             }
         }
         
@@ -393,51 +915,32 @@ impl EvmClient {
         let clearing_house = Address::from_str(ProtocolAddresses::PERP_CLEARING_HOUSE_OPT)
             .map_err(|_| DegenScoreError::ConfigError("Invalid Perpetual Protocol ClearingHouse address".to_string()))?;
 
-        let current_block = self.provider.get_block_number().await
-            .map_err(|e| DegenScoreError::RpcError {
-                chain: self.chain.as_str().to_string(),
-                message: format!("Failed to get current block: {}", e),
-            })?;
-
-        let from_block = current_block.saturating_sub(U64::from(8_000)); // Reduced range for RPC limits
-
-        // Check for any events from the ClearingHouse involving this user
+        // Every trade emits PositionChanged with the trader indexed
+        let position_changed = H256::from_slice(
+            &ethers::core::utils::keccak256(EventSignatures::PERP_POSITION_CHANGED.as_bytes())
+        );
         let filter = Filter::new()
-            .from_block(from_block)
-            .to_block(current_block)
-            .address(clearing_house);
-
-        let logs = self.provider.get_logs(&filter).await
-            .map_err(|e| DegenScoreError::RpcError {
-                chain: self.chain.as_str().to_string(),
-                message: format!("Failed to get Perpetual Protocol logs: {}", e),
-            })?;
-
-        let mut user_interactions = 0u32;
-        let mut estimated_volume = Decimal::ZERO;
-
-        // Filter logs that involve the user address
-        for log in &logs {
-            let user_involved = log.topics.iter().any(|topic| {
-                // Check if user address appears in any topic
-                topic.as_bytes().ends_with(address.as_bytes())
-            });
+            .address(clearing_house)
+            .topic0(position_changed)
+            .topic1(*address);
+        let logs = self.scan_logs(&filter, "Perpetual Protocol logs").await?;
 
-            if user_involved {
-                user_interactions += 1;
-                // Estimate volume based on interaction type
-                // Since we can't easily parse the exact volumes without event ABI,
-                // we'll use a reasonable estimate per interaction
-                estimated_volume += Decimal::from(500); // $500 per position interaction
-            }
-        }
+        let user_interactions = logs.len() as u32;
+        // exchangedPositionNotional, the signed quote amount of the trade in 18 decimals
+        let volume: Decimal = logs.iter()
+            .filter(|log| log.data.len() >= 64)
+            .map(|log| {
+                let notional = I256::from_raw(U256::from_big_endian(&log.data[32..64])).unsigned_abs();
+                Decimal::from_str(&notional.to_string()).unwrap_or(Decimal::ZERO) / Decimal::from(10u64.pow(18))
+            })
+            .sum();
 
-        info!("Perpetual Protocol: {} interactions, estimated ${} volume", user_interactions, estimated_volume);
+        info!("Perpetual Protocol: {} trades, ${} volume", user_interactions, volume);
 
         Ok(ProtocolMetrics {
             protocol_name: "Perpetual Protocol".to_string(),
             interaction_count: user_interactions,
-            volume_usd: estimated_volume,
+            volume_usd: volume,
             first_interaction: None,
             last_interaction: None,
             custom_metrics: HashMap::new(),
@@ -456,11 +959,13 @@ impl EvmClient {
                 match self.get_token_balance(*address, token_address).await {
                     Ok(balance) => {
                         if balance > Decimal::ZERO {
-                            // Convert from raw token units to human readable
-                            // Most tokens have 18 decimals
-                            let decimals = 18;
-                            let divisor = Decimal::from(10u64.pow(decimals));
-                            let human_balance = balance / divisor;
+                            let decimals = self.prices.token_decimals(token_address).await.unwrap_or(18);
+                            // 10^20 and up no longer fit a u64
+                            let Some(unit) = 10u64.checked_pow(decimals as u32) else {
+                                warn!("Skipping {}: {} decimals are out of range", symbol, decimals);
+                                continue;
+                            };
+                            let human_balance = balance / Decimal::from(unit);
                             
                             casino_holdings.insert(symbol.to_string(), human_balance);
                             info!("Found {} {} tokens", human_balance, symbol);
@@ -521,63 +1026,25 @@ impl EvmClient {
         let pool_addr = Address::from_str(aave_pool)
             .map_err(|_| DegenScoreError::ConfigError("Invalid Aave pool address".to_string()))?;
             
-        // Check for Deposit events
-        let deposit_topic = H256::from_slice(
-            &ethers::core::utils::keccak256(EventSignatures::AAVE_DEPOSIT.as_bytes())
-        );
-        
-        let current_block = self.provider.get_block_number().await
-            .map_err(|e| DegenScoreError::RpcError {
-                chain: self.chain.as_str().to_string(),
-                message: format!("Failed to get current block: {}", e),
-            })?;
-        
-        let from_block = current_block.saturating_sub(U64::from(8_000)); // Reduced range for RPC limits
+        // V2 emits Deposit, V3 renamed it Supply and narrowed the rate mode to uint8
+        let (supply_event, borrow_event) = match self.chain {
+            Chain::Ethereum => (EventSignatures::AAVE_DEPOSIT, EventSignatures::AAVE_BORROW),
+            _ => (EventSignatures::AAVE_V3_SUPPLY, EventSignatures::AAVE_V3_BORROW),
+        };
         
-        // Check if user deposited (onBehalfOf parameter)
-        let filter = Filter::new()
-            .from_block(from_block)
-            .to_block(current_block)
-            .address(pool_addr)
-            .topic0(deposit_topic);
-            
-        let logs = self.provider.get_logs(&filter).await
-            .map_err(|e| DegenScoreError::RpcError {
-                chain: self.chain.as_str().to_string(),
-                message: format!("Failed to get Aave logs: {}", e),
-            })?;
+        for (event, context) in [(supply_event, "Aave supply logs"), (borrow_event, "Aave borrow logs")] {
+            let topic = H256::from_slice(&ethers::core::utils::keccak256(event.as_bytes()));
+            let filter = Filter::new()
+                .address(pool_addr)
+                .topic0(topic)
+                .topic2(*address); // onBehalfOf is indexed
             
-        // Check if any logs have the user address as onBehalfOf (5th parameter)
-        for log in logs {
-            if log.topics.len() >= 2 && log.data.len() >= 160 {
-                // onBehalfOf is in the data field (5th parameter)
-                let on_behalf_of_bytes = &log.data[128..160];
-                let on_behalf_of = Address::from_slice(&on_behalf_of_bytes[12..]);
-                if on_behalf_of == *address {
-                    return Ok(true);
-                }
+            if !self.scan_logs(&filter, context).await?.is_empty() {
+                return Ok(true);
             }
         }
         
-        // Also check for Borrow events
-        let borrow_topic = H256::from_slice(
-            &ethers::core::utils::keccak256(EventSignatures::AAVE_BORROW.as_bytes())
-        );
-        
-        let filter_borrow = Filter::new()
-            .from_block(from_block)
-            .to_block(current_block)
-            .address(pool_addr)
-            .topic0(borrow_topic)
-            .topic2(*address); // borrower is indexed
-            
-        let borrow_logs = self.provider.get_logs(&filter_borrow).await
-            .map_err(|e| DegenScoreError::RpcError {
-                chain: self.chain.as_str().to_string(),
-                message: format!("Failed to get Aave borrow logs: {}", e),
-            })?;
-            
-        Ok(!borrow_logs.is_empty())
+        Ok(false)
     }
     
     async fn check_compound_activity(&self, address: &Address) -> Result<bool> {
@@ -650,31 +1117,21 @@ impl EvmClient {
     async fn check_hyperliquid_deposits(&self, user_addr: &Address, bridge_addr: &Address) -> Result<(u32, Decimal)> {
         // Check for USDC transfers from user to Hyperliquid bridge
         // Using native USDC on Arbitrum (not USDC.e bridged version)
-        let usdc_arb = Address::from_str("0xaf88d065ef77c8cC2239327C5EDb3A432268e5831") // Native USDC on Arbitrum
+        let usdc_arb = Address::from_str(ProtocolAddresses::USDC_ARB)
             .map_err(|_| DegenScoreError::ConfigError("Invalid USDC address".to_string()))?;
             
         let transfer_topic = H256::from_slice(
             &ethers::core::utils::keccak256(EventSignatures::ERC20_TRANSFER.as_bytes())
         );
         
-        let current_block = self.provider.get_block_number().await
-            .map_err(|e| DegenScoreError::RpcError {
-                chain: self.chain.as_str().to_string(),
-                message: format!("Failed to get current block: {}", e),
-            })?;
-        
-        let from_block = current_block.saturating_sub(U64::from(8_000)); // Reduced range for RPC limits
-        
         // Look for transfers: from=user, to=bridge
         let filter = Filter::new()
-            .from_block(from_block)
-            .to_block(current_block)
             .address(vec![usdc_arb])
             .topic0(transfer_topic)
             .topic1(vec![H256::from(*user_addr)])  // from user
             .topic2(vec![H256::from(*bridge_addr)]); // to bridge
             
-        let logs = self.provider.get_logs(&filter).await
+        let logs = self.scan_logs(&filter, "Hyperliquid deposit logs").await
             .unwrap_or_default();
             
         // Calculate total deposit volume
@@ -811,43 +1268,38 @@ impl EvmClient {
     }
     
     async fn check_contract_interaction(&self, user_addr: &Address, contract_addr: &Address) -> Result<bool> {
-        let current_block = self.provider.get_block_number().await
-            .map_err(|e| DegenScoreError::RpcError {
-                chain: self.chain.as_str().to_string(),
-                message: format!("Failed to get current block: {}", e),
-            })?;
-        
-        let from_block = current_block.saturating_sub(U64::from(8_000)); // Reduced range for RPC limits
-        
-        // Check by looking for any events from the contract where user is involved
-        // We'll check if user has ever called the contract by looking at nonce
-        let nonce_at_block = self.provider.get_transaction_count(*user_addr, Some(from_block.into())).await
-            .unwrap_or(U256::zero());
-        let current_nonce = self.provider.get_transaction_count(*user_addr, None).await
-            .unwrap_or(U256::zero());
-            
-        // If nonce increased, user made transactions
-        if current_nonce > nonce_at_block {
-            // For a more precise check, we'd need to iterate through blocks
-            // For now, we'll check if the contract was used by looking for events
-            let filter = Filter::new()
-                .from_block(from_block)
-                .to_block(current_block)
-                .address(*contract_addr);
-                
-            let logs = self.provider.get_logs(&filter).await
-                .map_err(|e| DegenScoreError::RpcError {
-                    chain: self.chain.as_str().to_string(),
-                    message: format!("Failed to get logs: {}", e),
-                })?;
-                
-            // Check if any log involves the user address in topics
-            for log in &logs {
-                for topic in &log.topics {
-                    if topic.as_bytes().ends_with(user_addr.as_bytes()) {
-                        return Ok(true);
+        // The explorer lists every transaction the user sent, calls without events included;
+        // the recipients are cached so checking many contracts fetches the history once
+        if let Some(explorer) = &self.explorer {
+            let user = format!("{:?}", user_addr);
+            let cache_key = CacheKey::contract_calls(self.chain.as_str(), &user);
+            let calls = match self.cache.get_protocol_interactions(&cache_key) {
+                Some(calls) => calls,
+                None => {
+                    let mut calls = HashMap::new();
+                    for tx in explorer.transactions(&user).await? {
+                        if let Some(to) = tx.to {
+                            *calls.entry(to.to_lowercase()).or_insert(0) += 1;
+                        }
                     }
+                    self.cache.set_protocol_interactions(cache_key, calls.clone());
+                    calls
                 }
+            };
+            return Ok(calls.contains_key(&format!("{:?}", contract_addr)));
+        }
+        
+        // Otherwise look for an event of the contract with the user in an indexed slot,
+        // one query per topic position so the node does the matching
+        for position in 1..=3 {
+            let filter = Filter::new().address(*contract_addr);
+            let filter = match position {
+                1 => filter.topic1(*user_addr),
+                2 => filter.topic2(*user_addr),
+                _ => filter.topic3(*user_addr),
+            };
+            if !self.scan_logs(&filter, "contract logs").await?.is_empty() {
+                return Ok(true);
             }
         }
         
@@ -867,41 +1319,21 @@ impl EvmClient {
             &ethers::core::utils::keccak256(EventSignatures::ERC20_TRANSFER.as_bytes())
         );
         
-        let current_block = self.provider.get_block_number().await
-            .map_err(|e| DegenScoreError::RpcError {
-                chain: self.chain.as_str().to_string(),
-                message: format!("Failed to get current block: {}", e),
-            })?;
-        
-        let from_block = current_block.saturating_sub(U64::from(8_000)); // Reduced range for RPC limits
-        
         // Check transfers FROM user (outgoing)
         let filter_from = Filter::new()
-            .from_block(from_block)
-            .to_block(current_block)
             .address(*token_addr)
             .topic0(transfer_topic)
             .topic1(*user_addr); // User as sender
             
-        let logs_from = self.provider.get_logs(&filter_from).await
-            .map_err(|e| DegenScoreError::RpcError {
-                chain: self.chain.as_str().to_string(),
-                message: format!("Failed to get outgoing transfer logs: {}", e),
-            })?;
+        let logs_from = self.scan_logs(&filter_from, "outgoing transfer logs").await?;
         
         // Check transfers TO user (incoming)
         let filter_to = Filter::new()
-            .from_block(from_block)
-            .to_block(current_block)
             .address(*token_addr)
             .topic0(transfer_topic)
             .topic2(*user_addr); // User as recipient
             
-        let logs_to = self.provider.get_logs(&filter_to).await
-            .map_err(|e| DegenScoreError::RpcError {
-                chain: self.chain.as_str().to_string(),
-                message: format!("Failed to get incoming transfer logs: {}", e),
-            })?;
+        let logs_to = self.scan_logs(&filter_to, "incoming transfer logs").await?;
         
         // Calculate total volume
         let mut total_volume = Decimal::ZERO;
@@ -964,28 +1396,12 @@ impl EvmClient {
         }
         
         // Check if user has interacted with a protocol by looking for transactions to that address
-        let current_block = self.resilient_client.call(|| {
-            let provider = self.provider.clone();
-            let chain_name = self.chain.as_str().to_string();
-            async move {
-                provider.get_block_number().await
-                    .map_err(|e| DegenScoreError::RpcError {
-                        chain: chain_name,
-                        message: format!("Failed to get current block: {}", e),
-                    })
-            }
-        }).await?;
-        
-        let from_block = current_block.saturating_sub(U64::from(8_000));
-        
         // Method 1: Check for transactions FROM user TO protocol
         let filter_to = Filter::new()
-            .from_block(from_block)
-            .to_block(current_block)
             .address(vec![*protocol_addr])
             .topic1(vec![H256::from(*user_addr)]); // User as sender in Transfer events
         
-        let logs_to = self.provider.get_logs(&filter_to).await
+        let logs_to = self.scan_logs(&filter_to, "protocol logs").await
             .unwrap_or_default();
         
         if !logs_to.is_empty() {
@@ -998,12 +1414,10 @@ impl EvmClient {
         
         // Method 2: Check for any events from protocol that involve user
         let filter_from = Filter::new()
-            .from_block(from_block)
-            .to_block(current_block)
             .address(vec![*protocol_addr])
             .topic2(vec![H256::from(*user_addr)]); // User as recipient
         
-        let logs_from = self.provider.get_logs(&filter_from).await
+        let logs_from = self.scan_logs(&filter_from, "protocol logs").await
             .unwrap_or_default();
         
         let has_interaction = !logs_from.is_empty();
@@ -1017,6 +1431,64 @@ impl EvmClient {
     }
 }
 
+fn metadata_client(settings: &NftSettings) -> reqwest::Client {
+    reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(settings.metadata_timeout_seconds))
+        .build()
+        .unwrap_or_default()
+}
+
+/// Native ETH balance; every supported EVM chain uses ETH for gas
+fn native_token_balance(balance: U256) -> TokenBalance {
+    TokenBalance {
+        token_address: format!("{:?}", Address::zero()),
+        balance,
+        decimals: 18,
+        symbol: "ETH".to_string(),
+        name: "Native Token".to_string(),
+    }
+}
+
+/// L1 data fee billed separately from execution gas, in wei. OP Stack receipts report it
+/// as `l1Fee`; Arbitrum charges L1 data as extra L2 gas already counted in `gasUsed`.
+fn l1_data_fee(chain: &Chain, receipt: &TransactionReceipt) -> U256 {
+    match chain {
+        Chain::Optimism | Chain::Blast => receipt.other.get("l1Fee")
+            .and_then(|fee| fee.as_str())
+            .and_then(|fee| U256::from_str_radix(fee.trim_start_matches("0x"), 16).ok())
+            .unwrap_or_default(),
+        _ => U256::zero(),
+    }
+}
+
+/// One (timestamp, fee in ETH) entry per distinct transaction touching `address`; only
+/// transactions it sent carry a fee
+fn transaction_activity(
+    address: &Address,
+    history: &[EVMTransaction],
+    transfers: &[EVMTokenTransfer],
+) -> Vec<(DateTime<Utc>, Decimal)> {
+    let sender = format!("{:?}", address);
+    let mut by_hash: HashMap<String, (DateTime<Utc>, Decimal)> = HashMap::new();
+    
+    for tx in history {
+        let fee = if tx.from.eq_ignore_ascii_case(&sender) {
+            units_to_decimal(tx.fee_paid(), 18)
+        } else {
+            Decimal::ZERO
+        };
+        // Internal transactions share the hash of the transaction that caused them
+        by_hash.entry(tx.hash.to_lowercase())
+            .and_modify(|entry| entry.1 += fee)
+            .or_insert((tx.timestamp, fee));
+    }
+    for transfer in transfers {
+        by_hash.entry(transfer.tx_hash.to_lowercase()).or_insert((transfer.timestamp, Decimal::ZERO));
+    }
+    
+    by_hash.into_values().collect()
+}
+
 #[async_trait]
 impl ChainClient for EvmClient {
     fn chain(&self) -> Chain {
@@ -1054,9 +1526,13 @@ impl ChainClient for EvmClient {
         
         info!("Address: {}, TX Count: {}", address, tx_count);
         
-        // Calculate real wallet age from first transaction
-        let wallet_age_days = self.calculate_wallet_age(&addr).await.unwrap_or(0);
-        metrics.wallet_age_days = wallet_age_days;
+        let history = match self.get_transaction_history(&addr).await {
+            Ok(history) => history,
+            Err(e) => {
+                warn!("Failed to fetch transaction history: {}", e);
+                Vec::new()
+            }
+        };
         
         // Get token transfers to identify trading activity
         let transfers = match self.get_erc20_transfers(&addr).await {
@@ -1069,10 +1545,10 @@ impl ChainClient for EvmClient {
                 Vec::new()
             }
         };
-        let unique_tokens: std::collections::HashSet<_> = transfers.iter()
-            .map(|t| &t.token_address)
+        metrics.tokens_traded = transfers.iter()
+            .map(|t| t.token_address.to_lowercase())
             .collect();
-        metrics.distinct_tokens_traded = unique_tokens.len() as u32;
+        metrics.distinct_tokens_traded = metrics.tokens_traded.len() as u32;
         
         // Check for memecoin trading
         let memecoin_addrs = KnownTokens::memecoin_addresses();
@@ -1087,14 +1563,46 @@ impl ChainClient for EvmClient {
             info!("Memecoin transfers detected: {}", memecoin_trades);
         }
         
-        // Calculate activity days from transfer timestamps
-        let mut activity_days = std::collections::HashSet::new();
-        for transfer in &transfers {
-            let timestamp = transfer.timestamp.timestamp() as u64;
-            let day = timestamp / 86400; // Convert to days since epoch
-            activity_days.insert(day);
+        // Calculate activity from transaction and transfer timestamps
+        let activity = transaction_activity(&addr, &history, &transfers);
+        for (timestamp, _) in &activity {
+            let day = timestamp.timestamp() / 86400; // Convert to days since epoch
+            metrics.active_day_numbers.insert(day);
+        }
+        let summary = TransactionSummary::from_activity(&activity, None, None);
+        metrics.active_days = metrics.active_day_numbers.len() as u32;
+        metrics.active_months = summary.active_months;
+        metrics.first_transaction = summary.first_tx;
+        metrics.last_transaction = summary.last_tx;
+        metrics.most_active_period = summary.most_active_period;
+        
+        if summary.gas_spent > Decimal::ZERO {
+            metrics.gas_spent_usd = self.gas_spent_usd(&format!("{:?}", addr), &history).await;
+        }
+        
+        // Wallet age from the first known transaction, else from the first nonce change
+        metrics.wallet_age_days = match metrics.first_transaction {
+            Some(first) if self.explorer.is_some() => (Utc::now() - first).num_days().max(0) as u32,
+            _ => self.calculate_wallet_age(&addr).await.unwrap_or(0),
+        };
+        
+        // NFT holdings and their value; metadata is only fetched for get_nft_balances
+        match self.discover_nfts(&addr, false).await {
+            Ok(nfts) => {
+                metrics.nft_collections = nfts.iter()
+                    .map(|nft| nft.contract_address.to_lowercase())
+                    .collect();
+                metrics.nft_collections_count = metrics.nft_collections.len() as u32;
+                metrics.nfts_held = nfts.iter()
+                    .map(|nft| format!("{}:{}", nft.contract_address.to_lowercase(), nft.token_id))
+                    .collect();
+                metrics.nft_count = metrics.nfts_held.len() as u32;
+                metrics.nft_total_value_usd = self.value_nfts(&nfts).await.iter()
+                    .filter_map(|holding| holding.estimated_value_usd)
+                    .sum();
+            }
+            Err(e) => warn!("Failed to discover NFTs: {}", e),
         }
-        metrics.active_days = activity_days.len() as u32;
         
         // Check protocol-specific activity
         if self.chain == Chain::Arbitrum {
@@ -1243,7 +1751,10 @@ impl ChainClient for EvmClient {
         // Check casino platform interactions (not just token holdings)
         match self.check_casino_interactions(&addr).await {
             Ok(casino_metrics) => {
-                metrics.casinos_used = casino_metrics.platforms_used.len() as u32;
+                metrics.casino_platforms = casino_metrics.platforms_used.iter()
+                    .map(|platform| format!("{:?}", platform))
+                    .collect();
+                metrics.casinos_used = metrics.casino_platforms.len() as u32;
                 info!("Casino platforms used: {}", metrics.casinos_used);
                 for platform in &casino_metrics.platforms_used {
                     debug!("  - {:?}", platform);
@@ -1463,44 +1974,26 @@ impl ChainClient for EvmClient {
         }
         
         // Set final unique protocol count
-        metrics.defi_protocols_used = protocols_used.len() as u32;
+        metrics.protocols_used = protocols_used.iter().map(|p| p.to_string()).collect();
+        metrics.defi_protocols_used = metrics.protocols_used.len() as u32;
         
         // Add this chain to active chains
         metrics.chains_active_on.push(self.chain.as_str().to_string());
         
-        // Calculate total balance and stablecoin percentage
-        // Note: This is a simplified version - in production, we'd fetch all token balances
-        // and their USD values from a price oracle
-        let stablecoins = KnownTokens::stablecoins();
-        let mut total_balance_usd = Decimal::ZERO;
-        let mut stablecoin_balance_usd = Decimal::ZERO;
-        
-        // Check balances of major stablecoins
-        for (token_addr, symbol) in stablecoins.iter() {
-            if let Ok(token_address) = Address::from_str(token_addr) {
-                match self.get_token_balance(addr, token_address).await {
-                    Ok(balance) => {
-                        if balance > Decimal::ZERO {
-                            // Assume 1:1 USD for stablecoins and 6 decimals (USDC/USDT standard)
-                            let balance_usd = balance / Decimal::from(1_000_000);
-                            stablecoin_balance_usd += balance_usd;
-                            total_balance_usd += balance_usd;
-                            info!("Found {} {} stablecoin balance", balance_usd, symbol);
-                        }
-                    }
-                    Err(e) => {
-                        warn!("Failed to check {} balance: {}", symbol, e);
-                    }
-                }
-            }
+        // Portfolio: the native balance plus every ERC-20 the address has moved, valued at head
+        let mut balances = Vec::new();
+        if !balance.is_zero() {
+            balances.push(native_token_balance(balance));
         }
-        
-        // Set the balance metrics
-        if total_balance_usd > Decimal::ZERO {
-            metrics.total_balance_usd = total_balance_usd;
-            metrics.stablecoin_percentage = (stablecoin_balance_usd / total_balance_usd).try_into().unwrap_or(0.0);
-            info!("Total balance: {} USD, Stablecoin percentage: {:.2}%", 
-                     total_balance_usd, metrics.stablecoin_percentage * 100.0);
+        match self.discover_tokens(&addr, &transfers).await {
+            Ok(tokens) => balances.extend(tokens),
+            Err(e) => warn!("Failed to discover token balances: {}", e),
+        }
+        let holdings = self.value_tokens(&balances).await;
+        portfolio::apply_holdings(&mut metrics, &holdings, &portfolio::sent_tokens(&addr, &transfers));
+        if metrics.total_balance_usd > Decimal::ZERO {
+            info!("Total balance: {} USD, Stablecoin percentage: {:.2}%",
+                  metrics.total_balance_usd, metrics.stablecoin_percentage * 100.0);
         }
         
         info!("Metrics fetched successfully for {} on {}", address, self.chain.as_str());
@@ -1523,26 +2016,31 @@ impl ChainClient for EvmClient {
     async fn get_transaction_summary(
         &self,
         address: &str,
-        _start_time: Option<DateTime<Utc>>,
-        _end_time: Option<DateTime<Utc>>
+        start_time: Option<DateTime<Utc>>,
+        end_time: Option<DateTime<Utc>>
     ) -> Result<TransactionSummary> {
         let addr = Address::from_str(address)
             .map_err(|_| DegenScoreError::InvalidAddress(address.to_string()))?;
         
-        let tx_count = self.provider.get_transaction_count(addr, None).await
-            .map_err(|e| DegenScoreError::RpcError {
-                chain: self.chain.as_str().to_string(),
-                message: format!("Failed to get transaction count: {}", e),
-            })?;
+        let history = self.get_transaction_history(&addr).await?;
+        let transfers = self.get_erc20_transfers(&addr).await?;
+        let mut summary = TransactionSummary::from_activity(
+            &transaction_activity(&addr, &history, &transfers),
+            start_time,
+            end_time,
+        );
         
-        Ok(TransactionSummary {
-            total_count: tx_count.as_u32(),
-            first_tx: None, // Would need to implement block scanning
-            last_tx: None,
-            active_days: 0,
-            average_tx_per_day: 0.0,
-            gas_spent: Decimal::ZERO,
-        })
+        // Without an explorer plain sends are invisible, but the nonce still counts them
+        if start_time.is_none() && end_time.is_none() {
+            let tx_count = self.provider.get_transaction_count(addr, None).await
+                .map_err(|e| DegenScoreError::RpcError {
+                    chain: self.chain.as_str().to_string(),
+                    message: format!("Failed to get transaction count: {}", e),
+                })?;
+            summary.total_count = summary.total_count.max(tx_count.as_u32());
+        }
+        
+        Ok(summary)
     }
     
     async fn get_token_balances(&self, address: &str) -> Result<Vec<TokenBalance>> {
@@ -1550,26 +2048,12 @@ impl ChainClient for EvmClient {
         let cache_key_prefix = format!("{}:{}", self.chain.as_str(), address.to_lowercase());
         if let Some(cached_balances) = self.cache.get_balances(&cache_key_prefix) {
             info!("Cache hit for token balances {} on {}", address, self.chain.as_str());
-            // Convert HashMap to Vec<TokenBalance> 
-            let mut balances = Vec::new();
-            for (token_addr, balance) in cached_balances {
-                // For now, return a simplified version
-                balances.push(TokenBalance {
-                    token_address: token_addr,
-                    balance: U256::from_dec_str(&balance.to_string()).unwrap_or(U256::zero()),
-                    decimals: 18, // Default, would need proper token info
-                    symbol: "UNKNOWN".to_string(),
-                    name: "Unknown Token".to_string(),
-                });
-            }
-            return Ok(balances);
+            return Ok(cached_balances);
         }
         
         let addr = Address::from_str(address)
             .map_err(|_| DegenScoreError::InvalidAddress(address.to_string()))?;
         
-        // In production, we'd scan for all token holdings
-        // For now, just check known tokens
         let mut balances = Vec::new();
         
         // Check native balance
@@ -1580,36 +2064,22 @@ impl ChainClient for EvmClient {
             })?;
         
         if !eth_balance.is_zero() {
-            balances.push(TokenBalance {
-                token_address: "0x0000000000000000000000000000000000000000".to_string(),
-                balance: eth_balance,
-                decimals: 18,
-                symbol: match self.chain {
-                    Chain::Ethereum => "ETH",
-                    Chain::Arbitrum => "ETH",
-                    Chain::Optimism => "ETH",
-                    Chain::Blast => "ETH",
-                    _ => "ETH",
-                }.to_string(),
-                name: "Native Token".to_string(),
-            });
+            balances.push(native_token_balance(eth_balance));
         }
         
-        // Cache the balances as a HashMap
-        let mut balance_map = HashMap::new();
-        for token_balance in &balances {
-            let balance_decimal = Decimal::from_str(&token_balance.balance.to_string()).unwrap_or(Decimal::ZERO);
-            balance_map.insert(token_balance.token_address.clone(), balance_decimal);
-        }
-        self.cache.set_balances(cache_key_prefix, balance_map);
+        let transfers = self.get_erc20_transfers(&addr).await?;
+        balances.extend(self.discover_tokens(&addr, &transfers).await?);
+        
+        self.cache.set_balances(cache_key_prefix, balances.clone());
         
         Ok(balances)
     }
     
     async fn get_nft_balances(&self, address: &str) -> Result<Vec<NFTBalance>> {
-        // Would implement ERC721 balance checking
-        // For now, return empty
-        Ok(vec![])
+        let addr = Address::from_str(address)
+            .map_err(|_| DegenScoreError::InvalidAddress(address.to_string()))?;
+        
+        self.discover_nfts(&addr, true).await
     }
     
     async fn has_used_protocol(&self, address: &str, protocol_address: &str) -> Result<bool> {
@@ -1620,25 +2090,11 @@ impl ChainClient for EvmClient {
             .map_err(|_| DegenScoreError::InvalidAddress(protocol_address.to_string()))?;
         
         // Check if address has sent transactions to protocol
-        let current_block = self.provider.get_block_number().await
-            .map_err(|e| DegenScoreError::RpcError {
-                chain: self.chain.as_str().to_string(),
-                message: format!("Failed to get current block: {}", e),
-            })?;
-        
-        let from_block = current_block.saturating_sub(U64::from(8_000));
-        
         let filter = Filter::new()
-            .from_block(from_block)
-            .to_block(current_block)
             .address(protocol_addr)
             .topic1(addr); // Many protocols index user address as first topic
         
-        let logs = self.provider.get_logs(&filter).await
-            .map_err(|e| DegenScoreError::RpcError {
-                chain: self.chain.as_str().to_string(),
-                message: format!("Failed to get logs: {}", e),
-            })?;
+        let logs = self.scan_logs(&filter, "logs").await?;
         
         Ok(!logs.is_empty())
     }
@@ -1669,6 +2125,12 @@ impl ChainClient for EvmClient {
             .map_err(|_| DegenScoreError::InvalidAddress(address.to_string()))?;
         Ok(())
     }
+    
+    async fn get_token_transfers(&self, address: &str) -> Result<Vec<EVMTokenTransfer>> {
+        let addr = Address::from_str(address)
+            .map_err(|_| DegenScoreError::InvalidAddress(address.to_string()))?;
+        self.get_erc20_transfers(&addr).await
+    }
 }
 
 #[cfg(test)]
@@ -1676,6 +2138,209 @@ mod tests {
     use super::*;
     use ethers::providers::MockProvider;
     
+    #[tokio::test]
+    async fn test_prefers_explorer_history() {
+        use crate::chains::explorer::tests::{serve_rpc, StandIn, USER};
+        use serde_json::{json, Value};
+        
+        // The stand-in only answers the chain ID check; everything else must come from the explorer
+        let (url, config) = serve_rpc(Arc::new(StandIn::default()), 1, |request: Value| async move {
+            json!({ "jsonrpc": "2.0", "id": request["id"], "result": "0x1" })
+        }).await;
+        let client = EvmClient::new(config, Chain::Ethereum).await.unwrap()
+            .with_explorer_api(format!("{}/api", url));
+        
+        let transfers = client.get_token_transfers(USER).await.unwrap();
+        assert_eq!(transfers.len(), 7);
+        let history = client.get_transaction_history(&USER.parse().unwrap()).await.unwrap();
+        assert_eq!(history.len(), 25);
+        
+        // Token transfers share their transactions' hashes, so nothing is counted twice;
+        // every sent transaction paid 21000 gas at 20 gwei
+        let summary = client.get_transaction_summary(USER, None, None).await.unwrap();
+        assert_eq!(summary.total_count, 25);
+        assert_eq!(summary.active_days, 1);
+        assert_eq!(summary.gas_spent, Decimal::from_str("0.0105").unwrap());
+        
+        let after_block_10 = DateTime::from_timestamp(1_700_000_000 + 10 * 12 + 1, 0);
+        let windowed = client.get_transaction_summary(USER, after_block_10, None).await.unwrap();
+        assert_eq!(windowed.total_count, 15);
+    }
+    
+    #[tokio::test]
+    async fn test_contract_checks_share_one_explorer_history() {
+        use crate::chains::explorer::tests::{serve_rpc, StandIn, USER};
+        use serde_json::{json, Value};
+        use std::sync::atomic::Ordering;
+        
+        let stand_in = Arc::new(StandIn::default());
+        let (url, config) = serve_rpc(stand_in.clone(), 1, |request: Value| async move {
+            json!({ "jsonrpc": "2.0", "id": request["id"], "result": "0x1" })
+        }).await;
+        let client = EvmClient::new(config, Chain::Ethereum).await.unwrap()
+            .with_explorer_api(format!("{}/api", url));
+        
+        // Every stand-in transaction goes to 0x…aa
+        let user: Address = USER.parse().unwrap();
+        let called: Address = "0x00000000000000000000000000000000000000aa".parse().unwrap();
+        assert!(client.check_contract_interaction(&user, &called).await.unwrap());
+        let requests = stand_in.requests.load(Ordering::SeqCst);
+        assert!(!client.check_contract_interaction(&user, &Address::zero()).await.unwrap());
+        assert!(client.check_contract_interaction(&user, &called).await.unwrap());
+        assert_eq!(stand_in.requests.load(Ordering::SeqCst), requests);
+    }
+    
+    #[tokio::test]
+    async fn test_log_transfers_are_dated_by_their_blocks() {
+        use crate::chains::explorer::tests::{serve_rpc, StandIn, USER};
+        use serde_json::{json, Value};
+        
+        // Transfers sent in blocks 5 and 6; block 6 can't be read
+        let (_, config) = serve_rpc(Arc::new(StandIn::default()), 1, |request: Value| async move {
+            let result = match request["method"].as_str().unwrap() {
+                "eth_blockNumber" => json!("0x64"),
+                "eth_getLogs" if request["params"][0]["topics"][1].is_string() => {
+                    let transfer_topic = H256::from(ethers::core::utils::keccak256(EventSignatures::ERC20_TRANSFER.as_bytes()));
+                    let logs: Vec<Log> = [5u64, 6].iter().map(|&block| Log {
+                        address: Address::repeat_byte(0xcc),
+                        topics: vec![transfer_topic, H256::from(USER.parse::<Address>().unwrap()), H256::repeat_byte(0xbb)],
+                        data: Bytes::from(vec![0u8; 32]),
+                        block_number: Some(U64::from(block)),
+                        transaction_hash: Some(H256::from_low_u64_be(block)),
+                        log_index: Some(U256::zero()),
+                        ..Log::default()
+                    }).collect();
+                    serde_json::to_value(logs).unwrap()
+                }
+                "eth_getLogs" => json!([]),
+                "eth_getBlockByNumber" if request["params"][0] == "0x5" => serde_json::to_value(Block::<H256> {
+                    number: Some(U64::from(5)),
+                    timestamp: U256::from(1_700_000_000u64),
+                    ..Block::default()
+                }).unwrap(),
+                "eth_getBlockByNumber" => Value::Null,
+                _ => json!("0x1"),
+            };
+            json!({ "jsonrpc": "2.0", "id": request["id"], "result": result })
+        }).await;
+        let client = EvmClient::new(config, Chain::Ethereum).await.unwrap();
+        
+        let transfers = client.get_token_transfers(USER).await.unwrap();
+        assert_eq!(transfers.len(), 1);
+        assert_eq!(transfers[0].timestamp.timestamp(), 1_700_000_000);
+    }
+    
+    #[tokio::test]
+    async fn test_l1_fees_are_batched_and_skip_failed_receipts() {
+        use crate::chains::explorer::tests::{serve_rpc, StandIn, USER};
+        use serde_json::{json, Value};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        
+        // Receipts carry 0.01 ETH of L1 fee, except a failed lookup for the transaction in
+        // block 2 and an unknown one in block 3; answers come back in reverse order
+        let batches = Arc::new(AtomicUsize::new(0));
+        let counter = batches.clone();
+        let (url, config) = serve_rpc(Arc::new(StandIn::default()), 10, move |request: Value| {
+            let counter = counter.clone();
+            async move {
+                let Some(requests) = request.as_array() else {
+                    return json!({ "jsonrpc": "2.0", "id": request["id"], "result": "0xa" });
+                };
+                counter.fetch_add(1, Ordering::SeqCst);
+                let receipt = serde_json::to_value(TransactionReceipt {
+                    other: serde_json::from_value(json!({ "l1Fee": "0x2386f26fc10000" })).unwrap(),
+                    ..TransactionReceipt::default()
+                }).unwrap();
+                Value::Array(requests.iter().rev().map(|request| {
+                    match request["params"][0].as_str().unwrap() {
+                        hash if hash.ends_with("02") => json!({ "jsonrpc": "2.0", "id": request["id"], "error": { "code": -32000, "message": "boom" } }),
+                        hash if hash.ends_with("03") => json!({ "jsonrpc": "2.0", "id": request["id"], "result": null }),
+                        _ => json!({ "jsonrpc": "2.0", "id": request["id"], "result": receipt }),
+                    }
+                }).collect())
+            }
+        }).await;
+        let client = EvmClient::new(config, Chain::Optimism).await.unwrap()
+            .with_explorer_api(format!("{}/api", url));
+        
+        let history = client.get_transaction_history(&USER.parse().unwrap()).await.unwrap();
+        assert_eq!(history.len(), 25);
+        assert_eq!(batches.load(Ordering::SeqCst), 1);
+        assert_eq!(history[0].l1_fee, U256::exp10(16));
+        assert!(history[1].l1_fee.is_zero());
+        assert!(history[2].l1_fee.is_zero());
+        assert!(history[3..].iter().all(|tx| tx.l1_fee == U256::exp10(16)));
+    }
+    
+    #[tokio::test]
+    async fn test_first_block_since_searches_block_timestamps() {
+        use crate::chains::explorer::tests::{serve_rpc, StandIn};
+        use serde_json::{json, Value};
+        
+        // A block every 12 seconds from the epoch
+        let (_, config) = serve_rpc(Arc::new(StandIn::default()), 1, |request: Value| async move {
+            let result = match request["method"].as_str().unwrap() {
+                "eth_getBlockByNumber" => {
+                    let number = U64::from_str_radix(request["params"][0].as_str().unwrap().trim_start_matches("0x"), 16).unwrap();
+                    serde_json::to_value(Block::<H256> {
+                        number: Some(number),
+                        timestamp: U256::from(number.as_u64() * 12),
+                        ..Block::default()
+                    }).unwrap()
+                }
+                _ => json!("0x1"),
+            };
+            json!({ "jsonrpc": "2.0", "id": request["id"], "result": result })
+        }).await;
+        let client = EvmClient::new(config, Chain::Ethereum).await.unwrap();
+        
+        // 10:00 is block 3000 exactly; 10:00:05 rounds down to the hour it falls in
+        let ten = DateTime::from_timestamp(36_000, 0).unwrap();
+        assert_eq!(client.first_block_since(ten, 1_000_000).await.unwrap(), 3_000);
+        let later = DateTime::from_timestamp(36_005, 0).unwrap();
+        assert_eq!(client.first_block_since(later, 1_000_000).await.unwrap(), 3_000);
+        let eleven = DateTime::from_timestamp(39_600, 0).unwrap();
+        assert_eq!(client.first_block_since(eleven, 1_000_000).await.unwrap(), 3_300);
+    }
+    
+    #[test]
+    fn test_l1_data_fee_and_activity() {
+        let receipt = TransactionReceipt {
+            other: serde_json::from_value(serde_json::json!({ "l1Fee": "0x2386f26fc10000" })).unwrap(),
+            ..TransactionReceipt::default()
+        };
+        // 0.01 ETH of L1 data on OP Stack chains; already inside gasUsed on Arbitrum
+        assert_eq!(l1_data_fee(&Chain::Optimism, &receipt), U256::exp10(16));
+        assert_eq!(l1_data_fee(&Chain::Blast, &receipt), U256::exp10(16));
+        assert!(l1_data_fee(&Chain::Arbitrum, &receipt).is_zero());
+        
+        let user: Address = "0x00000000000000000000000000000000000000aa".parse().unwrap();
+        let tx = |hash: &str, from: &str| EVMTransaction {
+            hash: hash.to_string(),
+            from: from.to_string(),
+            to: None,
+            value: U256::zero(),
+            gas_used: U256::from(100_000),
+            gas_price: U256::from(1_000_000_000u64),
+            timestamp: Utc::now(),
+            block_number: 1,
+            input_data: Vec::new(),
+            status: true,
+            l1_fee: U256::exp10(14),
+        };
+        let history = vec![
+            tx("0x01", "0x00000000000000000000000000000000000000AA"),
+            // Internal transaction of the same hash adds no fee
+            tx("0x01", "0x00000000000000000000000000000000000000bb"),
+            tx("0x02", "0x00000000000000000000000000000000000000bb"),
+        ];
+        
+        let activity = transaction_activity(&user, &history, &[]);
+        assert_eq!(activity.len(), 2);
+        let fees: Decimal = activity.iter().map(|(_, fee)| *fee).sum();
+        assert_eq!(fees, Decimal::from_str("0.0002").unwrap());
+    }
+    
     #[tokio::test]
     async fn test_check_casino_tokens() {
         // This test would require a mock provider - shown as example structure
@@ -0,0 +1,546 @@
+use chrono::{DateTime, Utc};
+use ethers::types::U256;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+use tracing::{debug, warn};
+
+use crate::{
+    config::{ExplorerConfig, ExplorerEndpoint, ExplorerSettings},
+    models::{Chain, DegenScoreError, EVMNftTransfer, EVMTokenTransfer, EVMTransaction, NftStandard, Result},
+};
+
+// Etherscan refuses pages past the first 10,000 rows of a query
+const RESULT_WINDOW: u32 = 10_000;
+
+/// Complete history of one address as reported by a block explorer
+#[derive(Debug, Clone, Default)]
+pub struct ExplorerHistory {
+    pub transactions: Vec<EVMTransaction>,
+    pub internal_transactions: Vec<EVMTransaction>,
+    pub token_transfers: Vec<EVMTokenTransfer>,
+    pub nft_transfers: Vec<EVMNftTransfer>,
+}
+
+/// Client for Etherscan-compatible explorer APIs (Etherscan, Arbiscan, Optimistic
+/// Etherscan, Blastscan).
+///
+/// Requests are spaced to the endpoint's rate limit, and "rate limit reached"
+/// responses are retried with a growing delay.
+pub struct ExplorerClient {
+    chain: Chain,
+    base_url: String,
+    api_key: Option<String>,
+    http: reqwest::Client,
+    page_size: u32,
+    result_window: u32,
+    min_interval: Duration,
+    last_request: Mutex<Option<Instant>>,
+    max_retries: u32,
+    retry_delay: Duration,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExplorerResponse {
+    status: String,
+    message: String,
+    result: serde_json::Value,
+}
+
+// Union of the row formats of every list action; all values are strings
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct ExplorerRow {
+    block_number: String,
+    time_stamp: String,
+    hash: String,
+    from: String,
+    to: String,
+    value: String,
+    gas_used: String,
+    gas_price: String,
+    is_error: String,
+    input: String,
+    contract_address: String,
+    log_index: String,
+    #[serde(rename = "tokenID")]
+    token_id: String,
+    token_value: String,
+    trace_id: String,
+}
+
+impl ExplorerRow {
+    // Identity of a row, for dropping the overlap when a query restarts at a block
+    fn key(&self) -> String {
+        format!("{}:{}:{}:{}:{}:{}:{}", self.hash, self.log_index, self.trace_id, self.from, self.to, self.value, self.token_id)
+    }
+
+    fn block(&self) -> u64 {
+        self.block_number.parse().unwrap_or(0)
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.time_stamp.parse().ok()
+            .and_then(|secs| DateTime::from_timestamp(secs, 0))
+            .unwrap_or_default()
+    }
+
+    fn to_transaction(&self) -> EVMTransaction {
+        EVMTransaction {
+            hash: self.hash.clone(),
+            from: self.from.clone(),
+            // Contract creations have no recipient
+            to: (!self.to.is_empty()).then(|| self.to.clone()),
+            value: parse_u256(&self.value),
+            gas_used: parse_u256(&self.gas_used),
+            // Internal transactions carry no gas price of their own
+            gas_price: parse_u256(&self.gas_price),
+            timestamp: self.timestamp(),
+            block_number: self.block(),
+            input_data: hex::decode(self.input.trim_start_matches("0x")).unwrap_or_default(),
+            status: self.is_error != "1",
+            // Not reported by explorers; filled in from receipts where it applies
+            l1_fee: U256::zero(),
+        }
+    }
+
+    fn to_token_transfer(&self) -> EVMTokenTransfer {
+        EVMTokenTransfer {
+            token_address: self.contract_address.clone(),
+            from: self.from.clone(),
+            to: self.to.clone(),
+            value: parse_u256(&self.value),
+            tx_hash: self.hash.clone(),
+            log_index: self.log_index.parse().unwrap_or(0),
+            timestamp: self.timestamp(),
+        }
+    }
+
+    fn to_nft_transfer(&self, standard: NftStandard) -> EVMNftTransfer {
+        EVMNftTransfer {
+            token_address: self.contract_address.clone(),
+            token_id: parse_u256(&self.token_id),
+            standard,
+            from: self.from.clone(),
+            to: self.to.clone(),
+            amount: match standard {
+                NftStandard::Erc1155 => parse_u256(&self.token_value),
+                NftStandard::Erc721 => U256::one(),
+                NftStandard::Metaplex => unreachable!("explorer rows are EVM token transfers"),
+            },
+            tx_hash: self.hash.clone(),
+            block_number: self.block(),
+            log_index: self.log_index.parse().unwrap_or(0),
+            timestamp: Some(self.timestamp()),
+        }
+    }
+}
+
+fn parse_u256(value: &str) -> U256 {
+    U256::from_dec_str(value).unwrap_or_default()
+}
+
+impl ExplorerClient {
+    pub fn new(chain: Chain, base_url: String) -> Self {
+        Self {
+            chain,
+            base_url,
+            api_key: None,
+            http: reqwest::Client::builder()
+                .timeout(Duration::from_secs(30))
+                .build()
+                .unwrap_or_default(),
+            page_size: ExplorerSettings::default().page_size,
+            result_window: RESULT_WINDOW,
+            min_interval: Duration::from_millis(200),
+            last_request: Mutex::new(None),
+            max_retries: 3,
+            retry_delay: Duration::from_secs(1),
+        }
+    }
+
+    pub fn from_endpoint(chain: Chain, endpoint: &ExplorerEndpoint) -> Self {
+        let mut client = Self::new(chain, endpoint.base_url.clone())
+            .with_rate_limit(endpoint.rate_limit.requests_per_second);
+        client.api_key = endpoint.api_key.clone();
+        client
+    }
+
+    /// Explorer for `chain` per the settings, or `None` if disabled or the chain has no endpoint
+    pub fn from_settings(chain: &Chain, settings: &ExplorerSettings) -> Option<Self> {
+        if !settings.enabled {
+            return None;
+        }
+
+        let defaults = ExplorerConfig::default();
+        let mut client = match (settings.base_urls.get(chain.as_str()), defaults.for_chain(chain)) {
+            (Some(url), Some(endpoint)) => Self::from_endpoint(chain.clone(), endpoint).with_base_url(url.clone()),
+            (Some(url), None) => Self::new(chain.clone(), url.clone()),
+            (None, Some(endpoint)) => Self::from_endpoint(chain.clone(), endpoint),
+            (None, None) => return None,
+        };
+        if let Some(key) = settings.api_keys.get(chain.as_str()) {
+            client = client.with_api_key(key.clone());
+        }
+
+        Some(client.with_page_size(settings.page_size))
+    }
+
+    pub fn with_api_key(mut self, api_key: String) -> Self {
+        self.api_key = Some(api_key);
+        self
+    }
+
+    pub fn with_base_url(mut self, base_url: String) -> Self {
+        self.base_url = base_url;
+        self
+    }
+
+    pub fn with_page_size(mut self, page_size: u32) -> Self {
+        self.page_size = page_size.clamp(1, RESULT_WINDOW);
+        self
+    }
+
+    pub fn with_rate_limit(mut self, requests_per_second: f64) -> Self {
+        if requests_per_second > 0.0 {
+            self.min_interval = Duration::from_secs_f64(1.0 / requests_per_second);
+        }
+        self
+    }
+
+    pub fn chain(&self) -> &Chain {
+        &self.chain
+    }
+
+    /// Normal, internal, ERC-20, ERC-721 and ERC-1155 history of `address`, oldest first
+    pub async fn history(&self, address: &str) -> Result<ExplorerHistory> {
+        let mut nft_transfers = self.erc721_transfers(address).await?;
+        nft_transfers.extend(self.erc1155_transfers(address).await?);
+
+        Ok(ExplorerHistory {
+            transactions: self.transactions(address).await?,
+            internal_transactions: self.internal_transactions(address).await?,
+            token_transfers: self.erc20_transfers(address).await?,
+            nft_transfers,
+        })
+    }
+
+    pub async fn transactions(&self, address: &str) -> Result<Vec<EVMTransaction>> {
+        let rows = self.list("txlist", address).await?;
+        Ok(rows.iter().map(ExplorerRow::to_transaction).collect())
+    }
+
+    /// Value transfers made by contracts on the address's behalf
+    pub async fn internal_transactions(&self, address: &str) -> Result<Vec<EVMTransaction>> {
+        let rows = self.list("txlistinternal", address).await?;
+        Ok(rows.iter().map(ExplorerRow::to_transaction).collect())
+    }
+
+    pub async fn erc20_transfers(&self, address: &str) -> Result<Vec<EVMTokenTransfer>> {
+        let rows = self.list("tokentx", address).await?;
+        Ok(rows.iter().map(ExplorerRow::to_token_transfer).collect())
+    }
+
+    pub async fn erc721_transfers(&self, address: &str) -> Result<Vec<EVMNftTransfer>> {
+        let rows = self.list("tokennfttx", address).await?;
+        Ok(rows.iter().map(|row| row.to_nft_transfer(NftStandard::Erc721)).collect())
+    }
+
+    pub async fn erc1155_transfers(&self, address: &str) -> Result<Vec<EVMNftTransfer>> {
+        let rows = self.list("token1155tx", address).await?;
+        Ok(rows.iter().map(|row| row.to_nft_transfer(NftStandard::Erc1155)).collect())
+    }
+
+    // Page through every row of a list action. Once the result window is exhausted the
+    // query restarts at the last block seen, dropping rows already collected.
+    async fn list(&self, action: &str, address: &str) -> Result<Vec<ExplorerRow>> {
+        let mut rows = Vec::new();
+        let mut seen = HashSet::new();
+        let mut start_block = 0u64;
+        let mut page = 1u32;
+
+        loop {
+            let batch = self.page(action, address, start_block, page).await?;
+            let full = batch.len() as u32 >= self.page_size;
+
+            let mut added = 0;
+            for row in batch {
+                if seen.insert(row.key()) {
+                    rows.push(row);
+                    added += 1;
+                }
+            }
+            debug!("Explorer {} page {} from block {}: {} new rows", action, page, start_block, added);
+
+            if !full {
+                break;
+            }
+            if (page + 1) * self.page_size > self.result_window {
+                let last_block = rows.last().map(ExplorerRow::block).unwrap_or(start_block);
+                if added == 0 || last_block == start_block {
+                    warn!("Explorer {} for {} stalled at block {}; history may be incomplete", action, address, start_block);
+                    break;
+                }
+                start_block = last_block;
+                page = 1;
+            } else {
+                page += 1;
+            }
+        }
+
+        Ok(rows)
+    }
+
+    async fn page(&self, action: &str, address: &str, start_block: u64, page: u32) -> Result<Vec<ExplorerRow>> {
+        let mut query = vec![
+            ("module", "account".to_string()),
+            ("action", action.to_string()),
+            ("address", address.to_string()),
+            // No `endblock`: explorers default it to the latest block, and any fixed cap
+            // falls behind chains like Arbitrum that are already past block 1e8
+            ("startblock", start_block.to_string()),
+            ("page", page.to_string()),
+            ("offset", self.page_size.to_string()),
+            ("sort", "asc".to_string()),
+        ];
+        if let Some(key) = &self.api_key {
+            query.push(("apikey", key.clone()));
+        }
+
+        let mut attempt = 0;
+        loop {
+            self.throttle().await;
+            let response: ExplorerResponse = self.http.get(&self.base_url)
+                .query(&query)
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+
+            if response.status == "1" {
+                return Ok(serde_json::from_value(response.result)?);
+            }
+
+            let detail = response.result.as_str().unwrap_or_default().to_string();
+            if response.message.starts_with("No ") || response.result.as_array().is_some_and(|rows| rows.is_empty()) {
+                return Ok(Vec::new());
+            }
+            if !detail.to_lowercase().contains("rate limit") {
+                return Err(DegenScoreError::RpcError {
+                    chain: self.chain.as_str().to_string(),
+                    message: format!("Explorer {} failed: {} {}", action, response.message, detail),
+                });
+            }
+
+            attempt += 1;
+            if attempt > self.max_retries {
+                return Err(DegenScoreError::RateLimitExceeded {
+                    service: format!("{} explorer", self.chain.as_str()),
+                });
+            }
+            debug!("Explorer rate limited, retry {} of {}", attempt, self.max_retries);
+            tokio::time::sleep(self.retry_delay * attempt).await;
+        }
+    }
+
+    // Space requests at least `min_interval` apart across concurrent callers
+    async fn throttle(&self) {
+        let mut last = self.last_request.lock().await;
+        if let Some(previous) = *last {
+            tokio::time::sleep_until(previous + self.min_interval).await;
+        }
+        *last = Some(Instant::now());
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::chains::client::ChainClientConfig;
+    use axum::{extract::{Query, State}, routing::{get, post}, Json, Router};
+    use serde_json::{json, Value};
+    use std::collections::HashMap;
+    use std::future::Future;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    pub(crate) const USER: &str = "0x742d35cc6634c0532925a3b844bc9e7595f6e842";
+
+    pub(crate) struct StandIn {
+        pub requests: AtomicUsize,
+        // Requests to answer with "rate limit reached" before serving normally
+        pub rate_limited: AtomicUsize,
+        pub window: usize,
+        // Rows sit in the blocks right after this one
+        pub first_block: u64,
+    }
+
+    impl Default for StandIn {
+        fn default() -> Self {
+            Self {
+                requests: AtomicUsize::new(0),
+                rate_limited: AtomicUsize::new(0),
+                window: RESULT_WINDOW as usize,
+                first_block: 0,
+            }
+        }
+    }
+
+    // Rows of an action: one per block after `first_block`, `count` of them, for every action
+    fn rows(action: &str, first_block: u64, count: u64) -> Vec<Value> {
+        (first_block + 1..=first_block + count).map(|block| match action {
+            "txlist" => json!({
+                "blockNumber": block.to_string(), "timeStamp": (1_700_000_000 + block * 12).to_string(),
+                "hash": format!("0x{:064x}", block), "from": USER, "to": "0x00000000000000000000000000000000000000aa",
+                "value": "1000", "gasUsed": "21000", "gasPrice": "20000000000", "isError": if block == first_block + 2 { "1" } else { "0" },
+                "input": "0x",
+            }),
+            "txlistinternal" => json!({
+                "blockNumber": block.to_string(), "timeStamp": "1700000000", "hash": format!("0x{:064x}", block),
+                "from": "0x00000000000000000000000000000000000000aa", "to": USER, "value": "5", "traceId": "0_1",
+                "contractAddress": "", "input": "", "isError": "0",
+            }),
+            "tokentx" => json!({
+                "blockNumber": block.to_string(), "timeStamp": "1700000000", "hash": format!("0x{:064x}", block),
+                "from": USER, "to": "0x00000000000000000000000000000000000000bb", "value": "42",
+                "contractAddress": "0xaf88d065e77c8cc2239327c5edb3a432268e5831", "logIndex": "3",
+            }),
+            _ => json!({
+                "blockNumber": block.to_string(), "timeStamp": "1700000000", "hash": format!("0x{:064x}", block),
+                "from": "0x0000000000000000000000000000000000000000", "to": USER, "tokenID": block.to_string(), "tokenValue": "4",
+                "contractAddress": "0xbc4ca0eda7647a8ab7c2061c2e118a18a936f13d", "logIndex": "0",
+            }),
+        }).collect()
+    }
+
+    async fn api(State(state): State<Arc<StandIn>>, Query(params): Query<HashMap<String, String>>) -> Json<Value> {
+        state.requests.fetch_add(1, Ordering::SeqCst);
+        if state.rate_limited.load(Ordering::SeqCst) > 0 {
+            state.rate_limited.fetch_sub(1, Ordering::SeqCst);
+            return Json(json!({ "status": "0", "message": "NOTOK", "result": "Max rate limit reached" }));
+        }
+        if params.get("apikey").map(String::as_str) == Some("bad") {
+            return Json(json!({ "status": "0", "message": "NOTOK", "result": "Invalid API Key" }));
+        }
+
+        let action = params["action"].as_str();
+        let total = match action { "txlist" => 25, "tokentx" => 7, "tokennfttx" => 3, "token1155tx" => 2, _ => 0 };
+        let start: u64 = params["startblock"].parse().unwrap();
+        let end: u64 = params.get("endblock").map_or(u64::MAX, |end| end.parse().unwrap());
+        let page: usize = params["page"].parse().unwrap();
+        let offset: usize = params["offset"].parse().unwrap();
+
+        let matching: Vec<Value> = rows(action, state.first_block, total)
+            .into_iter()
+            .filter(|row| (start..=end).contains(&row["blockNumber"].as_str().unwrap().parse::<u64>().unwrap()))
+            .collect();
+        // Mirror Etherscan's result window
+        if page * offset > state.window {
+            return Json(json!({ "status": "0", "message": "NOTOK", "result": "Result window is too large" }));
+        }
+        let result: Vec<Value> = matching.into_iter().skip((page - 1) * offset).take(offset).collect();
+        if result.is_empty() {
+            return Json(json!({ "status": "0", "message": "No transactions found", "result": [] }));
+        }
+        Json(json!({ "status": "1", "message": "OK", "result": result }))
+    }
+
+    /// Serve a stand-in explorer at `/api`, plus any extra routes; returns its base URL
+    pub(crate) async fn serve(state: Arc<StandIn>, extra: Router) -> String {
+        let app = Router::new().route("/api", get(api)).with_state(state).merge(extra);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        url
+    }
+
+    /// Serve a stand-in explorer along with a JSON-RPC node at `/rpc` whose responses
+    /// `rpc` builds from each request; returns the base URL and a client config for the node
+    pub(crate) async fn serve_rpc<F, Fut>(state: Arc<StandIn>, chain_id: u64, rpc: F) -> (String, ChainClientConfig)
+    where
+        F: Fn(Value) -> Fut + Clone + Send + Sync + 'static,
+        Fut: Future<Output = Value> + Send + 'static,
+    {
+        let node = Router::new().route("/rpc", post(move |Json(request): Json<Value>| {
+            let rpc = rpc.clone();
+            async move { Json(rpc(request).await) }
+        }));
+        let url = serve(state, node).await;
+        let config = ChainClientConfig {
+            rpc_url: format!("{}/rpc", url),
+            chain_id: Some(chain_id),
+            timeout_seconds: 5,
+            max_retries: 0,
+            rate_limit_per_second: 100.0,
+        };
+        (url, config)
+    }
+
+    fn client(url: &str) -> ExplorerClient {
+        ExplorerClient::new(Chain::Ethereum, format!("{}/api", url)).with_rate_limit(1_000.0)
+    }
+
+    #[tokio::test]
+    async fn test_history_pages_through_every_action() {
+        let state = Arc::new(StandIn { window: 12, ..StandIn::default() });
+        let url = serve(state.clone(), Router::new()).await;
+
+        // Pages of 4 under a 12-row window force restarts from the last block seen
+        let mut explorer = client(&url).with_page_size(4);
+        explorer.result_window = 12;
+        let history = explorer.history(USER).await.unwrap();
+
+        let blocks: Vec<u64> = history.transactions.iter().map(|tx| tx.block_number).collect();
+        assert_eq!(blocks, (1..=25).collect::<Vec<_>>());
+        assert!(!history.transactions[1].status);
+        assert_eq!(history.transactions[0].gas_used, U256::from(21_000));
+        assert!(history.internal_transactions.is_empty());
+        assert_eq!(history.token_transfers.len(), 7);
+        assert_eq!(history.token_transfers[0].value, U256::from(42));
+        assert_eq!(history.nft_transfers[2].token_id, U256::from(3));
+        assert_eq!(history.nft_transfers[2].to, USER);
+        assert_eq!(history.nft_transfers[2].amount, U256::one());
+        assert_eq!(history.nft_transfers.len(), 5);
+        assert_eq!(history.nft_transfers[4].standard, NftStandard::Erc1155);
+        assert_eq!(history.nft_transfers[4].amount, U256::from(4));
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_retries_and_api_errors() {
+        let state = Arc::new(StandIn { rate_limited: AtomicUsize::new(2), ..StandIn::default() });
+        let url = serve(state.clone(), Router::new()).await;
+
+        let mut explorer = client(&url);
+        explorer.retry_delay = Duration::from_millis(10);
+        assert_eq!(explorer.erc20_transfers(USER).await.unwrap().len(), 7);
+        assert_eq!(state.requests.load(Ordering::SeqCst), 3);
+
+        // Requests are spaced to the configured rate
+        let slow = client(&url).with_rate_limit(20.0);
+        let started = std::time::Instant::now();
+        slow.erc721_transfers(USER).await.unwrap();
+        slow.erc721_transfers(USER).await.unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(50));
+
+        state.rate_limited.store(10, Ordering::SeqCst);
+        let err = explorer.transactions(USER).await.unwrap_err();
+        assert!(matches!(err, DegenScoreError::RateLimitExceeded { .. }));
+
+        state.rate_limited.store(0, Ordering::SeqCst);
+        let err = client(&url).with_api_key("bad".to_string()).transactions(USER).await.unwrap_err();
+        assert!(err.to_string().contains("Invalid API Key"));
+    }
+
+    #[tokio::test]
+    async fn test_history_reaches_blocks_past_1e8() {
+        let state = Arc::new(StandIn { first_block: 250_000_000, ..StandIn::default() });
+        let url = serve(state, Router::new()).await;
+
+        let transactions = client(&url).transactions(USER).await.unwrap();
+        assert_eq!(transactions.len(), 25);
+        assert_eq!(transactions[0].block_number, 250_000_001);
+    }
+}
@@ -1,12 +1,30 @@
 pub mod evm;
-// pub mod solana;  // Disabled due to dependency conflicts
+#[cfg(feature = "solana-native")]
+pub mod solana;
 pub mod solana_mock;
 pub mod solana_rpc;
 pub mod client;
 pub mod resilience;
+pub mod checkpoint;
+pub mod scanner;
+pub mod explorer;
+pub mod nft;
+pub mod nft_valuation;
+pub mod pricing;
+pub mod portfolio;
+pub mod solana_history;
+pub mod solana_nft;
+pub mod solana_protocols;
+pub mod solana_tx;
 
 pub use client::ChainClient;
 pub use evm::EvmClient;
-// Use JSON-RPC client to avoid dependency conflicts
+// JSON-RPC client by default; the typed solana-rpc-client one with `solana-native`
+#[cfg(not(feature = "solana-native"))]
 pub use solana_rpc::SolanaRpcClient as SolanaClient;
-pub use resilience::{CircuitBreaker, ResilientRpcClient, RetryConfig, CircuitBreakerConfig};
\ No newline at end of file
+#[cfg(feature = "solana-native")]
+pub use solana::SolanaClient;
+pub use resilience::{CircuitBreaker, ResilientRpcClient, RetryConfig, CircuitBreakerConfig};
+pub use scanner::LogScanner;
+pub use explorer::ExplorerClient;
+pub use pricing::PriceOracle;
\ No newline at end of file
@@ -0,0 +1,245 @@
+use base64::Engine;
+use ethers::{
+    abi::{decode, ParamType, Token},
+    types::{Address, Log, H160, H256, U256},
+    utils::keccak256,
+};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::models::{EVMNftTransfer, EventSignatures, NftStandard};
+
+/// A token an address holds according to its transfer history
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NftHolding {
+    /// Lowercase contract address
+    pub contract: String,
+    pub token_id: U256,
+    pub standard: NftStandard,
+    pub balance: U256,
+}
+
+pub fn topic(signature: &str) -> H256 {
+    H256::from(keccak256(signature.as_bytes()))
+}
+
+/// NFT transfers in `logs`: ERC-721 `Transfer` (token id in topic3) and ERC-1155
+/// `TransferSingle`/`TransferBatch`. ERC-20 transfers and malformed logs are skipped.
+pub fn transfers_from_logs(logs: &[Log]) -> Vec<EVMNftTransfer> {
+    let transfer = topic(EventSignatures::ERC721_TRANSFER);
+    let single = topic(EventSignatures::ERC1155_TRANSFER_SINGLE);
+    let batch = topic(EventSignatures::ERC1155_TRANSFER_BATCH);
+
+    let mut transfers = Vec::new();
+    for log in logs {
+        let Some(&event) = log.topics.first() else { continue };
+        let entry = |token_id: U256, amount: U256, standard: NftStandard, from: H256, to: H256| EVMNftTransfer {
+            token_address: format!("{:?}", log.address),
+            token_id,
+            standard,
+            from: format!("{:?}", H160::from(from)),
+            to: format!("{:?}", H160::from(to)),
+            amount,
+            tx_hash: format!("{:?}", log.transaction_hash.unwrap_or_default()),
+            block_number: log.block_number.unwrap_or_default().as_u64(),
+            log_index: log.log_index.unwrap_or_default().as_u64(),
+            timestamp: None,
+        };
+
+        // ERC-20 Transfer shares the signature but indexes only from and to
+        if event == transfer && log.topics.len() == 4 {
+            let token_id = U256::from_big_endian(log.topics[3].as_bytes());
+            transfers.push(entry(token_id, U256::one(), NftStandard::Erc721, log.topics[1], log.topics[2]));
+        } else if event == single && log.topics.len() == 4 && log.data.len() >= 64 {
+            let token_id = U256::from_big_endian(&log.data[..32]);
+            let amount = U256::from_big_endian(&log.data[32..64]);
+            transfers.push(entry(token_id, amount, NftStandard::Erc1155, log.topics[2], log.topics[3]));
+        } else if event == batch && log.topics.len() == 4 {
+            let array = ParamType::Array(Box::new(ParamType::Uint(256)));
+            let Ok(tokens) = decode(&[array.clone(), array], &log.data) else { continue };
+            let (Some(Token::Array(ids)), Some(Token::Array(amounts))) = (tokens.first(), tokens.get(1)) else { continue };
+            for (id, amount) in ids.iter().zip(amounts) {
+                if let (Some(id), Some(amount)) = (id.clone().into_uint(), amount.clone().into_uint()) {
+                    transfers.push(entry(id, amount, NftStandard::Erc1155, log.topics[2], log.topics[3]));
+                }
+            }
+        }
+    }
+
+    transfers
+}
+
+/// Replay `transfers` in chain order and return what `owner` still holds
+pub fn reconstruct_holdings(owner: &Address, transfers: &[EVMNftTransfer]) -> Vec<NftHolding> {
+    let owner = format!("{:?}", owner);
+    let mut ordered: Vec<&EVMNftTransfer> = transfers.iter().collect();
+    ordered.sort_by_key(|t| (t.block_number, t.log_index));
+
+    // The same event shows up in both the incoming and outgoing queries of a self-transfer
+    let mut seen = HashSet::new();
+    let mut balances: BTreeMap<(String, U256), (NftStandard, U256)> = BTreeMap::new();
+    for transfer in ordered {
+        let contract = transfer.token_address.to_lowercase();
+        if !seen.insert((transfer.tx_hash.clone(), transfer.log_index, contract.clone(), transfer.token_id)) {
+            continue;
+        }
+
+        let entry = balances.entry((contract, transfer.token_id)).or_insert((transfer.standard, U256::zero()));
+        if transfer.to.eq_ignore_ascii_case(&owner) {
+            entry.1 = entry.1.saturating_add(transfer.amount);
+        }
+        // Saturates when the acquisition predates the scanned range
+        if transfer.from.eq_ignore_ascii_case(&owner) {
+            entry.1 = entry.1.saturating_sub(transfer.amount);
+        }
+    }
+
+    balances
+        .into_iter()
+        .filter(|(_, (_, balance))| !balance.is_zero())
+        .map(|((contract, token_id), (standard, balance))| NftHolding { contract, token_id, standard, balance })
+        .collect()
+}
+
+/// ABI-decode the string returned by `tokenURI`/`uri`
+pub fn decode_string(data: &[u8]) -> Option<String> {
+    decode(&[ParamType::String], data).ok()?.pop()?.into_string()
+}
+
+/// HTTP location of a token's metadata. ERC-1155 `{id}` placeholders are expanded, and
+/// IPFS and Arweave URIs go through gateways; `data:` URIs have no location.
+pub fn metadata_url(uri: &str, token_id: U256, ipfs_gateway: &str) -> Option<String> {
+    let uri = uri.trim().replace("{id}", &format!("{:064x}", token_id));
+
+    if let Some(path) = uri.strip_prefix("ipfs://") {
+        let path = path.strip_prefix("ipfs/").unwrap_or(path);
+        return Some(format!("{}/{}", ipfs_gateway.trim_end_matches('/'), path));
+    }
+    if let Some(path) = uri.strip_prefix("ar://") {
+        return Some(format!("https://arweave.net/{}", path));
+    }
+    if uri.starts_with("http://") || uri.starts_with("https://") {
+        return Some(uri);
+    }
+    None
+}
+
+/// Metadata embedded in a `data:application/json` URI, as used by on-chain collections
+pub fn decode_data_uri(uri: &str) -> Option<HashMap<String, Value>> {
+    let (header, payload) = uri.strip_prefix("data:")?.split_once(',')?;
+    if !header.starts_with("application/json") {
+        return None;
+    }
+
+    let json = if header.ends_with(";base64") {
+        String::from_utf8(base64::engine::general_purpose::STANDARD.decode(payload).ok()?).ok()?
+    } else {
+        payload.to_string()
+    };
+    serde_json::from_str(&json).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::abi::encode;
+    use ethers::types::U64;
+
+    const OWNER: &str = "0x00000000000000000000000000000000000000aa";
+    const OTHER: &str = "0x00000000000000000000000000000000000000bb";
+
+    fn address_topic(address: &str) -> H256 {
+        H256::from(address.parse::<Address>().unwrap())
+    }
+
+    fn log(contract: &str, block: u64, topics: Vec<H256>, data: Vec<u8>) -> Log {
+        Log {
+            address: contract.parse().unwrap(),
+            topics,
+            data: data.into(),
+            block_number: Some(U64::from(block)),
+            log_index: Some(U256::zero()),
+            transaction_hash: Some(H256::from_low_u64_be(block)),
+            ..Log::default()
+        }
+    }
+
+    fn erc721(block: u64, from: &str, to: &str, id: u64) -> Log {
+        let topics = vec![topic(EventSignatures::ERC721_TRANSFER), address_topic(from), address_topic(to), H256::from_low_u64_be(id)];
+        log("0x00000000000000000000000000000000000000c1", block, topics, Vec::new())
+    }
+
+    #[test]
+    fn test_holdings_from_erc721_and_erc1155_logs() {
+        let operator = address_topic(OTHER);
+        let single = |block, from, to, id: u64, amount: u64| {
+            let topics = vec![topic(EventSignatures::ERC1155_TRANSFER_SINGLE), operator, address_topic(from), address_topic(to)];
+            log("0x00000000000000000000000000000000000000c2", block, topics, encode(&[Token::Uint(id.into()), Token::Uint(amount.into())]))
+        };
+        let batch_data = encode(&[
+            Token::Array(vec![Token::Uint(7.into()), Token::Uint(8.into())]),
+            Token::Array(vec![Token::Uint(3.into()), Token::Uint(1.into())]),
+        ]);
+        let batch_topics = vec![topic(EventSignatures::ERC1155_TRANSFER_BATCH), operator, address_topic(OTHER), address_topic(OWNER)];
+        let erc20 = log(
+            "0x00000000000000000000000000000000000000c3",
+            1,
+            vec![topic(EventSignatures::ERC20_TRANSFER), address_topic(OTHER), address_topic(OWNER)],
+            encode(&[Token::Uint(100.into())]),
+        );
+
+        let logs = vec![
+            // Listed out of order: token 1 is bought, then sold; token 2 is kept
+            erc721(5, OWNER, OTHER, 1),
+            erc721(2, OTHER, OWNER, 1),
+            erc721(3, OTHER, OWNER, 2),
+            // Token 2 again from the incoming query of the same scan
+            erc721(3, OTHER, OWNER, 2),
+            single(4, OTHER, OWNER, 9, 5),
+            single(6, OWNER, OTHER, 9, 2),
+            log("0x00000000000000000000000000000000000000c2", 7, batch_topics, batch_data),
+            erc20,
+        ];
+
+        let transfers = transfers_from_logs(&logs);
+        assert_eq!(transfers.len(), 8);
+
+        let holdings = reconstruct_holdings(&OWNER.parse().unwrap(), &transfers);
+        let summary: Vec<(u64, NftStandard, u64)> = holdings
+            .iter()
+            .map(|h| (h.token_id.as_u64(), h.standard, h.balance.as_u64()))
+            .collect();
+        assert_eq!(summary, vec![
+            (2, NftStandard::Erc721, 1),
+            (7, NftStandard::Erc1155, 3),
+            (8, NftStandard::Erc1155, 1),
+            (9, NftStandard::Erc1155, 3),
+        ]);
+    }
+
+    #[test]
+    fn test_metadata_locations() {
+        let gateway = "https://ipfs.io/ipfs/";
+        assert_eq!(
+            metadata_url("ipfs://QmHash/1.json", U256::one(), gateway).unwrap(),
+            "https://ipfs.io/ipfs/QmHash/1.json"
+        );
+        assert_eq!(
+            metadata_url("ipfs://ipfs/QmHash", U256::one(), gateway).unwrap(),
+            "https://ipfs.io/ipfs/QmHash"
+        );
+        assert_eq!(
+            metadata_url("https://api.example.com/{id}.json", U256::from(255), gateway).unwrap(),
+            format!("https://api.example.com/{}ff.json", "0".repeat(62))
+        );
+        assert!(metadata_url("data:application/json,{}", U256::one(), gateway).is_none());
+
+        let encoded = base64::engine::general_purpose::STANDARD.encode(r#"{"name":"Punk #1"}"#);
+        let metadata = decode_data_uri(&format!("data:application/json;base64,{}", encoded)).unwrap();
+        assert_eq!(metadata["name"], "Punk #1");
+        assert!(decode_data_uri(r#"data:application/json;utf8,{"name":"x"}"#).is_some());
+        assert!(decode_data_uri("data:image/svg+xml;base64,PHN2Zz4=").is_none());
+
+        assert_eq!(decode_string(&encode(&[Token::String("ipfs://Qm".to_string())])).unwrap(), "ipfs://Qm");
+    }
+}
@@ -0,0 +1,551 @@
+use ethers::{
+    abi::{decode, ParamType, Token},
+    types::{Address, Log, H256, U256},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use crate::chains::nft::topic;
+use crate::models::{EventSignatures, ProtocolAddresses};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Marketplace {
+    Seaport,
+    Blur,
+    X2Y2,
+    LooksRare,
+}
+
+impl Marketplace {
+    /// Marketplace whose exchange contract is `address`
+    pub fn from_address(address: &Address) -> Option<Self> {
+        let address = format!("{:?}", address);
+        let exchanges = [
+            (ProtocolAddresses::OPENSEA_SEAPORT, Marketplace::Seaport),
+            (ProtocolAddresses::OPENSEA_SEAPORT_1_6, Marketplace::Seaport),
+            (ProtocolAddresses::BLUR_EXCHANGE, Marketplace::Blur),
+            (ProtocolAddresses::X2Y2_EXCHANGE, Marketplace::X2Y2),
+            (ProtocolAddresses::LOOKSRARE_EXCHANGE, Marketplace::LooksRare),
+        ];
+        exchanges
+            .iter()
+            .find(|(exchange, _)| exchange.eq_ignore_ascii_case(&address))
+            .map(|(_, marketplace)| *marketplace)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Marketplace::Seaport => "OpenSea",
+            Marketplace::Blur => "Blur",
+            Marketplace::X2Y2 => "X2Y2",
+            Marketplace::LooksRare => "LooksRare",
+        }
+    }
+}
+
+/// One NFT sold on a marketplace
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NftSale {
+    pub marketplace: Marketplace,
+    pub collection: Address,
+    pub token_id: U256,
+    /// Price of a single copy in wei, before marketplace and creator fees
+    pub price_wei: U256,
+    pub block_number: u64,
+    pub log_index: u64,
+    pub tx_hash: H256,
+}
+
+// (collection, token id, copies) sold for a total price
+struct Lot {
+    items: Vec<(Address, U256, U256)>,
+    total_wei: U256,
+}
+
+/// Sales decoded from marketplace events in `logs`. Only sales paid in ETH, WETH or
+/// Blur Pool ETH are returned; bundles are split evenly across their tokens. Blur's V2
+/// exchange emits packed `Execution721*` events, which are not decoded.
+pub fn sales_from_logs(logs: &[Log]) -> Vec<NftSale> {
+    let seaport = topic(EventSignatures::SEAPORT_ORDER_FULFILLED);
+    let blur = topic(EventSignatures::BLUR_ORDERS_MATCHED);
+    let looksrare = [
+        topic(EventSignatures::LOOKSRARE_TAKER_BID),
+        topic(EventSignatures::LOOKSRARE_TAKER_ASK),
+    ];
+    let x2y2 = topic(EventSignatures::X2Y2_INVENTORY);
+
+    let mut sales = Vec::new();
+    for log in logs {
+        let (Some(marketplace), Some(&event)) = (Marketplace::from_address(&log.address), log.topics.first()) else {
+            continue;
+        };
+        let lot = match marketplace {
+            Marketplace::Seaport if event == seaport => seaport_lot(&log.data),
+            Marketplace::Blur if event == blur => blur_lot(&log.data),
+            Marketplace::LooksRare if looksrare.contains(&event) => looksrare_lot(&log.data),
+            Marketplace::X2Y2 if event == x2y2 => x2y2_lot(&log.data),
+            _ => None,
+        };
+        let Some(lot) = lot else { continue };
+
+        let copies = lot.items.iter().fold(U256::zero(), |sum, (_, _, copies)| sum + (*copies).max(U256::one()));
+        if copies.is_zero() || lot.total_wei.is_zero() {
+            continue;
+        }
+        for (collection, token_id, _) in lot.items {
+            sales.push(NftSale {
+                marketplace,
+                collection,
+                token_id,
+                price_wei: lot.total_wei / copies,
+                block_number: log.block_number.unwrap_or_default().as_u64(),
+                log_index: log.log_index.unwrap_or_default().as_u64(),
+                tx_hash: log.transaction_hash.unwrap_or_default(),
+            });
+        }
+    }
+
+    sales
+}
+
+/// Whether a payment in `currency` is worth its amount in ETH
+fn is_eth(currency: &Address) -> bool {
+    let equivalents = [
+        ProtocolAddresses::WETH_ETH,
+        ProtocolAddresses::WETH_ARB,
+        ProtocolAddresses::WETH_OPT,
+        ProtocolAddresses::WETH_BLAST,
+        ProtocolAddresses::BLUR_POOL,
+    ];
+    let address = format!("{:?}", currency);
+    currency.is_zero() || equivalents.iter().any(|eth| eth.eq_ignore_ascii_case(&address))
+}
+
+fn uint(token: &Token) -> Option<U256> {
+    token.clone().into_uint()
+}
+
+fn address(token: &Token) -> Option<Address> {
+    token.clone().into_address()
+}
+
+fn tuple(token: &Token) -> Option<Vec<Token>> {
+    token.clone().into_tuple()
+}
+
+fn array(token: &Token) -> Option<Vec<Token>> {
+    token.clone().into_array()
+}
+
+/// `OrderFulfilled`: whichever side carries NFTs was sold for the other side's payments.
+/// Accepted listings offer NFTs for ETH; accepted bids offer WETH, with fees paid out of it.
+fn seaport_lot(data: &[u8]) -> Option<Lot> {
+    let spent = ParamType::Tuple(vec![ParamType::Uint(8), ParamType::Address, ParamType::Uint(256), ParamType::Uint(256)]);
+    let received = ParamType::Tuple(vec![
+        ParamType::Uint(8),
+        ParamType::Address,
+        ParamType::Uint(256),
+        ParamType::Uint(256),
+        ParamType::Address,
+    ]);
+    let tokens = decode(
+        &[ParamType::FixedBytes(32), ParamType::Address, ParamType::Array(Box::new(spent)), ParamType::Array(Box::new(received))],
+        data,
+    )
+    .ok()?;
+
+    // (item type, token, identifier, amount); types 2-5 are ERC-721/ERC-1155, plain or by criteria
+    let items = |token: &Token| -> Option<Vec<(u64, Address, U256, U256)>> {
+        array(token)?
+            .iter()
+            .map(|item| {
+                let fields = tuple(item)?;
+                Some((uint(&fields[0])?.low_u64(), address(&fields[1])?, uint(&fields[2])?, uint(&fields[3])?))
+            })
+            .collect()
+    };
+    let offer = items(&tokens[2])?;
+    let consideration = items(&tokens[3])?;
+    let is_nft = |kind: u64| (2..=5).contains(&kind);
+
+    let (nfts, payments) = if offer.iter().any(|item| is_nft(item.0)) {
+        (offer, consideration)
+    } else {
+        (consideration, offer)
+    };
+
+    let mut total_wei = U256::zero();
+    for (_, currency, _, amount) in payments.iter().filter(|item| !is_nft(item.0)) {
+        if !is_eth(currency) {
+            return None;
+        }
+        total_wei = total_wei.saturating_add(*amount);
+    }
+
+    let items = nfts
+        .into_iter()
+        .filter(|item| is_nft(item.0))
+        .map(|(_, collection, token_id, copies)| (collection, token_id, copies))
+        .collect();
+    Some(Lot { items, total_wei })
+}
+
+/// `OrdersMatched`: the sell order names the token, its price and the payment token
+fn blur_lot(data: &[u8]) -> Option<Lot> {
+    let order = ParamType::Tuple(vec![
+        ParamType::Address,
+        ParamType::Uint(8),
+        ParamType::Address,
+        ParamType::Address,
+        ParamType::Uint(256),
+        ParamType::Uint(256),
+        ParamType::Address,
+        ParamType::Uint(256),
+        ParamType::Uint(256),
+        ParamType::Uint(256),
+        ParamType::Array(Box::new(ParamType::Tuple(vec![ParamType::Uint(16), ParamType::Address]))),
+        ParamType::Uint(256),
+        ParamType::Bytes,
+    ]);
+    let tokens = decode(&[order.clone(), ParamType::FixedBytes(32), order, ParamType::FixedBytes(32)], data).ok()?;
+
+    // (trader, side, matchingPolicy, collection, tokenId, amount, paymentToken, price, ...)
+    let sell = tuple(&tokens[0])?;
+    if !is_eth(&address(&sell[6])?) {
+        return None;
+    }
+    Some(Lot {
+        items: vec![(address(&sell[3])?, uint(&sell[4])?, uint(&sell[5])?)],
+        total_wei: uint(&sell[7])?,
+    })
+}
+
+/// `TakerBid`/`TakerAsk`: (orderHash, orderNonce, currency, collection, tokenId, amount, price)
+fn looksrare_lot(data: &[u8]) -> Option<Lot> {
+    let tokens = decode(
+        &[
+            ParamType::FixedBytes(32),
+            ParamType::Uint(256),
+            ParamType::Address,
+            ParamType::Address,
+            ParamType::Uint(256),
+            ParamType::Uint(256),
+            ParamType::Uint(256),
+        ],
+        data,
+    )
+    .ok()?;
+
+    if !is_eth(&address(&tokens[2])?) {
+        return None;
+    }
+    Some(Lot {
+        items: vec![(address(&tokens[3])?, uint(&tokens[4])?, uint(&tokens[5])?)],
+        total_wei: uint(&tokens[6])?,
+    })
+}
+
+/// `EvInventory`: the item's data lists the tokens for its delegate type, and the settle
+/// detail carries the final price plus bytes that replace the masked part of that data
+fn x2y2_lot(data: &[u8]) -> Option<Lot> {
+    let item = ParamType::Tuple(vec![ParamType::Uint(256), ParamType::Bytes]);
+    let detail = ParamType::Tuple(vec![
+        ParamType::Uint(8),
+        ParamType::Uint(256),
+        ParamType::Uint(256),
+        ParamType::Uint(256),
+        ParamType::FixedBytes(32),
+        ParamType::Address,
+        ParamType::Bytes,
+        ParamType::Uint(256),
+        ParamType::Uint(256),
+        ParamType::Uint(256),
+        ParamType::Array(Box::new(ParamType::Tuple(vec![ParamType::Uint(256), ParamType::Address]))),
+    ]);
+    let tokens = decode(
+        &[
+            ParamType::Address,
+            ParamType::Address,
+            ParamType::Uint(256),
+            ParamType::Uint(256),
+            ParamType::Uint(256),
+            ParamType::Uint(256),
+            ParamType::Uint(256),
+            ParamType::Address,
+            ParamType::Bytes,
+            item,
+            detail,
+        ],
+        data,
+    )
+    .ok()?;
+
+    if !is_eth(&address(&tokens[7])?) {
+        return None;
+    }
+    let delegate_type = uint(&tokens[5])?;
+    let mask = tokens[8].clone().into_bytes()?;
+    let item = tuple(&tokens[9])?;
+    let detail = tuple(&tokens[10])?;
+    let mut item_data = item[1].clone().into_bytes()?;
+    let replacement = detail[6].clone().into_bytes()?;
+
+    // Collection offers leave the token id blank and fill it in at settlement
+    if mask.len() == item_data.len() && replacement.len() == item_data.len() {
+        for ((byte, mask), replacement) in item_data.iter_mut().zip(&mask).zip(&replacement) {
+            *byte = (*byte & !mask) | (replacement & mask);
+        }
+    }
+
+    // Delegate type 1 trades ERC-721 (token, id) pairs, type 2 ERC-1155 (token, id, amount)
+    let pair = match delegate_type.low_u64() {
+        1 => vec![ParamType::Address, ParamType::Uint(256)],
+        2 => vec![ParamType::Address, ParamType::Uint(256), ParamType::Uint(256)],
+        _ => return None,
+    };
+    let pairs = decode(&[ParamType::Array(Box::new(ParamType::Tuple(pair)))], &item_data).ok()?;
+    let items = array(&pairs[0])?
+        .iter()
+        .map(|pair| {
+            let fields = tuple(pair)?;
+            let copies = fields.get(2).and_then(uint).unwrap_or_else(U256::one);
+            Some((address(&fields[0])?, uint(&fields[1])?, copies))
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    Some(Lot { items, total_wei: uint(&detail[3])? })
+}
+
+/// Price levels of one collection from its most recent sales
+#[derive(Debug, Clone, PartialEq)]
+pub struct CollectionValuation {
+    /// Lowest price among the recent sales, in wei per copy
+    pub floor_wei: U256,
+    /// Price of the latest sale, in wei per copy
+    pub last_sale_wei: U256,
+    pub sales_count: usize,
+    // Latest price of each token that sold among the recent sales
+    token_sales: HashMap<U256, U256>,
+}
+
+impl CollectionValuation {
+    /// Value of one copy of `token_id`: its own recent sale if it has one, else the floor
+    pub fn estimate(&self, token_id: &U256) -> U256 {
+        self.token_sales.get(token_id).copied().unwrap_or(self.floor_wei)
+    }
+}
+
+/// Valuation from the latest `sample` of one collection's `sales`, or `None` without sales
+pub fn value_collection(sales: &[NftSale], sample: usize) -> Option<CollectionValuation> {
+    let mut ordered: Vec<&NftSale> = sales.iter().collect();
+    ordered.sort_by_key(|sale| std::cmp::Reverse((sale.block_number, sale.log_index)));
+
+    let mut seen = HashSet::new();
+    let recent: Vec<&NftSale> = ordered
+        .into_iter()
+        .filter(|sale| seen.insert((sale.tx_hash, sale.log_index, sale.token_id)))
+        .take(sample.max(1))
+        .collect();
+
+    let mut token_sales = HashMap::new();
+    for sale in &recent {
+        token_sales.entry(sale.token_id).or_insert(sale.price_wei);
+    }
+
+    Some(CollectionValuation {
+        floor_wei: recent.iter().map(|sale| sale.price_wei).min()?,
+        last_sale_wei: recent.first()?.price_wei,
+        sales_count: recent.len(),
+        token_sales,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::abi::encode;
+    use ethers::types::U64;
+
+    const COLLECTION: &str = "0x00000000000000000000000000000000000000c1";
+    const SELLER: &str = "0x00000000000000000000000000000000000000aa";
+    const BUYER: &str = "0x00000000000000000000000000000000000000bb";
+    const USDC: &str = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
+
+    fn addr(address: &str) -> Token {
+        Token::Address(address.parse().unwrap())
+    }
+
+    fn num(value: u64) -> Token {
+        Token::Uint(value.into())
+    }
+
+    fn eth(milli: u64) -> U256 {
+        U256::from(milli) * U256::exp10(15)
+    }
+
+    fn sale_log(exchange: &str, signature: &str, block: u64, data: Vec<Token>) -> Log {
+        Log {
+            address: exchange.parse().unwrap(),
+            topics: vec![topic(signature), H256::zero(), H256::zero()],
+            data: encode(&data).into(),
+            block_number: Some(U64::from(block)),
+            log_index: Some(U256::zero()),
+            transaction_hash: Some(H256::from_low_u64_be(block)),
+            ..Log::default()
+        }
+    }
+
+    // Listing of tokens 1 and 2 for 2 ETH, 2.5% of it to the marketplace
+    fn seaport_listing() -> Log {
+        let nft = |id| Token::Tuple(vec![num(2), addr(COLLECTION), num(id), num(1)]);
+        let pay = |milli: u64, to| Token::Tuple(vec![num(0), addr("0x0000000000000000000000000000000000000000"), num(0), Token::Uint(eth(milli)), addr(to)]);
+        sale_log(ProtocolAddresses::OPENSEA_SEAPORT, EventSignatures::SEAPORT_ORDER_FULFILLED, 10, vec![
+            Token::FixedBytes(vec![1; 32]),
+            addr(BUYER),
+            Token::Array(vec![nft(1), nft(2)]),
+            Token::Array(vec![pay(1_950, SELLER), pay(50, BUYER)]),
+        ])
+    }
+
+    // WETH bid of 0.8 ETH for token 3, accepted by the seller who pays the fee
+    fn seaport_bid() -> Log {
+        sale_log(ProtocolAddresses::OPENSEA_SEAPORT_1_6, EventSignatures::SEAPORT_ORDER_FULFILLED, 11, vec![
+            Token::FixedBytes(vec![2; 32]),
+            addr(SELLER),
+            Token::Array(vec![Token::Tuple(vec![num(1), addr(ProtocolAddresses::WETH_ETH), num(0), Token::Uint(eth(800))])]),
+            Token::Array(vec![
+                Token::Tuple(vec![num(2), addr(COLLECTION), num(3), num(1), addr(BUYER)]),
+                Token::Tuple(vec![num(1), addr(ProtocolAddresses::WETH_ETH), num(0), Token::Uint(eth(20)), addr(BUYER)]),
+            ]),
+        ])
+    }
+
+    fn blur_order(side: u64, trader: &str) -> Token {
+        Token::Tuple(vec![
+            addr(trader),
+            num(side),
+            addr(BUYER),
+            addr(COLLECTION),
+            num(4),
+            num(1),
+            addr(ProtocolAddresses::BLUR_POOL),
+            Token::Uint(eth(900)),
+            num(1),
+            num(2),
+            Token::Array(vec![Token::Tuple(vec![Token::Uint(50.into()), addr(BUYER)])]),
+            num(7),
+            Token::Bytes(vec![0xab; 3]),
+        ])
+    }
+
+    fn blur_match() -> Log {
+        sale_log(ProtocolAddresses::BLUR_EXCHANGE, EventSignatures::BLUR_ORDERS_MATCHED, 12, vec![
+            blur_order(1, SELLER),
+            Token::FixedBytes(vec![3; 32]),
+            blur_order(0, BUYER),
+            Token::FixedBytes(vec![4; 32]),
+        ])
+    }
+
+    fn looksrare(signature: &str, block: u64, currency: &str, id: u64, milli: u64) -> Log {
+        sale_log(ProtocolAddresses::LOOKSRARE_EXCHANGE, signature, block, vec![
+            Token::FixedBytes(vec![5; 32]),
+            num(9),
+            addr(currency),
+            addr(COLLECTION),
+            num(id),
+            num(1),
+            Token::Uint(eth(milli)),
+        ])
+    }
+
+    // Collection offer: the item's token id is blank and filled in from the replacement
+    fn x2y2_collection_offer() -> Log {
+        let pairs = |id| encode(&[Token::Array(vec![Token::Tuple(vec![addr(COLLECTION), num(id)])])]);
+        let blank = pairs(0);
+        let filled = pairs(6);
+        let mask: Vec<u8> = blank.iter().zip(&filled).map(|(a, b)| if a == b { 0 } else { 0xff }).collect();
+        sale_log(ProtocolAddresses::X2Y2_EXCHANGE, EventSignatures::X2Y2_INVENTORY, 14, vec![
+            addr(BUYER),
+            addr(SELLER),
+            num(1),
+            num(2),
+            num(3),
+            num(1),
+            num(99),
+            addr(ProtocolAddresses::WETH_ETH),
+            Token::Bytes(mask),
+            Token::Tuple(vec![Token::Uint(eth(700)), Token::Bytes(blank)]),
+            Token::Tuple(vec![
+                num(5),
+                num(0),
+                num(0),
+                Token::Uint(eth(650)),
+                Token::FixedBytes(vec![6; 32]),
+                addr(SELLER),
+                Token::Bytes(filled),
+                num(0),
+                num(0),
+                num(0),
+                Token::Array(vec![Token::Tuple(vec![num(5_000), addr(BUYER)])]),
+            ]),
+        ])
+    }
+
+    #[test]
+    fn test_decodes_each_marketplace_layout() {
+        let mut not_exchange = looksrare(EventSignatures::LOOKSRARE_TAKER_BID, 16, ProtocolAddresses::WETH_ETH, 9, 100);
+        not_exchange.address = BUYER.parse().unwrap();
+        let logs = vec![
+            seaport_listing(),
+            seaport_bid(),
+            blur_match(),
+            looksrare(EventSignatures::LOOKSRARE_TAKER_BID, 13, ProtocolAddresses::WETH_ETH, 5, 1_200),
+            looksrare(EventSignatures::LOOKSRARE_TAKER_ASK, 15, "0x0000000000000000000000000000000000000000", 5, 1_100),
+            x2y2_collection_offer(),
+            // Priced in a stablecoin, and a lookalike event from another contract
+            looksrare(EventSignatures::LOOKSRARE_TAKER_BID, 16, USDC, 8, 100),
+            not_exchange,
+        ];
+
+        let sales: Vec<(Marketplace, u64, U256)> = sales_from_logs(&logs)
+            .iter()
+            .map(|sale| {
+                assert_eq!(sale.collection, COLLECTION.parse::<Address>().unwrap());
+                (sale.marketplace, sale.token_id.as_u64(), sale.price_wei)
+            })
+            .collect();
+        assert_eq!(sales, vec![
+            (Marketplace::Seaport, 1, eth(1_000)),
+            (Marketplace::Seaport, 2, eth(1_000)),
+            (Marketplace::Seaport, 3, eth(800)),
+            (Marketplace::Blur, 4, eth(900)),
+            (Marketplace::LooksRare, 5, eth(1_200)),
+            (Marketplace::LooksRare, 5, eth(1_100)),
+            (Marketplace::X2Y2, 6, eth(650)),
+        ]);
+    }
+
+    #[test]
+    fn test_collection_valuation() {
+        let sales = sales_from_logs(&[
+            seaport_listing(),
+            seaport_bid(),
+            blur_match(),
+            looksrare(EventSignatures::LOOKSRARE_TAKER_BID, 13, ProtocolAddresses::WETH_ETH, 5, 1_200),
+            looksrare(EventSignatures::LOOKSRARE_TAKER_ASK, 15, ProtocolAddresses::WETH_ETH, 5, 1_100),
+        ]);
+
+        // The three latest sales: token 5 twice and token 4
+        let valuation = value_collection(&sales, 3).unwrap();
+        assert_eq!(valuation.sales_count, 3);
+        assert_eq!(valuation.floor_wei, eth(900));
+        assert_eq!(valuation.last_sale_wei, eth(1_100));
+        assert_eq!(valuation.estimate(&U256::from(5)), eth(1_100));
+        // Token 1 last sold before the sample, so it is worth the floor
+        assert_eq!(valuation.estimate(&U256::from(1)), eth(900));
+
+        assert_eq!(value_collection(&sales, 10).unwrap().floor_wei, eth(800));
+        assert!(value_collection(&[], 10).is_none());
+    }
+}
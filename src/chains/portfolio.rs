@@ -0,0 +1,250 @@
+use ethers::{
+    abi::{decode, encode, ParamType, Token},
+    types::{Address, Bytes, U256},
+    utils::keccak256,
+};
+use rust_decimal::Decimal;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::str::FromStr;
+
+use crate::chains::nft;
+use crate::models::{Chain, DegenMetrics, EVMTokenTransfer, KnownTokens, TokenHolding, TokenType};
+
+/// Every ERC-20 contract that appears in `transfers`, in address order
+pub fn touched_tokens(transfers: &[EVMTokenTransfer]) -> Vec<Address> {
+    transfers.iter()
+        .filter_map(|transfer| Address::from_str(&transfer.token_address).ok())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// Lowercase addresses of the tokens `owner` has sent at least once
+pub fn sent_tokens(owner: &Address, transfers: &[EVMTokenTransfer]) -> HashSet<String> {
+    let owner = format!("{:?}", owner);
+    transfers.iter()
+        .filter(|transfer| transfer.from.eq_ignore_ascii_case(&owner))
+        .map(|transfer| transfer.token_address.to_lowercase())
+        .collect()
+}
+
+pub fn selector(signature: &str) -> [u8; 4] {
+    let hash = keccak256(signature.as_bytes());
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+/// `balanceOf(holder)` call data
+pub fn balance_of(holder: &Address) -> Vec<u8> {
+    [&selector("balanceOf(address)")[..], &encode(&[Token::Address(*holder)])].concat()
+}
+
+/// Multicall3 `aggregate3` call data for `calls`, each allowed to fail on its own
+pub fn encode_aggregate3(calls: &[(Address, Vec<u8>)]) -> Vec<u8> {
+    let calls = calls.iter()
+        .map(|(target, data)| Token::Tuple(vec![Token::Address(*target), Token::Bool(true), Token::Bytes(data.clone())]))
+        .collect();
+    [&selector("aggregate3((address,bool,bytes)[])")[..], &encode(&[Token::Array(calls)])].concat()
+}
+
+/// Return data of each call in an `aggregate3` response; `None` for calls that reverted
+pub fn decode_aggregate3(data: &[u8]) -> Option<Vec<Option<Bytes>>> {
+    let result = ParamType::Array(Box::new(ParamType::Tuple(vec![ParamType::Bool, ParamType::Bytes])));
+    let results = decode(&[result], data).ok()?.pop()?.into_array()?;
+    results.into_iter()
+        .map(|result| {
+            let mut fields = result.into_tuple()?;
+            let data = fields.pop()?.into_bytes()?;
+            let success = fields.pop()?.into_bool()?;
+            Some(success.then(|| data.into()))
+        })
+        .collect()
+}
+
+/// `symbol()`/`name()` result: an ABI string, or the bytes32 some older tokens return
+pub fn decode_token_string(data: &[u8]) -> Option<String> {
+    if let Some(text) = nft::decode_string(data).filter(|text| !text.is_empty()) {
+        return Some(text);
+    }
+    if data.len() != 32 {
+        return None;
+    }
+    let text = String::from_utf8(data.iter().copied().take_while(|byte| *byte != 0).collect()).ok()?;
+    (!text.is_empty()).then_some(text)
+}
+
+/// `decimals()` result, if it fits a `u8`
+pub fn decode_decimals(data: &[u8]) -> Option<u8> {
+    let decimals = U256::from_big_endian(data.get(..32)?);
+    (decimals <= U256::from(u8::MAX)).then(|| decimals.as_u32() as u8)
+}
+
+/// Category of a token by its address on `chain`. Symbols and names are set by the
+/// token itself, so an unlisted token is `Other` whatever it calls itself.
+pub fn classify_token(chain: &Chain, address: &str) -> TokenType {
+    let listed = |tokens: HashMap<&str, &str>| tokens.keys().any(|token| token.eq_ignore_ascii_case(address));
+    if listed(KnownTokens::stablecoins_by_chain(chain)) {
+        TokenType::Stablecoin
+    } else if listed(KnownTokens::casino_tokens_by_chain(chain)) {
+        TokenType::CasinoToken
+    } else if listed(KnownTokens::memecoin_addresses()) {
+        TokenType::Memecoin
+    } else if listed(KnownTokens::bluechips_by_chain(chain)) {
+        TokenType::BluechipCrypto
+    } else if listed(KnownTokens::governance_tokens_by_chain(chain)) {
+        TokenType::GovernanceToken
+    } else {
+        TokenType::Other
+    }
+}
+
+/// Fill the portfolio metrics from `holdings`. Unpriced tokens add nothing to the USD
+/// totals, so they are left out of both percentages rather than weighing them down as
+/// $0 positions. They count as volatile positions only if the owner has sent them,
+/// which leaves out airdropped spam.
+pub fn apply_holdings(metrics: &mut DegenMetrics, holdings: &[TokenHolding], sent_tokens: &HashSet<String>) {
+    for holding in holdings {
+        let value_usd = holding.value_usd.unwrap_or_default();
+        metrics.total_balance_usd += value_usd;
+        if value_usd > Decimal::ZERO {
+            *metrics.position_values_usd.entry(holding.token_symbol.clone()).or_insert(Decimal::ZERO) += value_usd;
+        }
+
+        match holding.token_type {
+            TokenType::Stablecoin => metrics.stablecoin_balance_usd += value_usd,
+            _ if holding.balance.is_zero() => {}
+            _ if value_usd > Decimal::ZERO || sent_tokens.contains(&holding.token_address.to_lowercase()) => {
+                metrics.volatile_tokens.insert(holding.token_address.to_lowercase());
+            }
+            _ => {}
+        }
+    }
+
+    metrics.volatile_token_count = metrics.volatile_tokens.len() as u32;
+
+    let total = metrics.total_balance_usd;
+    if total > Decimal::ZERO {
+        let largest = metrics.position_values_usd.values().copied().max().unwrap_or_default();
+        metrics.stablecoin_percentage = share(metrics.stablecoin_balance_usd, total);
+        metrics.largest_position_percentage = share(largest, total);
+    }
+}
+
+fn share(part: Decimal, total: Decimal) -> f64 {
+    (part / total).try_into().unwrap_or(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    const OWNER: &str = "0x00000000000000000000000000000000000000aa";
+
+    fn transfer(token: &str, from: &str) -> EVMTokenTransfer {
+        EVMTokenTransfer {
+            token_address: token.to_string(),
+            from: from.to_string(),
+            to: "0x00000000000000000000000000000000000000bb".to_string(),
+            value: U256::one(),
+            tx_hash: "0x01".to_string(),
+            log_index: 0,
+            timestamp: Utc::now(),
+        }
+    }
+
+    fn holding(symbol: &str, address: &str, balance: u64, value_usd: Option<u64>, token_type: TokenType) -> TokenHolding {
+        TokenHolding {
+            token_address: address.to_string(),
+            token_symbol: symbol.to_string(),
+            token_name: symbol.to_string(),
+            balance: Decimal::from(balance),
+            decimals: 18,
+            value_usd: value_usd.map(Decimal::from),
+            token_type,
+            chain: "ethereum".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_multicall_round_trip() {
+        let token: Address = "0x6B175474E89094C44Da98b954EedeAC495271d0F".parse().unwrap();
+        let owner: Address = OWNER.parse().unwrap();
+        let data = encode_aggregate3(&[(token, balance_of(&owner)), (token, selector("decimals()").to_vec())]);
+        assert_eq!(&data[..4], &[0x82, 0xad, 0x56, 0xcb]);
+        assert_eq!(&balance_of(&owner)[..4], &[0x70, 0xa0, 0x82, 0x31]);
+
+        let response = encode(&[Token::Array(vec![
+            Token::Tuple(vec![Token::Bool(true), Token::Bytes(encode(&[Token::Uint(U256::from(18))]))]),
+            Token::Tuple(vec![Token::Bool(false), Token::Bytes(Vec::new())]),
+        ])]);
+        let results = decode_aggregate3(&response).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(decode_decimals(results[0].as_ref().unwrap()), Some(18));
+        assert!(results[1].is_none());
+    }
+
+    #[test]
+    fn test_token_strings_and_decimals() {
+        assert_eq!(decode_token_string(&encode(&[Token::String("USDC".to_string())])), Some("USDC".to_string()));
+        // MKR returns its symbol as bytes32
+        let mut mkr = [0u8; 32];
+        mkr[..3].copy_from_slice(b"MKR");
+        assert_eq!(decode_token_string(&mkr), Some("MKR".to_string()));
+        assert_eq!(decode_token_string(&[0u8; 32]), None);
+
+        assert_eq!(decode_decimals(&encode(&[Token::Uint(U256::from(6))])), Some(6));
+        assert_eq!(decode_decimals(&encode(&[Token::Uint(U256::from(300))])), None);
+        assert_eq!(decode_decimals(&[1, 2]), None);
+    }
+
+    #[test]
+    fn test_classify_token() {
+        let chain = Chain::Ethereum;
+        assert_eq!(classify_token(&chain, "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"), TokenType::Stablecoin);
+        assert_eq!(classify_token(&chain, "0x046eee2cc3188071c02bfc1745a6b17c656e3f3d"), TokenType::CasinoToken);
+        assert_eq!(classify_token(&chain, "0x6982508145454ce325ddbe47a25d4ec3d2311933"), TokenType::Memecoin);
+        assert_eq!(classify_token(&chain, "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2"), TokenType::BluechipCrypto);
+        assert_eq!(classify_token(&chain, "0x1f9840a85d5af5bf1d1762f925bdaddc4201f984"), TokenType::GovernanceToken);
+        assert_eq!(classify_token(&chain, "0x00000000000000000000000000000000000000c4"), TokenType::Other);
+        // Listed addresses only count on their own chain
+        assert_eq!(classify_token(&chain, "0xaf88d065e77c8cc2239327c5edb3a432268e5831"), TokenType::Other);
+        assert_eq!(classify_token(&Chain::Arbitrum, "0x912ce59144191c1204e64559fe8253a0e49e6548"), TokenType::GovernanceToken);
+    }
+
+    #[test]
+    fn test_touched_and_sent_tokens() {
+        let transfers = vec![
+            transfer("0x00000000000000000000000000000000000000c2", OWNER),
+            transfer("0x00000000000000000000000000000000000000C1", "0x00000000000000000000000000000000000000bb"),
+            transfer("0x00000000000000000000000000000000000000c2", "0x00000000000000000000000000000000000000bb"),
+        ];
+        let tokens = touched_tokens(&transfers);
+        assert_eq!(tokens.len(), 2);
+        assert!(tokens[0] < tokens[1]);
+
+        let sent = sent_tokens(&OWNER.parse().unwrap(), &transfers);
+        assert_eq!(sent, HashSet::from(["0x00000000000000000000000000000000000000c2".to_string()]));
+    }
+
+    #[test]
+    fn test_apply_holdings() {
+        let holdings = vec![
+            holding("ETH", "0x0000000000000000000000000000000000000000", 1, Some(600), TokenType::BluechipCrypto),
+            holding("USDC", "0xc1", 300, Some(300), TokenType::Stablecoin),
+            holding("DAI", "0xc2", 100, Some(100), TokenType::Stablecoin),
+            // Unpriced: counted because the owner traded it, spam is not; neither
+            // moves the percentages
+            holding("XYZ", "0xC3", 50, None, TokenType::Other),
+            holding("SPAM", "0xc4", 1_000, None, TokenType::Other),
+        ];
+        let mut metrics = DegenMetrics::default();
+        apply_holdings(&mut metrics, &holdings, &HashSet::from(["0xc3".to_string()]));
+
+        assert_eq!(metrics.total_balance_usd, Decimal::from(1_000));
+        assert_eq!(metrics.stablecoin_balance_usd, Decimal::from(400));
+        assert_eq!(metrics.stablecoin_percentage, 0.4);
+        assert_eq!(metrics.largest_position_percentage, 0.6);
+        assert_eq!(metrics.volatile_token_count, 2);
+        assert_eq!(metrics.position_values_usd.len(), 3);
+    }
+}
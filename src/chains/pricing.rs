@@ -0,0 +1,669 @@
+use ethers::{
+    abi::{decode, encode, ParamType, Token},
+    providers::{Http, Middleware, Provider},
+    types::{transaction::eip2718::TypedTransaction, Address, BlockId, BlockNumber, Bytes, TransactionRequest, I256, U256},
+    utils::{format_units, keccak256},
+};
+use futures::future::{BoxFuture, FutureExt};
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+use std::collections::{HashMap, VecDeque};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::config::PricingSettings;
+use crate::models::{CacheEntry, Chain, DegenScoreError, KnownTokens, PriceSource, ProtocolAddresses, Result};
+
+/// Block number under which prices read at chain head are cached
+pub const LATEST: u64 = u64::MAX;
+
+// Pools priced in a quote token that is itself priced from a pool stop after this many hops
+const MAX_QUOTE_DEPTH: u8 = 2;
+
+// Uniswap V3 fee tiers searched for a WETH pool of an unlisted token
+const V3_FEE_TIERS: [u32; 4] = [100, 500, 3_000, 10_000];
+
+/// Cache key of a token: EVM addresses are case-insensitive, Solana mints are not
+pub fn token_key(chain: &Chain, token: &str) -> String {
+    match chain {
+        Chain::Solana => token.to_string(),
+        _ => token.to_lowercase(),
+    }
+}
+
+/// Built-in sources of `chain` merged with the configured ones, keyed by `token_key`
+pub fn price_sources(chain: &Chain, settings: &PricingSettings) -> HashMap<String, PriceSource> {
+    let mut sources = HashMap::new();
+    for token in KnownTokens::stablecoins_by_chain(chain).keys() {
+        sources.insert(token_key(chain, token), PriceSource::Stablecoin);
+    }
+
+    let native = |feed: &str| PriceSource::Chainlink { feed: feed.to_string() };
+    let natives = match chain {
+        Chain::Ethereum => vec![("0x0000000000000000000000000000000000000000", native(ProtocolAddresses::CHAINLINK_ETH_USD_ETH)), (ProtocolAddresses::WETH_ETH, native(ProtocolAddresses::CHAINLINK_ETH_USD_ETH))],
+        Chain::Arbitrum => vec![("0x0000000000000000000000000000000000000000", native(ProtocolAddresses::CHAINLINK_ETH_USD_ARB)), (ProtocolAddresses::WETH_ARB, native(ProtocolAddresses::CHAINLINK_ETH_USD_ARB))],
+        Chain::Optimism => vec![("0x0000000000000000000000000000000000000000", native(ProtocolAddresses::CHAINLINK_ETH_USD_OPT)), (ProtocolAddresses::WETH_OPT, native(ProtocolAddresses::CHAINLINK_ETH_USD_OPT))],
+        Chain::Solana => {
+            let pyth = PriceSource::Pyth { account: ProtocolAddresses::PYTH_SOL_USD.to_string() };
+            vec![(ProtocolAddresses::SOL_NATIVE, pyth.clone()), (ProtocolAddresses::SOL_WRAPPED, pyth)]
+        }
+        // Blast has no Chainlink ETH/USD feed; configure a pool to price ETH there
+        Chain::Blast => Vec::new(),
+    };
+    for (token, source) in natives {
+        sources.insert(token_key(chain, token), source);
+    }
+
+    for entry in settings.sources.iter().filter(|entry| entry.chain.eq_ignore_ascii_case(chain.as_str())) {
+        sources.insert(token_key(chain, &entry.token), entry.source.clone());
+    }
+    sources
+}
+
+/// USD prices per (token, block). Historical prices never change and are kept until
+/// evicted; prices read at `LATEST` expire after the configured TTL.
+#[derive(Debug)]
+pub struct PriceCache {
+    entries: Mutex<HashMap<(String, u64), CacheEntry<Decimal>>>,
+    // Insertion order, oldest first, for eviction
+    order: Mutex<VecDeque<(String, u64)>>,
+    capacity: usize,
+    latest_ttl: Duration,
+}
+
+impl PriceCache {
+    pub fn new(capacity: usize, latest_ttl: Duration) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            order: Mutex::new(VecDeque::new()),
+            capacity: capacity.max(1),
+            latest_ttl,
+        }
+    }
+
+    pub fn from_settings(settings: &PricingSettings) -> Self {
+        Self::new(settings.cache_entries, Duration::from_secs(settings.latest_ttl_seconds))
+    }
+
+    pub fn get(&self, token: &str, block: u64) -> Option<Decimal> {
+        let entries = self.entries.lock().ok()?;
+        let entry = entries.get(&(token.to_string(), block))?;
+        (!entry.is_expired()).then_some(entry.value)
+    }
+
+    pub fn insert(&self, token: &str, block: u64, price: Decimal) {
+        let ttl = if block == LATEST { self.latest_ttl } else { Duration::MAX };
+        let (Ok(mut entries), Ok(mut order)) = (self.entries.lock(), self.order.lock()) else { return };
+
+        let key = (token.to_string(), block);
+        if entries.insert(key.clone(), CacheEntry::new(price, ttl)).is_none() {
+            order.push_back(key);
+        }
+        while entries.len() > self.capacity {
+            let Some(oldest) = order.pop_front() else { break };
+            entries.remove(&oldest);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().map(|entries| entries.len()).unwrap_or_default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Values EVM tokens in USD at a given block from Chainlink feeds and Uniswap pools.
+/// Prices at past blocks need an archive node behind the provider.
+pub struct PriceOracle {
+    provider: Arc<Provider<Http>>,
+    chain: Chain,
+    sources: HashMap<String, PriceSource>,
+    twap_seconds: u32,
+    max_feed_age: u64,
+    // WETH a discovered pool must hold; discovery is off when `None`
+    min_pool_weth: Option<U256>,
+    cache: PriceCache,
+    // Token decimals never change, so they are kept for the oracle's lifetime
+    decimals: Mutex<HashMap<Address, u8>>,
+    // Pool found for each unlisted token, or `None` when it has none deep enough
+    discovered: Mutex<HashMap<Address, Option<PriceSource>>>,
+}
+
+impl PriceOracle {
+    pub fn new(provider: Arc<Provider<Http>>, chain: Chain, settings: &PricingSettings) -> Self {
+        Self {
+            sources: price_sources(&chain, settings),
+            provider,
+            chain,
+            twap_seconds: settings.twap_seconds,
+            max_feed_age: settings.max_feed_age_seconds,
+            min_pool_weth: (settings.pool_discovery_min_weth > 0.0)
+                .then(|| Decimal::from_f64(settings.pool_discovery_min_weth))
+                .flatten()
+                .and_then(|weth| U256::from_dec_str(&(weth * Decimal::from(10u64.pow(18))).trunc().to_string()).ok()),
+            cache: PriceCache::from_settings(settings),
+            decimals: Mutex::new(HashMap::new()),
+            discovered: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn cache(&self) -> &PriceCache {
+        &self.cache
+    }
+
+    /// USD price of one whole `token` at `block`, or at head when `None`.
+    /// The zero address prices the chain's native token.
+    pub async fn price_usd(&self, token: Address, block: Option<u64>) -> Result<Decimal> {
+        self.price_at(token, block, 0).await
+    }
+
+    pub async fn native_price_usd(&self, block: Option<u64>) -> Result<Decimal> {
+        self.price_usd(Address::zero(), block).await
+    }
+
+    /// USD value of `amount` raw units of `token`
+    pub async fn value_usd(&self, token: Address, amount: U256, decimals: u8, block: Option<u64>) -> Result<Decimal> {
+        let price = self.price_usd(token, block).await?;
+        units_to_decimal(amount, decimals as u32).checked_mul(price)
+            .ok_or_else(|| self.unavailable(&token))
+    }
+
+    /// `decimals()` of an ERC-20 token; 18 for the native token
+    pub async fn token_decimals(&self, token: Address) -> Result<u8> {
+        if token.is_zero() {
+            return Ok(18);
+        }
+        if let Some(decimals) = self.decimals.lock().ok().and_then(|cache| cache.get(&token).copied()) {
+            return Ok(decimals);
+        }
+
+        let result = self.call(token, selector("decimals()").to_vec(), None, "decimals").await?;
+        let decimals = word(&result, 0)
+            .filter(|decimals| *decimals <= U256::from(u8::MAX))
+            .ok_or_else(|| self.malformed("decimals", &token))?
+            .as_u32() as u8;
+        if let Ok(mut cache) = self.decimals.lock() {
+            cache.insert(token, decimals);
+        }
+        Ok(decimals)
+    }
+
+    fn price_at(&self, token: Address, block: Option<u64>, depth: u8) -> BoxFuture<'_, Result<Decimal>> {
+        async move {
+            let key = token_key(&self.chain, &format!("{:?}", token));
+            let cache_block = block.unwrap_or(LATEST);
+            if let Some(price) = self.cache.get(&key, cache_block) {
+                return Ok(price);
+            }
+
+            let source = match self.sources.get(&key) {
+                Some(source) => source.clone(),
+                None => self.discovered_source(token).await.ok_or_else(|| self.unavailable(&token))?,
+            };
+            let price = match source {
+                PriceSource::Stablecoin => Decimal::ONE,
+                PriceSource::Chainlink { feed } => self.chainlink_price(parse_address(&feed)?, block).await?,
+                PriceSource::UniswapV2 { pair, quote } if depth < MAX_QUOTE_DEPTH => {
+                    let quote = parse_address(&quote)?;
+                    let in_quote = self.v2_price(token, parse_address(&pair)?, quote, block).await?;
+                    let quote_usd = self.price_at(quote, block, depth + 1).await?;
+                    in_quote.checked_mul(quote_usd).ok_or_else(|| self.unavailable(&token))?
+                }
+                PriceSource::UniswapV3 { pool, quote } if depth < MAX_QUOTE_DEPTH => {
+                    let quote = parse_address(&quote)?;
+                    let in_quote = self.v3_price(token, parse_address(&pool)?, quote, block).await?;
+                    let quote_usd = self.price_at(quote, block, depth + 1).await?;
+                    in_quote.checked_mul(quote_usd).ok_or_else(|| self.unavailable(&token))?
+                }
+                _ => return Err(self.unavailable(&token)),
+            };
+
+            self.cache.insert(&key, cache_block, price);
+            Ok(price)
+        }
+        .boxed()
+    }
+
+    async fn discovered_source(&self, token: Address) -> Option<PriceSource> {
+        if let Some(found) = self.discovered.lock().ok()?.get(&token) {
+            return found.clone();
+        }
+        let found = self.discover_pool(token).await;
+        if let Ok(mut discovered) = self.discovered.lock() {
+            discovered.insert(token, found.clone());
+        }
+        found
+    }
+
+    /// The Uniswap V3 pool pairing `token` with WETH that holds the most WETH, if it
+    /// holds at least `pool_discovery_min_weth`. Shallower pools are too cheap to move.
+    async fn discover_pool(&self, token: Address) -> Option<PriceSource> {
+        let min_weth = self.min_pool_weth?;
+        let weth = match self.chain {
+            Chain::Ethereum => ProtocolAddresses::WETH_ETH,
+            Chain::Arbitrum => ProtocolAddresses::WETH_ARB,
+            Chain::Optimism => ProtocolAddresses::WETH_OPT,
+            _ => return None,
+        };
+        let weth = parse_address(weth).ok()?;
+        if token == weth || token.is_zero() {
+            return None;
+        }
+        let factory = parse_address(ProtocolAddresses::UNISWAP_V3_FACTORY).ok()?;
+
+        let mut deepest: Option<(U256, Address)> = None;
+        for fee in V3_FEE_TIERS {
+            let data = [
+                &selector("getPool(address,address,uint24)")[..],
+                &encode(&[Token::Address(token), Token::Address(weth), Token::Uint(fee.into())]),
+            ].concat();
+            let Ok(result) = self.call(factory, data, None, "getPool").await else { continue };
+            let Some(pool) = result.get(12..32).map(Address::from_slice).filter(|pool| !pool.is_zero()) else { continue };
+
+            let data = [&selector("balanceOf(address)")[..], &encode(&[Token::Address(pool)])].concat();
+            let Some(reserve) = self.call(weth, data, None, "balanceOf").await.ok().and_then(|result| word(&result, 0)) else { continue };
+            if deepest.is_none_or(|(deepest, _)| reserve > deepest) {
+                deepest = Some((reserve, pool));
+            }
+        }
+
+        let (reserve, pool) = deepest.filter(|(reserve, _)| *reserve >= min_weth)?;
+        tracing::debug!("Pricing {:?} from pool {:?} holding {} WETH", token, pool, units_to_decimal(reserve, 18));
+        Some(PriceSource::UniswapV3 { pool: format!("{:?}", pool), quote: format!("{:?}", weth) })
+    }
+
+    async fn chainlink_price(&self, feed: Address, block: Option<u64>) -> Result<Decimal> {
+        let decimals = self.token_decimals(feed).await?;
+        let result = self.call(feed, selector("latestRoundData()").to_vec(), block, "latestRoundData").await?;
+        let (price, updated_at) = chainlink_answer(&result, decimals)
+            .ok_or_else(|| self.malformed("latestRoundData", &feed))?;
+
+        // A feed that stopped updating keeps answering its last price; judge its age
+        // against the block the price is read at
+        let read_at = match block {
+            Some(block) => self.provider.get_block(block).await
+                .map_err(|e| DegenScoreError::RpcError {
+                    chain: self.chain.as_str().to_string(),
+                    message: format!("Failed to get block {}: {}", block, e),
+                })?
+                .map(|block| block.timestamp.as_u64() as i64)
+                .ok_or_else(|| self.malformed("getBlock", &feed))?,
+            None => chrono::Utc::now().timestamp(),
+        };
+        let age = read_at.saturating_sub(updated_at);
+        if age > self.max_feed_age as i64 {
+            return Err(DegenScoreError::RpcError {
+                chain: self.chain.as_str().to_string(),
+                message: format!("Chainlink feed {:?} was last updated {}s before the read", feed, age),
+            });
+        }
+        Ok(price)
+    }
+
+    async fn v2_price(&self, token: Address, pair: Address, quote: Address, block: Option<u64>) -> Result<Decimal> {
+        let token0 = self.token0(pair).await?;
+        let reserves = self.call(pair, selector("getReserves()").to_vec(), block, "getReserves").await?;
+        let (Some(reserve0), Some(reserve1)) = (word(&reserves, 0), word(&reserves, 1)) else {
+            return Err(self.malformed("getReserves", &pair));
+        };
+        let (token_reserve, quote_reserve) = if token0 == token { (reserve0, reserve1) } else { (reserve1, reserve0) };
+
+        let token_decimals = self.token_decimals(token).await?;
+        let quote_decimals = self.token_decimals(quote).await?;
+        v2_price(token_reserve, quote_reserve, token_decimals, quote_decimals)
+            .ok_or_else(|| self.unavailable(&token))
+    }
+
+    /// TWAP over `twap_seconds` from `observe`, falling back to the `slot0` spot price when
+    /// the window is 0 or the pool has too little history
+    async fn v3_price(&self, token: Address, pool: Address, quote: Address, block: Option<u64>) -> Result<Decimal> {
+        let token0 = self.token0(pool).await?;
+
+        let mut raw = None;
+        if self.twap_seconds > 0 {
+            let data = [
+                &selector("observe(uint32[])")[..],
+                &encode(&[Token::Array(vec![Token::Uint(self.twap_seconds.into()), Token::Uint(U256::zero())])]),
+            ].concat();
+            if let Ok(result) = self.call(pool, data, block, "observe").await {
+                raw = twap_tick(&result, self.twap_seconds).map(tick_price);
+            }
+        }
+        let raw = match raw {
+            Some(raw) => raw,
+            None => {
+                let slot0 = self.call(pool, selector("slot0()").to_vec(), block, "slot0").await?;
+                word(&slot0, 0).map(sqrt_price_x96_price).ok_or_else(|| self.malformed("slot0", &pool))?
+            }
+        };
+
+        let token_decimals = self.token_decimals(token).await?;
+        let quote_decimals = self.token_decimals(quote).await?;
+        pool_price(raw, token0 == token, token_decimals, quote_decimals)
+            .ok_or_else(|| self.unavailable(&token))
+    }
+
+    async fn token0(&self, pool: Address) -> Result<Address> {
+        let result = self.call(pool, selector("token0()").to_vec(), None, "token0").await?;
+        word(&result, 0)
+            .map(|word| {
+                let mut bytes = [0u8; 32];
+                word.to_big_endian(&mut bytes);
+                Address::from_slice(&bytes[12..])
+            })
+            .ok_or_else(|| self.malformed("token0", &pool))
+    }
+
+    async fn call(&self, contract: Address, data: Vec<u8>, block: Option<u64>, context: &str) -> Result<Bytes> {
+        let request = TypedTransaction::Legacy(TransactionRequest {
+            to: Some(contract.into()),
+            data: Some(data.into()),
+            ..Default::default()
+        });
+        let block = block.map(|block| BlockId::Number(BlockNumber::Number(block.into())));
+
+        self.provider.call(&request, block).await
+            .map_err(|e| DegenScoreError::RpcError {
+                chain: self.chain.as_str().to_string(),
+                message: format!("Failed to call {} on {:?}: {}", context, contract, e),
+            })
+    }
+
+    fn unavailable(&self, token: &Address) -> DegenScoreError {
+        DegenScoreError::PriceUnavailable {
+            chain: self.chain.as_str().to_string(),
+            token: format!("{:?}", token),
+        }
+    }
+
+    fn malformed(&self, context: &str, contract: &Address) -> DegenScoreError {
+        DegenScoreError::RpcError {
+            chain: self.chain.as_str().to_string(),
+            message: format!("Malformed {} response from {:?}", context, contract),
+        }
+    }
+}
+
+fn parse_address(address: &str) -> Result<Address> {
+    Address::from_str(address).map_err(|_| DegenScoreError::InvalidAddress(address.to_string()))
+}
+
+fn selector(signature: &str) -> [u8; 4] {
+    let hash = keccak256(signature.as_bytes());
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+fn word(data: &[u8], index: usize) -> Option<U256> {
+    data.get(index * 32..(index + 1) * 32).map(U256::from_big_endian)
+}
+
+/// `amount` raw units with `decimals` decimals as a `Decimal`; zero if it does not fit
+pub fn units_to_decimal(amount: U256, decimals: u32) -> Decimal {
+    format_units(amount, decimals).ok()
+        .and_then(|units| Decimal::from_str(&units).ok())
+        .unwrap_or_default()
+}
+
+/// USD answer and `updatedAt` of a `latestRoundData()` response; `None` if the answer is
+/// not positive
+pub fn chainlink_answer(data: &[u8], decimals: u8) -> Option<(Decimal, i64)> {
+    // (roundId, answer, startedAt, updatedAt, answeredInRound)
+    let answer = I256::from_raw(word(data, 1)?);
+    let updated_at = word(data, 3)?;
+    if answer <= I256::zero() || updated_at > U256::from(i64::MAX) {
+        return None;
+    }
+    Some((units_to_decimal(answer.into_raw(), decimals as u32), updated_at.as_u64() as i64))
+}
+
+/// Price of one whole token in the quote token from a pair's reserves
+pub fn v2_price(token_reserve: U256, quote_reserve: U256, token_decimals: u8, quote_decimals: u8) -> Option<Decimal> {
+    let token_reserve = units_to_decimal(token_reserve, token_decimals as u32);
+    if token_reserve.is_zero() {
+        return None;
+    }
+    units_to_decimal(quote_reserve, quote_decimals as u32).checked_div(token_reserve)
+}
+
+/// Arithmetic mean tick of an `observe([seconds, 0])` response, rounded towards negative
+/// infinity like Uniswap's `OracleLibrary.consult`
+pub fn twap_tick(data: &[u8], seconds: u32) -> Option<i64> {
+    let tokens = decode(
+        &[ParamType::Array(Box::new(ParamType::Int(56))), ParamType::Array(Box::new(ParamType::Uint(160)))],
+        data,
+    ).ok()?;
+    let cumulatives = tokens.first()?.clone().into_array()?;
+    let [then, now] = cumulatives.as_slice() else { return None };
+    let then = I256::from_raw(then.clone().into_int()?).as_i64();
+    let now = I256::from_raw(now.clone().into_int()?).as_i64();
+
+    let seconds = seconds as i64;
+    let delta = now.checked_sub(then)?;
+    let mut tick = delta / seconds;
+    if delta < 0 && delta % seconds != 0 {
+        tick -= 1;
+    }
+    Some(tick)
+}
+
+/// Raw token1-per-token0 price at `tick`
+pub fn tick_price(tick: i64) -> f64 {
+    1.0001f64.powf(tick as f64)
+}
+
+/// Raw token1-per-token0 price of a `slot0` sqrtPriceX96
+pub fn sqrt_price_x96_price(sqrt_price_x96: U256) -> f64 {
+    let sqrt_price = sqrt_price_x96.to_string().parse::<f64>().unwrap_or_default() / 2f64.powi(96);
+    sqrt_price * sqrt_price
+}
+
+/// Price of one whole token in the quote token from a pool's raw token1-per-token0 price
+pub fn pool_price(raw: f64, token_is_token0: bool, token_decimals: u8, quote_decimals: u8) -> Option<Decimal> {
+    let raw = if token_is_token0 { raw } else { 1.0 / raw };
+    let price = raw * 10f64.powi(token_decimals as i32 - quote_decimals as i32);
+    if !price.is_finite() || price <= 0.0 {
+        return None;
+    }
+    Decimal::from_f64(price)
+}
+
+/// Price of a Pyth `PriceUpdateV2` account and the unix time it was published
+pub fn pyth_price(data: &[u8]) -> Option<(Decimal, i64)> {
+    // Anchor discriminator, write authority, then the verification level: Partial carries
+    // a signature count, Full does not
+    let mut offset = 8 + 32;
+    offset += match data.get(offset)? {
+        0 => 2,
+        1 => 1,
+        _ => return None,
+    };
+    // Price feed message: feed id, price, conf, exponent, publish time, ...
+    offset += 32;
+    let read = |at: usize, len: usize| data.get(at..at + len);
+    let price = i64::from_le_bytes(read(offset, 8)?.try_into().ok()?);
+    let exponent = i32::from_le_bytes(read(offset + 16, 4)?.try_into().ok()?);
+    let publish_time = i64::from_le_bytes(read(offset + 20, 8)?.try_into().ok()?);
+
+    if price <= 0 || !(-28..=0).contains(&exponent) {
+        return None;
+    }
+    Some((Decimal::new(price, exponent.unsigned_abs()), publish_time))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::PriceSourceEntry;
+
+    fn words(values: &[U256]) -> Vec<u8> {
+        encode(&values.iter().map(|value| Token::Uint(*value)).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn test_chainlink_answer() {
+        // $2,345.67890000 with 8 decimals
+        let data = words(&[U256::from(7), U256::from(234_567_890_000u64), U256::zero(), U256::from(1_700_000_000), U256::from(7)]);
+        assert_eq!(chainlink_answer(&data, 8), Some((Decimal::from_str("2345.6789").unwrap(), 1_700_000_000)));
+
+        let negative = words(&[U256::zero(), I256::from(-5).into_raw(), U256::zero(), U256::zero(), U256::zero()]);
+        assert_eq!(chainlink_answer(&negative, 8), None);
+        assert_eq!(chainlink_answer(&data[..40], 8), None);
+    }
+
+    #[test]
+    fn test_v2_price_scales_by_decimals() {
+        // 10 WETH against 20,000 USDC
+        let weth = U256::from(10) * U256::exp10(18);
+        let usdc = U256::from(20_000) * U256::exp10(6);
+        assert_eq!(v2_price(weth, usdc, 18, 6), Some(Decimal::from(2_000)));
+        assert_eq!(v2_price(usdc, weth, 6, 18), Some(Decimal::from_str("0.0005").unwrap()));
+        assert_eq!(v2_price(U256::zero(), usdc, 18, 6), None);
+    }
+
+    #[test]
+    fn test_v3_prices_from_sqrt_price_and_twap() {
+        // USDC (6 decimals) is token0 and WETH (18) token1 at $2,000: 5e8 raw WETH per raw USDC
+        let sqrt_price_x96 = U256::from_dec_str("1771595571142957166518320255467520").unwrap();
+        let raw = sqrt_price_x96_price(sqrt_price_x96);
+        let weth = pool_price(raw, false, 18, 6).unwrap();
+        assert!((weth - Decimal::from(2_000)).abs() < Decimal::from_str("0.01").unwrap());
+        let usdc = pool_price(raw, true, 6, 18).unwrap();
+        assert!((usdc - Decimal::from_str("0.0005").unwrap()).abs() < Decimal::from_str("0.0000001").unwrap());
+
+        // Mean tick over 1800 seconds, rounded down for negative deltas
+        let observe = |then: i64, now: i64| encode(&[
+            Token::Array(vec![Token::Int(I256::from(then).into_raw()), Token::Int(I256::from(now).into_raw())]),
+            Token::Array(vec![Token::Uint(U256::zero()), Token::Uint(U256::zero())]),
+        ]);
+        assert_eq!(twap_tick(&observe(1_000, 1_000 + 200_000 * 1_800), 1_800), Some(200_000));
+        assert_eq!(twap_tick(&observe(0, -1_801), 1_800), Some(-2));
+        assert!((tick_price(200_000) - 4.851e8).abs() / 4.851e8 < 0.001);
+    }
+
+    #[test]
+    fn test_pyth_price_update() {
+        let data = crate::fixtures::pyth_update(15_234_567_890, -8, 1_700_000_000);
+        assert_eq!(pyth_price(&data), Some((Decimal::from_str("152.3456789").unwrap(), 1_700_000_000)));
+
+        // Partial verification shifts the message by its signature count
+        let mut partial = data[..40].to_vec();
+        partial.extend([0, 5]);
+        partial.extend(&data[41..]);
+        assert_eq!(pyth_price(&partial), pyth_price(&data));
+        assert_eq!(pyth_price(&data[..60]), None);
+    }
+
+    #[tokio::test]
+    async fn test_stale_chainlink_answers_are_rejected() {
+        use crate::chains::explorer::tests::{serve, StandIn};
+        use axum::{routing::post, Json, Router};
+        use serde_json::{json, Value};
+        use std::sync::atomic::{AtomicI64, Ordering};
+
+        // An 8-decimal ETH/USD feed at $2,000, last updated at `updated_at`
+        let updated_at = Arc::new(AtomicI64::new(0));
+        let feed_time = updated_at.clone();
+        let rpc = Router::new().route("/rpc", post(move |Json(request): Json<Value>| {
+            let updated_at = feed_time.load(Ordering::SeqCst);
+            async move {
+                let data = request["params"][0]["data"].as_str().unwrap_or_default().to_string();
+                let result = if data.starts_with(&format!("0x{}", hex::encode(selector("decimals()")))) {
+                    words(&[U256::from(8)])
+                } else {
+                    words(&[U256::one(), U256::from(200_000_000_000u64), U256::zero(), U256::from(updated_at), U256::one()])
+                };
+                Json(json!({ "jsonrpc": "2.0", "id": request["id"], "result": format!("0x{}", hex::encode(result)) }))
+            }
+        }));
+        let url = serve(Arc::new(StandIn::default()), rpc).await;
+
+        let provider = Arc::new(Provider::<Http>::try_from(format!("{}/rpc", url)).unwrap());
+        let settings = PricingSettings { latest_ttl_seconds: 0, ..PricingSettings::default() };
+        let oracle = PriceOracle::new(provider, Chain::Ethereum, &settings);
+
+        updated_at.store(chrono::Utc::now().timestamp() - 2 * 86_400, Ordering::SeqCst);
+        let err = oracle.native_price_usd(None).await.unwrap_err();
+        assert!(err.to_string().contains("last updated"));
+
+        updated_at.store(chrono::Utc::now().timestamp() - 600, Ordering::SeqCst);
+        assert_eq!(oracle.native_price_usd(None).await.unwrap(), Decimal::from(2_000));
+    }
+
+    #[tokio::test]
+    async fn test_unlisted_tokens_use_the_deepest_weth_pool() {
+        use crate::chains::explorer::tests::{serve, StandIn};
+        use axum::{routing::post, Json, Router};
+        use serde_json::{json, Value};
+
+        // A 0.3% pool holding 50 WETH and a 0.05% pool holding 5; other tiers have none
+        let deep = Address::repeat_byte(0x30);
+        let shallow = Address::repeat_byte(0x05);
+        let rpc = Router::new().route("/rpc", post(move |Json(request): Json<Value>| async move {
+            let data = hex::decode(request["params"][0]["data"].as_str().unwrap_or_default().trim_start_matches("0x")).unwrap();
+            let result = if data.starts_with(&selector("getPool(address,address,uint24)")) {
+                let pool = match U256::from_big_endian(&data[68..100]).as_u32() {
+                    3_000 => deep,
+                    500 => shallow,
+                    _ => Address::zero(),
+                };
+                encode(&[Token::Address(pool)])
+            } else {
+                let reserve = if Address::from_slice(&data[16..36]) == deep { 50 } else { 5 };
+                words(&[U256::from(reserve) * U256::exp10(18)])
+            };
+            Json(json!({ "jsonrpc": "2.0", "id": request["id"], "result": format!("0x{}", hex::encode(result)) }))
+        }));
+        let url = serve(Arc::new(StandIn::default()), rpc).await;
+        let provider = Arc::new(Provider::<Http>::try_from(format!("{}/rpc", url)).unwrap());
+        let token = Address::repeat_byte(0xaa);
+
+        let oracle = PriceOracle::new(provider.clone(), Chain::Ethereum, &PricingSettings::default());
+        assert_eq!(
+            oracle.discovered_source(token).await,
+            Some(PriceSource::UniswapV3 { pool: format!("{:?}", deep), quote: ProtocolAddresses::WETH_ETH.to_lowercase() })
+        );
+
+        // Neither pool is deep enough at 100 WETH, and 0 turns discovery off
+        let settings = PricingSettings { pool_discovery_min_weth: 100.0, ..PricingSettings::default() };
+        assert_eq!(PriceOracle::new(provider.clone(), Chain::Ethereum, &settings).discovered_source(token).await, None);
+        let settings = PricingSettings { pool_discovery_min_weth: 0.0, ..PricingSettings::default() };
+        assert_eq!(PriceOracle::new(provider, Chain::Ethereum, &settings).discovered_source(token).await, None);
+    }
+
+    #[test]
+    fn test_cache_evicts_oldest_and_expires_latest() {
+        let cache = PriceCache::new(2, Duration::ZERO);
+        cache.insert("a", 1, Decimal::ONE);
+        cache.insert("a", 2, Decimal::TWO);
+        cache.insert("b", 1, Decimal::TEN);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get("a", 1), None);
+        assert_eq!(cache.get("a", 2), Some(Decimal::TWO));
+
+        cache.insert("b", LATEST, Decimal::TEN);
+        std::thread::sleep(Duration::from_millis(2));
+        assert_eq!(cache.get("b", LATEST), None);
+    }
+
+    #[test]
+    fn test_sources_merge_builtin_and_configured() {
+        let mut settings = PricingSettings::default();
+        settings.sources.push(PriceSourceEntry {
+            chain: "ethereum".to_string(),
+            token: ProtocolAddresses::RLB_TOKEN_ETH.to_string(),
+            source: PriceSource::UniswapV3 { pool: "0x0000000000000000000000000000000000000001".to_string(), quote: ProtocolAddresses::WETH_ETH.to_string() },
+        });
+
+        let sources = price_sources(&Chain::Ethereum, &settings);
+        assert_eq!(sources[&token_key(&Chain::Ethereum, ProtocolAddresses::WETH_ETH)], PriceSource::Chainlink { feed: ProtocolAddresses::CHAINLINK_ETH_USD_ETH.to_string() });
+        assert_eq!(sources["0x6b175474e89094c44da98b954eedeac495271d0f"], PriceSource::Stablecoin);
+        assert!(matches!(sources[&token_key(&Chain::Ethereum, ProtocolAddresses::RLB_TOKEN_ETH)], PriceSource::UniswapV3 { .. }));
+
+        // Configured sources stay on their own chain; Solana mints keep their case
+        assert!(!price_sources(&Chain::Arbitrum, &settings).contains_key(&token_key(&Chain::Ethereum, ProtocolAddresses::RLB_TOKEN_ETH)));
+        assert!(price_sources(&Chain::Solana, &settings).contains_key(ProtocolAddresses::SOL_WRAPPED));
+    }
+}
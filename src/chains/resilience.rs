@@ -358,7 +358,7 @@ mod tests {
         assert_eq!(cb.get_state(), CircuitState::Closed);
         
         // First failure
-        let result = cb.call(|| async { Err(DegenScoreError::RpcError {
+        let result: Result<()> = cb.call(|| async { Err(DegenScoreError::RpcError {
             chain: "test".to_string(),
             message: "test error".to_string(),
         }) }).await;
@@ -366,7 +366,7 @@ mod tests {
         assert_eq!(cb.get_state(), CircuitState::Closed);
         
         // Second failure - should open circuit
-        let result = cb.call(|| async { Err(DegenScoreError::RpcError {
+        let result: Result<()> = cb.call(|| async { Err(DegenScoreError::RpcError {
             chain: "test".to_string(),
             message: "test error".to_string(),
         }) }).await;
@@ -0,0 +1,258 @@
+use ethers::types::Log;
+use ring::digest::{digest, SHA256};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::RwLock;
+use tracing::debug;
+
+use crate::{
+    chains::checkpoint::CheckpointStore,
+    config::ScannerSettings,
+    models::{Chain, DegenScoreError, Result},
+};
+
+// Provider error fragments that mean "ask for fewer blocks", lowercased
+const RANGE_ERRORS: [&str; 8] = [
+    "exceed maximum block range",
+    "block range",
+    "range too large",
+    "range is too large",
+    "too many blocks",
+    "more than 10000 results",
+    "query returned more than",
+    "response size exceeded",
+];
+
+/// Whether a failed `eth_getLogs` should be retried over a smaller block range
+pub fn is_range_error(error: &DegenScoreError) -> bool {
+    let message = error.to_string().to_lowercase();
+    RANGE_ERRORS.iter().any(|fragment| message.contains(fragment))
+}
+
+/// Progress of one log query, so later scans only fetch new blocks
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScanCheckpoint {
+    pub start_block: u64,
+    /// First block not yet covered by the stored logs
+    pub next_block: u64,
+}
+
+/// Walks `eth_getLogs` queries from a start block to head in adaptive chunks.
+///
+/// The window halves whenever the provider rejects a range and doubles after each
+/// success. Finalized chunks are checkpointed per query, optionally under a directory,
+/// so a rescan resumes where the last one stopped. Providers differ per chain, so each
+/// chain gets its own scanner.
+pub struct LogScanner {
+    settings: ScannerSettings,
+    // Current window, carried between scans since a provider keeps the same limits
+    chunk: RwLock<u64>,
+    checkpoints: CheckpointStore<ScanCheckpoint, Log>,
+}
+
+impl LogScanner {
+    pub fn new(settings: ScannerSettings) -> Self {
+        let dir = settings.checkpoint_dir.as_ref().map(|dir| PathBuf::from(dir).join("logs"));
+
+        Self {
+            chunk: RwLock::new(clamp_chunk(&settings, settings.initial_chunk_blocks)),
+            settings,
+            checkpoints: CheckpointStore::new(dir),
+        }
+    }
+
+    pub fn settings(&self) -> &ScannerSettings {
+        &self.settings
+    }
+
+    /// Configured first block for `chain`, genesis unless overridden
+    pub fn start_block(&self, chain: &Chain) -> u64 {
+        self.settings.start_blocks.get(chain.as_str()).copied().unwrap_or(0)
+    }
+
+    pub fn chunk_size(&self) -> u64 {
+        *self.chunk.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn checkpoint(&self, key: &str) -> Option<ScanCheckpoint> {
+        self.checkpoints.get(key).map(|checkpoint| checkpoint.cursor)
+    }
+
+    /// Collect every log in `from..=to`. `fetch` runs one `eth_getLogs` over an inclusive
+    /// block range; `key` identifies the query for checkpointing.
+    pub async fn scan<F, Fut>(&self, key: &str, from: u64, to: u64, fetch: F) -> Result<Vec<Log>>
+    where
+        F: FnMut(u64, u64) -> Fut,
+        Fut: Future<Output = Result<Vec<Log>>>,
+    {
+        self.walk(Some(key), from, to, fetch).await
+    }
+
+    /// Collect every log in `from..=to` without checkpointing, for one-off windows
+    pub async fn scan_window<F, Fut>(&self, from: u64, to: u64, fetch: F) -> Result<Vec<Log>>
+    where
+        F: FnMut(u64, u64) -> Fut,
+        Fut: Future<Output = Result<Vec<Log>>>,
+    {
+        self.walk(None, from, to, fetch).await
+    }
+
+    async fn walk<F, Fut>(&self, key: Option<&str>, from: u64, to: u64, mut fetch: F) -> Result<Vec<Log>>
+    where
+        F: FnMut(u64, u64) -> Fut,
+        Fut: Future<Output = Result<Vec<Log>>>,
+    {
+        // Blocks this close to head may still reorg, so they are never checkpointed
+        let finalized = to.saturating_sub(self.settings.finality_blocks);
+
+        let (mut checkpoint, mut found) = match key.and_then(|key| self.checkpoints.get(key)) {
+            Some(stored) if stored.cursor.start_block == from => (stored.cursor, stored.items),
+            _ => {
+                let fresh = ScanCheckpoint { start_block: from, next_block: from };
+                if let Some(key) = key {
+                    self.checkpoints.reset(key, fresh.clone());
+                }
+                (fresh, Vec::new())
+            }
+        };
+        let mut unfinalized = Vec::new();
+        let mut cursor = checkpoint.next_block;
+
+        while cursor <= to {
+            let chunk = self.chunk_size();
+            let mut end = cursor.saturating_add(chunk - 1).min(to);
+            if cursor <= finalized {
+                end = end.min(finalized);
+            }
+
+            match fetch(cursor, end).await {
+                Ok(logs) => {
+                    debug!("Scanned blocks {}..={} ({} logs)", cursor, end, logs.len());
+                    if end <= finalized {
+                        checkpoint.next_block = end + 1;
+                        if let Some(key) = key {
+                            self.checkpoints.advance(key, checkpoint.clone(), &logs);
+                        }
+                        found.extend(logs);
+                    } else {
+                        unfinalized.extend(logs);
+                    }
+                    cursor = end + 1;
+                    self.resize(chunk.saturating_mul(2));
+                }
+                Err(e) if is_range_error(&e) && chunk > self.settings.min_chunk_blocks => {
+                    debug!("Shrinking scan window from {} blocks: {}", chunk, e);
+                    self.resize(chunk / 2);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        found.extend(unfinalized);
+        Ok(found)
+    }
+
+    fn resize(&self, blocks: u64) {
+        *self.chunk.write().unwrap_or_else(|e| e.into_inner()) = clamp_chunk(&self.settings, blocks);
+    }
+}
+
+fn clamp_chunk(settings: &ScannerSettings, blocks: u64) -> u64 {
+    let min = settings.min_chunk_blocks.max(1);
+    blocks.max(min).min(settings.max_chunk_blocks.max(min))
+}
+
+/// Checkpoint key for a log filter on `chain`, ignoring its block range
+pub fn filter_key(chain: &Chain, filter: &ethers::types::Filter) -> String {
+    let query = serde_json::json!({ "address": filter.address, "topics": filter.topics });
+    let hash = digest(&SHA256, query.to_string().as_bytes());
+    format!("{}:{}", chain.as_str(), hex::encode(&hash.as_ref()[..12]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::U64;
+    use std::sync::Mutex;
+
+    fn settings() -> ScannerSettings {
+        ScannerSettings {
+            initial_chunk_blocks: 100,
+            min_chunk_blocks: 10,
+            max_chunk_blocks: 400,
+            finality_blocks: 5,
+            ..ScannerSettings::default()
+        }
+    }
+
+    // Chain with one log every 50 blocks behind a provider that caps ranges at `max_range`
+    async fn fake_get_logs(calls: &Mutex<Vec<(u64, u64)>>, max_range: u64, from: u64, to: u64) -> Result<Vec<Log>> {
+        calls.lock().unwrap().push((from, to));
+        if to - from + 1 > max_range {
+            return Err(DegenScoreError::RpcError {
+                chain: "ethereum".to_string(),
+                message: "query exceeds maximum block range 150".to_string(),
+            });
+        }
+        Ok((from..=to)
+            .filter(|block| block % 50 == 0)
+            .map(|block| Log { block_number: Some(U64::from(block)), ..Log::default() })
+            .collect())
+    }
+
+    fn blocks(logs: &[Log]) -> Vec<u64> {
+        logs.iter().map(|log| log.block_number.unwrap().as_u64()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_adaptive_window_covers_every_block_once() {
+        let scanner = LogScanner::new(settings());
+        let calls = Mutex::new(Vec::new());
+
+        let logs = scanner.scan("q", 0, 1_000, |from, to| fake_get_logs(&calls, 150, from, to)).await.unwrap();
+        assert_eq!(blocks(&logs), (0..=1_000).step_by(50).collect::<Vec<_>>());
+
+        // Grew past the limit, was rejected and shrank back under it
+        let calls = calls.into_inner().unwrap();
+        assert!(calls.iter().any(|(from, to)| to - from + 1 > 150));
+        assert!(calls.iter().all(|(from, to)| to - from < 400));
+        assert!(scanner.chunk_size() <= 400);
+
+        // A provider that rejects even the minimum window surfaces the error
+        let calls = Mutex::new(Vec::new());
+        let err = scanner.scan("tiny", 0, 100, |from, to| fake_get_logs(&calls, 5, from, to)).await;
+        assert!(err.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_resumes_from_checkpoint() {
+        let dir = std::env::temp_dir().join(format!("scan-checkpoints-{}", std::process::id()));
+        let scanner = LogScanner::new(ScannerSettings {
+            checkpoint_dir: Some(dir.to_string_lossy().into_owned()),
+            ..settings()
+        });
+
+        let calls = Mutex::new(Vec::new());
+        scanner.scan("q", 0, 600, |from, to| fake_get_logs(&calls, 1_000, from, to)).await.unwrap();
+        assert_eq!(scanner.checkpoint("q").unwrap().next_block, 596);
+
+        // A fresh scanner picks the checkpoint up from disk and only fetches new blocks
+        let resumed = LogScanner::new(ScannerSettings {
+            checkpoint_dir: Some(dir.to_string_lossy().into_owned()),
+            ..settings()
+        });
+        let calls = Mutex::new(Vec::new());
+        let logs = resumed.scan("q", 0, 800, |from, to| fake_get_logs(&calls, 1_000, from, to)).await.unwrap();
+        assert_eq!(blocks(&logs), (0..=800).step_by(50).collect::<Vec<_>>());
+        assert_eq!(calls.into_inner().unwrap()[0].0, 596);
+
+        // Moving the start block invalidates the checkpoint
+        let calls = Mutex::new(Vec::new());
+        let logs = resumed.scan("q", 300, 800, |from, to| fake_get_logs(&calls, 1_000, from, to)).await.unwrap();
+        assert_eq!(blocks(&logs)[0], 300);
+        assert_eq!(calls.into_inner().unwrap()[0].0, 300);
+
+        std::fs::remove_dir_all(dir).ok();
+    }
+}
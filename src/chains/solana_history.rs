@@ -0,0 +1,345 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::path::PathBuf;
+use std::time::Duration;
+use tracing::debug;
+
+use crate::{
+    chains::checkpoint::{Checkpoint, CheckpointStore},
+    config::SolanaSettings,
+    models::{DegenScoreError, Result},
+};
+
+// Most signatures `getSignaturesForAddress` returns per call
+const MAX_PAGE_SIZE: usize = 1_000;
+
+/// A `getSignaturesForAddress` entry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignatureInfo {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub err: Option<serde_json::Value>,
+}
+
+/// How far the walk of one address's history has got, so later walks only fetch what
+/// is missing. The signatures found are appended to the checkpoint alongside it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HistoryCursor {
+    /// Newest signature seen, where the next walk forward stops
+    pub newest: Option<String>,
+    /// Oldest signature seen, where the walk back resumes
+    pub oldest: Option<String>,
+    /// Whether the walk reached the address's first transaction
+    pub complete: bool,
+}
+
+/// (timestamp, fee) pairs for `TransactionSummary::from_activity`. Signature listings
+/// carry no fees, so every fee is zero; signatures without a block time are left out.
+pub fn activity(signatures: &[SignatureInfo]) -> Vec<(DateTime<Utc>, Decimal)> {
+    signatures.iter()
+        .filter_map(|sig| DateTime::from_timestamp(sig.block_time?, 0))
+        .map(|timestamp| (timestamp, Decimal::ZERO))
+        .collect()
+}
+
+/// Whether a failed RPC call should be retried after a pause
+pub fn is_rate_limited(error: &DegenScoreError) -> bool {
+    if matches!(error, DegenScoreError::RateLimitExceeded { .. }) {
+        return true;
+    }
+    let message = error.to_string().to_lowercase();
+    message.contains("429") || message.contains("too many requests") || message.contains("rate limit")
+}
+
+/// Pages through `getSignaturesForAddress` with `before`/`until` cursors.
+///
+/// Signatures newer than the checkpoint are fetched first, then the walk back in time
+/// continues from the oldest signature seen until the first transaction or
+/// `max_signatures`. Progress is checkpointed per address after every page going
+/// back, optionally under a directory, so an interrupted walk resumes where it stopped.
+/// A gap of `max_signatures` or more since the last walk starts the checkpoint over
+/// from the newest signatures, as a first walk would.
+pub struct SignatureHistory {
+    settings: SolanaSettings,
+    checkpoints: CheckpointStore<HistoryCursor, SignatureInfo>,
+}
+
+impl SignatureHistory {
+    pub fn new(settings: SolanaSettings) -> Self {
+        let dir = settings.checkpoint_dir.as_ref().map(|dir| PathBuf::from(dir).join("signatures"));
+
+        Self {
+            settings,
+            checkpoints: CheckpointStore::new(dir),
+        }
+    }
+
+    pub fn checkpoint(&self, address: &str) -> Option<HistoryCursor> {
+        self.checkpoints.get(address).map(|checkpoint| checkpoint.cursor)
+    }
+
+    /// Every signature of `address`, newest first. `fetch_page` runs one
+    /// `getSignaturesForAddress` call with the given `before` and `until` cursors and limit.
+    pub async fn fetch<F, Fut>(&self, address: &str, mut fetch_page: F) -> Result<Vec<SignatureInfo>>
+    where
+        F: FnMut(Option<String>, Option<String>, usize) -> Fut,
+        Fut: Future<Output = Result<Vec<SignatureInfo>>>,
+    {
+        let page_size = self.settings.signature_page_size.clamp(1, MAX_PAGE_SIZE);
+        let Checkpoint { mut cursor, items } = self.checkpoints.get(address)
+            .unwrap_or(Checkpoint { cursor: HistoryCursor::default(), items: Vec::new() });
+        let mut count = items.len();
+        let mut pages = 0;
+
+        // Newer than the checkpoint; only recorded once the gap is closed
+        if let Some(newest) = cursor.newest.clone() {
+            let mut newer = Vec::new();
+            let mut closed = false;
+            while newer.len() < self.settings.max_signatures {
+                let limit = page_size.min(self.settings.max_signatures - newer.len());
+                let before = newer.last().map(|sig: &SignatureInfo| sig.signature.clone());
+                let page = self.page(&mut fetch_page, before, Some(newest.clone()), limit, pages).await?;
+                pages += 1;
+                closed = page.len() < limit;
+                newer.extend(page);
+                if closed {
+                    break;
+                }
+            }
+            if closed {
+                if let Some(first) = newer.first() {
+                    debug!("Found {} new signatures for {}", newer.len(), address);
+                    cursor.newest = Some(first.signature.clone());
+                    count += newer.len();
+                    self.checkpoints.advance(address, cursor.clone(), &newer);
+                }
+            } else if let (Some(first), Some(last)) = (newer.first(), newer.last()) {
+                // The new signatures alone fill the cap, so the older ones give way
+                debug!("Restarting the history of {} from its newest {} signatures", address, newer.len());
+                cursor = HistoryCursor {
+                    newest: Some(first.signature.clone()),
+                    oldest: Some(last.signature.clone()),
+                    complete: false,
+                };
+                count = newer.len();
+                self.checkpoints.reset(address, cursor.clone());
+                self.checkpoints.advance(address, cursor.clone(), &newer);
+            }
+        }
+
+        // Back in time from the oldest signature seen
+        while !cursor.complete && count < self.settings.max_signatures {
+            let limit = page_size.min(self.settings.max_signatures - count);
+            let page = self.page(&mut fetch_page, cursor.oldest.clone(), None, limit, pages).await?;
+            pages += 1;
+            cursor.complete = page.len() < limit;
+            if cursor.newest.is_none() {
+                cursor.newest = page.first().map(|sig| sig.signature.clone());
+            }
+            if let Some(last) = page.last() {
+                cursor.oldest = Some(last.signature.clone());
+            }
+            count += page.len();
+            self.checkpoints.advance(address, cursor.clone(), &page);
+        }
+
+        if !cursor.complete {
+            debug!("Stopped walking {} at {} signatures", address, count);
+        }
+
+        // Stored in the order they were found; newer walks land after older ones
+        let mut signatures = self.checkpoints.get(address).map(|checkpoint| checkpoint.items).unwrap_or_default();
+        signatures.sort_by_key(|sig| std::cmp::Reverse(sig.slot));
+        Ok(signatures)
+    }
+
+    // One page, after the configured pause unless it is the first of a walk, retrying
+    // rate-limited calls with a growing delay
+    async fn page<F, Fut>(
+        &self,
+        fetch_page: &mut F,
+        before: Option<String>,
+        until: Option<String>,
+        limit: usize,
+        pages: usize,
+    ) -> Result<Vec<SignatureInfo>>
+    where
+        F: FnMut(Option<String>, Option<String>, usize) -> Fut,
+        Fut: Future<Output = Result<Vec<SignatureInfo>>>,
+    {
+        if pages > 0 {
+            tokio::time::sleep(Duration::from_millis(self.settings.page_delay_ms)).await;
+        }
+
+        let mut attempt = 0;
+        loop {
+            match fetch_page(before.clone(), until.clone(), limit).await {
+                Err(e) if is_rate_limited(&e) => {
+                    attempt += 1;
+                    if attempt > self.settings.max_retries {
+                        return Err(DegenScoreError::RateLimitExceeded {
+                            service: "solana rpc".to_string(),
+                        });
+                    }
+                    debug!("Signature page rate limited, retry {} of {}", attempt, self.settings.max_retries);
+                    tokio::time::sleep(Duration::from_millis(self.settings.retry_delay_ms) * attempt).await;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    const ADDRESS: &str = "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM";
+
+    fn settings() -> SolanaSettings {
+        SolanaSettings {
+            signature_page_size: 10,
+            max_signatures: 1_000,
+            page_delay_ms: 0,
+            max_retries: 2,
+            retry_delay_ms: 1,
+            ..SolanaSettings::default()
+        }
+    }
+
+    // `count` signatures, newest first, named after their slot
+    fn chain_history(count: u64) -> Vec<SignatureInfo> {
+        (1..=count).rev()
+            .map(|slot| SignatureInfo { signature: format!("sig{}", slot), slot, block_time: Some(slot as i64 * 3_600), err: None })
+            .collect()
+    }
+
+    // Answers like `getSignaturesForAddress` over `history`, logging each call's cursors
+    async fn fake_page(
+        history: &[SignatureInfo],
+        calls: &Mutex<Vec<(Option<String>, Option<String>)>>,
+        before: Option<String>,
+        until: Option<String>,
+        limit: usize,
+    ) -> Result<Vec<SignatureInfo>> {
+        calls.lock().unwrap().push((before.clone(), until.clone()));
+        let start = before.map_or(0, |before| history.iter().position(|sig| sig.signature == before).unwrap() + 1);
+        Ok(history[start..].iter()
+            .take_while(|sig| Some(&sig.signature) != until.as_ref())
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
+    fn slots(signatures: &[SignatureInfo]) -> Vec<u64> {
+        signatures.iter().map(|sig| sig.slot).collect()
+    }
+
+    #[tokio::test]
+    async fn test_walks_every_page_up_to_the_cap() {
+        let chain = chain_history(35);
+        let calls = Mutex::new(Vec::new());
+        let history = SignatureHistory::new(settings());
+        let signatures = history.fetch(ADDRESS, |before, until, limit| fake_page(&chain, &calls, before, until, limit)).await.unwrap();
+        assert_eq!(slots(&signatures), (1..=35).rev().collect::<Vec<_>>());
+        assert!(history.checkpoint(ADDRESS).unwrap().complete);
+        // Four pages, each starting before the last signature of the one before
+        let calls = calls.into_inner().unwrap();
+        assert_eq!(calls.len(), 4);
+        assert_eq!(calls[1], (Some("sig26".to_string()), None));
+
+        let capped = SignatureHistory::new(SolanaSettings { max_signatures: 25, ..settings() });
+        let calls = Mutex::new(Vec::new());
+        let signatures = capped.fetch(ADDRESS, |before, until, limit| fake_page(&chain, &calls, before, until, limit)).await.unwrap();
+        assert_eq!(signatures.len(), 25);
+        assert_eq!(signatures.last().unwrap().slot, 11);
+        assert!(!capped.checkpoint(ADDRESS).unwrap().complete);
+    }
+
+    #[tokio::test]
+    async fn test_resumes_from_checkpoint() {
+        let dir = std::env::temp_dir().join(format!("solana-history-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let persisted = SolanaSettings { checkpoint_dir: Some(dir.to_string_lossy().into_owned()), ..settings() };
+
+        // A walk cut short by the cap is picked up from disk once the cap is raised
+        let chain = chain_history(30);
+        let first = SignatureHistory::new(SolanaSettings { max_signatures: 15, ..persisted.clone() });
+        let calls = Mutex::new(Vec::new());
+        first.fetch(ADDRESS, |before, until, limit| fake_page(&chain, &calls, before, until, limit)).await.unwrap();
+
+        // Meanwhile the address made three more transactions
+        let chain = chain_history(33);
+        let resumed = SignatureHistory::new(persisted);
+        let calls = Mutex::new(Vec::new());
+        let signatures = resumed.fetch(ADDRESS, |before, until, limit| fake_page(&chain, &calls, before, until, limit)).await.unwrap();
+        assert_eq!(slots(&signatures), (1..=33).rev().collect::<Vec<_>>());
+
+        let calls = calls.into_inner().unwrap();
+        assert_eq!(calls[0], (None, Some("sig30".to_string())));
+        assert_eq!(calls[1], (Some("sig16".to_string()), None));
+        assert_eq!(calls.len(), 3);
+
+        // Each signature was written once, however many pages moved the cursor
+        let written = std::fs::read_to_string(dir.join("signatures").join(format!("{}.jsonl", ADDRESS))).unwrap();
+        assert_eq!(written.lines().count(), 33);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_walk_forward_stops_at_the_cap() {
+        let history = SignatureHistory::new(SolanaSettings { max_signatures: 15, ..settings() });
+        let chain = chain_history(10);
+        let calls = Mutex::new(Vec::new());
+        history.fetch(ADDRESS, |before, until, limit| fake_page(&chain, &calls, before, until, limit)).await.unwrap();
+        assert!(history.checkpoint(ADDRESS).unwrap().complete);
+
+        // Thirty new transactions: only the newest fifteen are fetched and they replace the rest
+        let chain = chain_history(40);
+        let calls = Mutex::new(Vec::new());
+        let signatures = history.fetch(ADDRESS, |before, until, limit| fake_page(&chain, &calls, before, until, limit)).await.unwrap();
+        assert_eq!(slots(&signatures), (26..=40).rev().collect::<Vec<_>>());
+        assert_eq!(calls.into_inner().unwrap().len(), 2);
+
+        let cursor = history.checkpoint(ADDRESS).unwrap();
+        assert_eq!(cursor.oldest.as_deref(), Some("sig26"));
+        assert!(!cursor.complete);
+    }
+
+    #[tokio::test]
+    async fn test_retries_rate_limited_pages() {
+        let chain = chain_history(5);
+        let calls = Mutex::new(Vec::new());
+        let failures = Mutex::new(2);
+        let limited = || DegenScoreError::RpcError {
+            chain: "solana".to_string(),
+            message: "RPC error 429: Too many requests for a specific RPC call".to_string(),
+        };
+        let fetch = |before, until, limit| {
+            let fail = {
+                let mut failures = failures.lock().unwrap();
+                *failures > 0 && { *failures -= 1; true }
+            };
+            let (chain, calls) = (&chain, &calls);
+            async move {
+                if fail {
+                    return Err(limited());
+                }
+                fake_page(chain, calls, before, until, limit).await
+            }
+        };
+
+        let history = SignatureHistory::new(settings());
+        assert_eq!(history.fetch(ADDRESS, fetch).await.unwrap().len(), 5);
+
+        *failures.lock().unwrap() = 3;
+        let history = SignatureHistory::new(settings());
+        let err = history.fetch(ADDRESS, fetch).await.unwrap_err();
+        assert!(matches!(err, DegenScoreError::RateLimitExceeded { .. }));
+    }
+}
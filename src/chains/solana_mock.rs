@@ -76,6 +76,7 @@ impl ChainClient for SolanaClient {
             active_days: 120,
             average_tx_per_day: 0.375,
             gas_spent: Decimal::ZERO,
+            ..TransactionSummary::default()
         })
     }
     
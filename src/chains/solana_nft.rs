@@ -0,0 +1,232 @@
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+
+use crate::models::ProtocolAddresses;
+
+// Account discriminators of the Token Metadata program
+const METADATA_V1: u8 = 4;
+const EDITION_V1: u8 = 1;
+const MASTER_EDITION_V1: u8 = 2;
+const MASTER_EDITION_V2: u8 = 6;
+
+// Token standards that are not NFTs even with metadata
+const FUNGIBLE_ASSET: u8 = 1;
+const FUNGIBLE: u8 = 2;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Creator {
+    pub address: String,
+    pub verified: bool,
+    pub share: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Collection {
+    pub key: String,
+    pub verified: bool,
+}
+
+/// A decoded Metaplex metadata account. Names, symbols and URIs are stored padded
+/// with NULs, which are trimmed here.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Metadata {
+    pub update_authority: String,
+    pub mint: String,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub seller_fee_basis_points: u16,
+    pub creators: Vec<Creator>,
+    pub token_standard: Option<u8>,
+    /// Absent on accounts written before collections existed
+    pub collection: Option<Collection>,
+}
+
+impl Metadata {
+    /// Key grouping the NFT with its siblings: the verified collection, else the first
+    /// verified creator, which is how collections were identified before Metaplex had
+    /// them. Unverified collections are ignored since anyone can claim one.
+    pub fn collection_key(&self) -> Option<&str> {
+        self.collection.as_ref()
+            .filter(|collection| collection.verified)
+            .map(|collection| collection.key.as_str())
+            .or_else(|| self.creators.iter().find(|creator| creator.verified).map(|creator| creator.address.as_str()))
+    }
+
+    pub fn is_fungible(&self) -> bool {
+        matches!(self.token_standard, Some(FUNGIBLE_ASSET) | Some(FUNGIBLE))
+    }
+}
+
+fn metadata_program() -> Pubkey {
+    Pubkey::from_str(ProtocolAddresses::METAPLEX_TOKEN_METADATA).expect("valid program id")
+}
+
+/// Metadata PDA of `mint`: seeds `["metadata", program, mint]`
+pub fn metadata_address(mint: &Pubkey) -> Pubkey {
+    let program = metadata_program();
+    Pubkey::find_program_address(&[b"metadata", program.as_ref(), mint.as_ref()], &program).0
+}
+
+/// Edition PDA of `mint`: seeds `["metadata", program, mint, "edition"]`. It holds a
+/// master edition for originals and an edition for prints.
+pub fn edition_address(mint: &Pubkey) -> Pubkey {
+    let program = metadata_program();
+    Pubkey::find_program_address(&[b"metadata", program.as_ref(), mint.as_ref(), b"edition"], &program).0
+}
+
+/// Whether `data` is a master edition or print edition account
+pub fn is_edition(data: &[u8]) -> bool {
+    matches!(data.first(), Some(&(EDITION_V1 | MASTER_EDITION_V1 | MASTER_EDITION_V2)))
+}
+
+/// Decode a Borsh-encoded `MetadataV1` account
+pub fn decode_metadata(data: &[u8]) -> Option<Metadata> {
+    let mut reader = Reader { data };
+    if reader.u8()? != METADATA_V1 {
+        return None;
+    }
+
+    let update_authority = reader.pubkey()?;
+    let mint = reader.pubkey()?;
+    let name = reader.string()?;
+    let symbol = reader.string()?;
+    let uri = reader.string()?;
+    let seller_fee_basis_points = reader.u16()?;
+    let creators = match reader.option()? {
+        true => (0..reader.u32()?)
+            .map(|_| Some(Creator { address: reader.pubkey()?, verified: reader.bool()?, share: reader.u8()? }))
+            .collect::<Option<Vec<_>>>()?,
+        false => Vec::new(),
+    };
+
+    // Fields added in later versions; older accounts end here or are zero padded
+    let mut token_standard = None;
+    let mut collection = None;
+    let _primary_sale_happened = reader.bool();
+    let _is_mutable = reader.bool();
+    if reader.option() == Some(true) {
+        let _edition_nonce = reader.u8();
+    }
+    if reader.option() == Some(true) {
+        token_standard = reader.u8();
+    }
+    if reader.option() == Some(true) {
+        if let (Some(verified), Some(key)) = (reader.bool(), reader.pubkey()) {
+            collection = Some(Collection { key, verified });
+        }
+    }
+
+    Some(Metadata {
+        update_authority,
+        mint,
+        name,
+        symbol,
+        uri,
+        seller_fee_basis_points,
+        creators,
+        token_standard,
+        collection,
+    })
+}
+
+// Little-endian Borsh cursor that yields `None` once the data runs out
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl Reader<'_> {
+    fn take(&mut self, len: usize) -> Option<&[u8]> {
+        if self.data.len() < len {
+            return None;
+        }
+        let (head, rest) = self.data.split_at(len);
+        self.data = rest;
+        Some(head)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.take(1).map(|bytes| bytes[0])
+    }
+
+    fn bool(&mut self) -> Option<bool> {
+        self.u8().map(|byte| byte != 0)
+    }
+
+    fn option(&mut self) -> Option<bool> {
+        self.bool()
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        self.take(2).map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.take(4).map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn pubkey(&mut self) -> Option<String> {
+        self.take(32).map(|bytes| bs58::encode(bytes).into_string())
+    }
+
+    fn string(&mut self) -> Option<String> {
+        let len = self.u32()? as usize;
+        let bytes = self.take(len)?;
+        Some(String::from_utf8_lossy(bytes).trim_end_matches('\0').to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{metadata_fixture, METADATA_AUTHORITY, METADATA_COLLECTION, METADATA_CREATOR, METADATA_MINT};
+
+    #[test]
+    fn test_decode_metadata() {
+        let metadata = decode_metadata(&metadata_fixture(true)).unwrap();
+        assert_eq!(metadata.update_authority, bs58::encode(METADATA_AUTHORITY).into_string());
+        assert_eq!(metadata.mint, bs58::encode(METADATA_MINT).into_string());
+        assert_eq!(metadata.name, "Mad Lads #8420");
+        assert_eq!(metadata.symbol, "MAD");
+        assert_eq!(metadata.uri, "https://madlads.s3.us-west-2.amazonaws.com/json/8420.json");
+        assert_eq!(metadata.seller_fee_basis_points, 420);
+        assert_eq!(metadata.creators, vec![Creator { address: bs58::encode(METADATA_CREATOR).into_string(), verified: true, share: 100 }]);
+        assert_eq!(metadata.token_standard, Some(4));
+        assert!(!metadata.is_fungible());
+        assert_eq!(metadata.collection_key(), Some(bs58::encode(METADATA_COLLECTION).into_string().as_str()));
+
+        // An unverified collection falls back to the verified creator
+        let unverified = decode_metadata(&metadata_fixture(false)).unwrap();
+        assert_eq!(unverified.collection_key(), Some(bs58::encode(METADATA_CREATOR).into_string().as_str()));
+    }
+
+    #[test]
+    fn test_decode_legacy_and_invalid_metadata() {
+        // Written before edition nonces, token standards and collections
+        let mut legacy = metadata_fixture(true);
+        legacy.truncate(1 + 32 + 32 + 36 + 14 + 204 + 2 + 1 + 4 + 34 + 2);
+        let metadata = decode_metadata(&legacy).unwrap();
+        assert_eq!(metadata.name, "Mad Lads #8420");
+        assert_eq!(metadata.token_standard, None);
+        assert_eq!(metadata.collection, None);
+
+        let mut edition = metadata_fixture(true);
+        edition[0] = MASTER_EDITION_V2;
+        assert!(decode_metadata(&edition).is_none());
+        assert!(decode_metadata(&metadata_fixture(true)[..100]).is_none());
+        assert!(is_edition(&[MASTER_EDITION_V2, 0, 0]));
+        assert!(is_edition(&[EDITION_V1]));
+        assert!(!is_edition(&[METADATA_V1]));
+        assert!(!is_edition(&[]));
+    }
+
+    #[test]
+    fn test_pda_derivation() {
+        // The USDC mint and its metadata account on mainnet
+        let mint = Pubkey::from_str("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v").unwrap();
+        assert_eq!(metadata_address(&mint).to_string(), "5x38Kp4hvdomTCnCrAny4UtMUt5rQBdB6px2K1Ui45Wq");
+
+        let mint = Pubkey::new_from_array(METADATA_MINT);
+        assert_ne!(metadata_address(&mint), edition_address(&mint));
+    }
+}
@@ -0,0 +1,191 @@
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{
+    chains::solana_tx::{ParsedTransaction, SwapLeg},
+    config::SolanaSettings,
+    models::{ProtocolAddresses, SolanaProtocol, SolanaProtocolKind},
+};
+
+/// Use of one protocol across a set of transactions
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProtocolActivity {
+    pub protocol: SolanaProtocol,
+    pub interaction_count: u32,
+    pub first_interaction: Option<DateTime<Utc>>,
+    pub last_interaction: Option<DateTime<Utc>>,
+    /// Balance changes to value: collateral in and out for perps, wagers for casinos
+    pub flows: Vec<SwapLeg>,
+}
+
+/// Program IDs and mints of the Solana perp venues, casinos and casino tokens that
+/// are detected. Perp venues are built in; casinos and their tokens come from
+/// `[[solana.protocols]]` and `[[solana.casino_tokens]]`, which can also add or
+/// rename venues.
+#[derive(Debug, Clone)]
+pub struct ProtocolRegistry {
+    programs: HashMap<String, SolanaProtocol>,
+    casino_tokens: HashMap<String, String>,
+}
+
+impl ProtocolRegistry {
+    pub fn new(settings: &SolanaSettings) -> Self {
+        let perp = |name: &str, program_id: &str| SolanaProtocol {
+            name: name.to_string(),
+            kind: SolanaProtocolKind::Perp,
+            program_id: program_id.to_string(),
+        };
+        let built_in = [
+            perp("Drift", ProtocolAddresses::DRIFT_V2),
+            perp("Mango", ProtocolAddresses::MANGO_V4),
+            perp("Zeta", ProtocolAddresses::ZETA_MARKETS),
+            perp("Jupiter Perps", ProtocolAddresses::JUPITER_PERPS),
+        ];
+
+        Self {
+            programs: built_in.into_iter()
+                .chain(settings.protocols.iter().cloned())
+                .map(|protocol| (protocol.program_id.clone(), protocol))
+                .collect(),
+            casino_tokens: settings.casino_tokens.iter()
+                .map(|token| (token.mint.clone(), token.symbol.clone()))
+                .collect(),
+        }
+    }
+
+    pub fn protocol(&self, program_id: &str) -> Option<&SolanaProtocol> {
+        self.programs.get(program_id)
+    }
+
+    /// Protocol registered under `name`, ignoring case
+    pub fn find(&self, name: &str) -> Option<&SolanaProtocol> {
+        self.programs.values().find(|protocol| protocol.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Symbol of a casino token mint
+    pub fn casino_token(&self, mint: &str) -> Option<&str> {
+        self.casino_tokens.get(mint).map(String::as_str)
+    }
+
+    /// Activity per protocol name among `owner`'s successful `transactions`. A
+    /// transaction counts once per protocol, however many of its instructions or CPIs
+    /// invoke it.
+    pub fn activity(&self, transactions: &[ParsedTransaction], owner: &str) -> BTreeMap<String, ProtocolActivity> {
+        let mut activity: BTreeMap<String, ProtocolActivity> = BTreeMap::new();
+        for tx in transactions.iter().filter(|tx| tx.succeeded()) {
+            let protocols: BTreeMap<&str, &SolanaProtocol> = tx.invoked_programs().into_iter()
+                .filter_map(|program| self.protocol(program))
+                .map(|protocol| (protocol.name.as_str(), protocol))
+                .collect();
+            if protocols.is_empty() {
+                continue;
+            }
+
+            let deltas = tx.balance_deltas(owner);
+            for protocol in protocols.into_values() {
+                let entry = activity.entry(protocol.name.clone()).or_insert_with(|| ProtocolActivity {
+                    protocol: protocol.clone(),
+                    interaction_count: 0,
+                    first_interaction: None,
+                    last_interaction: None,
+                    flows: Vec::new(),
+                });
+                entry.interaction_count += 1;
+                if let Some(timestamp) = tx.timestamp() {
+                    entry.first_interaction = Some(entry.first_interaction.map_or(timestamp, |first| first.min(timestamp)));
+                    entry.last_interaction = entry.last_interaction.max(Some(timestamp));
+                }
+
+                let flows = deltas.iter()
+                    .filter(|(_, (amount, _))| protocol.kind == SolanaProtocolKind::Perp || *amount < 0)
+                    .filter_map(|(mint, (amount, decimals))| Some(SwapLeg {
+                        mint: mint.clone(),
+                        amount: u64::try_from(amount.unsigned_abs()).ok()?,
+                        decimals: *decimals,
+                    }));
+                entry.flows.extend(flows);
+            }
+        }
+        activity
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::SolanaCasinoToken;
+    use serde_json::json;
+
+    const OWNER: &str = "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM";
+    const USDC: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+    const CASINO: &str = "Casino1111111111111111111111111111111111111";
+
+    fn settings() -> SolanaSettings {
+        SolanaSettings {
+            protocols: vec![SolanaProtocol {
+                name: "Test Casino".to_string(),
+                kind: SolanaProtocolKind::Casino,
+                program_id: CASINO.to_string(),
+            }],
+            casino_tokens: vec![SolanaCasinoToken { mint: "Chip111111111111111111111111111111111111111".to_string(), symbol: "CHIP".to_string() }],
+            ..SolanaSettings::default()
+        }
+    }
+
+    // The owner's USDC balance moves by `change` in a transaction invoking `programs`
+    fn transaction(block_time: i64, programs: &[&str], change: i64) -> ParsedTransaction {
+        let mut keys = vec![OWNER.to_string(), "UsdcAccount1111111111111111111111111111111".to_string()];
+        keys.extend(programs.iter().map(|program| program.to_string()));
+        let instructions: Vec<_> = (0..programs.len()).map(|i| json!({ "programIdIndex": i + 2, "accounts": [0, 1] })).collect();
+        let balance = |amount: i64| json!([{ "accountIndex": 1, "mint": USDC, "owner": OWNER, "uiTokenAmount": { "amount": amount.to_string(), "decimals": 6 } }]);
+        serde_json::from_value(json!({
+            "blockTime": block_time,
+            "meta": {
+                "err": null,
+                "fee": 5_000,
+                "preBalances": [1_000_000_000u64, 2_039_280],
+                "postBalances": [999_995_000u64, 2_039_280],
+                "preTokenBalances": balance(500_000_000),
+                "postTokenBalances": balance(500_000_000 + change),
+            },
+            "transaction": { "signatures": [format!("sig{}", block_time)], "message": { "accountKeys": keys, "instructions": instructions } }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_registry_lookups() {
+        let registry = ProtocolRegistry::new(&settings());
+        assert_eq!(registry.protocol(ProtocolAddresses::DRIFT_V2).unwrap().name, "Drift");
+        assert_eq!(registry.protocol(CASINO).unwrap().kind, SolanaProtocolKind::Casino);
+        assert_eq!(registry.find("jupiter perps").unwrap().program_id, ProtocolAddresses::JUPITER_PERPS);
+        assert_eq!(registry.casino_token("Chip111111111111111111111111111111111111111"), Some("CHIP"));
+        assert!(registry.protocol(ProtocolAddresses::JUPITER_V6).is_none());
+    }
+
+    #[test]
+    fn test_activity_by_program_invocation() {
+        let registry = ProtocolRegistry::new(&settings());
+        let transactions = vec![
+            // Deposit then withdraw collateral on Drift, both directions count
+            transaction(2_000, &[ProtocolAddresses::DRIFT_V2], -100_000_000),
+            transaction(1_000, &[ProtocolAddresses::DRIFT_V2, ProtocolAddresses::DRIFT_V2], 40_000_000),
+            // A bet and a win: only the wager counts
+            transaction(3_000, &[CASINO], -5_000_000),
+            transaction(4_000, &[CASINO], 9_000_000),
+            transaction(5_000, &[ProtocolAddresses::JUPITER_V6], -1),
+        ];
+        let activity = registry.activity(&transactions, OWNER);
+        assert_eq!(activity.len(), 2);
+
+        let drift = &activity["Drift"];
+        assert_eq!(drift.interaction_count, 2);
+        assert_eq!(drift.first_interaction, DateTime::from_timestamp(1_000, 0));
+        assert_eq!(drift.last_interaction, DateTime::from_timestamp(2_000, 0));
+        assert_eq!(drift.flows.iter().map(|flow| flow.amount).collect::<Vec<_>>(), vec![100_000_000, 40_000_000]);
+
+        let casino = &activity["Test Casino"];
+        assert_eq!(casino.interaction_count, 2);
+        assert_eq!(casino.flows, vec![SwapLeg { mint: USDC.to_string(), amount: 5_000_000, decimals: 6 }]);
+    }
+}
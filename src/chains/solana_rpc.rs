@@ -1,5 +1,6 @@
 use async_trait::async_trait;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
 use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
@@ -7,16 +8,113 @@ use serde_json::json;
 use reqwest;
 use std::str::FromStr;
 
+use base64::Engine;
+use futures::{stream, StreamExt};
+use tracing::{debug, info, warn};
+
 use crate::{
     models::{
-        Chain, ChainMetrics, DegenMetrics, TokenBalance, NFTBalance,
-        TransactionSummary, DegenScoreError, Result,
+        Chain, ChainMetrics, DegenMetrics, TokenBalance, NFTBalance, NftStandard,
+        TransactionSummary, DegenScoreError, Result, KnownTokens, PriceSource, SolanaProtocolKind,
+    },
+    chains::{
+        ChainClient, client::{ProtocolMetrics, ChainClientConfig},
+        pricing::{self, PriceCache, LATEST},
+        solana_history::{self, SignatureHistory, SignatureInfo},
+        solana_nft,
+        solana_protocols::{ProtocolActivity, ProtocolRegistry},
+        solana_tx::{self, ParsedTransaction},
     },
-    chains::{ChainClient, client::{ProtocolMetrics, ChainClientConfig}},
+    config::{PricingSettings, SolanaSettings},
 };
 
-/// Solana RPC client using direct JSON-RPC calls to avoid dependency conflicts
-pub struct SolanaRpcClient {
+/// The RPC calls Solana scoring is built on. Everything above them is shared, so the
+/// JSON-RPC transport here and the typed `solana-rpc-client` one in `chains::solana`
+/// (`solana-native` feature) score wallets the same way.
+#[async_trait]
+pub trait SolanaTransport: Send + Sync {
+    /// Lamports held by `address`, or `None` if the account does not exist
+    async fn get_lamports(&self, address: &str) -> Result<Option<u64>>;
+    
+    /// One page of `getSignaturesForAddress`, newest first
+    async fn get_signatures_for_address(
+        &self,
+        address: &str,
+        before: Option<String>,
+        until: Option<String>,
+        limit: usize,
+    ) -> Result<Vec<SignatureInfo>>;
+    
+    async fn get_transaction(&self, signature: &str) -> Result<ParsedTransaction>;
+    
+    /// Raw bytes of each account in `addresses`, `None` for those that do not exist
+    async fn get_multiple_accounts_data(&self, addresses: &[String]) -> Result<Vec<Option<Vec<u8>>>>;
+    
+    /// SPL Token program accounts owned by `owner`
+    async fn get_token_accounts(&self, owner: &str) -> Result<Vec<SplTokenAccount>>;
+}
+
+/// A token account of the SPL Token program
+#[derive(Debug, Clone, PartialEq)]
+pub struct SplTokenAccount {
+    pub address: String,
+    pub mint: String,
+    /// Raw units
+    pub amount: u64,
+    pub decimals: u8,
+}
+
+/// Solana chain client. RPC calls go through `T`: direct JSON-RPC calls by default,
+/// which avoid the solana-rpc-client dependency tree.
+pub struct SolanaRpcClient<T = JsonRpcTransport> {
+    rpc: T,
+    chain: Chain,
+    price_sources: HashMap<String, PriceSource>,
+    prices: PriceCache,
+    // Oldest Pyth update, in seconds, still used as a price
+    max_feed_age: u64,
+    settings: SolanaSettings,
+    history: SignatureHistory,
+    registry: ProtocolRegistry,
+    transactions: TransactionCache,
+}
+
+/// Parsed transactions by signature. A confirmed transaction never changes, so
+/// entries only leave when the cache is full, oldest first.
+struct TransactionCache {
+    entries: Mutex<HashMap<String, ParsedTransaction>>,
+    // Insertion order, oldest first, for eviction
+    order: Mutex<VecDeque<String>>,
+    capacity: usize,
+}
+
+impl TransactionCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            order: Mutex::new(VecDeque::new()),
+            capacity: capacity.max(1),
+        }
+    }
+
+    fn get(&self, signature: &str) -> Option<ParsedTransaction> {
+        self.entries.lock().ok()?.get(signature).cloned()
+    }
+
+    fn insert(&self, signature: &str, transaction: &ParsedTransaction) {
+        let (Ok(mut entries), Ok(mut order)) = (self.entries.lock(), self.order.lock()) else { return };
+        if entries.insert(signature.to_string(), transaction.clone()).is_none() {
+            order.push_back(signature.to_string());
+        }
+        while entries.len() > self.capacity {
+            let Some(oldest) = order.pop_front() else { break };
+            entries.remove(&oldest);
+        }
+    }
+}
+
+/// `SolanaTransport` over hand-written JSON-RPC requests
+pub struct JsonRpcTransport {
     http_client: reqwest::Client,
     rpc_url: String,
     chain: Chain,
@@ -44,15 +142,6 @@ struct RpcError {
     message: String,
 }
 
-#[derive(Deserialize)]
-struct GetSignaturesForAddressResult {
-    signature: String,
-    slot: u64,
-    #[serde(rename = "blockTime")]
-    block_time: Option<i64>,
-    err: Option<serde_json::Value>,
-}
-
 #[derive(Deserialize)]
 struct GetAccountInfoResult {
     value: Option<AccountInfo>,
@@ -108,13 +197,9 @@ struct TokenInfo {
 struct TokenAmount {
     amount: String,
     decimals: u8,
-    #[serde(rename = "uiAmount")]
-    ui_amount: Option<f64>,
-    #[serde(rename = "uiAmountString")]
-    ui_amount_string: String,
 }
 
-impl SolanaRpcClient {
+impl JsonRpcTransport {
     pub fn new(config: ChainClientConfig) -> Result<Self> {
         let http_client = reqwest::Client::builder()
             .timeout(std::time::Duration::from_secs(config.timeout_seconds))
@@ -164,12 +249,36 @@ impl SolanaRpcClient {
             message: "Empty result from RPC".to_string(),
         })
     }
+}
+
+#[async_trait]
+impl SolanaTransport for JsonRpcTransport {
+    async fn get_lamports(&self, address: &str) -> Result<Option<u64>> {
+        let params = json!([
+            address,
+            {
+                "encoding": "jsonParsed",
+                "commitment": "confirmed"
+            }
+        ]);
+        
+        let result: GetAccountInfoResult = self.make_rpc_request("getAccountInfo", params).await?;
+        Ok(result.value.map(|info| info.lamports))
+    }
     
-    async fn get_signatures_for_address(&self, address: &str, limit: usize) -> Result<Vec<GetSignaturesForAddressResult>> {
+    async fn get_signatures_for_address(
+        &self,
+        address: &str,
+        before: Option<String>,
+        until: Option<String>,
+        limit: usize,
+    ) -> Result<Vec<SignatureInfo>> {
         let params = json!([
             address,
             {
                 "limit": limit,
+                "before": before,
+                "until": until,
                 "commitment": "confirmed"
             }
         ]);
@@ -177,21 +286,51 @@ impl SolanaRpcClient {
         self.make_rpc_request("getSignaturesForAddress", params).await
     }
     
-    async fn get_account_info(&self, address: &str) -> Result<GetAccountInfoResult> {
+    async fn get_transaction(&self, signature: &str) -> Result<ParsedTransaction> {
         let params = json!([
-            address,
+            signature,
             {
-                "encoding": "jsonParsed",
+                "encoding": "json",
+                "maxSupportedTransactionVersion": 0,
                 "commitment": "confirmed"
             }
         ]);
         
-        self.make_rpc_request("getAccountInfo", params).await
+        self.make_rpc_request("getTransaction", params).await
+    }
+    
+    async fn get_multiple_accounts_data(&self, addresses: &[String]) -> Result<Vec<Option<Vec<u8>>>> {
+        #[derive(Deserialize)]
+        struct GetMultipleAccountsResult {
+            value: Vec<Option<AccountInfo>>,
+        }
+        
+        let mut accounts = Vec::with_capacity(addresses.len());
+        // getMultipleAccounts takes at most 100 keys
+        for chunk in addresses.chunks(100) {
+            let params = json!([
+                chunk,
+                {
+                    "encoding": "base64",
+                    "commitment": "confirmed"
+                }
+            ]);
+            let result: GetMultipleAccountsResult = self.make_rpc_request("getMultipleAccounts", params).await?;
+            for account in result.value {
+                // Base64 data comes as [data, "base64"]
+                let data = account.and_then(|account| {
+                    let encoded = account.data.get(0)?.as_str()?.to_string();
+                    base64::engine::general_purpose::STANDARD.decode(encoded).ok()
+                });
+                accounts.push(data);
+            }
+        }
+        Ok(accounts)
     }
     
-    async fn get_token_accounts_by_owner(&self, address: &str) -> Result<GetTokenAccountsByOwnerResult> {
+    async fn get_token_accounts(&self, owner: &str) -> Result<Vec<SplTokenAccount>> {
         let params = json!([
-            address,
+            owner,
             {
                 "programId": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"
             },
@@ -200,7 +339,265 @@ impl SolanaRpcClient {
             }
         ]);
         
-        self.make_rpc_request("getTokenAccountsByOwner", params).await
+        let result: GetTokenAccountsByOwnerResult = self.make_rpc_request("getTokenAccountsByOwner", params).await?;
+        Ok(result.value.into_iter()
+            .filter_map(|account| {
+                let info = account.account.data.parsed.info;
+                Some(SplTokenAccount {
+                    address: account.pubkey,
+                    amount: info.token_amount.amount.parse().ok()?,
+                    decimals: info.token_amount.decimals,
+                    mint: info.mint,
+                })
+            })
+            .collect())
+    }
+}
+
+impl SolanaRpcClient {
+    pub fn new(config: ChainClientConfig) -> Result<Self> {
+        Ok(Self::with_transport(JsonRpcTransport::new(config)?))
+    }
+}
+
+impl<T: SolanaTransport> SolanaRpcClient<T> {
+    pub fn with_transport(rpc: T) -> Self {
+        let pricing = PricingSettings::default();
+        Self {
+            rpc,
+            chain: Chain::Solana,
+            price_sources: pricing::price_sources(&Chain::Solana, &pricing),
+            prices: PriceCache::from_settings(&pricing),
+            max_feed_age: pricing.max_feed_age_seconds,
+            settings: SolanaSettings::default(),
+            history: SignatureHistory::new(SolanaSettings::default()),
+            registry: ProtocolRegistry::new(&SolanaSettings::default()),
+            transactions: TransactionCache::new(SolanaSettings::default().transaction_cache_entries),
+        }
+    }
+    
+    pub fn with_solana_settings(mut self, settings: SolanaSettings) -> Self {
+        self.history = SignatureHistory::new(settings.clone());
+        self.registry = ProtocolRegistry::new(&settings);
+        self.transactions = TransactionCache::new(settings.transaction_cache_entries);
+        self.settings = settings;
+        self
+    }
+    
+    pub fn with_pricing_settings(mut self, settings: &PricingSettings) -> Self {
+        self.price_sources = pricing::price_sources(&Chain::Solana, settings);
+        self.prices = PriceCache::from_settings(settings);
+        self.max_feed_age = settings.max_feed_age_seconds;
+        self
+    }
+    
+    /// USD price of one whole `mint`. Pyth accounts only hold their latest update, so
+    /// Solana prices are always current and cached under `LATEST`; an update older than
+    /// `max_feed_age_seconds` is refused.
+    pub async fn price_usd(&self, mint: &str) -> Result<Decimal> {
+        if let Some(price) = self.prices.get(mint, LATEST) {
+            return Ok(price);
+        }
+        
+        let unavailable = || DegenScoreError::PriceUnavailable {
+            chain: self.chain.as_str().to_string(),
+            token: mint.to_string(),
+        };
+        let price = match self.price_sources.get(mint).ok_or_else(unavailable)? {
+            PriceSource::Stablecoin => Decimal::ONE,
+            PriceSource::Pyth { account } => {
+                let data = self.get_account_data(account).await?.ok_or_else(unavailable)?;
+                let (price, publish_time) = pricing::pyth_price(&data).ok_or_else(|| DegenScoreError::RpcError {
+                    chain: self.chain.as_str().to_string(),
+                    message: format!("Malformed Pyth price account {}", account),
+                })?;
+                let age = Utc::now().timestamp().saturating_sub(publish_time);
+                if age > self.max_feed_age as i64 {
+                    return Err(DegenScoreError::RpcError {
+                        chain: self.chain.as_str().to_string(),
+                        message: format!("Pyth price account {} was last updated {}s ago", account, age),
+                    });
+                }
+                price
+            }
+            _ => return Err(unavailable()),
+        };
+        
+        self.prices.insert(mint, LATEST, price);
+        Ok(price)
+    }
+    
+    /// USD value of `amount` raw units of `mint`
+    pub async fn value_usd(&self, mint: &str, amount: u64, decimals: u8) -> Result<Decimal> {
+        let price = self.price_usd(mint).await?;
+        Decimal::try_from_i128_with_scale(amount as i128, decimals as u32).ok()
+            .and_then(|amount| amount.checked_mul(price))
+            .ok_or_else(|| DegenScoreError::PriceUnavailable {
+                chain: self.chain.as_str().to_string(),
+                token: mint.to_string(),
+            })
+    }
+    
+    /// Every signature of the address, newest first, up to `max_signatures`
+    async fn get_signature_history(&self, address: &str) -> Result<Vec<SignatureInfo>> {
+        self.history.fetch(address, |before, until, limit| {
+            self.rpc.get_signatures_for_address(address, before, until, limit)
+        }).await
+    }
+    
+    /// The latest successful transactions among `signatures`, newest first, up to
+    /// `max_parsed_transactions`. Transactions already parsed come from the cache.
+    /// Transactions that fail to load are skipped.
+    async fn get_parsed_transactions(&self, signatures: &[SignatureInfo]) -> Vec<ParsedTransaction> {
+        let successful: Vec<String> = signatures.iter()
+            .filter(|sig| sig.err.is_none())
+            .take(self.settings.max_parsed_transactions)
+            .map(|sig| sig.signature.clone())
+            .collect();
+        
+        stream::iter(successful)
+            .map(|signature| async move {
+                if let Some(transaction) = self.transactions.get(&signature) {
+                    return Some(transaction);
+                }
+                match self.rpc.get_transaction(&signature).await {
+                    Ok(transaction) => {
+                        self.transactions.insert(&signature, &transaction);
+                        Some(transaction)
+                    }
+                    Err(e) => {
+                        warn!("Skipping Solana transaction {}: {}", signature, e);
+                        None
+                    }
+                }
+            })
+            .buffered(self.settings.transaction_concurrency.max(1))
+            .filter_map(|transaction| async move { transaction })
+            .collect()
+            .await
+    }
+    
+    /// Whether `max_parsed_transactions` leaves successful transactions among
+    /// `signatures` unparsed, so swap counts only cover the latest ones
+    fn parse_cap_reached(&self, signatures: &[SignatureInfo]) -> bool {
+        signatures.iter().filter(|sig| sig.err.is_none()).count() > self.settings.max_parsed_transactions
+    }
+    
+    /// Jupiter swaps among `transactions`. Volume is the input side at current prices,
+    /// or the output side when the input has no price source.
+    async fn jupiter_metrics(&self, address: &str, transactions: &[ParsedTransaction]) -> ProtocolMetrics {
+        let mut metrics = ProtocolMetrics {
+            protocol_name: "Jupiter".to_string(),
+            interaction_count: 0,
+            volume_usd: Decimal::ZERO,
+            first_interaction: None,
+            last_interaction: None,
+            custom_metrics: HashMap::new(),
+        };
+        let mut input_mints: HashMap<String, u32> = HashMap::new();
+        let mut output_mints: HashMap<String, u32> = HashMap::new();
+        
+        for swap in transactions.iter().filter_map(|tx| solana_tx::jupiter_swap(tx, address)) {
+            metrics.interaction_count += 1;
+            if let Some(timestamp) = swap.timestamp {
+                metrics.first_interaction = Some(metrics.first_interaction.map_or(timestamp, |first| first.min(timestamp)));
+                metrics.last_interaction = metrics.last_interaction.max(Some(timestamp));
+            }
+            
+            for leg in swap.input.iter().chain(&swap.output) {
+                if let Ok(volume) = self.value_usd(&leg.mint, leg.amount, leg.decimals).await {
+                    metrics.volume_usd += volume;
+                    break;
+                }
+            }
+            if let Some(input) = &swap.input {
+                *input_mints.entry(input.mint.clone()).or_insert(0) += 1;
+            }
+            if let Some(output) = &swap.output {
+                *output_mints.entry(output.mint.clone()).or_insert(0) += 1;
+            }
+        }
+        
+        metrics.custom_metrics.insert("input_mints".to_string(), json!(input_mints));
+        metrics.custom_metrics.insert("output_mints".to_string(), json!(output_mints));
+        metrics
+    }
+    
+    /// Metrics of one registry protocol, with its flows valued at current prices.
+    /// Flows in tokens without a price source are left out of the volume. For a perp
+    /// venue the volume is collateral moved in and out, not traded notional.
+    async fn registry_metrics(&self, activity: &ProtocolActivity) -> ProtocolMetrics {
+        let mut volume_usd = Decimal::ZERO;
+        for flow in &activity.flows {
+            if let Ok(value) = self.value_usd(&flow.mint, flow.amount, flow.decimals).await {
+                volume_usd += value;
+            }
+        }
+        
+        ProtocolMetrics {
+            protocol_name: activity.protocol.name.clone(),
+            interaction_count: activity.interaction_count,
+            volume_usd,
+            first_interaction: activity.first_interaction,
+            last_interaction: activity.last_interaction,
+            custom_metrics: HashMap::from([
+                ("kind".to_string(), json!(activity.protocol.kind)),
+                ("program_id".to_string(), json!(activity.protocol.program_id)),
+            ]),
+        }
+    }
+    
+    /// Raw bytes of an account, or `None` if it does not exist
+    async fn get_account_data(&self, address: &str) -> Result<Option<Vec<u8>>> {
+        let mut accounts = self.rpc.get_multiple_accounts_data(&[address.to_string()]).await?;
+        Ok(accounts.pop().flatten())
+    }
+    
+    /// Metaplex NFTs among the address's token accounts. Accounts holding one unit of a
+    /// zero-decimal mint are candidates; those with a metadata account and an edition
+    /// account are kept.
+    async fn discover_nfts(&self, token_accounts: &[SplTokenAccount]) -> Result<Vec<NFTBalance>> {
+        let mints: Vec<solana_sdk::pubkey::Pubkey> = token_accounts.iter()
+            .filter(|account| account.amount == 1 && account.decimals == 0)
+            .filter_map(|account| solana_sdk::pubkey::Pubkey::from_str(&account.mint).ok())
+            .collect();
+        if mints.is_empty() {
+            return Ok(Vec::new());
+        }
+        
+        let addresses: Vec<String> = mints.iter()
+            .flat_map(|mint| [solana_nft::metadata_address(mint).to_string(), solana_nft::edition_address(mint).to_string()])
+            .collect();
+        let accounts = self.rpc.get_multiple_accounts_data(&addresses).await?;
+        
+        let mut nfts = Vec::new();
+        for (mint, pair) in mints.iter().zip(accounts.chunks(2)) {
+            let (Some(metadata), Some(edition)) = (&pair[0], &pair[1]) else { continue };
+            let Some(metadata) = solana_nft::decode_metadata(metadata) else { continue };
+            if !solana_nft::is_edition(edition) || metadata.is_fungible() {
+                continue;
+            }
+            
+            let collection = metadata.collection_key().map(str::to_string);
+            let mut fields = HashMap::from([
+                ("name".to_string(), json!(metadata.name)),
+                ("symbol".to_string(), json!(metadata.symbol)),
+                ("update_authority".to_string(), json!(metadata.update_authority)),
+            ]);
+            if let Some(collection) = &collection {
+                fields.insert("collection".to_string(), json!(collection));
+            }
+            nfts.push(NFTBalance {
+                // Grouped like EVM contracts: by collection, or alone when it has none
+                contract_address: collection.unwrap_or_else(|| mint.to_string()),
+                token_id: mint.to_string(),
+                token_uri: Some(metadata.uri).filter(|uri| !uri.is_empty()),
+                metadata: Some(fields),
+                standard: NftStandard::Metaplex,
+                balance: ethers::types::U256::one(),
+            });
+        }
+        Ok(nfts)
     }
     
     fn validate_solana_address(address: &str) -> Result<()> {
@@ -223,7 +620,7 @@ impl SolanaRpcClient {
 }
 
 #[async_trait]
-impl ChainClient for SolanaRpcClient {
+impl<T: SolanaTransport> ChainClient for SolanaRpcClient<T> {
     fn chain(&self) -> Chain {
         self.chain.clone()
     }
@@ -235,92 +632,152 @@ impl ChainClient for SolanaRpcClient {
         let mut metrics = DegenMetrics::default();
         
         // Get account info
-        let account_info = self.get_account_info(address).await?;
-        if let Some(info) = account_info.value {
-            println!("Solana address {} has {} SOL", address, info.lamports as f64 / 1e9);
+        let lamports = self.rpc.get_lamports(address).await?.unwrap_or(0);
+        if lamports > 0 {
+            debug!("Solana address {} has {} SOL", address, lamports as f64 / 1e9);
         }
         
-        // Get transaction signatures
-        let signatures = self.get_signatures_for_address(address, 1000).await?;
+        // Full signature history, paged back to the first transaction or the cap
+        let signatures = self.get_signature_history(address).await?;
         metrics.total_tx_count = signatures.len() as u32;
-        println!("Total transactions: {}", metrics.total_tx_count);
-        
-        // Calculate wallet age from oldest transaction
-        if let Some(oldest) = signatures.last() {
-            if let Some(block_time) = oldest.block_time {
-                let tx_time = DateTime::from_timestamp(block_time, 0)
-                    .unwrap_or_else(|| Utc::now());
-                let age = Utc::now().signed_duration_since(tx_time);
-                metrics.wallet_age_days = age.num_days().max(0) as u32;
-                println!("Wallet age: {} days", metrics.wallet_age_days);
-            }
+        debug!("Total transactions: {}", metrics.total_tx_count);
+        
+        let activity = solana_history::activity(&signatures);
+        for (timestamp, _) in &activity {
+            metrics.active_day_numbers.insert(timestamp.timestamp().div_euclid(86400)); // Days since epoch
         }
+        let summary = TransactionSummary::from_activity(&activity, None, None);
+        metrics.active_days = metrics.active_day_numbers.len() as u32;
+        metrics.active_months = summary.active_months;
+        metrics.first_transaction = summary.first_tx;
+        metrics.last_transaction = summary.last_tx;
+        metrics.most_active_period = summary.most_active_period;
         
-        // Count active days
-        let mut active_days = std::collections::HashSet::new();
-        for sig in &signatures {
-            if let Some(block_time) = sig.block_time {
-                let day = block_time / 86400; // Convert to days since epoch
-                active_days.insert(day);
+        // Wallet age from the oldest transaction
+        if let Some(first_tx) = summary.first_tx {
+            metrics.wallet_age_days = Utc::now().signed_duration_since(first_tx).num_days().max(0) as u32;
+            debug!("Wallet age: {} days", metrics.wallet_age_days);
+        }
+        
+        // Jupiter swaps from the latest transactions' instructions and balance changes
+        let transactions = self.get_parsed_transactions(&signatures).await;
+        if self.parse_cap_reached(&signatures) {
+            warn!(
+                "Solana address {} has more successful transactions than max_parsed_transactions ({}); swaps are counted over the latest only",
+                address, self.settings.max_parsed_transactions
+            );
+            metrics.jupiter_swaps_truncated = true;
+        }
+        let jupiter = self.jupiter_metrics(address, &transactions).await;
+        if jupiter.interaction_count > 0 {
+            metrics.jupiter_swaps = jupiter.interaction_count;
+            metrics.total_swap_volume_usd += jupiter.volume_usd;
+            metrics.protocols_used.insert("Jupiter".to_string());
+            metrics.defi_protocols_used = metrics.protocols_used.len() as u32;
+            metrics.protocol_interaction_counts.insert("Jupiter".to_string(), jupiter.interaction_count);
+            metrics.protocol_volume_usd.insert("Jupiter".to_string(), jupiter.volume_usd);
+            if let Some(first) = jupiter.first_interaction {
+                metrics.protocol_first_use.insert("Jupiter".to_string(), first);
             }
         }
-        metrics.active_days = active_days.len() as u32;
-        
-        // Check for Jupiter interactions (simplified - just count recent txs as potential swaps)
-        // In production, we would parse transaction details
-        let jupiter_program_v6 = "JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4";
-        let mut jupiter_count = 0;
-        
-        // For now, estimate based on transaction count (simplified)
-        if metrics.total_tx_count > 50 {
-            jupiter_count = (metrics.total_tx_count / 20).min(50); // Rough estimate
-            metrics.jupiter_swaps = jupiter_count;
-            metrics.defi_protocols_used += 1;
+        
+        // Perp venues and casinos from the registry, by program invocation
+        for (name, activity) in self.registry.activity(&transactions, address) {
+            let protocol_metrics = self.registry_metrics(&activity).await;
+            match activity.protocol.kind {
+                // Fills aren't decoded, so neither traded volume nor positions are known
+                SolanaProtocolKind::Perp => {
+                    metrics.perp_collateral_flow_usd += protocol_metrics.volume_usd;
+                    metrics.protocols_used.insert(name.clone());
+                    metrics.defi_protocols_used = metrics.protocols_used.len() as u32;
+                }
+                SolanaProtocolKind::Casino => {
+                    metrics.gambling_volume_usd += protocol_metrics.volume_usd;
+                    metrics.casino_platforms.insert(name.clone());
+                    metrics.casinos_used = metrics.casino_platforms.len() as u32;
+                }
+            }
+            metrics.protocol_interaction_counts.insert(name.clone(), protocol_metrics.interaction_count);
+            metrics.protocol_volume_usd.insert(name.clone(), protocol_metrics.volume_usd);
+            if let Some(first) = protocol_metrics.first_interaction {
+                metrics.protocol_first_use.insert(name, first);
+            }
         }
         
         // Get SPL token accounts
-        match self.get_token_accounts_by_owner(address).await {
+        match self.rpc.get_token_accounts(address).await {
             Ok(token_accounts) => {
-                let token_count = token_accounts.value.len();
-                metrics.distinct_tokens_traded = token_count as u32;
-                println!("Found {} SPL token accounts", token_count);
+                let token_count = token_accounts.len();
+                metrics.tokens_traded = token_accounts.iter()
+                    .map(|account| account.mint.clone())
+                    .collect();
+                metrics.distinct_tokens_traded = metrics.tokens_traded.len() as u32;
+                debug!("Found {} SPL token accounts", token_count);
                 
-                // Check for NFTs (tokens with amount = 1 and decimals = 0)
-                let mut nft_count = 0;
-                for token_account in &token_accounts.value {
-                    let token_info = &token_account.account.data.parsed.info;
-                    let amount = &token_info.token_amount;
-                    
-                    if amount.amount == "1" && amount.decimals == 0 {
-                        nft_count += 1;
+                // Metaplex NFTs and the collections they belong to
+                match self.discover_nfts(&token_accounts).await {
+                    Ok(nfts) => {
+                        metrics.nft_collections = nfts.iter()
+                            .map(|nft| nft.contract_address.clone())
+                            .collect();
+                        metrics.nft_collections_count = metrics.nft_collections.len() as u32;
+                        metrics.nfts_held = nfts.iter()
+                            .map(|nft| format!("{}:{}", nft.contract_address, nft.token_id))
+                            .collect();
+                        metrics.nft_count = metrics.nfts_held.len() as u32;
+                        debug!("Found {} NFTs in {} collections", metrics.nft_count, metrics.nft_collections_count);
                     }
-                    
-                    // Check for casino tokens (simplified check)
-                    if token_info.mint.contains("dice") || token_info.mint.contains("DICE") {
-                        if let Ok(balance) = Decimal::from_str(&amount.ui_amount_string) {
-                            metrics.casino_tokens_held.insert("DICE".to_string(), balance);
-                            metrics.casinos_used = 1;
+                    Err(e) => warn!("Failed to discover Solana NFTs: {}", e),
+                }
+                
+                // Casino tokens by registered mint
+                for token_account in &token_accounts {
+                    let Some(symbol) = self.registry.casino_token(&token_account.mint) else { continue };
+                    if let Ok(balance) = Decimal::try_from_i128_with_scale(token_account.amount as i128, token_account.decimals as u32) {
+                        if balance > Decimal::ZERO {
+                            *metrics.casino_tokens_held.entry(symbol.to_string()).or_insert(Decimal::ZERO) += balance;
                         }
                     }
                 }
                 
-                metrics.nft_count = nft_count;
-                println!("Found {} NFTs", nft_count);
+                // Value stablecoin accounts; other SPL tokens have no price source yet
+                let stablecoins = KnownTokens::stablecoins_by_chain(&Chain::Solana);
+                for token_account in &token_accounts {
+                    let Some(symbol) = stablecoins.get(token_account.mint.as_str()) else { continue };
+                    if let Ok(balance_usd) = self.value_usd(&token_account.mint, token_account.amount, token_account.decimals).await {
+                        metrics.total_balance_usd += balance_usd;
+                        metrics.stablecoin_balance_usd += balance_usd;
+                        *metrics.position_values_usd.entry(symbol.to_string()).or_insert(Decimal::ZERO) += balance_usd;
+                    }
+                }
             }
-            Err(e) => {
-                println!("Failed to get token accounts: {}", e);
+            Err(e) => warn!("Failed to get token accounts: {}", e),
+        }
+        
+        if lamports > 0 {
+            match self.value_usd(crate::models::ProtocolAddresses::SOL_NATIVE, lamports, 9).await {
+                Ok(balance_usd) => {
+                    metrics.total_balance_usd += balance_usd;
+                    *metrics.position_values_usd.entry("SOL".to_string()).or_insert(Decimal::ZERO) += balance_usd;
+                }
+                Err(e) => warn!("SOL balance left unvalued: {}", e),
             }
         }
+        let total_balance_usd = metrics.total_balance_usd;
+        if total_balance_usd > Decimal::ZERO {
+            let largest = metrics.position_values_usd.values().copied().max().unwrap_or_default();
+            metrics.stablecoin_percentage = (metrics.stablecoin_balance_usd / total_balance_usd).try_into().unwrap_or(0.0);
+            metrics.largest_position_percentage = (largest / total_balance_usd).try_into().unwrap_or(0.0);
+        }
         
         // Add Solana to active chains
         metrics.chains_active_on.push("solana".to_string());
         
-        println!("Solana metrics fetched successfully");
-        println!("- Wallet age: {} days", metrics.wallet_age_days);
-        println!("- Active days: {}", metrics.active_days);
-        println!("- Jupiter swaps (estimated): {}", metrics.jupiter_swaps);
-        println!("- Distinct tokens: {}", metrics.distinct_tokens_traded);
-        println!("- NFT count: {}", metrics.nft_count);
+        info!(
+            "Solana metrics for {}: wallet age {} days, {} active days, {} Jupiter swaps, {} distinct tokens, {} NFTs",
+            address, metrics.wallet_age_days, metrics.active_days, metrics.jupiter_swaps,
+            metrics.distinct_tokens_traded, metrics.nft_count,
+        );
         
         Ok(ChainMetrics {
             chain: "solana".to_string(),
@@ -333,49 +790,14 @@ impl ChainClient for SolanaRpcClient {
     async fn get_transaction_summary(
         &self,
         address: &str,
-        _start_time: Option<DateTime<Utc>>,
-        _end_time: Option<DateTime<Utc>>
+        start_time: Option<DateTime<Utc>>,
+        end_time: Option<DateTime<Utc>>
     ) -> Result<TransactionSummary> {
         Self::validate_solana_address(address)?;
         
-        let signatures = self.get_signatures_for_address(address, 1000).await?;
-        let total_count = signatures.len() as u32;
-        
-        let mut first_tx = None;
-        let mut last_tx = None;
-        
-        if let Some(newest) = signatures.first() {
-            if let Some(block_time) = newest.block_time {
-                last_tx = DateTime::from_timestamp(block_time, 0);
-            }
-        }
-        
-        if let Some(oldest) = signatures.last() {
-            if let Some(block_time) = oldest.block_time {
-                first_tx = DateTime::from_timestamp(block_time, 0);
-            }
-        }
-        
-        let active_days = if let (Some(first), Some(last)) = (first_tx, last_tx) {
-            (last.signed_duration_since(first).num_days() as u32).max(1)
-        } else {
-            0
-        };
-        
-        let average_tx_per_day = if active_days > 0 {
-            total_count as f64 / active_days as f64
-        } else {
-            0.0
-        };
-        
-        Ok(TransactionSummary {
-            total_count,
-            first_tx,
-            last_tx,
-            active_days,
-            average_tx_per_day,
-            gas_spent: Decimal::ZERO,
-        })
+        // Signatures carry no fees, so gas spent stays zero
+        let signatures = self.get_signature_history(address).await?;
+        Ok(TransactionSummary::from_activity(&solana_history::activity(&signatures), start_time, end_time))
     }
     
     async fn get_token_balances(&self, address: &str) -> Result<Vec<TokenBalance>> {
@@ -384,11 +806,10 @@ impl ChainClient for SolanaRpcClient {
         let mut balances = Vec::new();
         
         // Get SOL balance
-        let account_info = self.get_account_info(address).await?;
-        if let Some(info) = account_info.value {
+        if let Some(lamports) = self.rpc.get_lamports(address).await? {
             balances.push(TokenBalance {
                 token_address: "11111111111111111111111111111111".to_string(),
-                balance: ethers::types::U256::from(info.lamports),
+                balance: ethers::types::U256::from(lamports),
                 decimals: 9,
                 symbol: "SOL".to_string(),
                 name: "Solana".to_string(),
@@ -396,41 +817,34 @@ impl ChainClient for SolanaRpcClient {
         }
         
         // Get SPL token balances
-        if let Ok(token_accounts) = self.get_token_accounts_by_owner(address).await {
-            for token_account in token_accounts.value {
-                let token_info = &token_account.account.data.parsed.info;
-                let amount = &token_info.token_amount;
-                
-                if let Ok(balance) = ethers::types::U256::from_dec_str(&amount.amount) {
-                    balances.push(TokenBalance {
-                        token_address: token_info.mint.clone(),
-                        balance,
-                        decimals: amount.decimals,
-                        symbol: token_info.mint[..4].to_string(), // Simplified
-                        name: "SPL Token".to_string(),
-                    });
-                }
+        if let Ok(token_accounts) = self.rpc.get_token_accounts(address).await {
+            for token_account in token_accounts {
+                balances.push(TokenBalance {
+                    symbol: token_account.mint[..4].to_string(), // Simplified
+                    token_address: token_account.mint,
+                    balance: ethers::types::U256::from(token_account.amount),
+                    decimals: token_account.decimals,
+                    name: "SPL Token".to_string(),
+                });
             }
         }
         
         Ok(balances)
     }
     
-    async fn get_nft_balances(&self, _address: &str) -> Result<Vec<NFTBalance>> {
-        // Simplified - would need Metaplex integration for full NFT metadata
-        Ok(vec![])
+    async fn get_nft_balances(&self, address: &str) -> Result<Vec<NFTBalance>> {
+        Self::validate_solana_address(address)?;
+        
+        let token_accounts = self.rpc.get_token_accounts(address).await?;
+        self.discover_nfts(&token_accounts).await
     }
     
     async fn has_used_protocol(&self, address: &str, protocol_address: &str) -> Result<bool> {
         Self::validate_solana_address(address)?;
         
-        // Simplified check - just see if address has many transactions
-        if protocol_address.contains("JUP") {
-            let signatures = self.get_signatures_for_address(address, 100).await?;
-            return Ok(signatures.len() > 10); // Simple heuristic
-        }
-        
-        Ok(false)
+        let signatures = self.get_signature_history(address).await?;
+        let transactions = self.get_parsed_transactions(&signatures).await;
+        Ok(transactions.iter().any(|tx| tx.succeeded() && tx.invokes(protocol_address)))
     }
     
     async fn get_protocol_metrics(
@@ -442,18 +856,27 @@ impl ChainClient for SolanaRpcClient {
         
         match protocol.to_lowercase().as_str() {
             "jupiter" => {
-                // Simplified metrics based on transaction count
-                let signatures = self.get_signatures_for_address(address, 1000).await?;
-                let swap_count = (signatures.len() as u32 / 20).min(50);
-                
-                Ok(ProtocolMetrics {
-                    protocol_name: "Jupiter".to_string(),
-                    interaction_count: swap_count,
-                    volume_usd: Decimal::from(swap_count) * Decimal::from(1000), // Estimate $1000 per swap
-                    first_interaction: None,
-                    last_interaction: None,
-                    custom_metrics: HashMap::new(),
-                })
+                let signatures = self.get_signature_history(address).await?;
+                let transactions = self.get_parsed_transactions(&signatures).await;
+                let mut metrics = self.jupiter_metrics(address, &transactions).await;
+                metrics.custom_metrics.insert("truncated".to_string(), json!(self.parse_cap_reached(&signatures)));
+                Ok(metrics)
+            },
+            name if self.registry.find(name).is_some() => {
+                let signatures = self.get_signature_history(address).await?;
+                let transactions = self.get_parsed_transactions(&signatures).await;
+                let protocol = self.registry.find(name).map(|protocol| protocol.name.clone()).unwrap_or_default();
+                match self.registry.activity(&transactions, address).remove(&protocol) {
+                    Some(activity) => Ok(self.registry_metrics(&activity).await),
+                    None => Ok(ProtocolMetrics {
+                        protocol_name: protocol,
+                        interaction_count: 0,
+                        volume_usd: Decimal::ZERO,
+                        first_interaction: None,
+                        last_interaction: None,
+                        custom_metrics: HashMap::new(),
+                    }),
+                }
             },
             _ => Ok(ProtocolMetrics {
                 protocol_name: protocol.to_string(),
@@ -469,4 +892,314 @@ impl ChainClient for SolanaRpcClient {
     fn validate_address(&self, address: &str) -> Result<()> {
         Self::validate_solana_address(address)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::fixtures::{metadata_fixture, METADATA_COLLECTION};
+    use crate::models::ProtocolAddresses;
+    use axum::{extract::State, routing::post, Json, Router};
+    use serde_json::Value;
+    use solana_sdk::pubkey::Pubkey;
+    use std::sync::{Arc, Mutex};
+
+    const WALLET: &str = "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM";
+    const USDC: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+    const TOKEN_PROGRAM: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+    const SYSTEM_PROGRAM: &str = "11111111111111111111111111111111";
+    // Slots count down from the newest of the wallet's five transactions
+    const TRANSACTIONS: u8 = 5;
+
+    fn signature(n: u8) -> String {
+        bs58::encode([n; 64]).into_string()
+    }
+
+    fn block_time(n: u8) -> i64 {
+        1_700_000_000 + n as i64 * 86_400
+    }
+
+    fn nft_mint() -> Pubkey {
+        Pubkey::new_from_array([7; 32])
+    }
+
+    fn usdc_account() -> String {
+        Pubkey::new_from_array([8; 32]).to_string()
+    }
+
+    fn token_amount(amount: u64, decimals: u8) -> Value {
+        let ui_amount = amount as f64 / 10f64.powi(decimals as i32);
+        json!({ "amount": amount.to_string(), "decimals": decimals, "uiAmount": ui_amount, "uiAmountString": ui_amount.to_string() })
+    }
+
+    fn account(owner: &str, data: &[u8]) -> Value {
+        json!({
+            "lamports": 2_039_280, "owner": owner, "executable": false, "rentEpoch": u64::MAX, "space": data.len(),
+            "data": [base64::engine::general_purpose::STANDARD.encode(data), "base64"],
+        })
+    }
+
+    // The wallet's transaction `n` as `getTransaction` returns it in `json` encoding:
+    // invoking `programs`, moving its USDC and SOL by the given raw amounts
+    fn transaction(n: u8, programs: &[&str], usdc_change: i64, lamports_change: i64) -> Value {
+        let mut keys = vec![WALLET.to_string(), usdc_account()];
+        keys.extend(programs.iter().map(|program| program.to_string()));
+        let instructions: Vec<Value> = (0..programs.len())
+            .map(|i| json!({ "programIdIndex": i + 2, "accounts": [0, 1], "data": "", "stackHeight": null }))
+            .collect();
+        let usdc = |amount: i64| json!([{
+            "accountIndex": 1, "mint": USDC, "owner": WALLET, "programId": TOKEN_PROGRAM,
+            "uiTokenAmount": token_amount(amount as u64, 6),
+        }]);
+        let fee = 5_000;
+        let err = (n == 3).then(|| json!({ "InstructionError": [0, { "Custom": 1 }] }));
+        let status = match &err {
+            Some(err) => json!({ "Err": err }),
+            None => json!({ "Ok": null }),
+        };
+        let unchanged = vec![1u64; programs.len()];
+        let pre_balances: Vec<u64> = [10_000_000_000, 2_039_280].into_iter().chain(unchanged.clone()).collect();
+        let post_balances: Vec<u64> = [(10_000_000_000 + lamports_change - fee) as u64, 2_039_280].into_iter().chain(unchanged).collect();
+
+        json!({
+            "slot": 300_000_000 + n as u64,
+            "blockTime": block_time(n),
+            "version": "legacy",
+            "meta": {
+                "err": err, "status": status, "fee": fee,
+                "preBalances": pre_balances, "postBalances": post_balances,
+                "innerInstructions": [], "logMessages": [], "rewards": [],
+                "preTokenBalances": usdc(500_000_000), "postTokenBalances": usdc(500_000_000 + usdc_change),
+                "loadedAddresses": { "writable": [], "readonly": [] },
+                "computeUnitsConsumed": 10_000,
+            },
+            "transaction": {
+                "signatures": [signature(n)],
+                "message": {
+                    "header": { "numRequiredSignatures": 1, "numReadonlySignedAccounts": 0, "numReadonlyUnsignedAccounts": programs.len() },
+                    "accountKeys": keys,
+                    "recentBlockhash": SYSTEM_PROGRAM,
+                    "instructions": instructions,
+                },
+            },
+        })
+    }
+
+    fn get_transaction(signature: &str) -> Value {
+        let n = (1..=TRANSACTIONS).find(|n| self::signature(*n) == signature).unwrap();
+        match n {
+            // 100 USDC for 0.5 SOL through Jupiter
+            5 => transaction(n, &[ProtocolAddresses::JUPITER_V6], -100_000_000, 500_000_000),
+            // 50 USDC of collateral into Drift
+            4 => transaction(n, &[ProtocolAddresses::DRIFT_V2], -50_000_000, 0),
+            _ => transaction(n, &[SYSTEM_PROGRAM], 0, -100_000_000),
+        }
+    }
+
+    fn get_signatures_for_address(config: &Value) -> Value {
+        let before = config["before"].as_str();
+        let until = config["until"].as_str();
+        let limit = config["limit"].as_u64().unwrap_or(1_000) as usize;
+        let entries: Vec<Value> = (1..=TRANSACTIONS).rev()
+            .skip_while(|n| before.is_some_and(|before| signature(*n) != before))
+            .skip(before.is_some() as usize)
+            .take_while(|n| until.is_none_or(|until| signature(*n) != until))
+            .take(limit)
+            .map(|n| {
+                let err = transaction(n, &[], 0, 0)["meta"]["err"].clone();
+                json!({ "signature": signature(n), "slot": 300_000_000 + n as u64, "err": err, "memo": null, "blockTime": block_time(n), "confirmationStatus": "finalized" })
+            })
+            .collect();
+        json!(entries)
+    }
+
+    fn get_multiple_accounts(addresses: &Value) -> Value {
+        let metadata = crate::chains::solana_nft::metadata_address(&nft_mint()).to_string();
+        let edition = crate::chains::solana_nft::edition_address(&nft_mint()).to_string();
+        let accounts: Vec<Value> = addresses.as_array().unwrap().iter()
+            .map(|address| match address.as_str().unwrap() {
+                address if address == metadata => account(ProtocolAddresses::METAPLEX_TOKEN_METADATA, &metadata_fixture(true)),
+                address if address == edition => account(ProtocolAddresses::METAPLEX_TOKEN_METADATA, &[6; 282]),
+                _ => Value::Null,
+            })
+            .collect();
+        json!({ "context": { "slot": 300_000_010 }, "value": accounts })
+    }
+
+    fn get_token_accounts_by_owner() -> Value {
+        let token_account = |address: String, mint: &str, amount: u64, decimals: u8| json!({
+            "pubkey": address,
+            "account": {
+                "lamports": 2_039_280, "owner": TOKEN_PROGRAM, "executable": false, "rentEpoch": u64::MAX, "space": 165,
+                "data": {
+                    "program": "spl-token", "space": 165,
+                    "parsed": { "type": "account", "info": {
+                        "isNative": false, "mint": mint, "owner": WALLET, "state": "initialized",
+                        "tokenAmount": token_amount(amount, decimals),
+                    } },
+                },
+            },
+        });
+        json!({ "context": { "slot": 300_000_010 }, "value": [
+            token_account(usdc_account(), USDC, 250_000_000, 6),
+            token_account(Pubkey::new_from_array([9; 32]).to_string(), &nft_mint().to_string(), 1, 0),
+        ] })
+    }
+
+    // A Solana node for one wallet with a Jupiter swap, a Drift deposit, a failed and two
+    // plain transactions, 250 USDC and a Metaplex NFT
+    async fn rpc(State(fetched): State<Arc<Mutex<Vec<String>>>>, Json(request): Json<Value>) -> Json<Value> {
+        let params = &request["params"];
+        if request["method"] == "getTransaction" {
+            fetched.lock().unwrap().push(params[0].as_str().unwrap().to_string());
+        }
+        let result = match request["method"].as_str().unwrap() {
+            "getAccountInfo" if params[0] == WALLET => json!({
+                "context": { "slot": 300_000_010 },
+                "value": { "lamports": 2_000_000_000u64, "owner": SYSTEM_PROGRAM, "executable": false, "rentEpoch": u64::MAX, "space": 0, "data": ["", "base64"] },
+            }),
+            "getAccountInfo" => json!({ "context": { "slot": 300_000_010 }, "value": null }),
+            "getSignaturesForAddress" => get_signatures_for_address(&params[1]),
+            "getTransaction" => get_transaction(params[0].as_str().unwrap()),
+            "getMultipleAccounts" => get_multiple_accounts(&params[0]),
+            "getTokenAccountsByOwner" => get_token_accounts_by_owner(),
+            method => {
+                return Json(json!({ "jsonrpc": "2.0", "id": request["id"], "error": { "code": -32601, "message": format!("Method not found: {}", method) } }));
+            }
+        };
+        Json(json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }))
+    }
+
+    // Serve the stand-in node; returns its URL and every transaction fetched from it
+    async fn serve() -> (String, Arc<Mutex<Vec<String>>>) {
+        let fetched = Arc::new(Mutex::new(Vec::new()));
+        let app = Router::new().route("/", post(rpc)).with_state(fetched.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (url, fetched)
+    }
+
+    /// Behaviour every `SolanaTransport` must give the client, checked against a
+    /// stand-in node. Signature pages of two make history walks span several calls.
+    pub(crate) async fn check_transport<T: SolanaTransport>(transport: impl FnOnce(ChainClientConfig) -> Result<T>) {
+        let (rpc_url, fetched) = serve().await;
+        let config = ChainClientConfig {
+            rpc_url,
+            chain_id: None,
+            timeout_seconds: 10,
+            max_retries: 0,
+            rate_limit_per_second: 100.0,
+        };
+        let client = SolanaRpcClient::with_transport(transport(config).unwrap())
+            .with_solana_settings(SolanaSettings { signature_page_size: 2, page_delay_ms: 0, ..SolanaSettings::default() });
+        let time = |n: u8| DateTime::from_timestamp(block_time(n), 0);
+
+        assert!(client.validate_address(WALLET).is_ok());
+        assert!(client.validate_address("0x742d35cc6634c0532925a3b844bc9e7595f6e842").is_err());
+        assert!(client.fetch_metrics("not a solana address").await.is_err());
+
+        let balances = client.get_token_balances(WALLET).await.unwrap();
+        let balances: Vec<_> = balances.iter().map(|balance| (balance.token_address.as_str(), balance.balance.as_u64(), balance.decimals)).collect();
+        let nft_mint = nft_mint().to_string();
+        assert_eq!(balances, vec![(SYSTEM_PROGRAM, 2_000_000_000, 9), (USDC, 250_000_000, 6), (nft_mint.as_str(), 1, 0)]);
+
+        let summary = client.get_transaction_summary(WALLET, None, None).await.unwrap();
+        assert_eq!(summary.total_count, TRANSACTIONS as u32);
+        assert_eq!((summary.first_tx, summary.last_tx), (time(1), time(5)));
+
+        assert!(client.has_used_protocol(WALLET, ProtocolAddresses::JUPITER_V6).await.unwrap());
+        assert!(!client.has_used_protocol(WALLET, ProtocolAddresses::MANGO_V4).await.unwrap());
+
+        let jupiter = client.get_protocol_metrics(WALLET, "jupiter").await.unwrap();
+        assert_eq!(jupiter.interaction_count, 1);
+        assert_eq!(jupiter.volume_usd, Decimal::from(100));
+        assert_eq!(jupiter.first_interaction, time(5));
+        assert_eq!(jupiter.custom_metrics["output_mints"], json!({ ProtocolAddresses::SOL_WRAPPED: 1 }));
+        let drift = client.get_protocol_metrics(WALLET, "drift").await.unwrap();
+        assert_eq!((drift.protocol_name.as_str(), drift.interaction_count, drift.volume_usd), ("Drift", 1, Decimal::from(50)));
+
+        let nfts = client.get_nft_balances(WALLET).await.unwrap();
+        assert_eq!(nfts.len(), 1);
+        assert_eq!(nfts[0].token_id, nft_mint);
+        assert_eq!(nfts[0].contract_address, bs58::encode(METADATA_COLLECTION).into_string());
+        assert_eq!(nfts[0].standard, NftStandard::Metaplex);
+
+        let metrics = client.fetch_metrics(WALLET).await.unwrap().metrics;
+        assert_eq!(metrics.total_tx_count, TRANSACTIONS as u32);
+        assert_eq!(metrics.active_days, TRANSACTIONS as u32);
+        assert_eq!(metrics.first_transaction, time(1));
+        assert_eq!(metrics.jupiter_swaps, 1);
+        assert_eq!(metrics.total_swap_volume_usd, Decimal::from(100));
+        assert_eq!(metrics.perp_collateral_flow_usd, Decimal::from(50));
+        assert_eq!((metrics.total_perp_volume_usd, metrics.leveraged_positions_count), (Decimal::ZERO, 0));
+        assert_eq!(metrics.protocols_used.len(), 2);
+        assert_eq!(metrics.distinct_tokens_traded, 2);
+        assert_eq!((metrics.nft_count, metrics.nft_collections_count), (1, 1));
+        // SOL has no reachable Pyth account here, so only the USDC is valued
+        assert_eq!(metrics.stablecoin_balance_usd, Decimal::from(250));
+        assert_eq!(metrics.total_balance_usd, Decimal::from(250));
+        assert_eq!(metrics.chains_active_on, vec!["solana".to_string()]);
+        assert!(!metrics.jupiter_swaps_truncated);
+
+        // Every lookup above shares one parse of each transaction, and failed signatures
+        // are known from the listing and never fetched
+        {
+            let fetched = fetched.lock().unwrap();
+            assert_eq!(fetched.len(), TRANSACTIONS as usize - 1);
+            assert!(!fetched.contains(&signature(3)));
+        }
+
+        // A cap below the successful transactions is reported with the swap count
+        let capped = client.with_solana_settings(SolanaSettings {
+            signature_page_size: 2,
+            page_delay_ms: 0,
+            max_parsed_transactions: 2,
+            ..SolanaSettings::default()
+        });
+        let metrics = capped.fetch_metrics(WALLET).await.unwrap().metrics;
+        assert_eq!(metrics.jupiter_swaps, 1);
+        assert!(metrics.jupiter_swaps_truncated);
+        let jupiter = capped.get_protocol_metrics(WALLET, "jupiter").await.unwrap();
+        assert_eq!(jupiter.custom_metrics["truncated"], json!(true));
+    }
+
+    #[tokio::test]
+    async fn test_stale_pyth_updates_are_rejected() {
+        use std::sync::atomic::{AtomicI64, Ordering};
+
+        // SOL's Pyth account holds a $150 update published at `published`
+        let published = Arc::new(AtomicI64::new(0));
+        let publish_time = published.clone();
+        let app = Router::new().route("/", post(move |Json(request): Json<Value>| {
+            let data = crate::fixtures::pyth_update(15_000_000_000, -8, publish_time.load(Ordering::SeqCst));
+            async move {
+                let accounts = json!({ "context": { "slot": 300_000_010 }, "value": [account(SYSTEM_PROGRAM, &data)] });
+                Json(json!({ "jsonrpc": "2.0", "id": request["id"], "result": accounts }))
+            }
+        }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let rpc_url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let client = SolanaRpcClient::new(ChainClientConfig {
+            rpc_url,
+            chain_id: None,
+            timeout_seconds: 10,
+            max_retries: 0,
+            rate_limit_per_second: 100.0,
+        }).unwrap().with_pricing_settings(&PricingSettings { max_feed_age_seconds: 3_600, ..PricingSettings::default() });
+
+        published.store(Utc::now().timestamp() - 7_200, Ordering::SeqCst);
+        let err = client.price_usd(ProtocolAddresses::SOL_WRAPPED).await.unwrap_err();
+        assert!(err.to_string().contains("last updated"));
+
+        published.store(Utc::now().timestamp() - 60, Ordering::SeqCst);
+        assert_eq!(client.price_usd(ProtocolAddresses::SOL_WRAPPED).await.unwrap(), Decimal::from(150));
+    }
+
+    #[tokio::test]
+    async fn test_json_rpc_transport() {
+        check_transport(JsonRpcTransport::new).await;
+    }
+}
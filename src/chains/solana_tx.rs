@@ -0,0 +1,321 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::models::ProtocolAddresses;
+
+/// A `getTransaction` result in `json` encoding
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParsedTransaction {
+    pub block_time: Option<i64>,
+    pub meta: Option<TransactionMeta>,
+    pub transaction: TransactionBody,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TransactionBody {
+    pub signatures: Vec<String>,
+    pub message: TransactionMessage,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionMessage {
+    pub account_keys: Vec<String>,
+    pub instructions: Vec<CompiledInstruction>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompiledInstruction {
+    pub program_id_index: usize,
+    #[serde(default)]
+    pub accounts: Vec<usize>,
+    #[serde(default)]
+    pub data: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct InnerInstructions {
+    pub index: usize,
+    pub instructions: Vec<CompiledInstruction>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct LoadedAddresses {
+    pub writable: Vec<String>,
+    pub readonly: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionMeta {
+    pub err: Option<serde_json::Value>,
+    pub fee: u64,
+    pub pre_balances: Vec<u64>,
+    pub post_balances: Vec<u64>,
+    #[serde(default)]
+    pub inner_instructions: Option<Vec<InnerInstructions>>,
+    #[serde(default)]
+    pub pre_token_balances: Option<Vec<TransactionTokenBalance>>,
+    #[serde(default)]
+    pub post_token_balances: Option<Vec<TransactionTokenBalance>>,
+    #[serde(default)]
+    pub loaded_addresses: Option<LoadedAddresses>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionTokenBalance {
+    pub account_index: usize,
+    pub mint: String,
+    #[serde(default)]
+    pub owner: Option<String>,
+    pub ui_token_amount: UiTokenAmount,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct UiTokenAmount {
+    pub amount: String,
+    pub decimals: u8,
+}
+
+/// An amount of `mint` in raw units: one side of a swap, or a balance change to value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SwapLeg {
+    pub mint: String,
+    pub amount: u64,
+    pub decimals: u8,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Swap {
+    pub signature: String,
+    pub timestamp: Option<DateTime<Utc>>,
+    /// What the owner gave up; `None` when no balance of theirs went down
+    pub input: Option<SwapLeg>,
+    /// What the owner received; `None` when no balance of theirs went up
+    pub output: Option<SwapLeg>,
+}
+
+impl ParsedTransaction {
+    pub fn signature(&self) -> &str {
+        self.transaction.signatures.first().map(String::as_str).unwrap_or_default()
+    }
+
+    pub fn timestamp(&self) -> Option<DateTime<Utc>> {
+        self.block_time.and_then(|time| DateTime::from_timestamp(time, 0))
+    }
+
+    pub fn succeeded(&self) -> bool {
+        self.meta.as_ref().is_some_and(|meta| meta.err.is_none())
+    }
+
+    /// Static keys followed by those loaded from lookup tables, which is the order
+    /// instruction and balance indexes refer to
+    pub fn account_keys(&self) -> Vec<&str> {
+        let loaded = self.meta.as_ref().and_then(|meta| meta.loaded_addresses.as_ref());
+        self.transaction.message.account_keys.iter()
+            .chain(loaded.into_iter().flat_map(|loaded| loaded.writable.iter().chain(&loaded.readonly)))
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Every program invoked, directly or through a CPI
+    pub fn invoked_programs(&self) -> BTreeSet<&str> {
+        let keys = self.account_keys();
+        let inner = self.meta.as_ref()
+            .and_then(|meta| meta.inner_instructions.as_ref())
+            .into_iter()
+            .flatten()
+            .flat_map(|inner| &inner.instructions);
+        self.transaction.message.instructions.iter()
+            .chain(inner)
+            .filter_map(|instruction| keys.get(instruction.program_id_index).copied())
+            .collect()
+    }
+
+    pub fn invokes(&self, program: &str) -> bool {
+        self.invoked_programs().contains(program)
+    }
+
+    /// Net change of each of `owner`'s balances, as (raw amount, decimals) per mint.
+    /// Native SOL is folded into wrapped SOL, with the fee added back when `owner`
+    /// paid it.
+    pub fn balance_deltas(&self, owner: &str) -> BTreeMap<String, (i128, u8)> {
+        let mut deltas = BTreeMap::new();
+        let Some(meta) = &self.meta else { return deltas };
+
+        let owned = |balances: &Option<Vec<TransactionTokenBalance>>| -> BTreeMap<usize, (String, i128, u8)> {
+            balances.iter()
+                .flatten()
+                .filter(|balance| balance.owner.as_deref() == Some(owner))
+                .filter_map(|balance| {
+                    let amount = balance.ui_token_amount.amount.parse().ok()?;
+                    Some((balance.account_index, (balance.mint.clone(), amount, balance.ui_token_amount.decimals)))
+                })
+                .collect()
+        };
+        let pre = owned(&meta.pre_token_balances);
+        let post = owned(&meta.post_token_balances);
+        for index in pre.keys().chain(post.keys()).collect::<BTreeSet<_>>() {
+            let before = pre.get(index);
+            let after = post.get(index);
+            let Some((mint, _, decimals)) = after.or(before) else { continue };
+            let change = after.map_or(0, |(_, amount, _)| *amount) - before.map_or(0, |(_, amount, _)| *amount);
+            deltas.entry(mint.clone()).or_insert((0, *decimals)).0 += change;
+        }
+
+        if let Some(index) = self.account_keys().iter().position(|key| *key == owner) {
+            if let (Some(before), Some(after)) = (meta.pre_balances.get(index), meta.post_balances.get(index)) {
+                let fee = if index == 0 { meta.fee as i128 } else { 0 };
+                let change = *after as i128 - *before as i128 + fee;
+                deltas.entry(ProtocolAddresses::SOL_WRAPPED.to_string()).or_insert((0, 9)).0 += change;
+            }
+        }
+
+        deltas.retain(|_, (amount, _)| *amount != 0);
+        deltas
+    }
+}
+
+/// The swap `owner` made in `tx`, if it succeeded and invoked Jupiter
+pub fn jupiter_swap(tx: &ParsedTransaction, owner: &str) -> Option<Swap> {
+    let programs = tx.invoked_programs();
+    if !tx.succeeded()
+        || !(programs.contains(ProtocolAddresses::JUPITER_V4) || programs.contains(ProtocolAddresses::JUPITER_V6))
+    {
+        return None;
+    }
+
+    let deltas = tx.balance_deltas(owner);
+    Some(Swap {
+        signature: tx.signature().to_string(),
+        timestamp: tx.timestamp(),
+        input: swap_leg(&deltas, |amount| amount < 0),
+        output: swap_leg(&deltas, |amount| amount > 0),
+    })
+}
+
+/// The largest change in one direction. SOL only counts when no token moved that
+/// way, since account rent also comes out of it.
+fn swap_leg(deltas: &BTreeMap<String, (i128, u8)>, direction: impl Fn(i128) -> bool) -> Option<SwapLeg> {
+    let size = |amount: i128, decimals: u8| {
+        Decimal::try_from_i128_with_scale(amount.abs(), decimals as u32).unwrap_or(Decimal::MAX)
+    };
+    let moved: Vec<_> = deltas.iter().filter(|(_, (amount, _))| direction(*amount)).collect();
+    let tokens: Vec<_> = moved.iter()
+        .filter(|(mint, _)| mint.as_str() != ProtocolAddresses::SOL_WRAPPED)
+        .copied()
+        .collect();
+    let candidates = if tokens.is_empty() { moved } else { tokens };
+
+    let (mint, (amount, decimals)) = candidates.into_iter()
+        .max_by_key(|(_, (amount, decimals))| size(*amount, *decimals))?;
+    Some(SwapLeg {
+        mint: mint.clone(),
+        amount: u64::try_from(amount.unsigned_abs()).ok()?,
+        decimals: *decimals,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const OWNER: &str = "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM";
+    const USDC: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+    const BONK: &str = "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263";
+
+    /// USDC for BONK through Jupiter v6, reached by CPI from a wrapper program. The
+    /// owner pays the fee and the rent of a new BONK account.
+    fn swap_fixture() -> serde_json::Value {
+        json!({
+            "blockTime": 1_700_000_000,
+            "slot": 230_000_000,
+            "meta": {
+                "err": null,
+                "fee": 5_000,
+                "preBalances": [1_000_000_000u64, 2_039_280, 0, 1, 1],
+                "postBalances": [997_960_720u64, 2_039_280, 2_039_280, 1, 1],
+                "innerInstructions": [{
+                    "index": 0,
+                    "instructions": [{ "programIdIndex": 5, "accounts": [0, 1, 2], "data": "" }]
+                }],
+                "preTokenBalances": [
+                    { "accountIndex": 1, "mint": USDC, "owner": OWNER, "uiTokenAmount": { "amount": "250000000", "decimals": 6 } }
+                ],
+                "postTokenBalances": [
+                    { "accountIndex": 1, "mint": USDC, "owner": OWNER, "uiTokenAmount": { "amount": "150000000", "decimals": 6 } },
+                    { "accountIndex": 2, "mint": BONK, "owner": OWNER, "uiTokenAmount": { "amount": "500000000000", "decimals": 5 } }
+                ],
+                "loadedAddresses": { "writable": [], "readonly": [ProtocolAddresses::JUPITER_V6] }
+            },
+            "transaction": {
+                "signatures": ["5sig"],
+                "message": {
+                    "accountKeys": [OWNER, "UsdcAccount1111111111111111111111111111111", "BonkAccount1111111111111111111111111111111", "Wrapper111111111111111111111111111111111111", "ComputeBudget111111111111111111111111111111"],
+                    "instructions": [
+                        { "programIdIndex": 4, "accounts": [], "data": "" },
+                        { "programIdIndex": 3, "accounts": [0, 1, 2, 5], "data": "" }
+                    ]
+                }
+            }
+        })
+    }
+
+    fn parse(value: serde_json::Value) -> ParsedTransaction {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_invoked_programs_include_inner_instructions() {
+        let tx = parse(swap_fixture());
+        assert_eq!(tx.account_keys().len(), 6);
+        assert!(tx.invokes(ProtocolAddresses::JUPITER_V6));
+        assert!(tx.invokes("Wrapper111111111111111111111111111111111111"));
+        assert!(!tx.invokes(ProtocolAddresses::JUPITER_V4));
+        assert_eq!(tx.timestamp(), DateTime::from_timestamp(1_700_000_000, 0));
+    }
+
+    #[test]
+    fn test_jupiter_swap_legs() {
+        let tx = parse(swap_fixture());
+        let deltas = tx.balance_deltas(OWNER);
+        assert_eq!(deltas[USDC], (-100_000_000, 6));
+        assert_eq!(deltas[BONK], (500_000_000_000, 5));
+        // Only the BONK account's rent, the fee is added back
+        assert_eq!(deltas[ProtocolAddresses::SOL_WRAPPED], (-2_034_280, 9));
+
+        let swap = jupiter_swap(&tx, OWNER).unwrap();
+        assert_eq!(swap.signature, "5sig");
+        assert_eq!(swap.input, Some(SwapLeg { mint: USDC.to_string(), amount: 100_000_000, decimals: 6 }));
+        assert_eq!(swap.output, Some(SwapLeg { mint: BONK.to_string(), amount: 500_000_000_000, decimals: 5 }));
+    }
+
+    #[test]
+    fn test_native_sol_input() {
+        let mut fixture = swap_fixture();
+        // Pay 1 SOL instead of USDC, into a BONK account that already existed
+        fixture["meta"]["preBalances"] = json!([2_000_000_000u64, 2_039_280, 2_039_280, 1, 1]);
+        fixture["meta"]["postBalances"][0] = json!(999_995_000u64);
+        fixture["meta"]["postTokenBalances"][0]["uiTokenAmount"]["amount"] = json!("250000000");
+        let swap = jupiter_swap(&parse(fixture), OWNER).unwrap();
+        assert_eq!(swap.input, Some(SwapLeg { mint: ProtocolAddresses::SOL_WRAPPED.to_string(), amount: 1_000_000_000, decimals: 9 }));
+        assert_eq!(swap.output.unwrap().mint, BONK);
+    }
+
+    #[test]
+    fn test_failed_and_unrelated_transactions_are_not_swaps() {
+        let mut failed = swap_fixture();
+        failed["meta"]["err"] = json!({ "InstructionError": [1, { "Custom": 6001 }] });
+        assert!(jupiter_swap(&parse(failed), OWNER).is_none());
+
+        let mut unrelated = swap_fixture();
+        unrelated["meta"]["innerInstructions"] = json!([]);
+        assert!(jupiter_swap(&parse(unrelated), OWNER).is_none());
+    }
+}
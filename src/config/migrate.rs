@@ -0,0 +1,135 @@
+use toml_edit::{value, DocumentMut, Item};
+
+use crate::models::{DegenScoreError, Result};
+use crate::scoring::weights::ScoringWeights;
+
+// Thresholds that only drove the old fixed risk bonus
+const REMOVED_THRESHOLDS: [&str; 2] = ["risk_increment", "max_risk_score"];
+
+// Ethereum blocks per day, which the old block-based NFT sales lookback assumed
+const ETHEREUM_BLOCKS_PER_DAY: i64 = 7_200;
+
+/// Rewrite a settings TOML file to the current `[scoring.weights]` layout.
+///
+/// Legacy weights are converted exactly as they are at load time and written back
+/// with four decimals; comments and unrelated sections are preserved. The NFT sales
+/// lookback moves from blocks to days. Returns `None` when the file is already current.
+pub fn migrate_config(contents: &str) -> Result<Option<String>> {
+    let mut doc: DocumentMut = contents
+        .parse()
+        .map_err(|e| DegenScoreError::ConfigError(format!("Invalid TOML: {}", e)))?;
+
+    let mut changed = false;
+
+    if let Some(weights) = doc
+        .get_mut("scoring")
+        .and_then(|s| s.get_mut("weights"))
+        .and_then(Item::as_table_like_mut)
+    {
+        if weights.contains_key("nft_holdings") {
+            let raw: serde_json::Map<String, serde_json::Value> = weights
+                .iter()
+                .filter_map(|(key, item)| {
+                    let number = item.as_float().or_else(|| item.as_integer().map(|i| i as f64))?;
+                    Some((key.to_string(), serde_json::json!(number)))
+                })
+                .collect();
+
+            let migrated: ScoringWeights = serde_json::from_value(serde_json::Value::Object(raw))
+                .map_err(|e| DegenScoreError::ConfigError(format!("Scoring weights: {}", e)))?;
+
+            weights.remove("nft_holdings");
+            for (key, weight) in migrated.components() {
+                let weight = value((weight * 10_000.0).round() / 10_000.0);
+                // Assign in place so the key keeps its position and comments
+                match weights.get_mut(key) {
+                    Some(item) => *item = weight,
+                    None => {
+                        weights.insert(key, weight);
+                    }
+                }
+            }
+            changed = true;
+        }
+    }
+
+    if let Some(thresholds) = doc
+        .get_mut("scoring")
+        .and_then(|s| s.get_mut("thresholds"))
+        .and_then(Item::as_table_like_mut)
+    {
+        for key in REMOVED_THRESHOLDS {
+            changed |= thresholds.remove(key).is_some();
+        }
+    }
+
+    if let Some(nft) = doc.get_mut("nft").and_then(Item::as_table_like_mut) {
+        if let Some(blocks) = nft.remove("sales_lookback_blocks").and_then(|item| item.as_integer()) {
+            let days = ((blocks + ETHEREUM_BLOCKS_PER_DAY / 2) / ETHEREUM_BLOCKS_PER_DAY).max(1);
+            nft.insert("sales_lookback_days", value(days));
+            changed = true;
+        }
+    }
+
+    Ok(changed.then(|| doc.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LEGACY: &str = r#"
+[scoring]
+min_score_for_airdrop = 20.0
+
+[scoring.weights]
+# Old layout
+trading_volume = 15.0
+trading_count = 10
+gambling_platforms = 10.0
+casino_tokens = 5.0
+defi_protocols = 10.0
+token_diversity = 5.0
+nft_holdings = 10.0
+wallet_age = 10.0
+activity_consistency = 25.0
+
+[scoring.thresholds]
+max_active_chains = 5
+risk_increment = 3.75
+max_risk_score = 15.0
+
+[nft]
+sales_lookback_blocks = 216000
+"#;
+
+    #[test]
+    fn test_migrates_legacy_weights_and_thresholds() {
+        let migrated = migrate_config(LEGACY).unwrap().unwrap();
+
+        assert!(!migrated.contains("nft_holdings"));
+        assert!(!migrated.contains("risk_increment"));
+        assert!(!migrated.contains("max_risk_score"));
+        assert!(migrated.contains("# Old layout"));
+        assert!(migrated.contains("max_active_chains = 5"));
+
+        let doc: DocumentMut = migrated.parse().unwrap();
+        let weights = doc["scoring"]["weights"].as_table().unwrap();
+        let total: f64 = weights.iter().map(|(_, w)| w.as_float().unwrap()).sum();
+        assert!((total - 100.0).abs() < 0.01);
+        assert_eq!(weights["nft_count"].as_float(), weights["nft_value"].as_float());
+        assert!(weights.contains_key("risk_battle_tested"));
+        assert_eq!(doc["nft"]["sales_lookback_days"].as_integer(), Some(30));
+        assert!(!migrated.contains("sales_lookback_blocks"));
+
+        // Running it again is a no-op
+        assert!(migrate_config(&migrated).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_shipped_config_is_current() {
+        let contents = std::fs::read_to_string("config/default.toml").unwrap();
+        assert!(migrate_config(&contents).unwrap().is_none());
+        assert!(migrate_config("not = [valid").is_err());
+    }
+}
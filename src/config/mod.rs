@@ -1,5 +1,7 @@
+pub mod migrate;
 pub mod rpc;
 pub mod settings;
 
+pub use migrate::migrate_config;
 pub use rpc::*;
 pub use settings::*;
\ No newline at end of file
@@ -172,6 +172,19 @@ pub struct ExplorerEndpoint {
     pub rate_limit: RateLimit,
 }
 
+impl ExplorerConfig {
+    /// Etherscan-compatible endpoint for an EVM chain
+    pub fn for_chain(&self, chain: &Chain) -> Option<&ExplorerEndpoint> {
+        match chain {
+            Chain::Ethereum => Some(&self.etherscan),
+            Chain::Arbitrum => Some(&self.arbiscan),
+            Chain::Optimism => Some(&self.optimistic_etherscan),
+            Chain::Blast => self.blastscan.as_ref(),
+            Chain::Solana => None,
+        }
+    }
+}
+
 impl Default for ExplorerConfig {
     fn default() -> Self {
         Self {
@@ -1,7 +1,12 @@
 use serde::{Deserialize, Serialize};
 use config::{Config, ConfigError, File};
+use std::collections::HashMap;
 use std::path::Path;
 
+// Single weight model shared by config and the scoring algorithm
+pub use crate::scoring::weights::ScoringWeights;
+use crate::models::{PriceSourceEntry, SolanaCasinoToken, SolanaProtocol};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Settings {
     pub app: AppSettings,
@@ -9,6 +14,22 @@ pub struct Settings {
     pub database: DatabaseSettings,
     pub cache: CacheSettings,
     pub api: ApiSettings,
+    #[serde(default)]
+    pub sybil: SybilSettings,
+    #[serde(default)]
+    pub registry: RegistrySettings,
+    #[serde(default)]
+    pub scanner: ScannerSettings,
+    #[serde(default)]
+    pub explorer: ExplorerSettings,
+    #[serde(default)]
+    pub nft: NftSettings,
+    #[serde(default)]
+    pub pricing: PricingSettings,
+    #[serde(default)]
+    pub portfolio: PortfolioSettings,
+    #[serde(default)]
+    pub solana: SolanaSettings,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -33,28 +54,439 @@ pub struct ScoringSettings {
     pub min_score_for_airdrop: f64,
     pub airdrop_pool_percentage: f64,
     pub wagering_requirement_multiplier: u32,
+    #[serde(default)]
+    pub allocation: AllocationSettings,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ScoringWeights {
-    pub trading_volume: f64,
-    pub trading_count: f64,
-    pub gambling_platforms: f64,
-    pub casino_tokens: f64,
-    pub defi_protocols: f64,
-    pub token_diversity: f64,
-    pub nft_holdings: f64,
-    pub wallet_age: f64,
-    pub activity_consistency: f64,
+impl ScoringSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        self.weights.validate()
+            .map_err(|e| format!("Scoring weights: {}", e))?;
+        
+        if self.min_score_for_airdrop < 0.0 || self.min_score_for_airdrop > 100.0 {
+            return Err("Minimum score for airdrop must be between 0 and 100".to_string());
+        }
+        
+        if self.airdrop_pool_percentage <= 0.0 || self.airdrop_pool_percentage > 100.0 {
+            return Err("Airdrop pool percentage must be greater than 0 and at most 100".to_string());
+        }
+        
+        self.thresholds.validate()?;
+        self.allocation.validate()
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Shape of the airdrop distribution curve
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AllocationCurveKind {
+    /// Pro-rata by score
+    #[default]
+    Linear,
+    /// Pro-rata by sqrt(score), flattening the top end
+    SquareRoot,
+    /// Pro-rata by score squared, rewarding the top end
+    Quadratic,
+    /// Fixed amount per score tier, scaled down if the pool is too small
+    Tiered,
+}
+
+/// Fixed allocation per `ScoreTier`, used by the tiered curve
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TierAmounts {
+    pub legendary: u64,
+    pub epic: u64,
+    pub rare: u64,
+    pub uncommon: u64,
+    pub common: u64,
+    pub novice: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AllocationSettings {
+    pub curve: AllocationCurveKind,
+    pub tier_amounts: TierAmounts,
+    /// Largest share of the pool a single user may receive, in percent; excess is redistributed
+    pub max_share_percentage: Option<f64>,
+    /// Amount every eligible user receives before the curve splits the rest
+    pub floor_amount: u64,
+}
+
+impl AllocationSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(share) = self.max_share_percentage {
+            if share <= 0.0 || share > 100.0 {
+                return Err("Allocation max_share_percentage must be greater than 0 and at most 100".to_string());
+            }
+        }
+        
+        if self.curve == AllocationCurveKind::Tiered && self.tier_amounts == TierAmounts::default() {
+            return Err("Tiered allocation curve requires at least one non-zero tier amount".to_string());
+        }
+        
+        Ok(())
+    }
+}
+
+/// Normalization constants used by `ScoringAlgorithm`; each metric scores full marks at its cap
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ScoringThresholds {
     pub max_trading_volume_usd: f64,
     pub max_trades_count: u32,
     pub max_protocols_count: u32,
     pub max_nft_count: u32,
     pub max_wallet_age_days: u32,
+    pub max_distinct_tokens: u32,
+    pub max_nft_value_usd: f64,
+    /// Active days within this window count towards activity consistency
+    pub activity_window_days: u32,
+    pub max_casino_platforms: u32,
+    pub max_casino_tokens: u32,
+    pub max_active_chains: u32,
+}
+
+impl Default for ScoringThresholds {
+    fn default() -> Self {
+        Self {
+            max_trading_volume_usd: 10_000_000.0,
+            max_trades_count: 100,
+            max_protocols_count: 20,
+            max_nft_count: 50,
+            max_wallet_age_days: 1825, // 5 years
+            max_distinct_tokens: 50,
+            max_nft_value_usd: 100_000.0,
+            activity_window_days: 365,
+            max_casino_platforms: 3,
+            max_casino_tokens: 2,
+            max_active_chains: 5,
+        }
+    }
+}
+
+impl ScoringThresholds {
+    pub fn validate(&self) -> Result<(), String> {
+        let counts = [
+            ("max_trades_count", self.max_trades_count),
+            ("max_protocols_count", self.max_protocols_count),
+            ("max_nft_count", self.max_nft_count),
+            ("max_wallet_age_days", self.max_wallet_age_days),
+            ("max_distinct_tokens", self.max_distinct_tokens),
+            ("activity_window_days", self.activity_window_days),
+            ("max_casino_platforms", self.max_casino_platforms),
+            ("max_casino_tokens", self.max_casino_tokens),
+            ("max_active_chains", self.max_active_chains),
+        ];
+        if let Some((name, _)) = counts.iter().find(|(_, v)| *v == 0) {
+            return Err(format!("Scoring threshold {} must be greater than 0", name));
+        }
+        
+        // Volume is scored logarithmically, so the cap must be above $1
+        if !self.max_trading_volume_usd.is_finite() || self.max_trading_volume_usd <= 1.0 {
+            return Err("Scoring threshold max_trading_volume_usd must be greater than 1".to_string());
+        }
+        
+        if !self.max_nft_value_usd.is_finite() || self.max_nft_value_usd <= 0.0 {
+            return Err("Scoring threshold max_nft_value_usd must be greater than 0".to_string());
+        }
+        
+        Ok(())
+    }
+}
+
+/// Tuning for sybil cluster detection across scored users
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SybilSettings {
+    /// First transactions at most this far apart count as identical timing
+    pub timing_window_seconds: u64,
+    /// Protocol sets smaller than this are too common to link wallets
+    pub min_shared_protocols: usize,
+    /// Score multiplier per distinct signal kind a flagged user takes part in
+    pub penalty_per_signal: f64,
+    /// Funding sources shared by unrelated users, e.g. exchange hot wallets
+    pub ignored_funders: Vec<String>,
+}
+
+impl Default for SybilSettings {
+    fn default() -> Self {
+        Self {
+            timing_window_seconds: 60,
+            min_shared_protocols: 3,
+            penalty_per_signal: 0.5,
+            ignored_funders: Vec::new(),
+        }
+    }
+}
+
+impl SybilSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.penalty_per_signal > 0.0 && self.penalty_per_signal <= 1.0) {
+            return Err("Sybil penalty_per_signal must be greater than 0 and at most 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Block ranges walked by EVM log queries
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScannerSettings {
+    /// First block scanned per chain name; genesis if absent. The defaults sit just
+    /// before the oldest contract scanned on each chain.
+    pub start_blocks: HashMap<String, u64>,
+    pub initial_chunk_blocks: u64,
+    pub min_chunk_blocks: u64,
+    pub max_chunk_blocks: u64,
+    /// Blocks behind head that are re-fetched on every scan instead of checkpointed
+    pub finality_blocks: u64,
+    /// Directory persisting scan progress across runs; in memory only when unset
+    pub checkpoint_dir: Option<String>,
+}
+
+impl Default for ScannerSettings {
+    fn default() -> Self {
+        Self {
+            start_blocks: HashMap::from([
+                ("ethereum".to_string(), 7_700_000),
+                ("arbitrum".to_string(), 100_000),
+                ("optimism".to_string(), 500_000),
+            ]),
+            initial_chunk_blocks: 10_000,
+            min_chunk_blocks: 100,
+            max_chunk_blocks: 500_000,
+            finality_blocks: 64,
+            checkpoint_dir: None,
+        }
+    }
+}
+
+impl ScannerSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.min_chunk_blocks == 0 || self.min_chunk_blocks > self.max_chunk_blocks {
+            return Err("Scanner min_chunk_blocks must be at least 1 and at most max_chunk_blocks".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Block-explorer (Etherscan-style) history source for EVM chains
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExplorerSettings {
+    /// Pull history from explorers instead of scanning logs
+    pub enabled: bool,
+    /// Rows requested per page
+    pub page_size: u32,
+    /// API key per chain name
+    pub api_keys: HashMap<String, String>,
+    /// Endpoint overrides per chain name, e.g. for chains without a default explorer
+    pub base_urls: HashMap<String, String>,
+}
+
+impl Default for ExplorerSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            page_size: 1_000,
+            api_keys: HashMap::new(),
+            base_urls: HashMap::new(),
+        }
+    }
+}
+
+impl ExplorerSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.page_size == 0 || self.page_size > 10_000 {
+            return Err("Explorer page_size must be between 1 and 10000".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// NFT metadata fetching on EVM chains
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NftSettings {
+    /// Gateway that `ipfs://` token URIs are fetched through
+    pub ipfs_gateway: String,
+    /// Tokens per address whose metadata is fetched; the rest are listed without it
+    pub max_metadata_fetches: usize,
+    pub metadata_timeout_seconds: u64,
+    /// Days back from now searched for a collection's marketplace sales, resolved to a
+    /// block by timestamp on each chain
+    pub sales_lookback_days: u32,
+    /// Transactions per collection whose receipts are checked for sales, newest first
+    pub max_sale_receipts: usize,
+    /// Latest sales per collection that set its floor
+    pub floor_sample_sales: usize,
+}
+
+impl Default for NftSettings {
+    fn default() -> Self {
+        Self {
+            ipfs_gateway: "https://ipfs.io/ipfs/".to_string(),
+            max_metadata_fetches: 50,
+            metadata_timeout_seconds: 10,
+            sales_lookback_days: 30,
+            max_sale_receipts: 25,
+            floor_sample_sales: 10,
+        }
+    }
+}
+
+/// USD pricing of tokens from on-chain oracles and pools
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PricingSettings {
+    /// Window of Uniswap V3 TWAPs; pools are priced at spot when 0
+    pub twap_seconds: u32,
+    /// Prices kept per (token, block) before the oldest are evicted
+    pub cache_entries: usize,
+    /// How long prices read at chain head are reused
+    pub latest_ttl_seconds: u64,
+    /// Oldest Chainlink answer accepted, measured from the block it is read at
+    pub max_feed_age_seconds: u64,
+    /// WETH a Uniswap V3 pool must hold to price a token no source lists; 0 disables
+    /// the pool lookup
+    pub pool_discovery_min_weth: f64,
+    /// Sources added to, or replacing, the built-in feeds and stablecoins
+    pub sources: Vec<PriceSourceEntry>,
+}
+
+impl Default for PricingSettings {
+    fn default() -> Self {
+        Self {
+            twap_seconds: 1_800,
+            cache_entries: 10_000,
+            latest_ttl_seconds: 60,
+            // Chainlink's L2 ETH/USD feeds have a 24 hour heartbeat
+            max_feed_age_seconds: 90_000,
+            pool_discovery_min_weth: 10.0,
+            sources: Vec::new(),
+        }
+    }
+}
+
+impl PricingSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.cache_entries == 0 {
+            return Err("Pricing cache_entries must be greater than 0".to_string());
+        }
+        if !self.pool_discovery_min_weth.is_finite() || self.pool_discovery_min_weth < 0.0 {
+            return Err("Pricing pool_discovery_min_weth must be 0 or more".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// ERC-20 portfolio discovery on EVM chains
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PortfolioSettings {
+    /// Calls per Multicall3 request
+    pub multicall_batch_size: usize,
+    /// Tokens per address whose balances are checked; the rest are skipped
+    pub max_tokens: usize,
+}
+
+impl Default for PortfolioSettings {
+    fn default() -> Self {
+        Self {
+            multicall_batch_size: 100,
+            max_tokens: 500,
+        }
+    }
+}
+
+impl PortfolioSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.multicall_batch_size == 0 {
+            return Err("Portfolio multicall_batch_size must be greater than 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Transaction history walking and parsing on Solana
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SolanaSettings {
+    /// Signatures requested per `getSignaturesForAddress` page, at most 1000
+    pub signature_page_size: usize,
+    /// Signatures per address walked back from the newest; older history is left out
+    pub max_signatures: usize,
+    /// Pause between signature pages
+    pub page_delay_ms: u64,
+    /// Retries of a rate-limited page, each waiting `retry_delay_ms` longer
+    pub max_retries: u32,
+    pub retry_delay_ms: u64,
+    /// Directory persisting walked history across runs; in memory only when unset
+    pub checkpoint_dir: Option<String>,
+    /// Successful transactions per address fetched with `getTransaction`, newest first
+    pub max_parsed_transactions: usize,
+    /// `getTransaction` requests in flight at once
+    pub transaction_concurrency: usize,
+    /// Parsed transactions kept in memory by signature, shared by every address
+    pub transaction_cache_entries: usize,
+    /// Casinos and extra perp venues, added to the built-in perp venues
+    pub protocols: Vec<SolanaProtocol>,
+    pub casino_tokens: Vec<SolanaCasinoToken>,
+}
+
+impl Default for SolanaSettings {
+    fn default() -> Self {
+        Self {
+            signature_page_size: 1_000,
+            max_signatures: 20_000,
+            page_delay_ms: 200,
+            max_retries: 5,
+            retry_delay_ms: 1_000,
+            checkpoint_dir: None,
+            max_parsed_transactions: 200,
+            transaction_concurrency: 8,
+            transaction_cache_entries: 20_000,
+            protocols: Vec::new(),
+            casino_tokens: Vec::new(),
+        }
+    }
+}
+
+impl SolanaSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.signature_page_size == 0 || self.signature_page_size > 1_000 {
+            return Err("Solana signature_page_size must be between 1 and 1000".to_string());
+        }
+        
+        if self.transaction_concurrency == 0 {
+            return Err("Solana transaction_concurrency must be greater than 0".to_string());
+        }
+        
+        if self.transaction_cache_entries == 0 {
+            return Err("Solana transaction_cache_entries must be greater than 0".to_string());
+        }
+        
+        Ok(())
+    }
+}
+
+/// What happens when a user links an address another user already owns
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictPolicy {
+    /// Refuse the link
+    #[default]
+    Reject,
+    /// Refuse the link and queue it for manual review
+    Escalate,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RegistrySettings {
+    pub on_conflict: ConflictPolicy,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -78,6 +510,8 @@ pub struct ApiSettings {
     pub port: u16,
     pub cors_origins: Vec<String>,
     pub max_request_size_mb: usize,
+    /// Unanswered verification challenges held at once; further requests are refused
+    pub max_pending_challenges: usize,
 }
 
 impl Default for Settings {
@@ -90,27 +524,12 @@ impl Default for Settings {
                 environment: Environment::Development,
             },
             scoring: ScoringSettings {
-                weights: ScoringWeights {
-                    trading_volume: 15.0,
-                    trading_count: 10.0,
-                    gambling_platforms: 10.0,
-                    casino_tokens: 5.0,
-                    defi_protocols: 10.0,
-                    token_diversity: 5.0,
-                    nft_holdings: 10.0,
-                    wallet_age: 10.0,
-                    activity_consistency: 10.0,
-                },
-                thresholds: ScoringThresholds {
-                    max_trading_volume_usd: 10_000_000.0,
-                    max_trades_count: 100,
-                    max_protocols_count: 20,
-                    max_nft_count: 50,
-                    max_wallet_age_days: 1825, // 5 years
-                },
+                weights: ScoringWeights::default(),
+                thresholds: ScoringThresholds::default(),
                 min_score_for_airdrop: 20.0,
                 airdrop_pool_percentage: 50.0,
                 wagering_requirement_multiplier: 100,
+                allocation: AllocationSettings::default(),
             },
             database: DatabaseSettings {
                 url: "sqlite://degen_scores.db".to_string(),
@@ -128,23 +547,16 @@ impl Default for Settings {
                 port: 8080,
                 cors_origins: vec!["*".to_string()],
                 max_request_size_mb: 10,
+                max_pending_challenges: 10_000,
             },
-        }
-    }
-}
-
-impl Default for ScoringWeights {
-    fn default() -> Self {
-        Self {
-            trading_volume: 15.0,
-            trading_count: 10.0,
-            gambling_platforms: 10.0,
-            casino_tokens: 5.0,
-            defi_protocols: 10.0,
-            token_diversity: 5.0,
-            nft_holdings: 10.0,
-            wallet_age: 10.0,
-            activity_consistency: 10.0,
+            sybil: SybilSettings::default(),
+            registry: RegistrySettings::default(),
+            scanner: ScannerSettings::default(),
+            explorer: ExplorerSettings::default(),
+            nft: NftSettings::default(),
+            pricing: PricingSettings::default(),
+            portfolio: PortfolioSettings::default(),
+            solana: SolanaSettings::default(),
         }
     }
 }
@@ -171,22 +583,114 @@ impl Settings {
     }
     
     pub fn total_weight(&self) -> f64 {
-        let w = &self.scoring.weights;
-        w.trading_volume + w.trading_count + w.gambling_platforms + 
-        w.casino_tokens + w.defi_protocols + w.token_diversity + 
-        w.nft_holdings + w.wallet_age + w.activity_consistency
+        self.scoring.weights.total()
     }
     
     pub fn validate(&self) -> Result<(), String> {
-        let total = self.total_weight();
-        if (total - 100.0).abs() > 0.01 {
-            return Err(format!("Scoring weights must sum to 100, got {}", total));
-        }
-        
-        if self.scoring.min_score_for_airdrop < 0.0 || self.scoring.min_score_for_airdrop > 100.0 {
-            return Err("Minimum score for airdrop must be between 0 and 100".to_string());
-        }
-        
-        Ok(())
+        self.scoring.validate()?;
+        self.sybil.validate()?;
+        self.scanner.validate()?;
+        self.explorer.validate()?;
+        self.pricing.validate()?;
+        self.portfolio.validate()?;
+        self.solana.validate()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_config_file_parses_allocation() {
+        let mut settings = Settings::from_file("config/default.toml").unwrap();
+        assert_eq!(settings.scoring.allocation.curve, AllocationCurveKind::Linear);
+        assert_eq!(settings.scoring.allocation.max_share_percentage, None);
+        assert!(settings.validate().is_ok());
+
+        settings.scoring.allocation.curve = AllocationCurveKind::Tiered;
+        assert!(settings.validate().is_err());
+        settings.scoring.allocation.tier_amounts.rare = 1_000;
+        assert!(settings.validate().is_ok());
+    }
+
+    #[test]
+    fn test_thresholds_default_when_missing_and_are_validated() {
+        let settings = Settings::from_file("config/default.toml").unwrap();
+        assert_eq!(settings.scoring.thresholds.max_trading_volume_usd, 10_000_000.0);
+        assert_eq!(settings.scoring.thresholds.max_active_chains, 5);
+
+        let mut thresholds = ScoringThresholds::default();
+        assert!(thresholds.validate().is_ok());
+
+        thresholds.max_casino_tokens = 0;
+        assert!(thresholds.validate().unwrap_err().contains("max_casino_tokens"));
+
+        let thresholds = ScoringThresholds { max_trading_volume_usd: 1.0, ..Default::default() };
+        assert!(thresholds.validate().is_err());
+    }
+
+    #[test]
+    fn test_sections_validate_on_their_own() {
+        assert!(SolanaSettings::default().validate().is_ok());
+        let solana = SolanaSettings { transaction_concurrency: 0, ..Default::default() };
+        assert!(solana.validate().unwrap_err().contains("transaction_concurrency"));
+
+        let scanner = ScannerSettings { min_chunk_blocks: 0, ..Default::default() };
+        assert!(scanner.validate().is_err());
+
+        // Section errors surface through the top-level check
+        let settings = Settings { solana, ..Settings::default() };
+        assert!(settings.validate().unwrap_err().contains("transaction_concurrency"));
+    }
+
+    #[test]
+    fn test_pricing_sources_parse_from_toml() {
+        use crate::models::PriceSource;
+
+        let toml = r#"
+            [[pricing.sources]]
+            chain = "solana"
+            token = "JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN"
+            source = { kind = "pyth", account = "7dbob1psH1iZBS7qPsm3Kwbf5DzSXK8Jyg31CTgTnxH5" }
+        "#;
+        let settings: Settings = Config::builder()
+            .add_source(Config::try_from(&Settings::default()).unwrap())
+            .add_source(File::from_str(toml, config::FileFormat::Toml))
+            .build()
+            .and_then(|config| config.try_deserialize())
+            .unwrap();
+
+        assert_eq!(settings.pricing.twap_seconds, 1_800);
+        let entry = &settings.pricing.sources[0];
+        assert_eq!(entry.token, "JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN");
+        assert_eq!(entry.source, PriceSource::Pyth { account: "7dbob1psH1iZBS7qPsm3Kwbf5DzSXK8Jyg31CTgTnxH5".to_string() });
+    }
+
+    #[test]
+    fn test_solana_protocols_parse_from_toml() {
+        use crate::models::SolanaProtocolKind;
+
+        let toml = r#"
+            [[solana.protocols]]
+            name = "Some Casino"
+            kind = "casino"
+            program_id = "Casino1111111111111111111111111111111111111"
+
+            [[solana.casino_tokens]]
+            mint = "Chip111111111111111111111111111111111111111"
+            symbol = "CHIP"
+        "#;
+        let settings: Settings = Config::builder()
+            .add_source(Config::try_from(&Settings::default()).unwrap())
+            .add_source(File::from_str(toml, config::FileFormat::Toml))
+            .build()
+            .and_then(|config| config.try_deserialize())
+            .unwrap();
+
+        assert_eq!(settings.solana.max_signatures, 20_000);
+        assert_eq!(settings.solana.protocols[0].kind, SolanaProtocolKind::Casino);
+        assert_eq!(settings.solana.protocols[0].program_id, "Casino1111111111111111111111111111111111111");
+        assert_eq!(settings.solana.casino_tokens[0].symbol, "CHIP");
+    }
+}
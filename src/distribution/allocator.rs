@@ -0,0 +1,391 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+use crate::{
+    config::ScoringSettings,
+    models::{AirdropAllocation, AllocationCurve, CurveInput, DegenScoreError, Result, ScoreTier, UserProfile},
+};
+
+/// A scored user competing for a share of the pool
+#[derive(Debug, Clone)]
+pub struct AllocationCandidate {
+    pub user_id: String,
+    pub score: f64,
+    pub addresses: Vec<String>,
+}
+
+impl AllocationCandidate {
+    /// Build from a profile's latest score and verified addresses, if it has been scored
+    pub fn from_profile(user: &UserProfile) -> Option<Self> {
+        user.degen_score.as_ref().map(|score| Self {
+            user_id: user.id.clone(),
+            score: score.total_score,
+            addresses: user.verified_addresses.iter().map(|a| a.address.clone()).collect(),
+        })
+    }
+}
+
+/// Aggregate figures for a completed allocation run
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AllocationSummary {
+    pub curve: String,
+    pub total_supply: u64,
+    pub pool_size: u64,
+    pub total_allocated: u64,
+    /// Pool left over when caps or fixed tier amounts leave it short of fully allocated
+    pub unallocated: u64,
+    /// Units handed out by largest remainder after the pro-rata floor
+    pub dust_distributed: u64,
+    pub eligible_users: usize,
+    pub ineligible_users: usize,
+    pub min_allocation: u64,
+    pub max_allocation: u64,
+    pub mean_allocation: f64,
+    pub median_allocation: u64,
+    pub total_credits: u64,
+    pub total_wagering_requirement: u64,
+    /// 0 = perfectly equal split, approaching 1 = one user takes everything
+    pub gini_coefficient: f64,
+    /// Share of the allocated total going to the top 10% of recipients, in percent
+    pub top_decile_share: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AllocationReport {
+    pub allocations: Vec<AirdropAllocation>,
+    pub summary: AllocationSummary,
+}
+
+/// One curve's outcome for a cohort in a `CurveComparison`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CurveResult {
+    pub summary: AllocationSummary,
+    /// Token amount per user, in the order of `CurveComparison::users`
+    pub amounts: Vec<u64>,
+}
+
+/// How several allocation curves would distribute the same cohort
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CurveComparison {
+    pub users: Vec<(String, f64)>,
+    pub curves: Vec<CurveResult>,
+}
+
+impl CurveComparison {
+    /// Plain-text table: one summary row per curve, then the top users under each curve
+    pub fn render_text(&self, max_users: usize) -> String {
+        let mut out = String::new();
+
+        let _ = writeln!(out, "{:<14} {:>14} {:>12} {:>12} {:>12} {:>7} {:>8}",
+            "curve", "allocated", "min", "median", "max", "gini", "top10%");
+        for curve in &self.curves {
+            let s = &curve.summary;
+            let _ = writeln!(out, "{:<14} {:>14} {:>12} {:>12} {:>12} {:>7.3} {:>7.1}%",
+                s.curve, s.total_allocated, s.min_allocation, s.median_allocation,
+                s.max_allocation, s.gini_coefficient, s.top_decile_share);
+        }
+
+        // Highest scores first so the whales are visible
+        let mut order: Vec<usize> = (0..self.users.len()).collect();
+        order.sort_by(|a, b| self.users[*b].1.total_cmp(&self.users[*a].1).then(a.cmp(b)));
+
+        let _ = write!(out, "\n{:<20} {:>7}", "user", "score");
+        for curve in &self.curves {
+            let _ = write!(out, " {:>14}", curve.summary.curve);
+        }
+        let _ = writeln!(out);
+
+        for &i in order.iter().take(max_users) {
+            let (user_id, score) = &self.users[i];
+            let _ = write!(out, "{:<20} {:>7.2}", user_id, score);
+            for curve in &self.curves {
+                let _ = write!(out, " {:>14}", curve.amounts[i]);
+            }
+            let _ = writeln!(out);
+        }
+
+        out
+    }
+}
+
+/// Splits the airdrop pool across every eligible user according to an allocation curve
+#[derive(Debug, Clone)]
+pub struct AirdropAllocator {
+    min_score: f64,
+    pool_percentage: f64,
+    wagering_multiplier: u32,
+    curve: AllocationCurve,
+}
+
+impl AirdropAllocator {
+    pub fn new(settings: &ScoringSettings) -> Result<Self> {
+        if !(settings.airdrop_pool_percentage > 0.0 && settings.airdrop_pool_percentage <= 100.0) {
+            return Err(DegenScoreError::ConfigError(format!(
+                "airdrop_pool_percentage must be in (0, 100], got {}",
+                settings.airdrop_pool_percentage
+            )));
+        }
+
+        Ok(Self {
+            min_score: settings.min_score_for_airdrop,
+            pool_percentage: settings.airdrop_pool_percentage,
+            wagering_multiplier: settings.wagering_requirement_multiplier,
+            curve: AllocationCurve::from_settings(&settings.allocation),
+        })
+    }
+
+    /// Same thresholds, different curve
+    pub fn with_curve(mut self, curve: AllocationCurve) -> Self {
+        self.curve = curve;
+        self
+    }
+
+    /// Tokens reserved for the airdrop out of the total supply
+    pub fn pool_size(&self, total_supply: u64) -> u64 {
+        let basis_points = (self.pool_percentage * 100.0).round() as u128;
+        (total_supply as u128 * basis_points / 10_000) as u64
+    }
+
+    pub fn allocate_users(&self, total_supply: u64, users: &[UserProfile]) -> AllocationReport {
+        let candidates: Vec<_> = users.iter().filter_map(AllocationCandidate::from_profile).collect();
+        let unscored = users.len() - candidates.len();
+
+        let mut report = self.allocate(total_supply, candidates);
+        report.summary.ineligible_users += unscored;
+        report
+    }
+
+    /// Allocate the pool so the sum of `token_amount` never exceeds it.
+    ///
+    /// Eligible users are ordered by user id before the curve runs, so rounding
+    /// dust always lands the same way for the same cohort.
+    pub fn allocate(&self, total_supply: u64, candidates: Vec<AllocationCandidate>) -> AllocationReport {
+        let pool = self.pool_size(total_supply);
+
+        let (mut eligible, ineligible): (Vec<_>, Vec<_>) = candidates
+            .into_iter()
+            .partition(|c| c.score >= self.min_score && c.score > 0.0 && !c.addresses.is_empty());
+        eligible.sort_by(|a, b| a.user_id.cmp(&b.user_id));
+
+        let inputs: Vec<CurveInput> = eligible
+            .iter()
+            .map(|c| CurveInput { score: c.score, tier: ScoreTier::from_score(c.score) })
+            .collect();
+        let outcome = self.curve.distribute(pool, &inputs);
+
+        let allocations: Vec<AirdropAllocation> = eligible
+            .into_iter()
+            .zip(&outcome.amounts)
+            .map(|(candidate, &amount)| AirdropAllocation {
+                user_id: candidate.user_id,
+                score: candidate.score,
+                token_amount: amount,
+                // Tokens are issued as locked credits that must be wagered through
+                credits_amount: amount,
+                wagering_requirement: amount.saturating_mul(self.wagering_multiplier as u64),
+                eligible_addresses: candidate.addresses,
+            })
+            .collect();
+
+        let mut summary = summarize(total_supply, pool, outcome.dust, ineligible.len(), &allocations);
+        summary.curve = self.curve.name.clone();
+        AllocationReport { allocations, summary }
+    }
+
+    /// Run every curve over the same cohort
+    pub fn compare(
+        &self,
+        total_supply: u64,
+        candidates: &[AllocationCandidate],
+        curves: &[AllocationCurve],
+    ) -> CurveComparison {
+        let mut users: Vec<(String, f64)> = candidates.iter().map(|c| (c.user_id.clone(), c.score)).collect();
+        users.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let curves = curves
+            .iter()
+            .map(|curve| {
+                let report = self.clone().with_curve(curve.clone()).allocate(total_supply, candidates.to_vec());
+                let by_user: std::collections::HashMap<&str, u64> = report.allocations
+                    .iter()
+                    .map(|a| (a.user_id.as_str(), a.token_amount))
+                    .collect();
+
+                CurveResult {
+                    amounts: users.iter().map(|(id, _)| by_user.get(id.as_str()).copied().unwrap_or(0)).collect(),
+                    summary: report.summary,
+                }
+            })
+            .collect();
+
+        CurveComparison { users, curves }
+    }
+}
+
+fn summarize(
+    total_supply: u64,
+    pool_size: u64,
+    dust: u64,
+    ineligible_users: usize,
+    allocations: &[AirdropAllocation],
+) -> AllocationSummary {
+    let mut amounts: Vec<u64> = allocations.iter().map(|a| a.token_amount).collect();
+    amounts.sort_unstable();
+
+    let total_allocated: u64 = amounts.iter().sum();
+
+    AllocationSummary {
+        curve: String::new(),
+        total_supply,
+        pool_size,
+        total_allocated,
+        unallocated: pool_size - total_allocated,
+        dust_distributed: dust,
+        eligible_users: allocations.len(),
+        ineligible_users,
+        min_allocation: amounts.first().copied().unwrap_or(0),
+        max_allocation: amounts.last().copied().unwrap_or(0),
+        mean_allocation: if amounts.is_empty() { 0.0 } else { total_allocated as f64 / amounts.len() as f64 },
+        median_allocation: amounts.get(amounts.len() / 2).copied().unwrap_or(0),
+        total_credits: allocations.iter().map(|a| a.credits_amount).sum(),
+        total_wagering_requirement: allocations
+            .iter()
+            .fold(0u64, |acc, a| acc.saturating_add(a.wagering_requirement)),
+        gini_coefficient: gini(&amounts),
+        top_decile_share: top_decile_share(&amounts, total_allocated),
+    }
+}
+
+// Expects amounts sorted ascending
+fn gini(amounts: &[u64]) -> f64 {
+    let n = amounts.len() as f64;
+    let total: f64 = amounts.iter().map(|a| *a as f64).sum();
+    if amounts.is_empty() || total == 0.0 {
+        return 0.0;
+    }
+
+    let weighted: f64 = amounts
+        .iter()
+        .enumerate()
+        .map(|(i, a)| (i as f64 + 1.0) * *a as f64)
+        .sum();
+
+    (2.0 * weighted) / (n * total) - (n + 1.0) / n
+}
+
+// Expects amounts sorted ascending
+fn top_decile_share(amounts: &[u64], total: u64) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+
+    let top_count = amounts.len().div_ceil(10);
+    let top: u64 = amounts.iter().rev().take(top_count).sum();
+    top as f64 / total as f64 * 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Settings;
+
+    fn candidate(user_id: &str, score: f64) -> AllocationCandidate {
+        AllocationCandidate {
+            user_id: user_id.to_string(),
+            score,
+            addresses: vec![format!("addr-{}", user_id)],
+        }
+    }
+
+    fn allocator() -> AirdropAllocator {
+        AirdropAllocator::new(&Settings::default().scoring).unwrap()
+    }
+
+    #[test]
+    fn test_pool_is_split_exactly() {
+        let candidates = vec![
+            candidate("a", 33.3),
+            candidate("b", 33.3),
+            candidate("c", 33.3),
+            candidate("d", 90.0),
+            candidate("e", 10.0), // below the 20 point minimum
+        ];
+
+        let report = allocator().allocate(1_000_001, candidates);
+        let summary = &report.summary;
+
+        assert_eq!(summary.pool_size, 500_000);
+        assert_eq!(summary.total_allocated, summary.pool_size);
+        assert_eq!(summary.eligible_users, 4);
+        assert_eq!(summary.ineligible_users, 1);
+        assert!(summary.dust_distributed > 0);
+
+        // Equal scores differ by at most the one unit of dust, lowest user id first
+        let a = report.allocations[0].token_amount;
+        let c = report.allocations[2].token_amount;
+        assert!(a >= c && a - c <= 1);
+
+        let d = &report.allocations[3];
+        assert_eq!(d.user_id, "d");
+        assert_eq!(summary.max_allocation, d.token_amount);
+        assert_eq!(d.credits_amount, d.token_amount);
+        assert_eq!(d.wagering_requirement, d.token_amount * 100);
+    }
+
+    #[test]
+    fn test_allocation_is_deterministic() {
+        let forward: Vec<_> = (0..50).map(|i| candidate(&format!("u{:02}", i), 20.0 + i as f64)).collect();
+        let mut reversed = forward.clone();
+        reversed.reverse();
+
+        let a = allocator().allocate(7_777_777, forward);
+        let b = allocator().allocate(7_777_777, reversed);
+
+        let amounts = |r: &AllocationReport| r.allocations.iter().map(|a| (a.user_id.clone(), a.token_amount)).collect::<Vec<_>>();
+        assert_eq!(amounts(&a), amounts(&b));
+        assert_eq!(a.summary.total_allocated, a.summary.pool_size);
+    }
+
+    #[test]
+    fn test_compare_curves_on_same_cohort() {
+        use crate::config::{AllocationCurveKind, AllocationSettings};
+
+        let candidates = vec![candidate("whale", 100.0), candidate("a", 25.0), candidate("b", 25.0), candidate("c", 25.0)];
+        let mut settings = AllocationSettings { curve: AllocationCurveKind::Quadratic, ..Default::default() };
+        settings.max_share_percentage = Some(50.0);
+
+        let curves = AllocationCurve::presets(&settings);
+        let comparison = allocator().compare(2_000_000, &candidates, &curves);
+
+        // configured + linear + square_root + quadratic (tiered has no amounts)
+        assert_eq!(comparison.curves.len(), 4);
+        assert_eq!(comparison.users[3].0, "whale");
+
+        let whale = |name: &str| {
+            let curve = comparison.curves.iter().find(|c| c.summary.curve == name).unwrap();
+            curve.amounts[3]
+        };
+        assert_eq!(whale("configured"), 500_000);
+        assert!(whale("square_root") < whale("linear"));
+        assert!(whale("linear") < whale("quadratic"));
+
+        let linear = &comparison.curves[1].summary;
+        assert!(linear.gini_coefficient > 0.0 && linear.gini_coefficient < 1.0);
+        assert_eq!(linear.unallocated, 0);
+
+        let text = comparison.render_text(10);
+        assert!(text.contains("square_root"));
+        assert!(text.lines().any(|l| l.starts_with("whale")));
+    }
+
+    #[test]
+    fn test_no_eligible_users_allocates_nothing() {
+        let report = allocator().allocate(1_000, vec![candidate("a", 5.0)]);
+        assert!(report.allocations.is_empty());
+        assert_eq!(report.summary.total_allocated, 0);
+
+        let mut settings = Settings::default().scoring;
+        settings.airdrop_pool_percentage = 0.0;
+        assert!(AirdropAllocator::new(&settings).is_err());
+    }
+}
@@ -0,0 +1,390 @@
+use ethers::{
+    abi::{self, Token},
+    types::{Address, U256},
+    utils::keccak256,
+};
+use ring::digest::{Context, SHA256};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use crate::models::{AirdropAllocation, DegenScoreError, Result};
+
+pub type Hash = [u8; 32];
+
+// Domain separation used by Jito's merkle-distributor claim program
+const SOLANA_LEAF_PREFIX: u8 = 0;
+const SOLANA_INTERMEDIATE_PREFIX: u8 = 1;
+
+/// How leaves and nodes are hashed, matching the on-chain verifier
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LeafEncoding {
+    /// OpenZeppelin `MerkleProof`: leaf = keccak256(keccak256(abi.encode(address, uint256))),
+    /// nodes = keccak256 of the sorted pair
+    Evm,
+    /// Jito merkle-distributor `new_claim`: leaf = sha256(0x00 || sha256(pubkey ||
+    /// amount_unlocked_le || amount_locked_le)), nodes = sha256(0x01 || sorted pair).
+    /// The whole amount is unlocked at claim, so amount_locked is always 0.
+    Solana,
+}
+
+impl LeafEncoding {
+    /// Canonical claimant key, or None if the address is not valid for this encoding
+    fn normalize(&self, address: &str) -> Option<String> {
+        match self {
+            LeafEncoding::Evm => Address::from_str(address).ok().map(|a| format!("{:?}", a)),
+            LeafEncoding::Solana => {
+                let bytes = bs58::decode(address).into_vec().ok()?;
+                (bytes.len() == 32).then(|| address.to_string())
+            }
+        }
+    }
+
+    pub fn leaf(&self, claimant: &str, amount: u64) -> Result<Hash> {
+        match self {
+            LeafEncoding::Evm => {
+                let address = Address::from_str(claimant)
+                    .map_err(|_| DegenScoreError::InvalidAddress(claimant.to_string()))?;
+                let encoded = abi::encode(&[Token::Address(address), Token::Uint(U256::from(amount))]);
+                Ok(keccak256(keccak256(encoded)))
+            }
+            LeafEncoding::Solana => {
+                let pubkey = bs58::decode(claimant)
+                    .into_vec()
+                    .ok()
+                    .filter(|b| b.len() == 32)
+                    .ok_or_else(|| DegenScoreError::InvalidAddress(claimant.to_string()))?;
+                let amount_locked = 0u64;
+                let inner = sha256(&[&pubkey, &amount.to_le_bytes(), &amount_locked.to_le_bytes()]);
+                Ok(sha256(&[&[SOLANA_LEAF_PREFIX], &inner]))
+            }
+        }
+    }
+
+    pub fn node(&self, a: &Hash, b: &Hash) -> Hash {
+        let (left, right) = if a <= b { (a, b) } else { (b, a) };
+        match self {
+            LeafEncoding::Evm => {
+                let mut buf = [0u8; 64];
+                buf[..32].copy_from_slice(left);
+                buf[32..].copy_from_slice(right);
+                keccak256(buf)
+            }
+            LeafEncoding::Solana => sha256(&[&[SOLANA_INTERMEDIATE_PREFIX], left, right]),
+        }
+    }
+}
+
+fn sha256(parts: &[&[u8]]) -> Hash {
+    let mut ctx = Context::new(&SHA256);
+    for part in parts {
+        ctx.update(part);
+    }
+    let mut out = [0u8; 32];
+    out.copy_from_slice(ctx.finish().as_ref());
+    out
+}
+
+/// A single claimant's entry in the distribution
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MerkleClaim {
+    pub index: usize,
+    pub user_id: String,
+    pub amount: u64,
+    pub credits_amount: u64,
+    pub wagering_requirement: u64,
+    #[serde(with = "hex_hash")]
+    pub leaf: Hash,
+    #[serde(with = "hex_hashes")]
+    pub proof: Vec<Hash>,
+}
+
+/// Merkle root plus per-claimant proofs, serialisable as the distribution JSON
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MerkleDistribution {
+    pub encoding: LeafEncoding,
+    #[serde(with = "hex_hash")]
+    pub root: Hash,
+    pub total_amount: u64,
+    pub claims: BTreeMap<String, MerkleClaim>,
+    /// Users without an address usable under this encoding
+    pub skipped_users: Vec<String>,
+}
+
+impl MerkleDistribution {
+    /// Build a deterministic tree over (claimant, token_amount).
+    ///
+    /// Each allocation claims to its first eligible address valid for the encoding;
+    /// claimants are ordered by address so the same input always yields the same root.
+    pub fn build(allocations: &[AirdropAllocation], encoding: LeafEncoding) -> Result<Self> {
+        let mut entries: BTreeMap<String, &AirdropAllocation> = BTreeMap::new();
+        let mut skipped_users = Vec::new();
+
+        for allocation in allocations.iter().filter(|a| a.token_amount > 0) {
+            let claimant = allocation.eligible_addresses
+                .iter()
+                .find_map(|address| encoding.normalize(address));
+
+            match claimant {
+                Some(claimant) => {
+                    if let Some(existing) = entries.insert(claimant.clone(), allocation) {
+                        return Err(DegenScoreError::ConfigError(format!(
+                            "Claimant {} is allocated to both {} and {}",
+                            claimant, existing.user_id, allocation.user_id
+                        )));
+                    }
+                }
+                None => skipped_users.push(allocation.user_id.clone()),
+            }
+        }
+
+        if entries.is_empty() {
+            return Err(DegenScoreError::ConfigError("No claimable allocations for distribution".to_string()));
+        }
+
+        let leaves = entries
+            .iter()
+            .map(|(claimant, allocation)| encoding.leaf(claimant, allocation.token_amount))
+            .collect::<Result<Vec<_>>>()?;
+
+        let layers = build_layers(&leaves, encoding);
+        let root = layers.last().and_then(|l| l.first()).copied().unwrap_or_default();
+
+        let mut total_amount: u64 = 0;
+        let mut claims = BTreeMap::new();
+        for (index, (claimant, allocation)) in entries.into_iter().enumerate() {
+            total_amount = total_amount.checked_add(allocation.token_amount)
+                .ok_or_else(|| DegenScoreError::ConfigError("Distribution total overflows u64".to_string()))?;
+
+            claims.insert(claimant, MerkleClaim {
+                index,
+                user_id: allocation.user_id.clone(),
+                amount: allocation.token_amount,
+                credits_amount: allocation.credits_amount,
+                wagering_requirement: allocation.wagering_requirement,
+                leaf: leaves[index],
+                proof: proof_for(&layers, index, encoding),
+            });
+        }
+
+        Ok(Self { encoding, root, total_amount, claims, skipped_users })
+    }
+
+    pub fn root_hex(&self) -> String {
+        format!("0x{}", hex::encode(self.root))
+    }
+
+    pub fn claim(&self, claimant: &str) -> Option<&MerkleClaim> {
+        let key = self.encoding.normalize(claimant)?;
+        self.claims.get(&key)
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+}
+
+/// Check a single claim against a root, as the on-chain verifier would
+pub fn verify_proof(
+    encoding: LeafEncoding,
+    claimant: &str,
+    amount: u64,
+    proof: &[Hash],
+    root: &Hash,
+) -> Result<bool> {
+    let computed = proof
+        .iter()
+        .fold(encoding.leaf(claimant, amount)?, |acc, sibling| encoding.node(&acc, sibling));
+
+    Ok(&computed == root)
+}
+
+// layers[0] are the leaves, the last layer holds only the root.
+// An unpaired node is carried up unchanged under the EVM encoding, so its proof simply skips
+// that level. Jito's tree pairs it with itself instead, which the Solana encoding follows so
+// the root matches the distributor's own CLI.
+fn build_layers(leaves: &[Hash], encoding: LeafEncoding) -> Vec<Vec<Hash>> {
+    let mut layers = vec![leaves.to_vec()];
+
+    while layers.last().is_some_and(|l| l.len() > 1) {
+        let next = layers.last().unwrap()
+            .chunks(2)
+            .map(|pair| match pair {
+                [a, b] => encoding.node(a, b),
+                [a] => match encoding {
+                    LeafEncoding::Evm => *a,
+                    LeafEncoding::Solana => encoding.node(a, a),
+                },
+                _ => unreachable!(),
+            })
+            .collect();
+        layers.push(next);
+    }
+
+    layers
+}
+
+fn proof_for(layers: &[Vec<Hash>], mut index: usize, encoding: LeafEncoding) -> Vec<Hash> {
+    let mut proof = Vec::new();
+    for layer in &layers[..layers.len() - 1] {
+        let sibling = index ^ 1;
+        if sibling < layer.len() {
+            proof.push(layer[sibling]);
+        } else if encoding == LeafEncoding::Solana {
+            proof.push(layer[index]);
+        }
+        index /= 2;
+    }
+    proof
+}
+
+mod hex_hash {
+    use super::Hash;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(hash: &Hash, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("0x{}", hex::encode(hash)))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Hash, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        parse(&s).map_err(D::Error::custom)
+    }
+
+    pub fn parse(s: &str) -> std::result::Result<Hash, String> {
+        let bytes = hex::decode(s.trim_start_matches("0x")).map_err(|e| e.to_string())?;
+        bytes.try_into().map_err(|_| format!("Expected 32-byte hash, got {}", s))
+    }
+}
+
+mod hex_hashes {
+    use super::{hex_hash, Hash};
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(hashes: &[Hash], serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_seq(hashes.iter().map(|h| format!("0x{}", hex::encode(h))))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Vec<Hash>, D::Error> {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|s| hex_hash::parse(s).map_err(D::Error::custom))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOL_ADDRESSES: [&str; 3] = [
+        "7VXNe1r6nTqVw6TKyBzt1TNSSQqPqNcEYizv8TduLWpU",
+        "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM",
+        "DRpbCBMxVnDK7maPM5tGv6MvB3v1sRMC86PZ8okm21hy",
+    ];
+
+    fn allocation(user_id: &str, addresses: &[&str], amount: u64) -> AirdropAllocation {
+        AirdropAllocation {
+            user_id: user_id.to_string(),
+            score: 50.0,
+            token_amount: amount,
+            credits_amount: amount * 2,
+            wagering_requirement: amount * 4,
+            eligible_addresses: addresses.iter().map(|a| a.to_string()).collect(),
+        }
+    }
+
+    fn allocations() -> Vec<AirdropAllocation> {
+        vec![
+            allocation("alice", &["0x742d35Cc6634C0532925a3b844Bc9e7595f6e842", SOL_ADDRESSES[0]], 1_000),
+            allocation("bob", &["0x0000000000000000000000000000000000000001", SOL_ADDRESSES[1]], 2_500),
+            allocation("carol", &["0xAb5801a7D398351b8bE11C439e05C5B3259aeC9B", SOL_ADDRESSES[2]], 42),
+            allocation("dave", &["0x00000000000000000000000000000000000000aa"], 7),
+            allocation("erin", &["0x00000000000000000000000000000000000000bb"], 0),
+        ]
+    }
+
+    #[test]
+    fn test_every_proof_verifies() {
+        for encoding in [LeafEncoding::Evm, LeafEncoding::Solana] {
+            let distribution = MerkleDistribution::build(&allocations(), encoding).unwrap();
+
+            for (claimant, claim) in &distribution.claims {
+                assert!(verify_proof(encoding, claimant, claim.amount, &claim.proof, &distribution.root).unwrap());
+                // Any other amount must fail
+                assert!(!verify_proof(encoding, claimant, claim.amount + 1, &claim.proof, &distribution.root).unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn test_build_is_deterministic_and_skips_incompatible_users() {
+        let mut reversed = allocations();
+        reversed.reverse();
+
+        let a = MerkleDistribution::build(&allocations(), LeafEncoding::Evm).unwrap();
+        let b = MerkleDistribution::build(&reversed, LeafEncoding::Evm).unwrap();
+        assert_eq!(a.root, b.root);
+        assert_eq!(a.claims.len(), 4);
+        assert_eq!(a.total_amount, 3_549);
+
+        let sol = MerkleDistribution::build(&allocations(), LeafEncoding::Solana).unwrap();
+        assert_eq!(sol.claims.len(), 3);
+        assert_eq!(sol.skipped_users, vec!["dave".to_string()]);
+        assert_ne!(sol.root, a.root);
+    }
+
+    #[test]
+    fn test_evm_leaf_matches_openzeppelin_standard_tree() {
+        // keccak256(bytes.concat(keccak256(abi.encode(address(1), uint256(1)))))
+        let leaf = LeafEncoding::Evm.leaf("0x0000000000000000000000000000000000000001", 1).unwrap();
+        let mut encoded = [0u8; 64];
+        encoded[31] = 1;
+        encoded[63] = 1;
+        assert_eq!(leaf, keccak256(keccak256(encoded)));
+
+        // Claims are looked up case-insensitively
+        let distribution = MerkleDistribution::build(&allocations(), LeafEncoding::Evm).unwrap();
+        assert!(distribution.claim("0x742d35cc6634c0532925a3b844bc9e7595f6e842").is_some());
+    }
+
+    #[test]
+    fn test_solana_leaf_matches_jito_distributor() {
+        // new_claim: hashv([LEAF_PREFIX, hashv([claimant, amount_unlocked_le, amount_locked_le])])
+        let pubkey = bs58::decode(SOL_ADDRESSES[0]).into_vec().unwrap();
+        let node = sha256(&[&pubkey, &1_000u64.to_le_bytes(), &0u64.to_le_bytes()]);
+        let leaf = LeafEncoding::Solana.leaf(SOL_ADDRESSES[0], 1_000).unwrap();
+        assert_eq!(leaf, sha256(&[&[0], &node]));
+
+        // Three leaves: the odd one is paired with itself, and every proof has one
+        // sibling per level as the on-chain verifier walks it
+        let distribution = MerkleDistribution::build(&allocations(), LeafEncoding::Solana).unwrap();
+        let leaves: Vec<Hash> = distribution.claims.values().map(|c| c.leaf).collect();
+        let pair = LeafEncoding::Solana.node(&leaves[0], &leaves[1]);
+        let odd = LeafEncoding::Solana.node(&leaves[2], &leaves[2]);
+        assert_eq!(distribution.root, LeafEncoding::Solana.node(&pair, &odd));
+        assert!(distribution.claims.values().all(|c| c.proof.len() == 2));
+    }
+
+    #[test]
+    fn test_json_roundtrip_and_duplicate_claimant() {
+        let distribution = MerkleDistribution::build(&allocations(), LeafEncoding::Solana).unwrap();
+        let parsed = MerkleDistribution::from_json(&distribution.to_json().unwrap()).unwrap();
+        assert_eq!(parsed.root, distribution.root);
+
+        let claim = parsed.claim(SOL_ADDRESSES[1]).unwrap();
+        assert_eq!(claim.user_id, "bob");
+        assert!(verify_proof(LeafEncoding::Solana, SOL_ADDRESSES[1], claim.amount, &claim.proof, &parsed.root).unwrap());
+
+        let duplicate = vec![
+            allocation("alice", &[SOL_ADDRESSES[0]], 1),
+            allocation("mallory", &[SOL_ADDRESSES[0]], 1),
+        ];
+        assert!(MerkleDistribution::build(&duplicate, LeafEncoding::Solana).is_err());
+    }
+}
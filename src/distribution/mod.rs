@@ -0,0 +1,7 @@
+pub mod allocator;
+pub mod merkle;
+
+pub use allocator::{
+    AirdropAllocator, AllocationCandidate, AllocationReport, AllocationSummary, CurveComparison, CurveResult,
+};
+pub use merkle::{verify_proof, LeafEncoding, MerkleClaim, MerkleDistribution};
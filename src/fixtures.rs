@@ -0,0 +1,119 @@
+//! Fixtures shared by the unit tests of several modules
+
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+
+use crate::{
+    chains::{client::ChainClientConfig, solana_mock::SolanaClient as MockSolanaClient, ChainClient},
+    config::Settings,
+    models::{DegenScore, ScoreBreakdown, ScoreTier},
+    scoring::ScoreCalculator,
+};
+
+/// A calculator with no EVM clients and the mock Solana client
+pub(crate) fn mock_calculator(settings: &Settings) -> ScoreCalculator {
+    let solana = MockSolanaClient::new(ChainClientConfig {
+        rpc_url: "http://localhost".to_string(),
+        chain_id: None,
+        timeout_seconds: 1,
+        max_retries: 0,
+        rate_limit_per_second: 1.0,
+    }).unwrap();
+    ScoreCalculator::new(Vec::new(), Arc::new(solana) as Arc<dyn ChainClient>, settings.clone())
+}
+
+/// A `DegenScore` with every optional field unset. New fields only need a default here.
+pub(crate) struct DegenScoreBuilder {
+    score: DegenScore,
+}
+
+impl DegenScoreBuilder {
+    pub fn new(total_score: f64) -> Self {
+        Self {
+            score: DegenScore {
+                total_score,
+                percentile: 0.0,
+                breakdown: ScoreBreakdown {
+                    trading_score: 0.0,
+                    gambling_score: 0.0,
+                    defi_activity_score: 0.0,
+                    nft_portfolio_score: 0.0,
+                    longevity_score: 0.0,
+                    risk_profile_score: 0.0,
+                },
+                calculated_at: Utc::now(),
+                tier: ScoreTier::from_score(total_score),
+                category_percentiles: None,
+                thresholds: None,
+                explanation: None,
+                algorithm_version: None,
+                fingerprint: None,
+                sybil_multiplier: None,
+            },
+        }
+    }
+
+    pub fn trading(mut self, trading_score: f64) -> Self {
+        self.score.breakdown.trading_score = trading_score;
+        self
+    }
+
+    pub fn calculated_at(mut self, at: DateTime<Utc>) -> Self {
+        self.score.calculated_at = at;
+        self
+    }
+
+    pub fn build(self) -> DegenScore {
+        self.score
+    }
+}
+
+pub(crate) const METADATA_AUTHORITY: [u8; 32] = [1; 32];
+pub(crate) const METADATA_MINT: [u8; 32] = [2; 32];
+pub(crate) const METADATA_CREATOR: [u8; 32] = [3; 32];
+pub(crate) const METADATA_COLLECTION: [u8; 32] = [4; 32];
+
+fn padded(text: &str, len: usize) -> Vec<u8> {
+    let mut bytes = (len as u32).to_le_bytes().to_vec();
+    bytes.extend(text.as_bytes());
+    bytes.resize(4 + len, 0);
+    bytes
+}
+
+/// A Metaplex `MetadataV1` account as the program writes it: strings padded to their
+/// maximum length and the account zero padded to 679 bytes
+pub(crate) fn metadata_fixture(collection_verified: bool) -> Vec<u8> {
+    let mut data = vec![4]; // key: MetadataV1
+    data.extend(METADATA_AUTHORITY);
+    data.extend(METADATA_MINT);
+    data.extend(padded("Mad Lads #8420", 32));
+    data.extend(padded("MAD", 10));
+    data.extend(padded("https://madlads.s3.us-west-2.amazonaws.com/json/8420.json", 200));
+    data.extend(420u16.to_le_bytes());
+    data.extend([1]); // creators: Some
+    data.extend(1u32.to_le_bytes());
+    data.extend(METADATA_CREATOR);
+    data.extend([1, 100]); // verified, share
+    data.extend([1, 1]); // primary sale happened, mutable
+    data.extend([1, 254]); // edition nonce
+    data.extend([1, 4]); // token standard: programmable non-fungible
+    data.extend([1, collection_verified as u8]);
+    data.extend(METADATA_COLLECTION);
+    data.resize(679, 0);
+    data
+}
+
+/// A fully verified Pyth price update account holding `price` * 10^`exponent`, published
+/// at `publish_time`
+pub(crate) fn pyth_update(price: i64, exponent: i32, publish_time: i64) -> Vec<u8> {
+    // Discriminator, write authority, verification level, then the feed message
+    let mut data = vec![0u8; 8 + 32];
+    data.push(1);
+    data.extend([7u8; 32]);
+    data.extend(price.to_le_bytes());
+    data.extend(1_000u64.to_le_bytes());
+    data.extend(exponent.to_le_bytes());
+    data.extend(publish_time.to_le_bytes());
+    data.extend([0u8; 32]);
+    data
+}
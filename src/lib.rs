@@ -5,6 +5,13 @@ pub mod scoring;
 pub mod verification;
 pub mod utils;
 pub mod tui;
+pub mod api;
+pub mod storage;
+pub mod distribution;
+pub mod sybil;
+
+#[cfg(test)]
+pub(crate) mod fixtures;
 
 pub use models::{DegenMetrics, UserProfile, Chain, DegenScore, DegenScoreError, Result};
 pub use config::{Settings, RpcConfig};
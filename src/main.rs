@@ -1,14 +1,18 @@
 use clap::{Parser, Subcommand};
 use degen_scorer::{
-    models::{UserProfile, VerifiedAddress, Chain, VerificationMethod},
-    chains::{EvmClient, SolanaClient, ChainClient, client::ChainClientConfig},
-    scoring::ScoreCalculator,
-    config::{Settings, RpcConfig},
-    verification::WalletVerifier,
+    models::{UserProfile, VerifiedAddress, Chain, VerificationMethod, AllocationCurve},
+    chains::{EvmClient, SolanaClient, ChainClient, LogScanner, ExplorerClient, client::ChainClientConfig},
+    scoring::{rescore::rescore_store, ScoreCalculator, ScoringAlgorithm},
+    config::{migrate_config, Settings, RpcConfig},
+    verification::{AddressRegistry, WalletVerifier},
+    api::{ApiServer, AppState},
+    storage::ScoreStore,
+    distribution::{AirdropAllocator, AllocationCandidate, LeafEncoding, MerkleDistribution},
     utils,
 };
+use std::collections::HashMap;
 use std::sync::Arc;
-use tracing::{info, error};
+use tracing::{info, warn, error};
 use tracing_subscriber;
 use chrono::Utc;
 
@@ -45,6 +49,48 @@ enum Commands {
         /// Solana address
         #[clap(long)]
         sol_address: Option<String>,
+        
+        /// Persist the user, metric snapshots and score to the database
+        #[clap(long)]
+        save: bool,
+        
+        /// Show per-metric contributions (text, json)
+        #[clap(long, value_name = "FORMAT", num_args = 0..=1, default_missing_value = "text")]
+        explain: Option<String>,
+        
+        /// Run sybil detection over the stored users and this one, and penalize the score if flagged
+        #[clap(long)]
+        sybil: bool,
+    },
+    
+    /// Show stored score history for a user
+    History {
+        /// User ID
+        #[clap(short, long)]
+        user_id: String,
+        
+        /// Maximum number of scores to show
+        #[clap(short, long, default_value = "10")]
+        limit: u32,
+    },
+    
+    /// Allocate the airdrop pool across stored scores and build the Merkle distribution
+    Airdrop {
+        /// Total token supply; the pool is `airdrop_pool_percentage` of it
+        #[clap(long)]
+        total_supply: u64,
+        
+        /// Claim encoding (evm, solana)
+        #[clap(short, long, default_value = "solana")]
+        encoding: String,
+        
+        /// File to write the distribution JSON to
+        #[clap(short, long, default_value = "airdrop_distribution.json")]
+        output: String,
+        
+        /// Also print how each allocation curve would split the same cohort
+        #[clap(long)]
+        compare: bool,
     },
     
     /// Verify a wallet address
@@ -68,9 +114,72 @@ enum Commands {
     
     /// Start the API server
     Serve {
-        /// Port to listen on
-        #[clap(short, long, default_value = "8080")]
-        port: u16,
+        /// Host to bind (defaults to api.host from config)
+        #[clap(long)]
+        host: Option<String>,
+        
+        /// Port to listen on (defaults to api.port from config)
+        #[clap(short, long)]
+        port: Option<u16>,
+    },
+    
+    /// Detect sybil clusters among stored users
+    Sybil {
+        /// Print the report as JSON
+        #[clap(long)]
+        json: bool,
+    },
+    
+    /// Recompute stored scores from stored metrics and show the per-user diff
+    Rescore {
+        /// Settings file with the weights/thresholds to score under (defaults to the active config)
+        #[clap(long)]
+        config: Option<String>,
+        
+        /// Append the new scores to each user's history
+        #[clap(long)]
+        save: bool,
+        
+        /// Run sybil detection first and penalize the scores of flagged users
+        #[clap(long)]
+        sybil: bool,
+        
+        /// Print the diff as JSON
+        #[clap(long)]
+        json: bool,
+        
+        /// Maximum number of users to show in the text diff
+        #[clap(short, long, default_value = "20")]
+        limit: usize,
+    },
+    
+    /// Move a verified address to another stored user, e.g. to resolve an escalation
+    Reassign {
+        /// Address to move
+        #[clap(short, long)]
+        address: String,
+        
+        /// Chain (ethereum, arbitrum, optimism, blast, solana)
+        #[clap(short, long)]
+        chain: String,
+        
+        /// User ID of the new owner
+        #[clap(short, long)]
+        user_id: String,
+        
+        /// Reason recorded in the address audit trail
+        #[clap(short, long)]
+        reason: String,
+    },
+    
+    /// Rewrite a settings file from the legacy weights layout
+    MigrateConfig {
+        /// Settings TOML file to migrate
+        path: String,
+        
+        /// Write the result here instead of overwriting the input
+        #[clap(short, long)]
+        output: Option<String>,
     },
     
     /// Launch interactive TUI
@@ -103,6 +212,9 @@ async fn main() -> anyhow::Result<()> {
             arb_address,
             op_address,
             sol_address,
+            save,
+            explain,
+            sybil,
         } => {
             info!("Calculating score for user: {}", user_id);
             
@@ -193,62 +305,59 @@ async fn main() -> anyhow::Result<()> {
                 return Ok(());
             }
             
-            // Initialize chain clients
-            let mut evm_clients: Vec<Arc<dyn ChainClient>> = Vec::new();
+            let mut calculator = build_calculator(&rpc_config, settings.clone()).await?;
+            if sybil {
+                let store = if save {
+                    ScoreStore::connect(&settings.database).await?
+                } else {
+                    ScoreStore::connect_read_only(&settings.database).await?
+                };
+                let mut users = store.list_users().await?;
+                users.retain(|stored| stored.id != user.id);
+                users.push(user.clone());
+                let sybil_report = calculator.detect_sybils(&users, &store).await?;
+                info!("{} users flagged in {} sybil clusters", sybil_report.flagged_users(), sybil_report.clusters.len());
+                calculator = calculator.with_sybil_penalties(sybil_report.penalties());
+            }
             
-            // Create EVM clients
-            for chain in &[Chain::Ethereum, Chain::Arbitrum, Chain::Optimism] {
-                if let Some(endpoint) = rpc_config.get_primary_endpoint(chain) {
-                    let config = ChainClientConfig {
-                        rpc_url: endpoint.url.clone(),
-                        chain_id: endpoint.chain_id,
-                        timeout_seconds: rpc_config.timeout_seconds,
-                        max_retries: rpc_config.max_retries,
-                        rate_limit_per_second: 5.0,
-                    };
-                    
-                    match EvmClient::new(config, chain.clone()).await {
-                        Ok(client) => {
-                            info!("Initialized {} client", chain.as_str());
-                            evm_clients.push(Arc::new(client));
-                        }
-                        Err(e) => {
-                            error!("Failed to initialize {} client: {}", chain.as_str(), e);
+            // Calculate score
+            let result = if save {
+                let store = ScoreStore::connect(&settings.database).await?;
+                let mut stored_users = store.list_users().await?;
+                let mut registry = AddressRegistry::load(settings.registry.on_conflict, store.clone(), &mut stored_users).await?;
+                for verified in &user.verified_addresses {
+                    registry.link(&user.id, &verified.chain, &verified.address).await?;
+                }
+                calculator.load_percentiles(&store).await?;
+                calculator.calculate_and_store(&user, &store).await
+            } else {
+                // Rank against the stored cohort without saving; alone, a percentile means nothing
+                match ScoreStore::connect_read_only(&settings.database).await {
+                    Ok(store) => {
+                        if let Err(e) = calculator.load_percentiles(&store).await {
+                            warn!("Failed to load the stored cohort: {}", e);
                         }
                     }
+                    Err(e) => warn!("No stored cohort to rank against: {}", e),
                 }
-            }
-            
-            // Create Solana client
-            let sol_endpoint = rpc_config.get_primary_endpoint(&Chain::Solana)
-                .ok_or_else(|| anyhow::anyhow!("No Solana RPC endpoint configured"))?;
-            
-            let sol_config = ChainClientConfig {
-                rpc_url: sol_endpoint.url.clone(),
-                chain_id: None,
-                timeout_seconds: rpc_config.timeout_seconds,
-                max_retries: rpc_config.max_retries,
-                rate_limit_per_second: 10.0,
+                calculator.calculate_user_score(&user).await
             };
             
-            let solana_client = Arc::new(
-                SolanaClient::new(sol_config)?
-            ) as Arc<dyn ChainClient>;
-            
-            // Create score calculator
-            let calculator = ScoreCalculator::new(
-                evm_clients,
-                solana_client,
-                settings.clone(),
-            );
-            
-            // Calculate score
-            match calculator.calculate_user_score(&user).await {
+            match result {
                 Ok(score) => {
                     println!("\n=== Degen Score Results ===");
                     println!("User ID: {}", user_id);
                     println!("Total Score: {:.2}/100", score.total_score);
                     println!("Tier: {:?}", score.tier);
+                    let cohort = calculator.percentile_cohort();
+                    if cohort.len() > usize::from(cohort.contains(&user.id)) {
+                        println!("Percentile: {:.1} (cohort of {})", score.percentile, cohort.len());
+                    } else {
+                        println!("Percentile: n/a (no stored cohort)");
+                    }
+                    if let (Some(version), Some(fingerprint)) = (&score.algorithm_version, &score.fingerprint) {
+                        println!("Algorithm: v{} ({})", version, fingerprint);
+                    }
                     println!("\nBreakdown:");
                     println!("  Trading: {:.2}", score.breakdown.trading_score);
                     println!("  Gambling: {:.2}", score.breakdown.gambling_score);
@@ -257,6 +366,13 @@ async fn main() -> anyhow::Result<()> {
                     println!("  Longevity: {:.2}", score.breakdown.longevity_score);
                     println!("  Risk Profile: {:.2}", score.breakdown.risk_profile_score);
                     
+                    if let (Some(format), Some(explanation)) = (explain.as_deref(), score.explanation.as_ref()) {
+                        match format {
+                            "json" => println!("\n{}", explanation.to_json()?),
+                            _ => println!("\nExplanation:\n{}", explanation.render_text()),
+                        }
+                    }
+                    
                     if calculator.is_eligible_for_airdrop(&score) {
                         println!("\n✅ Eligible for airdrop!");
                     } else {
@@ -270,6 +386,70 @@ async fn main() -> anyhow::Result<()> {
             }
         }
         
+        Commands::History { user_id, limit } => {
+            let store = ScoreStore::connect(&settings.database).await?;
+            let history = store.get_score_history(&user_id, limit).await?;
+            
+            if history.is_empty() {
+                println!("No stored scores for user {}", user_id);
+                return Ok(());
+            }
+            
+            println!("\n=== Score History: {} ===", user_id);
+            for record in history {
+                println!("{}  {:>6.2}  p{:<5.1}  {:?}",
+                    record.score.calculated_at.format("%Y-%m-%d %H:%M:%S"),
+                    record.score.total_score,
+                    record.score.percentile,
+                    record.score.tier);
+            }
+        }
+        
+        Commands::Airdrop { total_supply, encoding, output, compare } => {
+            let encoding = match encoding.to_lowercase().as_str() {
+                "evm" => LeafEncoding::Evm,
+                "solana" | "sol" => LeafEncoding::Solana,
+                other => return Err(anyhow::anyhow!("Invalid encoding: {}", other)),
+            };
+            
+            let store = ScoreStore::connect(&settings.database).await?;
+            let mut users = store.list_users().await?;
+            // Drops addresses claimed by two users, which the distribution can't pay twice
+            AddressRegistry::load(settings.registry.on_conflict, store.clone(), &mut users).await?;
+            
+            let allocator = AirdropAllocator::new(&settings.scoring)?;
+            let report = allocator.allocate_users(total_supply, &users);
+            let distribution = MerkleDistribution::build(&report.allocations, encoding)?;
+            
+            let json = serde_json::to_string_pretty(&serde_json::json!({
+                "summary": report.summary,
+                "distribution": distribution,
+            }))?;
+            std::fs::write(&output, json)?;
+            
+            let summary = &report.summary;
+            println!("\n=== Airdrop Allocation ({} curve) ===", summary.curve);
+            println!("Pool: {} of {} total supply", summary.pool_size, summary.total_supply);
+            println!("Allocated: {} ({} dust)", summary.total_allocated, summary.dust_distributed);
+            println!("Eligible users: {} ({} ineligible)", summary.eligible_users, summary.ineligible_users);
+            println!("Allocation min/median/max: {} / {} / {}",
+                summary.min_allocation, summary.median_allocation, summary.max_allocation);
+            println!("Total wagering requirement: {}", summary.total_wagering_requirement);
+            println!("\nMerkle root: {}", distribution.root_hex());
+            println!("Claims: {} ({} users without a usable address)",
+                distribution.claims.len(), distribution.skipped_users.len());
+            println!("Written to {}", output);
+            
+            if compare {
+                let candidates: Vec<_> = users.iter().filter_map(AllocationCandidate::from_profile).collect();
+                let curves = AllocationCurve::presets(&settings.scoring.allocation);
+                let comparison = allocator.compare(total_supply, &candidates, &curves);
+                
+                println!("\n=== Curve Comparison ===");
+                print!("{}", comparison.render_text(20));
+            }
+        }
+        
         Commands::Verify { address, chain, signature, message } => {
             let chain_enum = Chain::from_str(&chain)
                 .ok_or_else(|| anyhow::anyhow!("Invalid chain: {}", chain))?;
@@ -308,9 +488,107 @@ async fn main() -> anyhow::Result<()> {
             }
         }
         
-        Commands::Serve { port } => {
-            println!("API server not yet implemented");
-            println!("Would start server on port {}", port);
+        Commands::Serve { host, port } => {
+            let mut api_settings = settings.api.clone();
+            if let Some(host) = host {
+                api_settings.host = host;
+            }
+            if let Some(port) = port {
+                api_settings.port = port;
+            }
+            
+            let calculator = build_calculator(&RpcConfig::default(), settings.clone()).await?;
+            let store = ScoreStore::connect(&settings.database).await?;
+            let state = AppState::with_store(calculator, store).await?;
+            let server = ApiServer::new(api_settings, state);
+            server.run().await?;
+        }
+        
+        Commands::Sybil { json } => {
+            let calculator = build_calculator(&RpcConfig::default(), settings.clone()).await?;
+            let store = ScoreStore::connect(&settings.database).await?;
+            let users = store.list_users().await?;
+            let report = calculator.detect_sybils(&users, &store).await?;
+            
+            if json {
+                println!("{}", report.to_json()?);
+            } else {
+                print!("{}", report.render_text());
+            }
+        }
+        
+        Commands::Rescore { config, save, sybil, json, limit } => {
+            let target = match config {
+                Some(path) => {
+                    let target = Settings::from_file(&path)?;
+                    target.validate().map_err(|e| anyhow::anyhow!(e))?;
+                    target
+                }
+                None => settings.clone(),
+            };
+            
+            let algorithm = ScoringAlgorithm::with_thresholds(
+                target.scoring.weights.clone(),
+                target.scoring.thresholds.clone(),
+            );
+            let store = ScoreStore::connect(&settings.database).await?;
+            let penalties = if sybil {
+                let calculator = build_calculator(&RpcConfig::default(), target.clone()).await?;
+                let users = store.list_users().await?;
+                let sybil_report = calculator.detect_sybils(&users, &store).await?;
+                info!("{} users flagged in {} sybil clusters", sybil_report.flagged_users(), sybil_report.clusters.len());
+                sybil_report.penalties()
+            } else {
+                HashMap::new()
+            };
+            let report = rescore_store(&algorithm, &store, &penalties, save).await?;
+            
+            if json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                print!("{}", report.render_text(limit));
+            }
+            if save {
+                println!("\nSaved {} re-scored users", report.entries.len());
+            }
+        }
+        
+        Commands::Reassign { address, chain, user_id, reason } => {
+            let chain_enum = Chain::from_str(&chain)
+                .ok_or_else(|| anyhow::anyhow!("Invalid chain: {}", chain))?;
+            
+            let store = ScoreStore::connect(&settings.database).await?;
+            let mut stored_users = store.list_users().await?;
+            let mut registry = AddressRegistry::load(settings.registry.on_conflict, store.clone(), &mut stored_users).await?;
+            let mut users: HashMap<_, _> = stored_users.into_iter().map(|user| (user.id.clone(), user)).collect();
+            if !users.contains_key(&user_id) {
+                return Err(anyhow::anyhow!("Unknown user: {}", user_id));
+            }
+            
+            let previous = registry.reassign(&mut users, &chain_enum, &address, &user_id, &reason).await?;
+            for id in previous.iter().chain(std::iter::once(&user_id)) {
+                if let Some(profile) = users.get(id) {
+                    store.upsert_user(profile).await?;
+                }
+            }
+            
+            match previous {
+                Some(owner) if owner != user_id => println!("Moved {} from {} to {}", address, owner, user_id),
+                Some(_) => println!("{} already belongs to {}", address, user_id),
+                None => println!("Assigned {} to {}", address, user_id),
+            }
+        }
+        
+        Commands::MigrateConfig { path, output } => {
+            let contents = std::fs::read_to_string(&path)?;
+            match migrate_config(&contents)? {
+                Some(migrated) => {
+                    let output = output.unwrap_or(path);
+                    std::fs::write(&output, migrated)?;
+                    println!("Migrated scoring weights written to {}", output);
+                }
+                None => println!("{} already uses the current weights layout", path),
+            }
         }
         
         Commands::Tui => {
@@ -320,3 +598,65 @@ async fn main() -> anyhow::Result<()> {
     
     Ok(())
 }
+
+/// Create chain clients for every configured chain and wrap them in a calculator
+async fn build_calculator(rpc_config: &RpcConfig, settings: Settings) -> anyhow::Result<ScoreCalculator> {
+    let mut evm_clients: Vec<Arc<dyn ChainClient>> = Vec::new();
+    
+    // Create EVM clients
+    for chain in &[Chain::Ethereum, Chain::Arbitrum, Chain::Optimism] {
+        if let Some(endpoint) = rpc_config.get_primary_endpoint(chain) {
+            let config = ChainClientConfig {
+                rpc_url: endpoint.url.clone(),
+                chain_id: endpoint.chain_id,
+                timeout_seconds: rpc_config.timeout_seconds,
+                max_retries: rpc_config.max_retries,
+                rate_limit_per_second: 5.0,
+            };
+            
+            match EvmClient::new(config, chain.clone()).await {
+                Ok(client) => {
+                    // Each chain's provider has its own range limit, so each gets its own
+                    // scanner window; checkpoint keys are per chain, so they share a directory
+                    let mut client = client
+                        .with_log_scanner(Arc::new(LogScanner::new(settings.scanner.clone())))
+                        .with_nft_settings(settings.nft.clone())
+                        .with_pricing_settings(&settings.pricing)
+                        .with_portfolio_settings(settings.portfolio.clone());
+                    if let Some(explorer) = ExplorerClient::from_settings(chain, &settings.explorer) {
+                        client = client.with_explorer(explorer);
+                    }
+                    info!("Initialized {} client", chain.as_str());
+                    evm_clients.push(Arc::new(client));
+                }
+                Err(e) => {
+                    error!("Failed to initialize {} client: {}", chain.as_str(), e);
+                }
+            }
+        }
+    }
+    
+    // Create Solana client
+    let sol_endpoint = rpc_config.get_primary_endpoint(&Chain::Solana)
+        .ok_or_else(|| anyhow::anyhow!("No Solana RPC endpoint configured"))?;
+    
+    let sol_config = ChainClientConfig {
+        rpc_url: sol_endpoint.url.clone(),
+        chain_id: None,
+        timeout_seconds: rpc_config.timeout_seconds,
+        max_retries: rpc_config.max_retries,
+        rate_limit_per_second: 10.0,
+    };
+    
+    let solana_client = Arc::new(
+        SolanaClient::new(sol_config)?
+            .with_pricing_settings(&settings.pricing)
+            .with_solana_settings(settings.solana.clone())
+    ) as Arc<dyn ChainClient>;
+    
+    Ok(ScoreCalculator::new(
+        evm_clients,
+        solana_client,
+        settings,
+    ))
+}
@@ -0,0 +1,282 @@
+use std::num::NonZeroU128;
+
+use super::ScoreTier;
+use crate::config::{AllocationCurveKind, AllocationSettings, TierAmounts};
+
+// Curve weights are turned into integers at this precision so splits are exact
+const WEIGHT_PRECISION: f64 = 1_000_000.0;
+
+/// One eligible user as seen by a curve
+#[derive(Debug, Clone)]
+pub struct CurveInput {
+    pub score: f64,
+    pub tier: ScoreTier,
+}
+
+/// Per-user amounts produced by a curve, in input order
+#[derive(Debug, Clone, Default)]
+pub struct CurveOutcome {
+    pub amounts: Vec<u64>,
+    /// Units handed out by largest remainder after flooring the pro-rata shares
+    pub dust: u64,
+}
+
+/// Distribution curve used to turn scores into shares of the airdrop pool
+#[derive(Debug, Clone, PartialEq)]
+pub struct AllocationCurve {
+    pub name: String,
+    pub kind: AllocationCurveKind,
+    pub tier_amounts: TierAmounts,
+    pub max_share_percentage: Option<f64>,
+    pub floor_amount: u64,
+}
+
+impl AllocationCurve {
+    pub fn from_settings(settings: &AllocationSettings) -> Self {
+        Self {
+            name: curve_name(settings.curve).to_string(),
+            kind: settings.curve,
+            tier_amounts: settings.tier_amounts.clone(),
+            max_share_percentage: settings.max_share_percentage,
+            floor_amount: settings.floor_amount,
+        }
+    }
+
+    pub fn linear() -> Self {
+        Self::from_settings(&AllocationSettings::default())
+    }
+
+    /// The configured curve plus every plain curve, for side-by-side comparison
+    pub fn presets(settings: &AllocationSettings) -> Vec<Self> {
+        let mut curves = vec![Self {
+            name: "configured".to_string(),
+            ..Self::from_settings(settings)
+        }];
+
+        for kind in [
+            AllocationCurveKind::Linear,
+            AllocationCurveKind::SquareRoot,
+            AllocationCurveKind::Quadratic,
+            AllocationCurveKind::Tiered,
+        ] {
+            if kind == AllocationCurveKind::Tiered && settings.tier_amounts == TierAmounts::default() {
+                continue;
+            }
+            curves.push(Self::from_settings(&AllocationSettings {
+                curve: kind,
+                tier_amounts: settings.tier_amounts.clone(),
+                ..AllocationSettings::default()
+            }));
+        }
+
+        curves
+    }
+
+    /// Largest amount a single user may receive from this pool
+    pub fn cap(&self, pool: u64) -> Option<u64> {
+        self.max_share_percentage.map(|pct| {
+            let basis_points = (pct * 100.0).round() as u128;
+            ((pool as u128 * basis_points / 10_000) as u64).max(self.floor_amount)
+        })
+    }
+
+    /// Split `pool` across `inputs`. The result never sums to more than `pool`.
+    ///
+    /// Everyone first receives the floor; the remainder is split by the curve's
+    /// weights, and anything above the cap is redistributed to uncapped users.
+    /// Ties in rounding go to the earlier input, so callers should pass a stable order.
+    pub fn distribute(&self, pool: u64, inputs: &[CurveInput]) -> CurveOutcome {
+        let n = inputs.len() as u64;
+        if n == 0 || pool == 0 {
+            return CurveOutcome { amounts: vec![0; inputs.len()], dust: 0 };
+        }
+
+        // A floor the pool cannot cover degrades to an equal split
+        if self.floor_amount.saturating_mul(n) >= pool {
+            let (amounts, dust) = split(pool, &vec![1; inputs.len()]);
+            return CurveOutcome { amounts, dust };
+        }
+
+        let base = self.floor_amount;
+        let remaining = pool - base * n;
+        let cap = self.cap(pool).unwrap_or(u64::MAX);
+
+        let weights: Vec<u128> = match self.kind {
+            AllocationCurveKind::Tiered => {
+                let desired: Vec<u64> = inputs.iter().map(|i| self.tier_amount(&i.tier)).collect();
+
+                // Fixed amounts fit: hand them out as-is and leave the rest of the pool unallocated
+                if desired.iter().sum::<u64>() <= remaining {
+                    let amounts = desired.iter().map(|d| (base + d).min(cap)).collect();
+                    return CurveOutcome { amounts, dust: 0 };
+                }
+                desired.into_iter().map(|d| d as u128).collect()
+            }
+            kind => inputs
+                .iter()
+                .map(|i| {
+                    let score = i.score.max(0.0);
+                    let weight = match kind {
+                        AllocationCurveKind::SquareRoot => score.sqrt(),
+                        AllocationCurveKind::Quadratic => score * score,
+                        _ => score,
+                    };
+                    (weight * WEIGHT_PRECISION).round() as u128
+                })
+                .collect(),
+        };
+
+        // Water-fill: cap anyone over the limit and re-split what is left among the rest
+        let mut capped = vec![false; inputs.len()];
+        loop {
+            let capped_total: u64 = capped.iter().filter(|c| **c).map(|_| cap - base).sum();
+            let active_weights: Vec<u128> = weights
+                .iter()
+                .zip(&capped)
+                .map(|(w, c)| if *c { 0 } else { *w })
+                .collect();
+
+            let (shares, dust) = split(remaining - capped_total, &active_weights);
+            let amounts: Vec<u64> = shares
+                .iter()
+                .zip(&capped)
+                .map(|(share, c)| if *c { cap } else { base + share })
+                .collect();
+
+            let mut newly_capped = false;
+            for (i, amount) in amounts.iter().enumerate() {
+                if !capped[i] && *amount > cap {
+                    capped[i] = true;
+                    newly_capped = true;
+                }
+            }
+
+            if !newly_capped {
+                return CurveOutcome { amounts, dust };
+            }
+        }
+    }
+
+    fn tier_amount(&self, tier: &ScoreTier) -> u64 {
+        let t = &self.tier_amounts;
+        match tier {
+            ScoreTier::Legendary => t.legendary,
+            ScoreTier::Epic => t.epic,
+            ScoreTier::Rare => t.rare,
+            ScoreTier::Uncommon => t.uncommon,
+            ScoreTier::Common => t.common,
+            ScoreTier::Novice => t.novice,
+        }
+    }
+}
+
+fn curve_name(kind: AllocationCurveKind) -> &'static str {
+    match kind {
+        AllocationCurveKind::Linear => "linear",
+        AllocationCurveKind::SquareRoot => "square_root",
+        AllocationCurveKind::Quadratic => "quadratic",
+        AllocationCurveKind::Tiered => "tiered",
+    }
+}
+
+/// Largest-remainder split of `amount` by `weights`; returns the shares and the dust handed out.
+/// With all-zero weights nothing is allocated.
+fn split(amount: u64, weights: &[u128]) -> (Vec<u64>, u64) {
+    let mut shares = vec![0u64; weights.len()];
+    let total_weight = match NonZeroU128::new(weights.iter().sum()) {
+        Some(total) => total,
+        None => return (shares, 0),
+    };
+
+    let mut remainders = Vec::with_capacity(weights.len());
+    for (i, weight) in weights.iter().enumerate() {
+        let share = amount as u128 * weight;
+        shares[i] = (share / total_weight) as u64;
+        remainders.push((share % total_weight, i));
+    }
+
+    let dust = amount - shares.iter().sum::<u64>();
+    remainders.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    for &(_, i) in remainders.iter().take(dust as usize) {
+        shares[i] += 1;
+    }
+
+    (shares, dust)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inputs(scores: &[f64]) -> Vec<CurveInput> {
+        scores
+            .iter()
+            .map(|s| CurveInput { score: *s, tier: ScoreTier::from_score(*s) })
+            .collect()
+    }
+
+    fn curve(kind: AllocationCurveKind) -> AllocationCurve {
+        AllocationCurve::from_settings(&AllocationSettings { curve: kind, ..Default::default() })
+    }
+
+    #[test]
+    fn test_weighting_curves_order_whale_share() {
+        let cohort = inputs(&[25.0, 25.0, 25.0, 100.0]);
+        let whale = |kind| curve(kind).distribute(1_000_000, &cohort).amounts[3];
+
+        let sqrt = whale(AllocationCurveKind::SquareRoot);
+        let linear = whale(AllocationCurveKind::Linear);
+        let quadratic = whale(AllocationCurveKind::Quadratic);
+
+        assert_eq!(sqrt, 400_000);
+        assert_eq!(linear, 571_429);
+        assert!(quadratic > linear);
+
+        for kind in [AllocationCurveKind::Linear, AllocationCurveKind::SquareRoot, AllocationCurveKind::Quadratic] {
+            assert_eq!(curve(kind).distribute(1_000_000, &cohort).amounts.iter().sum::<u64>(), 1_000_000);
+        }
+    }
+
+    #[test]
+    fn test_cap_redistributes_excess() {
+        let mut capped = curve(AllocationCurveKind::Linear);
+        capped.max_share_percentage = Some(40.0);
+
+        let outcome = capped.distribute(1_000, &inputs(&[90.0, 80.0, 20.0, 10.0]));
+        assert_eq!(outcome.amounts[0], 400);
+        assert_eq!(outcome.amounts[1], 400);
+        assert_eq!(outcome.amounts.iter().sum::<u64>(), 1_000);
+        assert!(outcome.amounts[2] > outcome.amounts[3]);
+
+        // When everyone hits the cap the remainder stays unallocated
+        let outcome = capped.distribute(1_000, &inputs(&[50.0, 50.0]));
+        assert_eq!(outcome.amounts, vec![400, 400]);
+    }
+
+    #[test]
+    fn test_floor_is_paid_before_curve() {
+        let mut floored = curve(AllocationCurveKind::Linear);
+        floored.floor_amount = 100;
+
+        let outcome = floored.distribute(1_000, &inputs(&[20.0, 60.0]));
+        assert_eq!(outcome.amounts, vec![300, 700]);
+
+        // Pool too small to honour the floor: equal split instead
+        let outcome = floored.distribute(150, &inputs(&[20.0, 60.0]));
+        assert_eq!(outcome.amounts, vec![75, 75]);
+    }
+
+    #[test]
+    fn test_tiered_fixed_and_scaled() {
+        let mut tiered = curve(AllocationCurveKind::Tiered);
+        tiered.tier_amounts = TierAmounts { legendary: 500, rare: 200, common: 50, ..Default::default() };
+        let cohort = inputs(&[95.0, 65.0, 25.0]);
+
+        let outcome = tiered.distribute(10_000, &cohort);
+        assert_eq!(outcome.amounts, vec![500, 200, 50]);
+
+        // Not enough for everyone: scale the fixed amounts down pro-rata
+        let outcome = tiered.distribute(375, &cohort);
+        assert_eq!(outcome.amounts, vec![250, 100, 25]);
+    }
+}
@@ -3,9 +3,8 @@ use std::sync::{Arc, RwLock};
 use std::time::{Duration, Instant};
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
-use rust_decimal::Decimal;
 
-use crate::models::{DegenMetrics, ChainMetrics};
+use crate::models::{DegenMetrics, ChainMetrics, TokenBalance};
 
 /// Cache entry with TTL
 #[derive(Debug, Clone)]
@@ -35,8 +34,8 @@ pub struct ScoreCache {
     // Address -> ChainMetrics cache
     metrics_cache: Arc<RwLock<HashMap<String, CacheEntry<ChainMetrics>>>>,
     
-    // Address -> token balance cache (token_address -> balance)
-    balance_cache: Arc<RwLock<HashMap<String, CacheEntry<HashMap<String, Decimal>>>>>,
+    // Address -> token balances with their metadata
+    balance_cache: Arc<RwLock<HashMap<String, CacheEntry<Vec<TokenBalance>>>>>,
     
     // Address -> protocol interaction cache (protocol -> interaction_count)
     protocol_cache: Arc<RwLock<HashMap<String, CacheEntry<HashMap<String, u32>>>>>,
@@ -89,7 +88,7 @@ impl ScoreCache {
     }
     
     /// Get cached token balances for an address
-    pub fn get_balances(&self, address: &str) -> Option<HashMap<String, Decimal>> {
+    pub fn get_balances(&self, address: &str) -> Option<Vec<TokenBalance>> {
         let cache = self.balance_cache.read().ok()?;
         let entry = cache.get(address)?;
         
@@ -101,7 +100,7 @@ impl ScoreCache {
     }
     
     /// Cache token balances for an address
-    pub fn set_balances(&self, address: String, balances: HashMap<String, Decimal>) {
+    pub fn set_balances(&self, address: String, balances: Vec<TokenBalance>) {
         if let Ok(mut cache) = self.balance_cache.write() {
             cache.insert(address, CacheEntry::new(balances, self.balance_ttl));
         }
@@ -200,6 +199,11 @@ impl CacheKey {
     pub fn token_interaction(chain: &str, address: &str, token: &str) -> String {
         format!("token_interaction:{}:{}:{}", chain, address.to_lowercase(), token.to_lowercase())
     }
+    
+    /// Contracts an address has sent transactions to, with how many
+    pub fn contract_calls(chain: &str, address: &str) -> String {
+        format!("contract_calls:{}:{}", chain, address.to_lowercase())
+    }
 }
 
 #[cfg(test)]
@@ -23,6 +23,16 @@ impl ProtocolAddresses {
     pub const JUPITER_V4: &'static str = "JUP4Fb2cqiRUcaTHdrPC8h2gNsA2ETXiPDD33WcGuJB";
     pub const JUPITER_V6: &'static str = "JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4";
     
+    // Solana perpetuals venues
+    pub const DRIFT_V2: &'static str = "dRiftyHA39MWEi3m9aunc5MzRF1JYuBsbn6VPcn33UH";
+    pub const MANGO_V4: &'static str = "4MangoMjqJ2firMokCjjGgoK8d4MXcrgL7XJaL3w6fVg";
+    pub const ZETA_MARKETS: &'static str = "ZETAxsqBRek56DhiGXrn75yj2NHU3aYUnxvHXpkf3aD";
+    pub const JUPITER_PERPS: &'static str = "PERPHjGBqRHArX4DySjwM6UJHiR3sWAatqfdBS2qQJu";
+    
+    // Solana casinos and casino tokens: none built in yet
+    // TODO: Add casino program IDs and token mints once verified on mainnet; until then
+    // they are configured under [[solana.protocols]] and [[solana.casino_tokens]]
+    
     // Hyperliquid on Arbitrum
     pub const HYPERLIQUID_BRIDGE_ARB: &'static str = "0x2Df1c51E09aECF9cacB7bc98cB1742757f163dF7";
     
@@ -74,6 +84,7 @@ impl ProtocolAddresses {
     
     // OpenSea
     pub const OPENSEA_SEAPORT: &'static str = "0x00000000000000ADc04C56Bf30aC9d3c0aAF14dC"; // Seaport 1.5
+    pub const OPENSEA_SEAPORT_1_6: &'static str = "0x0000000000000068F116a894984e2DB1123eB395"; // Seaport 1.6
     pub const OPENSEA_WYVERN_EXCHANGE: &'static str = "0x7Be8076f4EA4A4AD08075C2508e481d6C946D12b"; // Legacy Wyvern
     
     // Additional Major DeFi
@@ -85,6 +96,13 @@ impl ProtocolAddresses {
     pub const BLUR_EXCHANGE: &'static str = "0x000000000000Ad05Ccc4F10045630fb830B95127"; // Blur marketplace
     pub const X2Y2_EXCHANGE: &'static str = "0x74312363e45DCaBA76c59ec49a7Aa8A65a67EeD3"; // X2Y2 marketplace
     pub const LOOKSRARE_EXCHANGE: &'static str = "0x59728544B08AB483533076417FbBB2fD0B17CE3a"; // LooksRare
+    pub const BLUR_POOL: &'static str = "0x0000000000A39bb272e79075ade125fd351887Ac"; // ETH deposits used as Blur bids
+    
+    // Wrapped ETH
+    pub const WETH_ETH: &'static str = "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2";
+    pub const WETH_ARB: &'static str = "0x82aF49447D8a07e3bd95BD0d56f35241523fBab1";
+    pub const WETH_OPT: &'static str = "0x4200000000000000000000000000000000000006";
+    pub const WETH_BLAST: &'static str = "0x4300000000000000000000000000000000000004";
     
     // DeFi Lending Protocols
     pub const AAVE_V2_POOL_ETH: &'static str = "0x7d2768dE32b0b80b7a3454c06BdAc94A69DDc7A9";
@@ -101,9 +119,30 @@ impl ProtocolAddresses {
     pub const HOP_BRIDGE_ETH: &'static str = "0x3666f603Cc164936C1b87e207F36BEBa4AC5f18a";
     pub const ACROSS_BRIDGE_ETH: &'static str = "0x4D9079Bb4165aeb4084c526a32695dCfd2F77381";
     
+    // Chainlink ETH/USD price feeds (8 decimals)
+    pub const CHAINLINK_ETH_USD_ETH: &'static str = "0x5f4eC3Df9cbd43714FE2740f5E3616155c5b8419";
+    pub const CHAINLINK_ETH_USD_ARB: &'static str = "0x639Fe6ab55C921f74e7fac1ee960C0B6293ba612";
+    pub const CHAINLINK_ETH_USD_OPT: &'static str = "0x13e3Ee699D1909E989722E753853AE30b17e08c5";
+    
+    // Multicall3, deployed at the same address on every EVM chain
+    pub const MULTICALL3: &'static str = "0xcA11bde05977b3631167028862bE2a173976CA11";
+    
+    // Native USDC on Arbitrum
+    pub const USDC_ARB: &'static str = "0xaf88d065e77c8cC2239327C5EDb3A432268e5831";
+    
+    // Solana native and wrapped SOL, and the Pyth SOL/USD price update account
+    pub const SOL_NATIVE: &'static str = "11111111111111111111111111111111";
+    pub const SOL_WRAPPED: &'static str = "So11111111111111111111111111111111111111112";
+    pub const PYTH_SOL_USD: &'static str = "7UVimffxr9ow1uXYxsr4LHAcV58mLzhmwaeKvJ1pjLiE";
+    
+    // Metaplex Token Metadata program, owner of Solana NFT metadata and edition accounts
+    pub const METAPLEX_TOKEN_METADATA: &'static str = "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s";
+    
     // Uniswap V2/V3 routers (all chains)
     pub const UNISWAP_V2_ROUTER: &'static str = "0x7a250d5630B4cF539739dF2C5dAcb4c659F2488D";
     pub const UNISWAP_V3_ROUTER: &'static str = "0xE592427A0AEce92De3Edee1F18E0157C05861564";
+    // Same address on Ethereum, Arbitrum and Optimism
+    pub const UNISWAP_V3_FACTORY: &'static str = "0x1F98431c8aD98523631AE4a59f267346ea31F984";
     pub const UNISWAP_UNIVERSAL_ROUTER: &'static str = "0x3fC91A3afd70395Cd496C647d5a6CC9D4B2b7FAD";
     
     // Sushiswap
@@ -125,6 +164,17 @@ pub struct EVMTransaction {
     pub block_number: u64,
     pub input_data: Vec<u8>,
     pub status: bool,
+    /// L1 data fee charged on top of execution gas (OP Stack chains). Arbitrum bills
+    /// L1 data as extra L2 gas, so there it is already part of `gas_used`.
+    #[serde(default)]
+    pub l1_fee: U256,
+}
+
+impl EVMTransaction {
+    /// Total fee paid by the sender, in wei
+    pub fn fee_paid(&self) -> U256 {
+        self.gas_used.saturating_mul(self.gas_price).saturating_add(self.l1_fee)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -138,6 +188,33 @@ pub struct EVMTokenTransfer {
     pub timestamp: DateTime<Utc>,
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NftStandard {
+    #[default]
+    Erc721,
+    Erc1155,
+    /// Solana SPL token with Metaplex metadata and a master edition
+    Metaplex,
+}
+
+/// One token moved by an ERC-721 or ERC-1155 transfer; `amount` is always one for ERC-721
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EVMNftTransfer {
+    pub token_address: String,
+    pub token_id: U256,
+    #[serde(default)]
+    pub standard: NftStandard,
+    pub from: String,
+    pub to: String,
+    pub amount: U256,
+    pub tx_hash: String,
+    pub block_number: u64,
+    pub log_index: u64,
+    /// Unknown for transfers read from logs
+    pub timestamp: Option<DateTime<Utc>>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SolanaTransaction {
     pub signature: String,
@@ -180,6 +257,15 @@ pub struct NFTBalance {
     pub token_id: String,
     pub token_uri: Option<String>,
     pub metadata: Option<HashMap<String, serde_json::Value>>,
+    #[serde(default)]
+    pub standard: NftStandard,
+    /// Copies held; always one for ERC-721
+    #[serde(default = "one")]
+    pub balance: U256,
+}
+
+fn one() -> U256 {
+    U256::one()
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -239,6 +325,31 @@ impl KnownTokens {
         ].iter().cloned().collect()
     }
     
+    pub fn stablecoins_by_chain(chain: &Chain) -> HashMap<&'static str, &'static str> {
+        match chain {
+            Chain::Ethereum => Self::stablecoins(),
+            Chain::Arbitrum => [
+                (ProtocolAddresses::USDC_ARB, "USDC"),
+                ("0xFF970A61A04b1cA14834A43f5dE4533eBDDB5CC8", "USDC.e"),
+                ("0xFd086bC7CD5C481DCC9C85ebE478A1C0b69FCbb9", "USDT"),
+                ("0xDA10009cBd5D07dd0CeCc66161FC93D7c9000da1", "DAI"),
+            ].iter().cloned().collect(),
+            Chain::Optimism => [
+                ("0x0b2C639c533813f4Aa9D7837CAf62653d097Ff85", "USDC"),
+                ("0x7F5c764cBc14f9669B88837ca1490cCa17c31607", "USDC.e"),
+                ("0x94b008aA00579c1307B0EF2c499aD98a8ce58e58", "USDT"),
+                ("0xDA10009cBd5D07dd0CeCc66161FC93D7c9000da1", "DAI"),
+            ].iter().cloned().collect(),
+            Chain::Blast => [
+                ("0x4300000000000000000000000000000000000003", "USDB"),
+            ].iter().cloned().collect(),
+            Chain::Solana => [
+                ("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v", "USDC"),
+                ("Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB", "USDT"),
+            ].iter().cloned().collect(),
+        }
+    }
+    
     pub fn casino_tokens() -> HashMap<&'static str, &'static str> {
         [
             // Ethereum
@@ -266,13 +377,70 @@ impl KnownTokens {
         }
     }
     
-    pub fn memecoins() -> Vec<&'static str> {
-        vec![
-            "PEPE", "DOGE", "SHIB", "FLOKI", "ELON", "SAFEMOON",
-            "BABYDOGE", "AKITA", "KISHU", "HOKK", "FEG", "PIG",
-            "WOJAK", "TURBO", "LADYS", "BOB", "PSYOP", "MONG",
-            "JEFF", "BEN", "AIDOGE", "SPONGE", "WAGMI", "COPE"
-        ]
+    pub fn bluechips_by_chain(chain: &Chain) -> HashMap<&'static str, &'static str> {
+        match chain {
+            Chain::Ethereum => [
+                (ProtocolAddresses::WETH_ETH, "WETH"),
+                ("0x2260FAC5E5542a773Aa44fBCfeDf7C193bc2C599", "WBTC"),
+                ("0xcbB7C0000aB88B473b1f5aFd9ef808440eed33Bf", "cbBTC"),
+                ("0x18084fbA666a33d37592fA2633fD49a74DD93a88", "tBTC"),
+                ("0xae7ab96520DE3A18E5e111B5EaAb095312D7fE84", "stETH"),
+                ("0x7f39C581F595B53c5cb19bD0b3f8dA6c935E2Ca0", "wstETH"),
+                ("0xae78736Cd615f374D3085123A210448E74Fc6393", "rETH"),
+                ("0xBe9895146f7AF43049ca1c1AE358B0541Ea49704", "cbETH"),
+                ("0xCd5fE23C85820F7B72D0926FC9b05b43E359b7ee", "weETH"),
+            ].iter().cloned().collect(),
+            Chain::Arbitrum => [
+                (ProtocolAddresses::WETH_ARB, "WETH"),
+                ("0x2f2a2543B76A4166549F7aaB2e75Bef0aefC5B0f", "WBTC"),
+                ("0x5979D7b546E38E414F7E9822514be443A4800529", "wstETH"),
+                ("0xEC70Dcb4A1EFa46b8F2D97C310C9c4790ba5ffA8", "rETH"),
+                ("0x35751007a407ca6FEFfE80b3cB397736D2cf4dbe", "weETH"),
+            ].iter().cloned().collect(),
+            Chain::Optimism => [
+                (ProtocolAddresses::WETH_OPT, "WETH"),
+                ("0x68f180fcCe6836688e9084f035309E29Bf0A2095", "WBTC"),
+                ("0x1F32b1c2345538c0c6f582fCB022739c4A194Ebb", "wstETH"),
+                ("0x9Bcef72be871e61ED4fBbc7630889beE758eb81D", "rETH"),
+            ].iter().cloned().collect(),
+            Chain::Blast => [
+                (ProtocolAddresses::WETH_BLAST, "WETH"),
+            ].iter().cloned().collect(),
+            Chain::Solana => HashMap::new(),
+        }
+    }
+    
+    pub fn governance_tokens_by_chain(chain: &Chain) -> HashMap<&'static str, &'static str> {
+        match chain {
+            Chain::Ethereum => [
+                ("0x1f9840a85d5aF5bf1D1762F925BDADdC4201F984", "UNI"),
+                ("0x7Fc66500c84A76Ad7e9c93437bFc5Ac33E2DDaE9", "AAVE"),
+                ("0xc00e94Cb662C3520282E6f5717214004A7f26888", "COMP"),
+                ("0x9f8F72aA9304c8B593d555F12eF6589cC3A579A2", "MKR"),
+                ("0xD533a949740bb3306d119CC777fa900bA034cd52", "CRV"),
+                ("0x5A98FcBEA516Cf06857215779Fd812CA3beF1B32", "LDO"),
+                ("0x92D6C1e31e14520e676a687F0a93788B716BEff5", "DYDX"),
+                ("0x6B3595068778DD592e39A122f4f5a5cF09C90fE2", "SUSHI"),
+                ("0xba100000625a3754423978a60c9317c58a424e3D", "BAL"),
+                ("0xC011a73ee8576Fb46F5E1c5751cA3B9Fe0af2a6F", "SNX"),
+                ("0x0bc529c00C6401aEF6D220BE8C6Ea1667F6Ad93e", "YFI"),
+                ("0x111111111117dC0aa78b770fA6A738034120C302", "1INCH"),
+                ("0xC18360217D8F7Ab5e7c516566761Ea12Ce7F9D72", "ENS"),
+                ("0x5283D291DBCF85356A21bA090E6db59121208b44", "BLUR"),
+                ("0x808507121B80c02388fAd14726482e061B8da827", "PENDLE"),
+            ].iter().cloned().collect(),
+            Chain::Arbitrum => [
+                ("0x912CE59144191C1204E64559FE8253a0e49E6548", "ARB"),
+                ("0xfc5A1A6EB076a2C7aD06eD22C90d7E710E35ad0a", "GMX"),
+                ("0x18c11FD286C5EC11c3b683Caa813B77f5163A122", "GNS"),
+                ("0x0c880f6761F1af8d9Aa9C466984b80DAb9a8c9e8", "PENDLE"),
+            ].iter().cloned().collect(),
+            Chain::Optimism => [
+                ("0x4200000000000000000000000000000000000042", "OP"),
+                ("0x8700dAec35aF8Ff88c16BdF0418774CB3D7599B4", "SNX"),
+            ].iter().cloned().collect(),
+            _ => HashMap::new(),
+        }
     }
     
     pub fn memecoin_addresses() -> HashMap<&'static str, &'static str> {
@@ -297,19 +465,33 @@ pub struct EventSignatures;
 impl EventSignatures {
     pub const ERC20_TRANSFER: &'static str = "Transfer(address,address,uint256)";
     pub const ERC721_TRANSFER: &'static str = "Transfer(address,address,uint256)";
+    pub const ERC1155_TRANSFER_SINGLE: &'static str = "TransferSingle(address,address,address,uint256,uint256)";
+    pub const ERC1155_TRANSFER_BATCH: &'static str = "TransferBatch(address,address,address,uint256[],uint256[])";
     pub const UNISWAP_SWAP: &'static str = "Swap(address,uint256,uint256,uint256,uint256,address)";
     pub const GMX_INCREASE_POSITION: &'static str = "IncreasePosition(bytes32,address,address,address,uint256,uint256,bool,uint256,uint256)";
     pub const GMX_DECREASE_POSITION: &'static str = "DecreasePosition(bytes32,address,address,address,uint256,uint256,bool,uint256,uint256)";
     
-    // Aave events
-    pub const AAVE_DEPOSIT: &'static str = "Deposit(address,address,address,uint256,address,uint16)";
+    // Perpetual Protocol v2 ClearingHouse; the trader is indexed
+    pub const PERP_POSITION_CHANGED: &'static str = "PositionChanged(address,address,int256,int256,uint256,int256,int256,uint256)";
+    
+    // Aave events; `onBehalfOf` is the second indexed topic in both versions
+    pub const AAVE_DEPOSIT: &'static str = "Deposit(address,address,address,uint256,uint16)";
     pub const AAVE_BORROW: &'static str = "Borrow(address,address,address,uint256,uint256,uint256,uint16)";
+    pub const AAVE_V3_SUPPLY: &'static str = "Supply(address,address,address,uint256,uint16)";
+    pub const AAVE_V3_BORROW: &'static str = "Borrow(address,address,address,uint256,uint8,uint256,uint16)";
     pub const AAVE_LIQUIDATION: &'static str = "LiquidationCall(address,address,address,uint256,uint256,address,bool)";
     
     // Compound events
     pub const COMPOUND_MINT: &'static str = "Mint(address,uint256,uint256)";
     pub const COMPOUND_BORROW: &'static str = "Borrow(address,uint256,uint256,uint256)";
     
+    // NFT marketplace sales
+    pub const SEAPORT_ORDER_FULFILLED: &'static str = "OrderFulfilled(bytes32,address,address,address,(uint8,address,uint256,uint256)[],(uint8,address,uint256,uint256,address)[])";
+    pub const BLUR_ORDERS_MATCHED: &'static str = "OrdersMatched(address,address,(address,uint8,address,address,uint256,uint256,address,uint256,uint256,uint256,(uint16,address)[],uint256,bytes),bytes32,(address,uint8,address,address,uint256,uint256,address,uint256,uint256,uint256,(uint16,address)[],uint256,bytes),bytes32)";
+    pub const LOOKSRARE_TAKER_BID: &'static str = "TakerBid(bytes32,uint256,address,address,address,address,address,uint256,uint256,uint256)";
+    pub const LOOKSRARE_TAKER_ASK: &'static str = "TakerAsk(bytes32,uint256,address,address,address,address,address,uint256,uint256,uint256)";
+    pub const X2Y2_INVENTORY: &'static str = "EvInventory(bytes32,address,address,uint256,uint256,uint256,uint256,uint256,address,bytes,(uint256,bytes),(uint8,uint256,uint256,uint256,bytes32,address,bytes,uint256,uint256,uint256,(uint256,address)[]))";
+    
     // Casino events
     pub const ROLLBIT_BET: &'static str = "BetPlaced(address,uint256,uint256)";
     pub const SHUFFLE_DEPOSIT: &'static str = "Deposit(address,uint256)";
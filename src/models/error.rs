@@ -38,8 +38,17 @@ pub enum DegenScoreError {
     #[error("Circuit breaker is open: {0}")]
     CircuitBreakerOpen(String),
     
+    #[error("No USD price for {token} on {chain}")]
+    PriceUnavailable { chain: String, token: String },
+    
     #[error("Cache error: {0}")]
     CacheError(String),
+    
+    #[error("Address {address} is already linked to another user ({resolution})")]
+    AddressConflict { address: String, resolution: String },
+    
+    #[error("Address {address} is linked to another user; the claim is queued for review")]
+    AddressEscalated { address: String },
 }
 
 pub type Result<T> = std::result::Result<T, DegenScoreError>;
\ No newline at end of file
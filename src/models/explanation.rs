@@ -0,0 +1,168 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+use super::{Result, ScoreBreakdown};
+
+/// The `ScoreBreakdown` category a sub-component counts towards
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScoreCategory {
+    Trading,
+    Gambling,
+    DefiActivity,
+    NftPortfolio,
+    Longevity,
+    RiskProfile,
+}
+
+impl ScoreCategory {
+    pub const ALL: [ScoreCategory; 6] = [
+        ScoreCategory::Trading,
+        ScoreCategory::Gambling,
+        ScoreCategory::DefiActivity,
+        ScoreCategory::NftPortfolio,
+        ScoreCategory::Longevity,
+        ScoreCategory::RiskProfile,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ScoreCategory::Trading => "Trading",
+            ScoreCategory::Gambling => "Gambling",
+            ScoreCategory::DefiActivity => "DeFi Activity",
+            ScoreCategory::NftPortfolio => "NFT Portfolio",
+            ScoreCategory::Longevity => "Longevity",
+            ScoreCategory::RiskProfile => "Risk Profile",
+        }
+    }
+}
+
+/// How one sub-component turned a raw metric into points
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ComponentExplanation {
+    /// Matches the `ScoringWeights` field, e.g. `trading_volume`
+    pub component: String,
+    pub category: ScoreCategory,
+    /// Metric value as fed to the algorithm (USD, counts, days or a share)
+    pub raw_input: f64,
+    /// Raw value at which the component earns its full weight
+    pub cap: f64,
+    /// Normalized 0-1 factor derived from `raw_input`
+    pub factor: f64,
+    pub weight: f64,
+    pub points: f64,
+    /// Points still available before the component is maxed out
+    pub headroom: f64,
+}
+
+impl ComponentExplanation {
+    pub fn new(component: &str, category: ScoreCategory, raw_input: f64, cap: f64, factor: f64, weight: f64) -> Self {
+        let points = factor * weight;
+        Self {
+            component: component.to_string(),
+            category,
+            raw_input,
+            cap,
+            factor,
+            weight,
+            points,
+            headroom: (weight - points).max(0.0),
+        }
+    }
+}
+
+/// Per-metric contributions behind a `DegenScore`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ScoreExplanation {
+    pub total_score: f64,
+    pub components: Vec<ComponentExplanation>,
+}
+
+impl ScoreExplanation {
+    pub fn new(components: Vec<ComponentExplanation>) -> Self {
+        let mut explanation = Self { total_score: 0.0, components };
+        explanation.total_score = ScoreCategory::ALL.iter().map(|c| explanation.category_points(*c)).sum();
+        explanation
+    }
+
+    pub fn component(&self, name: &str) -> Option<&ComponentExplanation> {
+        self.components.iter().find(|c| c.component == name)
+    }
+
+    pub fn category_points(&self, category: ScoreCategory) -> f64 {
+        self.components.iter().filter(|c| c.category == category).map(|c| c.points).sum()
+    }
+
+    pub fn total_headroom(&self) -> f64 {
+        self.components.iter().map(|c| c.headroom).sum()
+    }
+
+    /// Scale every component's points, e.g. for a sybil penalty; headroom stays measured
+    /// against the full weight
+    pub fn scale(&mut self, multiplier: f64) {
+        for c in &mut self.components {
+            c.points *= multiplier;
+            c.headroom = (c.weight - c.points).max(0.0);
+        }
+        self.total_score *= multiplier;
+    }
+
+    /// Category totals, summed from the components
+    pub fn breakdown(&self) -> ScoreBreakdown {
+        ScoreBreakdown {
+            trading_score: self.category_points(ScoreCategory::Trading),
+            gambling_score: self.category_points(ScoreCategory::Gambling),
+            defi_activity_score: self.category_points(ScoreCategory::DefiActivity),
+            nft_portfolio_score: self.category_points(ScoreCategory::NftPortfolio),
+            longevity_score: self.category_points(ScoreCategory::Longevity),
+            risk_profile_score: self.category_points(ScoreCategory::RiskProfile),
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Plain-text table grouped by category
+    pub fn render_text(&self) -> String {
+        let mut out = String::new();
+
+        let _ = writeln!(out, "{:<22} {:>14} {:>14} {:>7} {:>7} {:>7} {:>9}",
+            "component", "raw", "cap", "factor", "weight", "points", "headroom");
+        for category in ScoreCategory::ALL {
+            let _ = writeln!(out, "{} ({:.2})", category.label(), self.category_points(category));
+            for c in self.components.iter().filter(|c| c.category == category) {
+                let _ = writeln!(out, "  {:<20} {:>14.2} {:>14.2} {:>7.3} {:>7.2} {:>7.2} {:>9.2}",
+                    c.component, c.raw_input, c.cap, c.factor, c.weight, c.points, c.headroom);
+            }
+        }
+        let _ = writeln!(out, "Total: {:.2} (headroom {:.2})", self.total_score, self.total_headroom());
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explanation_sums_and_renders() {
+        let explanation = ScoreExplanation::new(vec![
+            ComponentExplanation::new("trading_count", ScoreCategory::Trading, 50.0, 100.0, 0.5, 10.0),
+            ComponentExplanation::new("wallet_age", ScoreCategory::Longevity, 1825.0, 1825.0, 1.0, 10.0),
+        ]);
+
+        assert_eq!(explanation.total_score, 15.0);
+        assert_eq!(explanation.total_headroom(), 5.0);
+        assert_eq!(explanation.breakdown().trading_score, 5.0);
+        assert_eq!(explanation.component("wallet_age").unwrap().headroom, 0.0);
+
+        let text = explanation.render_text();
+        assert!(text.contains("Longevity (10.00)"));
+        assert!(text.lines().any(|l| l.trim_start().starts_with("trading_count")));
+
+        let parsed: ScoreExplanation = serde_json::from_str(&explanation.to_json().unwrap()).unwrap();
+        assert_eq!(parsed, explanation);
+    }
+}
@@ -1,17 +1,22 @@
 use serde::{Deserialize, Serialize};
 use rust_decimal::Decimal;
-use chrono::{DateTime, Utc};
-use std::collections::HashMap;
+use chrono::{DateTime, Datelike, Utc};
+use rust_decimal::prelude::ToPrimitive;
+use std::collections::{BTreeSet, HashMap};
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub struct DegenMetrics {
     // Trading metrics
     pub gmx_volume_usd: Decimal,
     pub gmx_trades: u32,
     pub jupiter_swaps: u32,
+    #[serde(default)]
+    pub jupiter_swaps_truncated: bool, // swaps only counted over the latest parsed transactions
     pub bridges_used: u32,  // Number of bridge interactions (Hyperliquid, Hop, Across, etc.)
     pub hyperliquid_volume_usd: Decimal, // Total USDC deposited to Hyperliquid
     pub total_perp_volume_usd: Decimal,
+    #[serde(default)]
+    pub perp_collateral_flow_usd: Decimal, // collateral moved in and out of perp venues whose fills aren't decoded
     
     // Gambling metrics
     pub casinos_used: u32,
@@ -60,6 +65,33 @@ pub struct DegenMetrics {
     pub protocol_interaction_counts: HashMap<String, u32>, // protocol_name -> interaction_count
     pub protocol_volume_usd: HashMap<String, Decimal>, // protocol_name -> total_volume_usd
     pub protocol_first_use: HashMap<String, DateTime<Utc>>, // protocol_name -> first_interaction_timestamp
+    
+    // Sets behind the distinct counts above, merged by union so activity seen on
+    // several wallets or chains is only counted once
+    #[serde(default)]
+    pub protocols_used: BTreeSet<String>,
+    #[serde(default)]
+    pub tokens_traded: BTreeSet<String>, // lowercase token addresses / mints
+    #[serde(default)]
+    pub nft_collections: BTreeSet<String>,
+    #[serde(default)]
+    pub casino_platforms: BTreeSet<String>,
+    #[serde(default)]
+    pub active_day_numbers: BTreeSet<i64>, // days since the Unix epoch
+    #[serde(default)]
+    pub volatile_tokens: BTreeSet<String>, // lowercase addresses of volatile positions held
+    #[serde(default)]
+    pub nfts_held: BTreeSet<String>, // contract:token_id
+    
+    // Balances behind the portfolio percentages
+    #[serde(default)]
+    pub stablecoin_balance_usd: Decimal,
+    #[serde(default)]
+    pub position_values_usd: HashMap<String, Decimal>, // token -> USD value held
+    
+    // chain:address of every wallet snapshot merged into these metrics
+    #[serde(default)]
+    pub sources: BTreeSet<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -70,6 +102,26 @@ pub struct ChainMetrics {
     pub last_updated: DateTime<Utc>,
 }
 
+impl ChainMetrics {
+    /// The wallet these metrics were fetched for, as recorded in `DegenMetrics::sources`
+    pub fn source_key(&self) -> String {
+        // EVM addresses are case-insensitive, base58 ones are not
+        let address = if self.address.starts_with("0x") {
+            self.address.to_lowercase()
+        } else {
+            self.address.clone()
+        };
+        format!("{}:{}", self.chain.to_lowercase(), address)
+    }
+
+    /// Metrics tagged with their source, ready to be merged into a user's aggregate
+    pub fn sourced_metrics(&self) -> DegenMetrics {
+        let mut metrics = self.metrics.clone();
+        metrics.sources.insert(self.source_key());
+        metrics
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProtocolInteraction {
     pub protocol_name: String,
@@ -101,7 +153,8 @@ pub struct TokenHolding {
     pub token_name: String,
     pub balance: Decimal,
     pub decimals: u8,
-    pub value_usd: Decimal,
+    /// `None` when no price source covers the token
+    pub value_usd: Option<Decimal>,
     pub token_type: TokenType,
     pub chain: String,
 }
@@ -128,97 +181,395 @@ pub struct NFTHolding {
     pub acquisition_date: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct TransactionSummary {
     pub total_count: u32,
     pub first_tx: Option<DateTime<Utc>>,
     pub last_tx: Option<DateTime<Utc>>,
     pub active_days: u32,
     pub average_tx_per_day: f64,
+    /// Fees paid, in the chain's native token
     pub gas_spent: Decimal,
+    #[serde(default)]
+    pub active_months: u32,
+    /// First and last transaction of the busiest 30-day stretch
+    #[serde(default)]
+    pub most_active_period: Option<(DateTime<Utc>, DateTime<Utc>)>,
+}
+
+impl TransactionSummary {
+    /// Summarize transactions given as (timestamp, fee paid) pairs, keeping those within
+    /// `start..=end`. The average is taken over the days from first to last transaction.
+    pub fn from_activity(
+        activity: &[(DateTime<Utc>, Decimal)],
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+    ) -> Self {
+        let mut activity: Vec<(DateTime<Utc>, Decimal)> = activity
+            .iter()
+            .filter(|(at, _)| start.is_none_or(|start| *at >= start) && end.is_none_or(|end| *at <= end))
+            .copied()
+            .collect();
+        activity.sort();
+        
+        let (first_tx, last_tx) = match (activity.first(), activity.last()) {
+            (Some(first), Some(last)) => (first.0, last.0),
+            _ => return Self::default(),
+        };
+        
+        let days: BTreeSet<i64> = activity.iter().map(|(at, _)| at.timestamp().div_euclid(86400)).collect();
+        let months: BTreeSet<(i32, u32)> = activity.iter().map(|(at, _)| (at.year(), at.month())).collect();
+        let span_days = (last_tx.date_naive() - first_tx.date_naive()).num_days() + 1;
+        
+        // Widest run of transactions fitting in 30 days, earliest on ties
+        let mut most_active = (0, 0);
+        let mut window_start = 0;
+        for end in 0..activity.len() {
+            while activity[end].0 - activity[window_start].0 > chrono::Duration::days(30) {
+                window_start += 1;
+            }
+            if end - window_start > most_active.1 - most_active.0 {
+                most_active = (window_start, end);
+            }
+        }
+        
+        Self {
+            total_count: activity.len() as u32,
+            first_tx: Some(first_tx),
+            last_tx: Some(last_tx),
+            active_days: days.len() as u32,
+            average_tx_per_day: activity.len() as f64 / span_days as f64,
+            gas_spent: activity.iter().map(|(_, fee)| *fee).sum(),
+            active_months: months.len() as u32,
+            most_active_period: Some((activity[most_active.0].0, activity[most_active.1].0)),
+        }
+    }
 }
 
 impl DegenMetrics {
+    /// Fold another wallet's metrics into these.
+    ///
+    /// Distinct counts come from the union of their underlying sets, so they never
+    /// double count, and portfolio percentages are recomputed from the merged
+    /// balances. A snapshot whose sources were all merged in already only
+    /// contributes its sets, which makes merging idempotent as well as commutative.
     pub fn merge(&mut self, other: &DegenMetrics) {
+        let already_merged = !other.sources.is_empty() && other.sources.is_subset(&self.sources);
+        if !already_merged {
+            self.add_totals(other);
+        }
+        
+        self.wallet_age_days = self.wallet_age_days.max(other.wallet_age_days);
+        self.jupiter_swaps_truncated |= other.jupiter_swaps_truncated;
+        self.max_single_loss_usd = self.max_single_loss_usd.max(other.max_single_loss_usd);
+        self.first_transaction = earliest(self.first_transaction, other.first_transaction);
+        self.last_transaction = self.last_transaction.max(other.last_transaction);
+        // No per-day data to recompute it from; keep a deterministic pick
+        self.most_active_period = self.most_active_period.max(other.most_active_period);
+        
+        for (protocol, timestamp) in &other.protocol_first_use {
+            self.protocol_first_use.entry(protocol.clone())
+                .and_modify(|existing| *existing = (*existing).min(*timestamp))
+                .or_insert(*timestamp);
+        }
+        
+        for chain in &other.chains_active_on {
+            if !self.chains_active_on.contains(chain) {
+                self.chains_active_on.push(chain.clone());
+            }
+        }
+        self.chains_active_on.sort();
+        
+        self.protocols_used.extend(other.protocols_used.iter().cloned());
+        self.tokens_traded.extend(other.tokens_traded.iter().cloned());
+        self.nft_collections.extend(other.nft_collections.iter().cloned());
+        self.casino_platforms.extend(other.casino_platforms.iter().cloned());
+        self.active_day_numbers.extend(other.active_day_numbers.iter().copied());
+        self.volatile_tokens.extend(other.volatile_tokens.iter().cloned());
+        self.nfts_held.extend(other.nfts_held.iter().cloned());
+        self.sources.extend(other.sources.iter().cloned());
+        
+        // Snapshots without sets only report a count, which is kept as a lower bound
+        self.defi_protocols_used = distinct(self.defi_protocols_used, other.defi_protocols_used, self.protocols_used.len());
+        self.distinct_tokens_traded = distinct(self.distinct_tokens_traded, other.distinct_tokens_traded, self.tokens_traded.len());
+        self.nft_collections_count = distinct(self.nft_collections_count, other.nft_collections_count, self.nft_collections.len());
+        self.casinos_used = distinct(self.casinos_used, other.casinos_used, self.casino_platforms.len());
+        self.active_days = distinct(self.active_days, other.active_days, self.active_day_numbers.len());
+        self.active_months = distinct(self.active_months, other.active_months, self.active_month_count());
+        self.volatile_token_count = distinct(self.volatile_token_count, other.volatile_token_count, self.volatile_tokens.len());
+        self.nft_count = distinct(self.nft_count, other.nft_count, self.nfts_held.len());
+    }
+    
+    // Additive totals, balances and the percentages derived from them
+    fn add_totals(&mut self, other: &DegenMetrics) {
+        let stablecoin_usd = self.stablecoin_usd() + other.stablecoin_usd();
+        let unitemized_position_usd = self.unitemized_position_usd().max(other.unitemized_position_usd());
+        
         self.gmx_volume_usd += other.gmx_volume_usd;
         self.gmx_trades += other.gmx_trades;
         self.jupiter_swaps += other.jupiter_swaps;
         self.bridges_used += other.bridges_used;
         self.hyperliquid_volume_usd += other.hyperliquid_volume_usd;
         self.total_perp_volume_usd += other.total_perp_volume_usd;
+        self.perp_collateral_flow_usd += other.perp_collateral_flow_usd;
         
-        self.casinos_used += other.casinos_used;
         for (token, amount) in &other.casino_tokens_held {
             *self.casino_tokens_held.entry(token.clone()).or_insert(Decimal::ZERO) += amount;
         }
         self.gambling_volume_usd += other.gambling_volume_usd;
         
-        self.defi_protocols_used += other.defi_protocols_used;
-        self.distinct_tokens_traded += other.distinct_tokens_traded;
         self.memecoin_trades += other.memecoin_trades;
         self.total_swap_volume_usd += other.total_swap_volume_usd;
         self.liquidity_provided_usd += other.liquidity_provided_usd;
         
-        self.nft_count += other.nft_count;
-        self.nft_collections_count += other.nft_collections_count;
         self.nft_total_value_usd += other.nft_total_value_usd;
         self.nft_trades += other.nft_trades;
         
-        self.total_balance_usd += other.total_balance_usd;
-        self.volatile_token_count += other.volatile_token_count;
-        
         self.total_tx_count += other.total_tx_count;
-        self.wallet_age_days = self.wallet_age_days.max(other.wallet_age_days);
-        self.active_days += other.active_days;
-        self.active_months += other.active_months;
         self.gas_spent_usd += other.gas_spent_usd;
         
-        for chain in &other.chains_active_on {
-            if !self.chains_active_on.contains(chain) {
-                self.chains_active_on.push(chain.clone());
-            }
-        }
-        
         self.leveraged_positions_count += other.leveraged_positions_count;
         self.liquidations_count += other.liquidations_count;
         self.rugpull_exposure_count += other.rugpull_exposure_count;
         
-        if other.max_single_loss_usd > self.max_single_loss_usd {
-            self.max_single_loss_usd = other.max_single_loss_usd;
-        }
-        
-        // Merge protocol tracking data
         for (protocol, count) in &other.protocol_interaction_counts {
             *self.protocol_interaction_counts.entry(protocol.clone()).or_insert(0) += count;
         }
-        
         for (protocol, volume) in &other.protocol_volume_usd {
             *self.protocol_volume_usd.entry(protocol.clone()).or_insert(Decimal::ZERO) += volume;
         }
         
-        for (protocol, timestamp) in &other.protocol_first_use {
-            self.protocol_first_use.entry(protocol.clone())
-                .and_modify(|existing| {
-                    if timestamp < existing {
-                        *existing = *timestamp;
-                    }
-                })
-                .or_insert(*timestamp);
+        self.total_balance_usd += other.total_balance_usd;
+        self.stablecoin_balance_usd = stablecoin_usd;
+        for (token, value) in &other.position_values_usd {
+            *self.position_values_usd.entry(token.clone()).or_insert(Decimal::ZERO) += value;
         }
         
-        // Update time-based metrics
-        if let Some(other_first) = other.first_transaction {
-            self.first_transaction = Some(match self.first_transaction {
-                Some(self_first) => self_first.min(other_first),
-                None => other_first,
-            });
+        if self.total_balance_usd > Decimal::ZERO {
+            let largest_usd = self.position_values_usd.values().copied()
+                .fold(unitemized_position_usd, Decimal::max);
+            self.stablecoin_percentage = share(stablecoin_usd, self.total_balance_usd);
+            self.largest_position_percentage = share(largest_usd, self.total_balance_usd);
+        } else {
+            self.stablecoin_percentage = self.stablecoin_percentage.max(other.stablecoin_percentage);
+            self.largest_position_percentage = self.largest_position_percentage.max(other.largest_position_percentage);
         }
-        
-        if let Some(other_last) = other.last_transaction {
-            self.last_transaction = Some(match self.last_transaction {
-                Some(self_last) => self_last.max(other_last),
-                None => other_last,
-            });
+    }
+    
+    // Stablecoin holdings in USD, derived from the percentage for older snapshots
+    fn stablecoin_usd(&self) -> Decimal {
+        if self.stablecoin_balance_usd > Decimal::ZERO {
+            return self.stablecoin_balance_usd;
+        }
+        self.total_balance_usd * Decimal::from_f64_retain(self.stablecoin_percentage).unwrap_or_default()
+    }
+    
+    // Largest position of a snapshot that reports only the percentage
+    fn unitemized_position_usd(&self) -> Decimal {
+        if !self.position_values_usd.is_empty() {
+            return Decimal::ZERO;
+        }
+        self.total_balance_usd * Decimal::from_f64_retain(self.largest_position_percentage).unwrap_or_default()
+    }
+    
+    fn active_month_count(&self) -> usize {
+        self.active_day_numbers
+            .iter()
+            .filter_map(|day| DateTime::from_timestamp(day * 86400, 0))
+            .map(|date| (date.year(), date.month()))
+            .collect::<BTreeSet<_>>()
+            .len()
+    }
+}
+
+fn distinct(current: u32, other: u32, set_len: usize) -> u32 {
+    current.max(other).max(set_len as u32)
+}
+
+fn earliest(a: Option<DateTime<Utc>>, b: Option<DateTime<Utc>>) -> Option<DateTime<Utc>> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+fn share(part: Decimal, total: Decimal) -> f64 {
+    (part / total).to_f64().unwrap_or(0.0).clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use proptest::collection::{btree_set, hash_map};
+    use proptest::prelude::*;
+
+    fn wallet(chain: &str, address: &str) -> ChainMetrics {
+        ChainMetrics {
+            chain: chain.to_string(),
+            address: address.to_string(),
+            metrics: DegenMetrics::default(),
+            last_updated: Utc::now(),
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_merge_counts_shared_activity_once() {
+        let mut eth = wallet("ethereum", "0xAbC");
+        eth.metrics.protocols_used = ["Uniswap".to_string(), "GMX".to_string()].into();
+        eth.metrics.defi_protocols_used = 2;
+        eth.metrics.active_day_numbers = [19_000, 19_001].into();
+        eth.metrics.active_days = 2;
+        eth.metrics.gmx_trades = 4;
+        eth.metrics.total_balance_usd = Decimal::from(300);
+        eth.metrics.stablecoin_balance_usd = Decimal::from(300);
+        eth.metrics.position_values_usd.insert("USDC".to_string(), Decimal::from(300));
+        eth.metrics.volatile_tokens = ["0x0000000000000000000000000000000000000000".to_string()].into();
+        eth.metrics.volatile_token_count = 1;
+        eth.metrics.nfts_held = ["0xbc4c:1".to_string()].into();
+        eth.metrics.nft_count = 1;
+
+        let mut arb = wallet("arbitrum", "0xdef");
+        arb.metrics.protocols_used = ["Uniswap".to_string()].into();
+        arb.metrics.defi_protocols_used = 1;
+        arb.metrics.active_day_numbers = [19_001, 19_040].into();
+        arb.metrics.active_days = 2;
+        arb.metrics.gmx_trades = 1;
+        arb.metrics.total_balance_usd = Decimal::from(100);
+        arb.metrics.position_values_usd.insert("ARB".to_string(), Decimal::from(100));
+        arb.metrics.volatile_tokens = ["0x0000000000000000000000000000000000000000".to_string(), "0x912c".to_string()].into();
+        arb.metrics.volatile_token_count = 2;
+        arb.metrics.nfts_held = ["0xbc4c:1".to_string(), "0xbc4c:2".to_string()].into();
+        arb.metrics.nft_count = 2;
+
+        let mut merged = DegenMetrics::default();
+        merged.merge(&eth.sourced_metrics());
+        merged.merge(&arb.sourced_metrics());
+
+        assert_eq!(merged.defi_protocols_used, 2);
+        assert_eq!(merged.active_days, 3);
+        assert_eq!(merged.active_months, 2);
+        assert_eq!(merged.gmx_trades, 5);
+        assert_eq!(merged.volatile_token_count, 2);
+        assert_eq!(merged.nft_count, 2);
+        assert_eq!(merged.stablecoin_percentage, 0.75);
+        assert_eq!(merged.largest_position_percentage, 0.75);
+        assert!(merged.sources.contains("ethereum:0xabc"));
+
+        // Re-merging a wallet already counted changes nothing
+        let before = merged.clone();
+        merged.merge(&eth.sourced_metrics());
+        assert_eq!(merged, before);
+    }
+
+    #[test]
+    fn test_transaction_summary_window_and_busiest_period() {
+        let at = |month: u32, day: u32| Utc.with_ymd_and_hms(2024, month, day, 12, 0, 0).unwrap();
+        let fee = Decimal::new(1, 3);
+        let activity = vec![
+            (at(1, 1), fee),
+            (at(3, 1), fee),
+            (at(3, 1), Decimal::ZERO), // received, no fee
+            (at(3, 20), fee),
+            (at(3, 25), fee),
+            (at(6, 30), fee),
+        ];
+
+        let all = TransactionSummary::from_activity(&activity, None, None);
+        assert_eq!(all.total_count, 6);
+        assert_eq!(all.first_tx, Some(at(1, 1)));
+        assert_eq!(all.active_days, 5);
+        assert_eq!(all.active_months, 3);
+        assert_eq!(all.gas_spent, Decimal::new(5, 3));
+        assert_eq!(all.most_active_period, Some((at(3, 1), at(3, 25))));
+        assert!((all.average_tx_per_day - 6.0 / 182.0).abs() < 1e-9);
+
+        let march = TransactionSummary::from_activity(&activity, Some(at(2, 1)), Some(at(3, 31)));
+        assert_eq!(march.total_count, 4);
+        assert_eq!(march.last_tx, Some(at(3, 25)));
+        assert_eq!(march.active_months, 1);
+
+        let none = TransactionSummary::from_activity(&activity, Some(at(7, 1)), None);
+        assert_eq!(none.total_count, 0);
+        assert_eq!(none.most_active_period, None);
+    }
+
+    prop_compose! {
+        fn snapshot()(
+            source in "(ethereum|arbitrum|solana):[0-9a-f]{6}",
+            protocols in btree_set("[A-F]", 0..5),
+            tokens in btree_set("0x[0-9a-f]{2}", 0..6),
+            casinos in btree_set("(Rollbit|Shuffle|Yeet)", 0..3),
+            days in btree_set(19_000i64..19_400, 0..12),
+            chains in btree_set("(ethereum|arbitrum|solana)", 0..3),
+            trades in 0u32..200,
+            volume_cents in 0i64..100_000_000,
+            positions in hash_map("(USDC|ETH|ARB)", 1i64..1_000_000, 0..3),
+            legacy_percentages in (0.0f64..=1.0, 0.0f64..=1.0),
+            itemized in any::<bool>(),
+            wallet_age_days in 0u32..2_000,
+            first_seen in proptest::option::of(0i64..1_000_000_000),
+        ) -> DegenMetrics {
+            let mut m = DegenMetrics {
+                gmx_trades: trades,
+                gmx_volume_usd: Decimal::new(volume_cents, 2),
+                defi_protocols_used: protocols.len() as u32,
+                distinct_tokens_traded: tokens.len() as u32,
+                casinos_used: casinos.len() as u32,
+                active_days: days.len() as u32,
+                wallet_age_days,
+                first_transaction: first_seen.and_then(|s| DateTime::from_timestamp(s, 0)),
+                chains_active_on: chains.into_iter().collect(),
+                protocols_used: protocols,
+                tokens_traded: tokens,
+                casino_platforms: casinos,
+                active_day_numbers: days,
+                ..DegenMetrics::default()
+            };
+            m.active_months = m.active_month_count() as u32;
+            m.protocol_interaction_counts = m.protocols_used.iter().map(|p| (p.clone(), trades)).collect();
+
+            let total: Decimal = positions.values().map(|cents| Decimal::new(*cents, 2)).sum();
+            m.total_balance_usd = total;
+            if itemized {
+                m.position_values_usd = positions.iter().map(|(t, cents)| (t.clone(), Decimal::new(*cents, 2))).collect();
+                m.stablecoin_balance_usd = m.position_values_usd.get("USDC").copied().unwrap_or_default();
+            } else {
+                // Older snapshot with only the percentages
+                (m.stablecoin_percentage, m.largest_position_percentage) = legacy_percentages;
+            }
+            m.sources.insert(source);
+            m
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn prop_merge_is_commutative(a in snapshot(), b in snapshot()) {
+            prop_assume!(a.sources.is_disjoint(&b.sources));
+
+            let mut ab = a.clone();
+            ab.merge(&b);
+            let mut ba = b.clone();
+            ba.merge(&a);
+            prop_assert_eq!(ab, ba);
+        }
+
+        #[test]
+        fn prop_merge_is_idempotent(a in snapshot(), b in snapshot()) {
+            prop_assume!(a.sources.is_disjoint(&b.sources));
+
+            let mut once = a.clone();
+            once.merge(&a);
+            prop_assert_eq!(&once, &a);
+
+            let mut aggregate = DegenMetrics::default();
+            aggregate.merge(&a);
+            aggregate.merge(&b);
+            let snapshot = aggregate.clone();
+            aggregate.merge(&aggregate.clone());
+            aggregate.merge(&b);
+            prop_assert_eq!(aggregate, snapshot);
+        }
+    }
+}
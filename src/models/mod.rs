@@ -4,10 +4,18 @@ pub mod chain_data;
 pub mod error;
 pub mod casino;
 pub mod cache;
+pub mod explanation;
+pub mod allocation;
+pub mod pricing;
+pub mod solana_protocols;
 
 pub use metrics::*;
 pub use user::*;
 pub use chain_data::*;
 pub use error::*;
 pub use casino::*;
-pub use cache::*;
\ No newline at end of file
+pub use cache::*;
+pub use explanation::*;
+pub use allocation::{AllocationCurve, CurveInput, CurveOutcome};
+pub use pricing::{PriceSource, PriceSourceEntry};
+pub use solana_protocols::{SolanaCasinoToken, SolanaProtocol, SolanaProtocolKind};
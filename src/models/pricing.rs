@@ -0,0 +1,27 @@
+use serde::{Deserialize, Serialize};
+
+/// Where the USD price of one token comes from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PriceSource {
+    /// Pegged at one dollar
+    Stablecoin,
+    /// Chainlink aggregator quoting the token in USD
+    Chainlink { feed: String },
+    /// Uniswap V2 style pair, priced from its reserves in `quote`
+    UniswapV2 { pair: String, quote: String },
+    /// Uniswap V3 pool, priced from its TWAP in `quote`
+    UniswapV3 { pool: String, quote: String },
+    /// Pyth price update account on Solana
+    Pyth { account: String },
+}
+
+/// One configured price source
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PriceSourceEntry {
+    /// Chain name, e.g. `arbitrum`
+    pub chain: String,
+    /// Token address or mint; the zero address or `11111111111111111111111111111111` for native tokens
+    pub token: String,
+    pub source: PriceSource,
+}
@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SolanaProtocolKind {
+    /// Perpetuals venue; collateral moved in and out counts as collateral flow, not
+    /// as traded volume
+    Perp,
+    /// On-chain casino; what the wallet sends in counts as wagered
+    Casino,
+}
+
+/// A Solana program detected by invocation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SolanaProtocol {
+    pub name: String,
+    pub kind: SolanaProtocolKind,
+    pub program_id: String,
+}
+
+/// A token issued by a casino, e.g. for rewards or house staking
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SolanaCasinoToken {
+    pub mint: String,
+    pub symbol: String,
+}
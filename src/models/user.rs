@@ -70,6 +70,38 @@ pub struct DegenScore {
     pub breakdown: ScoreBreakdown,
     pub calculated_at: DateTime<Utc>,
     pub tier: ScoreTier,
+    /// Per-category percentiles within the scored cohort, once ranked
+    #[serde(default)]
+    pub category_percentiles: Option<CategoryPercentiles>,
+    /// Normalization constants the score was computed with
+    #[serde(default)]
+    pub thresholds: Option<crate::config::ScoringThresholds>,
+    /// Per-metric contributions behind the breakdown
+    #[serde(default)]
+    pub explanation: Option<crate::models::ScoreExplanation>,
+    /// `ALGORITHM_VERSION` of the formulas that produced the score
+    #[serde(default)]
+    pub algorithm_version: Option<String>,
+    /// Fingerprint of the weights and thresholds in effect
+    #[serde(default)]
+    pub fingerprint: Option<String>,
+    /// Sybil penalty already applied to `total_score`, if the user was flagged
+    #[serde(default)]
+    pub sybil_multiplier: Option<f64>,
+}
+
+impl DegenScore {
+    /// Scale the score down, including the breakdown and explanation so they still sum
+    /// to the total
+    pub fn apply_sybil_penalty(&mut self, multiplier: f64) {
+        self.total_score *= multiplier;
+        self.breakdown.scale(multiplier);
+        if let Some(explanation) = &mut self.explanation {
+            explanation.scale(multiplier);
+        }
+        self.tier = ScoreTier::from_score(self.total_score);
+        self.sybil_multiplier = Some(multiplier);
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -82,6 +114,28 @@ pub struct ScoreBreakdown {
     pub risk_profile_score: f64,
 }
 
+impl ScoreBreakdown {
+    pub fn scale(&mut self, multiplier: f64) {
+        self.trading_score *= multiplier;
+        self.gambling_score *= multiplier;
+        self.defi_activity_score *= multiplier;
+        self.nft_portfolio_score *= multiplier;
+        self.longevity_score *= multiplier;
+        self.risk_profile_score *= multiplier;
+    }
+}
+
+/// Percentile (0-100) of each `ScoreBreakdown` field within the cohort
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct CategoryPercentiles {
+    pub trading_score: f64,
+    pub gambling_score: f64,
+    pub defi_activity_score: f64,
+    pub nft_portfolio_score: f64,
+    pub longevity_score: f64,
+    pub risk_profile_score: f64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum ScoreTier {
     Legendary,    // 90-100
@@ -1,211 +1,258 @@
 use crate::{
-    models::{DegenMetrics, DegenScore, ScoreBreakdown, ScoreTier},
-    config::ScoringWeights,
+    models::{ComponentExplanation, DegenMetrics, DegenScore, ScoreBreakdown, ScoreCategory, ScoreExplanation, ScoreTier},
+    config::{ScoringThresholds, ScoringWeights},
 };
 use rust_decimal::Decimal;
 use chrono::Utc;
+use ring::digest::{digest, SHA256};
+
+/// Identifies the scoring formulas; bump whenever a component's math changes
+pub const ALGORITHM_VERSION: &str = "2.1";
 
 pub struct ScoringAlgorithm {
     weights: ScoringWeights,
-    max_trading_volume: f64,
-    max_trades_count: u32,
-    max_protocols_count: u32,
-    max_nft_count: u32,
-    max_wallet_age_days: u32,
+    thresholds: ScoringThresholds,
 }
 
 impl ScoringAlgorithm {
     pub fn new(weights: ScoringWeights) -> Self {
-        Self {
-            weights,
-            max_trading_volume: 10_000_000.0, // $10M
-            max_trades_count: 100,
-            max_protocols_count: 20,
-            max_nft_count: 50,
-            max_wallet_age_days: 1825, // 5 years
-        }
+        Self::with_thresholds(weights, ScoringThresholds::default())
+    }
+    
+    /// Use campaign-specific normalization constants (validated by `Settings::validate`)
+    pub fn with_thresholds(weights: ScoringWeights, thresholds: ScoringThresholds) -> Self {
+        Self { weights, thresholds }
+    }
+    
+    pub fn thresholds(&self) -> &ScoringThresholds {
+        &self.thresholds
+    }
+    
+    pub fn weights(&self) -> &ScoringWeights {
+        &self.weights
+    }
+    
+    /// Short hash of the weights and thresholds, so scores from differently
+    /// configured campaigns can be told apart
+    pub fn fingerprint(&self) -> String {
+        let config = serde_json::json!({
+            "weights": self.weights,
+            "thresholds": self.thresholds,
+        });
+        let hash = digest(&SHA256, config.to_string().as_bytes());
+        hex::encode(&hash.as_ref()[..8])
     }
     
     pub fn calculate_score(&self, metrics: &DegenMetrics) -> DegenScore {
-        let breakdown = self.calculate_breakdown(metrics);
+        let explanation = self.explain(metrics);
+        let breakdown = explanation.breakdown();
         let total_score = self.sum_breakdown(&breakdown);
         
         DegenScore {
             total_score,
-            percentile: 0.0, // Would be calculated based on all users
+            percentile: 0.0, // Filled in by PercentileEngine against the cohort
             breakdown,
             calculated_at: Utc::now(),
             tier: ScoreTier::from_score(total_score),
+            category_percentiles: None,
+            thresholds: Some(self.thresholds.clone()),
+            explanation: Some(explanation),
+            algorithm_version: Some(ALGORITHM_VERSION.to_string()),
+            fingerprint: Some(self.fingerprint()),
+            sybil_multiplier: None,
         }
     }
     
-    fn calculate_breakdown(&self, metrics: &DegenMetrics) -> ScoreBreakdown {
-        ScoreBreakdown {
-            trading_score: self.calculate_trading_score(metrics),
-            gambling_score: self.calculate_gambling_score(metrics),
-            defi_activity_score: self.calculate_defi_score(metrics),
-            nft_portfolio_score: self.calculate_nft_score(metrics),
-            longevity_score: self.calculate_longevity_score(metrics),
-            risk_profile_score: self.calculate_risk_score(metrics),
-        }
+    /// Per-metric contributions; the `ScoreBreakdown` is summed from these
+    pub fn explain(&self, metrics: &DegenMetrics) -> ScoreExplanation {
+        let mut components = Vec::with_capacity(14);
+        components.extend(self.trading_components(metrics));
+        components.extend(self.gambling_components(metrics));
+        components.extend(self.defi_components(metrics));
+        components.extend(self.nft_components(metrics));
+        components.extend(self.longevity_components(metrics));
+        components.extend(self.risk_components(metrics));
+        
+        ScoreExplanation::new(components)
     }
     
     fn sum_breakdown(&self, breakdown: &ScoreBreakdown) -> f64 {
-        let base_score = breakdown.trading_score +
+        breakdown.trading_score +
             breakdown.gambling_score +
             breakdown.defi_activity_score +
             breakdown.nft_portfolio_score +
-            breakdown.longevity_score;
-        
-        // Add risk profile bonus (up to 15 points)
-        base_score + breakdown.risk_profile_score
+            breakdown.longevity_score +
+            breakdown.risk_profile_score
     }
     
-    fn calculate_trading_score(&self, metrics: &DegenMetrics) -> f64 {
-        let volume_score = self.calculate_volume_component(metrics);
-        let count_score = self.calculate_trades_count_component(metrics);
-        
-        volume_score + count_score
+    fn trading_components(&self, metrics: &DegenMetrics) -> [ComponentExplanation; 2] {
+        [
+            self.volume_component(metrics),
+            self.trades_count_component(metrics),
+        ]
     }
     
-    fn calculate_volume_component(&self, metrics: &DegenMetrics) -> f64 {
-        let total_volume = metrics.gmx_volume_usd + metrics.total_perp_volume_usd;
+    fn volume_component(&self, metrics: &DegenMetrics) -> ComponentExplanation {
+        // Solana perp fills aren't decoded, so the collateral moved through a venue stands in
+        let total_volume = metrics.gmx_volume_usd + metrics.total_perp_volume_usd + metrics.perp_collateral_flow_usd;
         let volume_f64: f64 = total_volume.try_into().unwrap_or(0.0);
-        
-        if volume_f64 <= 0.0 {
-            return 0.0;
-        }
+        let max_volume = self.thresholds.max_trading_volume_usd;
         
         // Logarithmic scaling for volume
-        let capped_volume = volume_f64.min(self.max_trading_volume);
-        let volume_factor = (capped_volume.ln() / self.max_trading_volume.ln()).min(1.0).max(0.0);
+        let volume_factor = if volume_f64 <= 0.0 {
+            0.0
+        } else {
+            let capped_volume = volume_f64.min(max_volume);
+            (capped_volume.ln() / max_volume.ln()).clamp(0.0, 1.0)
+        };
         
-        volume_factor * self.weights.trading_volume
+        ComponentExplanation::new("trading_volume", ScoreCategory::Trading,
+            volume_f64, max_volume, volume_factor, self.weights.trading_volume)
     }
     
-    fn calculate_trades_count_component(&self, metrics: &DegenMetrics) -> f64 {
+    fn trades_count_component(&self, metrics: &DegenMetrics) -> ComponentExplanation {
         let total_trades = metrics.gmx_trades + metrics.jupiter_swaps;
-        let trades_factor = (total_trades as f64 / self.max_trades_count as f64).min(1.0);
+        let max_trades = self.thresholds.max_trades_count as f64;
+        let trades_factor = (total_trades as f64 / max_trades).min(1.0);
         
-        trades_factor * self.weights.trading_count
+        ComponentExplanation::new("trading_count", ScoreCategory::Trading,
+            total_trades as f64, max_trades, trades_factor, self.weights.trading_count)
     }
     
-    fn calculate_gambling_score(&self, metrics: &DegenMetrics) -> f64 {
+    fn gambling_components(&self, metrics: &DegenMetrics) -> [ComponentExplanation; 2] {
         // Platforms used component
-        let platforms_score = {
-            let platforms_factor = (metrics.casinos_used.min(3) as f64 / 3.0).min(1.0);
-            platforms_factor * self.weights.gambling_platforms
+        let platforms = {
+            let max_platforms = self.thresholds.max_casino_platforms;
+            let platforms_factor = (metrics.casinos_used.min(max_platforms) as f64 / max_platforms as f64).min(1.0);
+            ComponentExplanation::new("gambling_platforms", ScoreCategory::Gambling,
+                metrics.casinos_used as f64, max_platforms as f64, platforms_factor, self.weights.gambling_platforms)
         };
         
         // Casino tokens held component
-        let tokens_score = {
+        let tokens = {
             let token_count = metrics.casino_tokens_held.len() as u32;
-            let tokens_factor = (token_count.min(2) as f64 / 2.0).min(1.0);
-            tokens_factor * self.weights.casino_tokens
+            let max_tokens = self.thresholds.max_casino_tokens;
+            let tokens_factor = (token_count.min(max_tokens) as f64 / max_tokens as f64).min(1.0);
+            ComponentExplanation::new("casino_tokens", ScoreCategory::Gambling,
+                token_count as f64, max_tokens as f64, tokens_factor, self.weights.casino_tokens)
         };
         
-        platforms_score + tokens_score
+        [platforms, tokens]
     }
     
-    fn calculate_defi_score(&self, metrics: &DegenMetrics) -> f64 {
+    fn defi_components(&self, metrics: &DegenMetrics) -> [ComponentExplanation; 2] {
         // Protocol diversity
-        let protocols_score = {
-            let protocols_factor = (metrics.defi_protocols_used as f64 / 
-                self.max_protocols_count as f64).min(1.0);
-            protocols_factor * self.weights.defi_protocols
+        let protocols = {
+            let max_protocols = self.thresholds.max_protocols_count as f64;
+            let protocols_factor = (metrics.defi_protocols_used as f64 / max_protocols).min(1.0);
+            ComponentExplanation::new("defi_protocols", ScoreCategory::DefiActivity,
+                metrics.defi_protocols_used as f64, max_protocols, protocols_factor, self.weights.defi_protocols)
         };
         
         // Token diversity (memecoin trading indicator)
-        let tokens_score = {
-            let tokens_factor = (metrics.distinct_tokens_traded as f64 / 50.0).min(1.0);
-            tokens_factor * self.weights.token_diversity
+        let tokens = {
+            let max_tokens = self.thresholds.max_distinct_tokens as f64;
+            let tokens_factor = (metrics.distinct_tokens_traded as f64 / max_tokens).min(1.0);
+            ComponentExplanation::new("token_diversity", ScoreCategory::DefiActivity,
+                metrics.distinct_tokens_traded as f64, max_tokens, tokens_factor, self.weights.token_diversity)
         };
         
-        protocols_score + tokens_score
+        [protocols, tokens]
     }
     
-    fn calculate_nft_score(&self, metrics: &DegenMetrics) -> f64 {
-        // NFT count component (50% of NFT score)
-        let count_score = {
-            let count_factor = (metrics.nft_count as f64 / self.max_nft_count as f64).min(1.0);
-            count_factor * (self.weights.nft_holdings / 2.0)
+    fn nft_components(&self, metrics: &DegenMetrics) -> [ComponentExplanation; 2] {
+        // NFT count component
+        let count = {
+            let max_count = self.thresholds.max_nft_count as f64;
+            let count_factor = (metrics.nft_count as f64 / max_count).min(1.0);
+            ComponentExplanation::new("nft_count", ScoreCategory::NftPortfolio,
+                metrics.nft_count as f64, max_count, count_factor, self.weights.nft_count)
         };
         
-        // NFT value component (50% of NFT score)
-        let value_score = {
+        // NFT value component
+        let value = {
             let value_f64: f64 = metrics.nft_total_value_usd.try_into().unwrap_or(0.0);
+            let max_value = self.thresholds.max_nft_value_usd;
             let value_factor = if value_f64 > 0.0 {
-                (value_f64.min(100_000.0) / 100_000.0).min(1.0)
+                (value_f64.min(max_value) / max_value).min(1.0)
             } else {
                 0.0
             };
-            value_factor * (self.weights.nft_holdings / 2.0)
+            ComponentExplanation::new("nft_value", ScoreCategory::NftPortfolio,
+                value_f64, max_value, value_factor, self.weights.nft_value)
         };
         
-        count_score + value_score
+        [count, value]
     }
     
-    fn calculate_longevity_score(&self, metrics: &DegenMetrics) -> f64 {
-        // Wallet age component (50% of longevity score)
-        let age_score = {
-            let age_factor = (metrics.wallet_age_days as f64 / 
-                self.max_wallet_age_days as f64).min(1.0);
-            age_factor * (self.weights.wallet_age / 2.0)
+    fn longevity_components(&self, metrics: &DegenMetrics) -> [ComponentExplanation; 2] {
+        // Wallet age component
+        let age = {
+            let max_age = self.thresholds.max_wallet_age_days as f64;
+            let age_factor = (metrics.wallet_age_days as f64 / max_age).min(1.0);
+            ComponentExplanation::new("wallet_age", ScoreCategory::Longevity,
+                metrics.wallet_age_days as f64, max_age, age_factor, self.weights.wallet_age)
         };
         
-        // Activity consistency component (50% of longevity score)
-        let consistency_score = {
-            let consistency_factor = (metrics.active_days as f64 / 365.0).min(1.0);
-            consistency_factor * (self.weights.activity_consistency / 2.0)
+        // Activity consistency component
+        let consistency = {
+            let window = self.thresholds.activity_window_days as f64;
+            let consistency_factor = (metrics.active_days as f64 / window).min(1.0);
+            ComponentExplanation::new("activity_consistency", ScoreCategory::Longevity,
+                metrics.active_days as f64, window, consistency_factor, self.weights.activity_consistency)
         };
         
-        age_score + consistency_score
+        [age, consistency]
     }
     
-    fn calculate_risk_score(&self, metrics: &DegenMetrics) -> f64 {
-        let mut risk_score = 0.0;
+    fn risk_components(&self, metrics: &DegenMetrics) -> [ComponentExplanation; 4] {
+        let w = &self.weights;
         
         // High volatility portfolio (less stablecoins = more degen)
-        if metrics.total_balance_usd > Decimal::ZERO {
-            let volatile_factor = 1.0 - metrics.stablecoin_percentage;
-            risk_score += volatile_factor * 3.75;
-        }
+        let volatility = {
+            let volatile_share = if metrics.total_balance_usd > Decimal::ZERO {
+                1.0 - metrics.stablecoin_percentage
+            } else {
+                0.0
+            };
+            ComponentExplanation::new("risk_volatility", ScoreCategory::RiskProfile,
+                volatile_share, 1.0, volatile_share, w.risk_volatility)
+        };
         
         // Multiple chains active (cross-chain degen)
-        let chains_factor = (metrics.chains_active_on.len() as f64 / 5.0).min(1.0);
-        risk_score += chains_factor * 3.75;
+        let multichain = {
+            let max_chains = self.thresholds.max_active_chains as f64;
+            let chains = metrics.chains_active_on.len() as f64;
+            ComponentExplanation::new("risk_multichain", ScoreCategory::RiskProfile,
+                chains, max_chains, (chains / max_chains).min(1.0), w.risk_multichain)
+        };
         
         // Leveraged positions
-        if metrics.leveraged_positions_count > 0 {
-            risk_score += 3.75;
-        }
+        let leverage = {
+            let positions = metrics.leveraged_positions_count;
+            let factor = if positions > 0 { 1.0 } else { 0.0 };
+            ComponentExplanation::new("risk_leverage", ScoreCategory::RiskProfile,
+                positions as f64, 1.0, factor, w.risk_leverage)
+        };
         
         // Survived liquidations/rugpulls (battle-tested degen)
-        if metrics.liquidations_count > 0 || metrics.rugpull_exposure_count > 0 {
-            risk_score += 3.75;
-        }
+        let battle_tested = {
+            let events = metrics.liquidations_count + metrics.rugpull_exposure_count;
+            let factor = if events > 0 { 1.0 } else { 0.0 };
+            ComponentExplanation::new("risk_battle_tested", ScoreCategory::RiskProfile,
+                events as f64, 1.0, factor, w.risk_battle_tested)
+        };
         
-        risk_score.min(15.0) // Cap at 15 points as per documentation
+        [volatility, multichain, leverage, battle_tested]
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::collections::HashMap;
     
     fn default_weights() -> ScoringWeights {
-        ScoringWeights {
-            trading_volume: 15.0,
-            trading_count: 10.0,
-            gambling_platforms: 10.0,
-            casino_tokens: 5.0,
-            defi_protocols: 10.0,
-            token_diversity: 5.0,
-            nft_holdings: 10.0,
-            wallet_age: 10.0,
-            activity_consistency: 10.0,
-        }
+        ScoringWeights::default()
     }
     
     #[test]
@@ -231,4 +278,103 @@ mod tests {
         
         assert!((score.breakdown.trading_score - expected_trading_score).abs() < 0.1);
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_solana_perp_collateral_counts_as_trading_volume() {
+        let algo = ScoringAlgorithm::new(default_weights());
+        let idle = algo.calculate_score(&DegenMetrics::default());
+        let perps = algo.calculate_score(&DegenMetrics {
+            perp_collateral_flow_usd: Decimal::from(50_000),
+            ..DegenMetrics::default()
+        });
+        
+        assert!(perps.breakdown.trading_score > idle.breakdown.trading_score);
+        assert!(perps.total_score > idle.total_score);
+        let volume = perps.explanation.as_ref().unwrap().component("trading_volume").unwrap();
+        assert_eq!(volume.raw_input, 50_000.0);
+    }
+    
+    #[test]
+    fn test_configured_thresholds_change_normalization() {
+        let metrics = DegenMetrics {
+            distinct_tokens_traded: 10,
+            nft_total_value_usd: Decimal::from(10_000),
+            leveraged_positions_count: 1,
+            ..DegenMetrics::default()
+        };
+        
+        let default_score = ScoringAlgorithm::new(default_weights()).calculate_score(&metrics);
+        
+        let thresholds = ScoringThresholds {
+            max_distinct_tokens: 10,
+            max_nft_value_usd: 10_000.0,
+            ..ScoringThresholds::default()
+        };
+        let tuned = ScoringAlgorithm::with_thresholds(default_weights(), thresholds.clone())
+            .calculate_score(&metrics);
+        
+        assert!((default_score.breakdown.defi_activity_score - 1.0).abs() < 1e-9);
+        assert!((tuned.breakdown.defi_activity_score - 5.0).abs() < 1e-9);
+        assert!((tuned.breakdown.nft_portfolio_score - 5.0).abs() < 1e-9);
+        assert!((tuned.breakdown.risk_profile_score - 3.75).abs() < 1e-9);
+        
+        // The constants used are recorded with the score
+        assert_eq!(tuned.thresholds, Some(thresholds));
+        assert_eq!(default_score.thresholds, Some(ScoringThresholds::default()));
+    }
+    
+    #[test]
+    fn test_risk_signals_use_configured_weights() {
+        let metrics = DegenMetrics {
+            leveraged_positions_count: 2,
+            liquidations_count: 1,
+            ..DegenMetrics::default()
+        };
+        
+        let weights = ScoringWeights {
+            risk_leverage: 10.0,
+            risk_battle_tested: 0.0,
+            risk_volatility: 2.5,
+            risk_multichain: 2.5,
+            ..default_weights()
+        };
+        assert!(weights.validate().is_ok());
+        
+        let default_score = ScoringAlgorithm::new(default_weights()).calculate_score(&metrics);
+        let tuned = ScoringAlgorithm::new(weights).calculate_score(&metrics);
+        
+        assert!((default_score.breakdown.risk_profile_score - 7.5).abs() < 1e-9);
+        assert!((tuned.breakdown.risk_profile_score - 10.0).abs() < 1e-9);
+    }
+    
+    #[test]
+    fn test_explanation_accounts_for_every_point() {
+        let metrics = DegenMetrics {
+            gmx_volume_usd: Decimal::from(50_000),
+            gmx_trades: 40,
+            casinos_used: 5,
+            wallet_age_days: 365,
+            chains_active_on: vec!["ethereum".to_string(), "solana".to_string()],
+            ..DegenMetrics::default()
+        };
+        
+        let score = ScoringAlgorithm::new(default_weights()).calculate_score(&metrics);
+        let explanation = score.explanation.as_ref().unwrap();
+        
+        assert_eq!(explanation.components.len(), 14);
+        assert!((explanation.total_score - score.total_score).abs() < 1e-9);
+        assert!((explanation.total_score + explanation.total_headroom() - 100.0).abs() < 1e-9);
+        
+        let trades = explanation.component("trading_count").unwrap();
+        assert_eq!((trades.raw_input, trades.cap, trades.factor), (40.0, 100.0, 0.4));
+        assert!((trades.points - 4.0).abs() < 1e-9);
+        assert!((trades.headroom - 6.0).abs() < 1e-9);
+        
+        // Raw input beyond the cap is reported as-is, with no headroom left
+        let platforms = explanation.component("gambling_platforms").unwrap();
+        assert_eq!((platforms.raw_input, platforms.factor, platforms.headroom), (5.0, 1.0, 0.0));
+        
+        let chains = explanation.component("risk_multichain").unwrap();
+        assert!((chains.points - score.breakdown.risk_profile_score).abs() < 1e-9);
+    }
+}
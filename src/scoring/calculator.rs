@@ -1,10 +1,15 @@
 use crate::{
-    models::{UserProfile, DegenMetrics, DegenScore, Chain, Result},
+    models::{UserProfile, DegenMetrics, DegenScore, Chain, ChainMetrics, AllocationCurve, Result},
     chains::ChainClient,
-    scoring::ScoringAlgorithm,
+    scoring::{PercentileEngine, ScoringAlgorithm},
     config::Settings,
+    distribution::{AirdropAllocator, AllocationCandidate, AllocationReport, CurveComparison},
+    storage::ScoreStore,
+    sybil::{SybilDetector, SybilReport, WalletActivity},
 };
-use std::sync::Arc;
+use chrono::Utc;
+use std::collections::HashMap;
+use std::sync::{Arc, PoisonError, RwLock};
 use std::pin::Pin;
 use std::future::Future;
 use futures::future::join_all;
@@ -15,6 +20,10 @@ pub struct ScoreCalculator {
     solana_client: Arc<dyn ChainClient>,
     algorithm: ScoringAlgorithm,
     settings: Settings,
+    // Cohort every newly calculated score is ranked against
+    percentiles: RwLock<PercentileEngine>,
+    // user id -> sybil multiplier applied to every score calculated for that user
+    sybil_penalties: HashMap<String, f64>,
 }
 
 impl ScoreCalculator {
@@ -23,21 +32,117 @@ impl ScoreCalculator {
         solana_client: Arc<dyn ChainClient>,
         settings: Settings,
     ) -> Self {
-        let algorithm = ScoringAlgorithm::new(settings.scoring.weights.clone());
+        let algorithm = ScoringAlgorithm::with_thresholds(
+            settings.scoring.weights.clone(),
+            settings.scoring.thresholds.clone(),
+        );
         
         Self {
             evm_clients,
             solana_client,
             algorithm,
             settings,
+            percentiles: RwLock::new(PercentileEngine::new()),
+            sybil_penalties: HashMap::new(),
         }
     }
     
+    /// Penalize the scores of flagged users, e.g. with `SybilReport::penalties`
+    pub fn with_sybil_penalties(mut self, penalties: HashMap<String, f64>) -> Self {
+        self.sybil_penalties = penalties;
+        self
+    }
+    
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+    
+    /// Seed the percentile cohort with the latest stored score of every user
+    pub async fn load_percentiles(&self, store: &ScoreStore) -> Result<usize> {
+        let engine = PercentileEngine::from_store(store).await?;
+        let count = engine.len();
+        *self.percentiles.write().unwrap_or_else(PoisonError::into_inner) = engine;
+        
+        info!("Loaded {} scores into the percentile cohort", count);
+        Ok(count)
+    }
+    
+    /// Snapshot of the current percentile cohort
+    pub fn percentile_cohort(&self) -> PercentileEngine {
+        self.percentiles.read().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+    
+    /// Run sybil detection over the verified wallets of `users`, using their stored
+    /// metric snapshots and freshly fetched token transfers
+    pub async fn detect_sybils(&self, users: &[UserProfile], store: &ScoreStore) -> Result<SybilReport> {
+        let mut wallets = Vec::new();
+        
+        for user in users {
+            let snapshots = store.get_latest_chain_metrics(&user.id).await?;
+            
+            for verified in &user.verified_addresses {
+                let chain = verified.chain.as_str();
+                let snapshot = snapshots.iter()
+                    .find(|m| m.chain == chain && m.address.eq_ignore_ascii_case(&verified.address));
+                let wallet = match snapshot {
+                    Some(metrics) => WalletActivity::from_chain_metrics(&user.id, metrics),
+                    None => WalletActivity::new(&user.id, chain, &verified.address),
+                };
+                
+                let client = self.evm_clients.iter().find(|c| c.chain() == verified.chain);
+                let transfers = match client {
+                    Some(client) => client.get_token_transfers(&verified.address).await.unwrap_or_else(|e| {
+                        warn!("Failed to fetch transfers for {}: {}", verified.address, e);
+                        Vec::new()
+                    }),
+                    None => Vec::new(),
+                };
+                
+                wallets.push(wallet.with_transfers(transfers));
+            }
+        }
+        
+        Ok(SybilDetector::new(self.settings.sybil.clone()).analyze(&wallets))
+    }
+    
     pub async fn calculate_user_score(&self, user: &UserProfile) -> Result<DegenScore> {
+        let chain_metrics = self.fetch_user_metrics(user).await?;
+        let mut score = self.score_chain_metrics(user, &chain_metrics);
+        
+        self.percentiles.write().unwrap_or_else(PoisonError::into_inner)
+            .rank(&user.id, &mut score);
+        
+        Ok(score)
+    }
+    
+    /// Calculate a score and persist the metric snapshots, aggregated metrics and score
+    pub async fn calculate_and_store(&self, user: &UserProfile, store: &ScoreStore) -> Result<DegenScore> {
+        let chain_metrics = self.fetch_user_metrics(user).await?;
+        let mut score = self.score_chain_metrics(user, &chain_metrics);
+        
+        self.percentiles.write().unwrap_or_else(PoisonError::into_inner)
+            .rank(&user.id, &mut score);
+        
+        let mut profile = user.clone();
+        profile.aggregated_metrics = aggregate_metrics(&chain_metrics);
+        profile.degen_score = Some(score.clone());
+        profile.updated_at = Utc::now();
+        store.upsert_user(&profile).await?;
+        
+        for metrics in &chain_metrics {
+            store.save_chain_metrics(&user.id, metrics).await?;
+        }
+        store.record_score(&user.id, &score).await?;
+        
+        Ok(score)
+    }
+    
+    /// Fetch per-address metrics for every verified address of the user
+    pub async fn fetch_user_metrics(&self, user: &UserProfile) -> Result<Vec<ChainMetrics>> {
         info!("Calculating score for user: {}", user.id);
         
         // Collect all futures for parallel execution
-        let mut metric_futures: Vec<Pin<Box<dyn Future<Output = Option<DegenMetrics>> + Send>>> = Vec::new();
+        let mut metric_futures: Vec<Pin<Box<dyn Future<Output = Option<ChainMetrics>> + Send>>> = Vec::new();
         
         // EVM chains - create futures for parallel execution
         for client in &self.evm_clients {
@@ -53,7 +158,7 @@ impl ScoreCalculator {
                     match client_ref.fetch_metrics(&address_owned).await {
                         Ok(chain_metrics) => {
                             info!("Fetched metrics for {} on {}", address_owned, chain_name);
-                            Some(chain_metrics.metrics)
+                            Some(chain_metrics)
                         }
                         Err(e) => {
                             warn!("Failed to fetch metrics for {} on {}: {}", 
@@ -76,7 +181,7 @@ impl ScoreCalculator {
                 match client_ref.fetch_metrics(&address_owned).await {
                     Ok(chain_metrics) => {
                         info!("Fetched Solana metrics for {}", address_owned);
-                        Some(chain_metrics.metrics)
+                        Some(chain_metrics)
                     }
                     Err(e) => {
                         warn!("Failed to fetch Solana metrics for {}: {}", address_owned, e);
@@ -87,30 +192,31 @@ impl ScoreCalculator {
             metric_futures.push(future);
         }
         
-        // Execute all futures in parallel
-        let results = join_all(metric_futures).await;
-        
-        // Aggregate all successful results
-        let mut aggregated_metrics = DegenMetrics::default();
-        let mut successful_fetches = 0;
-        
-        for result in results {
-            if let Some(metrics) = result {
-                aggregated_metrics.merge(&metrics);
-                successful_fetches += 1;
-            }
-        }
+        // Execute all futures in parallel and keep the successful results
+        let results: Vec<ChainMetrics> = join_all(metric_futures).await
+            .into_iter()
+            .flatten()
+            .collect();
         
-        if successful_fetches == 0 {
+        if results.is_empty() {
             return Err(crate::models::DegenScoreError::ScoreCalculationError(
                 "No metrics could be fetched from any chain".to_string()
             ));
         }
         
-        info!("Successfully fetched metrics from {} chains/addresses in parallel", successful_fetches);
+        info!("Successfully fetched metrics from {} chains/addresses in parallel", results.len());
+        
+        Ok(results)
+    }
+    
+    fn score_chain_metrics(&self, user: &UserProfile, chain_metrics: &[ChainMetrics]) -> DegenScore {
+        let aggregated_metrics = aggregate_metrics(chain_metrics);
         
         // Calculate final score
-        let score = self.algorithm.calculate_score(&aggregated_metrics);
+        let mut score = self.algorithm.calculate_score(&aggregated_metrics);
+        if let Some(multiplier) = self.sybil_penalties.get(&user.id) {
+            score.apply_sybil_penalty(*multiplier);
+        }
         
         // Check if score meets minimum threshold
         if score.total_score < self.settings.scoring.min_score_for_airdrop {
@@ -118,36 +224,93 @@ impl ScoreCalculator {
                 user.id, score.total_score, self.settings.scoring.min_score_for_airdrop);
         }
         
-        Ok(score)
+        score
     }
     
     pub async fn calculate_batch_scores(&self, users: &[UserProfile]) -> Vec<Result<DegenScore>> {
         info!("Calculating scores for {} users in parallel", users.len());
         
         // Create futures for parallel user score calculation
-        let score_futures = users.iter().map(|user| {
-            self.calculate_user_score(user)
+        let score_futures = users.iter().map(|user| async move {
+            let chain_metrics = self.fetch_user_metrics(user).await?;
+            Ok(self.score_chain_metrics(user, &chain_metrics))
         });
         
         // Execute all score calculations in parallel
-        join_all(score_futures).await
+        let mut results: Vec<Result<DegenScore>> = join_all(score_futures).await;
+        
+        // Rank only once the whole batch is in the cohort
+        let mut percentiles = self.percentiles.write().unwrap_or_else(PoisonError::into_inner);
+        for (user, result) in users.iter().zip(&results) {
+            if let Ok(score) = result {
+                percentiles.insert(&user.id, score);
+            }
+        }
+        for score in results.iter_mut().flatten() {
+            percentiles.apply(score);
+        }
+        
+        results
     }
     
     pub fn is_eligible_for_airdrop(&self, score: &DegenScore) -> bool {
         score.total_score >= self.settings.scoring.min_score_for_airdrop
     }
     
-    pub fn calculate_airdrop_amount(&self, score: &DegenScore, total_pool: u64) -> u64 {
-        if !self.is_eligible_for_airdrop(score) {
-            return 0;
-        }
-        
-        // This would be calculated based on all users' scores
-        // For now, return a proportional amount based on score
-        let score_factor = score.total_score / 100.0;
-        let base_amount = (total_pool as f64 * score_factor) as u64;
+    /// Split the airdrop pool pro-rata across every scored user in the cohort
+    pub fn allocate_airdrop(&self, total_supply: u64, users: &[UserProfile]) -> Result<AllocationReport> {
+        let allocator = AirdropAllocator::new(&self.settings.scoring)?;
+        Ok(allocator.allocate_users(total_supply, users))
+    }
+    
+    /// Show how the configured curve and each plain curve would split the same cohort
+    pub fn compare_allocation_curves(&self, total_supply: u64, users: &[UserProfile]) -> Result<CurveComparison> {
+        let allocator = AirdropAllocator::new(&self.settings.scoring)?;
+        let candidates: Vec<_> = users.iter().filter_map(AllocationCandidate::from_profile).collect();
+        let curves = AllocationCurve::presets(&self.settings.scoring.allocation);
         
-        // Apply wagering requirement multiplier info
-        base_amount
+        Ok(allocator.compare(total_supply, &candidates, &curves))
     }
-}
\ No newline at end of file
+}
+
+fn aggregate_metrics(chain_metrics: &[ChainMetrics]) -> DegenMetrics {
+    let mut aggregated = DegenMetrics::default();
+    for chain_metric in chain_metrics {
+        aggregated.merge(&chain_metric.sourced_metrics());
+    }
+    aggregated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fixtures::mock_calculator,
+        models::{VerificationMethod, VerifiedAddress},
+    };
+
+    #[tokio::test]
+    async fn test_sybil_penalties_apply_to_calculated_scores() {
+        let mut user = UserProfile::new("alice".to_string());
+        user.add_verified_address(VerifiedAddress {
+            address: "7VXNdemo1111111111111111111111111111111111".to_string(),
+            chain: Chain::Solana,
+            verification_method: VerificationMethod::Signature {
+                message: "msg".to_string(),
+                signature: "sig".to_string(),
+            },
+            verified_at: Utc::now(),
+            nonce: "42".to_string(),
+        });
+
+        let clean = mock_calculator(&Settings::default()).calculate_user_score(&user).await.unwrap();
+        assert!(clean.total_score > 0.0);
+        assert_eq!(clean.sybil_multiplier, None);
+
+        let penalized = mock_calculator(&Settings::default())
+            .with_sybil_penalties(HashMap::from([("alice".to_string(), 0.5)]))
+            .calculate_user_score(&user).await.unwrap();
+        assert!((penalized.total_score - clean.total_score * 0.5).abs() < 1e-9);
+        assert_eq!(penalized.sybil_multiplier, Some(0.5));
+    }
+}
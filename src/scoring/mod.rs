@@ -2,8 +2,11 @@ pub mod algorithm;
 pub mod weights;
 pub mod calculator;
 pub mod simple_calculator;
+pub mod percentile;
+pub mod rescore;
 
-pub use algorithm::ScoringAlgorithm;
+pub use algorithm::{ScoringAlgorithm, ALGORITHM_VERSION};
 pub use weights::ScoringWeights;
 pub use calculator::ScoreCalculator;
+pub use percentile::PercentileEngine;
 pub use simple_calculator::ScoreCalculator as SimpleScoreCalculator;
\ No newline at end of file
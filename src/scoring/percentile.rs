@@ -0,0 +1,233 @@
+use crate::{
+    models::{CategoryPercentiles, DegenScore, Result, ScoreBreakdown},
+    storage::ScoreStore,
+};
+use std::collections::HashMap;
+
+/// Sorted score distribution for one dimension (total or a single category)
+#[derive(Debug, Clone, Default)]
+struct Distribution {
+    sorted: Vec<f64>,
+}
+
+impl Distribution {
+    fn insert(&mut self, value: f64) {
+        let idx = self.sorted.partition_point(|v| v.total_cmp(&value).is_lt());
+        self.sorted.insert(idx, value);
+    }
+
+    fn remove(&mut self, value: f64) {
+        let idx = self.sorted.partition_point(|v| v.total_cmp(&value).is_lt());
+        if idx < self.sorted.len() && self.sorted[idx].total_cmp(&value).is_eq() {
+            self.sorted.remove(idx);
+        }
+    }
+
+    /// Percentile rank: share of the cohort strictly below plus half of any ties
+    fn percentile(&self, value: f64) -> f64 {
+        if self.sorted.is_empty() {
+            return 0.0;
+        }
+
+        let below = self.sorted.partition_point(|v| v.total_cmp(&value).is_lt());
+        let at_or_below = self.sorted.partition_point(|v| v.total_cmp(&value).is_le());
+        let ties = at_or_below - below;
+
+        (below as f64 + ties as f64 / 2.0) / self.sorted.len() as f64 * 100.0
+    }
+}
+
+/// Ranks scores against a cohort of scored users.
+///
+/// Each user contributes exactly one score; inserting a user again replaces
+/// their previous score, so the cohort can be kept current as users are re-scored.
+#[derive(Debug, Clone, Default)]
+pub struct PercentileEngine {
+    scores: HashMap<String, (f64, ScoreBreakdown)>,
+    total: Distribution,
+    trading: Distribution,
+    gambling: Distribution,
+    defi_activity: Distribution,
+    nft_portfolio: Distribution,
+    longevity: Distribution,
+    risk_profile: Distribution,
+}
+
+impl PercentileEngine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build a cohort from (user_id, score) pairs, e.g. the output of a batch run
+    pub fn from_scores<'a, I>(scores: I) -> Self
+    where
+        I: IntoIterator<Item = (&'a str, &'a DegenScore)>,
+    {
+        let mut engine = Self::new();
+        for (user_id, score) in scores {
+            engine.insert(user_id, score);
+        }
+        engine
+    }
+
+    /// Build a cohort from the latest stored score of every user
+    pub async fn from_store(store: &ScoreStore) -> Result<Self> {
+        let records = store.get_latest_scores().await?;
+        Ok(Self::from_scores(records.iter().map(|r| (r.user_id.as_str(), &r.score))))
+    }
+
+    pub fn len(&self) -> usize {
+        self.scores.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scores.is_empty()
+    }
+
+    pub fn contains(&self, user_id: &str) -> bool {
+        self.scores.contains_key(user_id)
+    }
+
+    /// Add a user's score to the cohort, replacing any earlier score for them
+    pub fn insert(&mut self, user_id: &str, score: &DegenScore) {
+        self.remove(user_id);
+
+        let b = &score.breakdown;
+        self.total.insert(score.total_score);
+        self.trading.insert(b.trading_score);
+        self.gambling.insert(b.gambling_score);
+        self.defi_activity.insert(b.defi_activity_score);
+        self.nft_portfolio.insert(b.nft_portfolio_score);
+        self.longevity.insert(b.longevity_score);
+        self.risk_profile.insert(b.risk_profile_score);
+
+        self.scores.insert(user_id.to_string(), (score.total_score, b.clone()));
+    }
+
+    pub fn remove(&mut self, user_id: &str) -> bool {
+        let (total, b) = match self.scores.remove(user_id) {
+            Some(entry) => entry,
+            None => return false,
+        };
+
+        self.total.remove(total);
+        self.trading.remove(b.trading_score);
+        self.gambling.remove(b.gambling_score);
+        self.defi_activity.remove(b.defi_activity_score);
+        self.nft_portfolio.remove(b.nft_portfolio_score);
+        self.longevity.remove(b.longevity_score);
+        self.risk_profile.remove(b.risk_profile_score);
+        true
+    }
+
+    /// Percentile (0-100) of a total score within the cohort
+    pub fn percentile(&self, total_score: f64) -> f64 {
+        self.total.percentile(total_score)
+    }
+
+    pub fn category_percentiles(&self, breakdown: &ScoreBreakdown) -> CategoryPercentiles {
+        CategoryPercentiles {
+            trading_score: self.trading.percentile(breakdown.trading_score),
+            gambling_score: self.gambling.percentile(breakdown.gambling_score),
+            defi_activity_score: self.defi_activity.percentile(breakdown.defi_activity_score),
+            nft_portfolio_score: self.nft_portfolio.percentile(breakdown.nft_portfolio_score),
+            longevity_score: self.longevity.percentile(breakdown.longevity_score),
+            risk_profile_score: self.risk_profile.percentile(breakdown.risk_profile_score),
+        }
+    }
+
+    /// Fill in `percentile` and `category_percentiles` from the current cohort
+    pub fn apply(&self, score: &mut DegenScore) {
+        score.percentile = self.percentile(score.total_score);
+        score.category_percentiles = Some(self.category_percentiles(&score.breakdown));
+    }
+
+    /// Insert a freshly calculated score and rank it against the cohort
+    pub fn rank(&mut self, user_id: &str, score: &mut DegenScore) {
+        self.insert(user_id, score);
+        self.apply(score);
+    }
+
+    /// Re-rank every user's latest stored score against this cohort
+    pub async fn refresh_store(&self, store: &ScoreStore) -> Result<usize> {
+        let mut updated = 0;
+        for mut record in store.get_latest_scores().await? {
+            self.apply(&mut record.score);
+            store.update_score(record.id, &record.score).await?;
+            updated += 1;
+        }
+        Ok(updated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::DegenScoreBuilder;
+
+    fn score(total: f64, trading: f64) -> DegenScore {
+        DegenScoreBuilder::new(total).trading(trading).build()
+    }
+
+    #[test]
+    fn test_percentile_ranks_with_ties() {
+        let scores = [("a", score(10.0, 5.0)), ("b", score(20.0, 5.0)), ("c", score(30.0, 5.0)), ("d", score(40.0, 25.0))];
+        let engine = PercentileEngine::from_scores(scores.iter().map(|(id, s)| (*id, s)));
+
+        assert_eq!(engine.len(), 4);
+        assert_eq!(engine.percentile(10.0), 12.5);
+        assert_eq!(engine.percentile(40.0), 87.5);
+        assert_eq!(engine.percentile(100.0), 100.0);
+        assert_eq!(engine.percentile(0.0), 0.0);
+
+        // Three users tie on trading, one is clearly ahead
+        let categories = engine.category_percentiles(&scores[0].1.breakdown);
+        assert_eq!(categories.trading_score, 37.5);
+        assert_eq!(engine.category_percentiles(&scores[3].1.breakdown).trading_score, 87.5);
+        assert_eq!(categories.gambling_score, 50.0);
+    }
+
+    #[test]
+    fn test_incremental_rescore_replaces_previous_score() {
+        let mut engine = PercentileEngine::new();
+        let mut alice = score(10.0, 0.0);
+        let mut bob = score(50.0, 0.0);
+
+        engine.rank("alice", &mut alice);
+        assert_eq!(alice.percentile, 50.0);
+
+        engine.rank("bob", &mut bob);
+        assert_eq!(bob.percentile, 75.0);
+
+        // Alice is re-scored above Bob; her old score must not linger in the cohort
+        let mut alice = score(90.0, 0.0);
+        engine.rank("alice", &mut alice);
+        assert_eq!(engine.len(), 2);
+        assert_eq!(alice.percentile, 75.0);
+        assert_eq!(engine.percentile(50.0), 25.0);
+        assert!(alice.category_percentiles.is_some());
+
+        assert!(engine.remove("bob"));
+        assert!(!engine.remove("bob"));
+        assert_eq!(engine.percentile(90.0), 50.0);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_store_reranks_latest_scores() {
+        use crate::models::UserProfile;
+
+        let store = ScoreStore::in_memory().await.unwrap();
+        for (id, total) in [("alice", 10.0), ("bob", 30.0)] {
+            store.upsert_user(&UserProfile::new(id.to_string())).await.unwrap();
+            store.record_score(id, &score(total, 0.0)).await.unwrap();
+        }
+
+        let engine = PercentileEngine::from_store(&store).await.unwrap();
+        assert_eq!(engine.len(), 2);
+        assert_eq!(engine.refresh_store(&store).await.unwrap(), 2);
+
+        let bob = store.get_latest_score("bob").await.unwrap().unwrap();
+        assert_eq!(bob.percentile, 75.0);
+        assert_eq!(bob.category_percentiles.unwrap().trading_score, 50.0);
+    }
+}
@@ -0,0 +1,250 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use crate::{
+    models::{DegenScore, Result, ScoreTier, UserProfile},
+    scoring::{algorithm::ALGORITHM_VERSION, PercentileEngine, ScoringAlgorithm},
+    storage::ScoreStore,
+};
+
+/// One user's stored score next to the score recomputed from their stored metrics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RescoreEntry {
+    pub user_id: String,
+    pub previous_total: f64,
+    pub previous_tier: ScoreTier,
+    /// `None` for scores recorded before versioning was introduced
+    pub previous_version: Option<String>,
+    pub previous_fingerprint: Option<String>,
+    pub new_total: f64,
+    pub new_tier: ScoreTier,
+    pub delta: f64,
+    /// Sybil penalty applied to the new score
+    #[serde(default)]
+    pub sybil_multiplier: Option<f64>,
+}
+
+impl RescoreEntry {
+    pub fn tier_changed(&self) -> bool {
+        self.previous_tier != self.new_tier
+    }
+}
+
+/// Per-user diff of re-scoring a cohort under one algorithm version and configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RescoreReport {
+    pub algorithm_version: String,
+    pub fingerprint: String,
+    pub entries: Vec<RescoreEntry>,
+    /// Users without a stored score, and so without metrics to re-score from
+    pub skipped_users: Vec<String>,
+    /// Recomputed scores, in the order of `entries`
+    #[serde(skip)]
+    pub scores: Vec<DegenScore>,
+}
+
+impl RescoreReport {
+    pub fn tier_changes(&self) -> usize {
+        self.entries.iter().filter(|e| e.tier_changed()).count()
+    }
+
+    pub fn mean_delta(&self) -> f64 {
+        if self.entries.is_empty() {
+            return 0.0;
+        }
+        self.entries.iter().map(|e| e.delta).sum::<f64>() / self.entries.len() as f64
+    }
+
+    /// Plain-text diff, largest score changes first
+    pub fn render_text(&self, max_users: usize) -> String {
+        let mut out = String::new();
+
+        let _ = writeln!(out, "Re-scored {} users with algorithm {} ({}); {} tier changes, mean delta {:+.2}",
+            self.entries.len(), self.algorithm_version, self.fingerprint, self.tier_changes(), self.mean_delta());
+        if !self.skipped_users.is_empty() {
+            let _ = writeln!(out, "Skipped {} users with no stored score", self.skipped_users.len());
+        }
+
+        let mut order: Vec<&RescoreEntry> = self.entries.iter().collect();
+        order.sort_by(|a, b| b.delta.abs().total_cmp(&a.delta.abs()).then(a.user_id.cmp(&b.user_id)));
+
+        let _ = writeln!(out, "\n{:<20} {:>14} {:>8} {:>8} {:>8} {:>10} {:>10}",
+            "user", "from", "old", "new", "delta", "old tier", "new tier");
+        for e in order.into_iter().take(max_users) {
+            let from = e.previous_version.as_deref().unwrap_or("unversioned");
+            let _ = writeln!(out, "{:<20} {:>14} {:>8.2} {:>8.2} {:>+8.2} {:>10} {:>10}{}",
+                e.user_id, from, e.previous_total, e.new_total, e.delta,
+                format!("{:?}", e.previous_tier), format!("{:?}", e.new_tier),
+                if e.tier_changed() { " *" } else { "" });
+        }
+
+        out
+    }
+}
+
+/// Recompute every scored user's score from their stored aggregated metrics, applying the
+/// sybil multiplier of each flagged user (see `SybilReport::penalties`). No chain data is fetched.
+pub fn rescore_users(
+    algorithm: &ScoringAlgorithm,
+    users: &[UserProfile],
+    sybil_penalties: &HashMap<String, f64>,
+) -> RescoreReport {
+    let mut report = RescoreReport {
+        algorithm_version: ALGORITHM_VERSION.to_string(),
+        fingerprint: algorithm.fingerprint(),
+        entries: Vec::new(),
+        skipped_users: Vec::new(),
+        scores: Vec::new(),
+    };
+
+    for user in users {
+        let previous = match &user.degen_score {
+            Some(score) => score,
+            None => {
+                report.skipped_users.push(user.id.clone());
+                continue;
+            }
+        };
+
+        let mut score = algorithm.calculate_score(&user.aggregated_metrics);
+        if let Some(multiplier) = sybil_penalties.get(&user.id) {
+            score.apply_sybil_penalty(*multiplier);
+        }
+        report.entries.push(RescoreEntry {
+            user_id: user.id.clone(),
+            previous_total: previous.total_score,
+            previous_tier: previous.tier.clone(),
+            previous_version: previous.algorithm_version.clone(),
+            previous_fingerprint: previous.fingerprint.clone(),
+            new_total: score.total_score,
+            new_tier: score.tier.clone(),
+            delta: score.total_score - previous.total_score,
+            sybil_multiplier: score.sybil_multiplier,
+        });
+        report.scores.push(score);
+    }
+
+    // Rank the new scores against each other, not against the old cohort
+    let engine = PercentileEngine::from_scores(
+        report.entries.iter().map(|e| e.user_id.as_str()).zip(&report.scores),
+    );
+    for score in &mut report.scores {
+        engine.apply(score);
+    }
+
+    report
+}
+
+/// Re-score every stored user; with `persist` the new scores are appended to their history
+pub async fn rescore_store(
+    algorithm: &ScoringAlgorithm,
+    store: &ScoreStore,
+    sybil_penalties: &HashMap<String, f64>,
+    persist: bool,
+) -> Result<RescoreReport> {
+    let users = store.list_users().await?;
+    let report = rescore_users(algorithm, &users, sybil_penalties);
+
+    if persist {
+        for (entry, score) in report.entries.iter().zip(&report.scores) {
+            store.record_score(&entry.user_id, score).await?;
+        }
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::ScoringWeights, models::DegenMetrics};
+    use rust_decimal::Decimal;
+
+    fn metrics(trades: u32) -> DegenMetrics {
+        DegenMetrics {
+            gmx_volume_usd: Decimal::from(250_000),
+            gmx_trades: trades,
+            casinos_used: 2,
+            wallet_age_days: 900,
+            active_days: 200,
+            ..DegenMetrics::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rescore_from_stored_metrics_under_new_weights() {
+        let store = ScoreStore::in_memory().await.unwrap();
+        let current = ScoringAlgorithm::new(ScoringWeights::default());
+
+        for (id, trades) in [("alice", 10), ("bob", 100)] {
+            let mut user = UserProfile::new(id.to_string());
+            user.aggregated_metrics = metrics(trades);
+            store.upsert_user(&user).await.unwrap();
+            store.record_score(id, &current.calculate_score(&user.aggregated_metrics)).await.unwrap();
+        }
+        store.upsert_user(&UserProfile::new("carol".to_string())).await.unwrap();
+
+        // Same configuration: identical scores and fingerprint
+        let unchanged = rescore_store(&current, &store, &HashMap::new(), false).await.unwrap();
+        assert_eq!(unchanged.entries.len(), 2);
+        assert_eq!(unchanged.skipped_users, vec!["carol".to_string()]);
+        assert!(unchanged.entries.iter().all(|e| e.delta.abs() < 1e-9 && !e.tier_changed()));
+        assert_eq!(unchanged.entries[0].previous_fingerprint.as_deref(), Some(current.fingerprint().as_str()));
+
+        // Shift trade-count points into gambling
+        let candidate = ScoringAlgorithm::new(ScoringWeights {
+            trading_count: 0.0,
+            gambling_platforms: 20.0,
+            ..ScoringWeights::default()
+        });
+        assert_ne!(candidate.fingerprint(), current.fingerprint());
+
+        let report = rescore_store(&candidate, &store, &HashMap::new(), true).await.unwrap();
+        let bob = report.entries.iter().find(|e| e.user_id == "bob").unwrap();
+        let alice = report.entries.iter().find(|e| e.user_id == "alice").unwrap();
+        assert!((bob.delta - (-10.0 + 20.0 * 2.0 / 3.0 - 10.0 * 2.0 / 3.0)).abs() < 1e-9);
+        assert!(alice.delta > bob.delta);
+        assert!(report.render_text(10).contains("alice"));
+
+        // Persisted scores carry the new fingerprint
+        let latest = store.get_latest_score("bob").await.unwrap().unwrap();
+        assert_eq!(latest.fingerprint, Some(candidate.fingerprint()));
+        assert_eq!(latest.algorithm_version.as_deref(), Some(ALGORITHM_VERSION));
+        assert_eq!(store.get_score_history("bob", 10).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_rescore_persists_sybil_penalties() {
+        let store = ScoreStore::in_memory().await.unwrap();
+        let algorithm = ScoringAlgorithm::new(ScoringWeights::default());
+
+        for id in ["alice", "bob"] {
+            let mut user = UserProfile::new(id.to_string());
+            user.aggregated_metrics = metrics(50);
+            store.upsert_user(&user).await.unwrap();
+            store.record_score(id, &algorithm.calculate_score(&user.aggregated_metrics)).await.unwrap();
+        }
+
+        let penalties = HashMap::from([("bob".to_string(), 0.5)]);
+        let report = rescore_store(&algorithm, &store, &penalties, true).await.unwrap();
+        let bob = report.entries.iter().find(|e| e.user_id == "bob").unwrap();
+        assert_eq!(bob.sybil_multiplier, Some(0.5));
+        assert!((bob.new_total + bob.previous_total / 2.0 - bob.previous_total).abs() < 1e-9);
+
+        let alice = store.get_latest_score("alice").await.unwrap().unwrap();
+        let bob = store.get_latest_score("bob").await.unwrap().unwrap();
+        assert_eq!(alice.sybil_multiplier, None);
+        assert_eq!(bob.sybil_multiplier, Some(0.5));
+        assert!((bob.total_score - alice.total_score / 2.0).abs() < 1e-9);
+
+        // The breakdown and explanation are scaled with the total
+        let b = &bob.breakdown;
+        let sum = b.trading_score + b.gambling_score + b.defi_activity_score
+            + b.nft_portfolio_score + b.longevity_score + b.risk_profile_score;
+        assert!((sum - bob.total_score).abs() < 1e-9);
+        let explanation = bob.explanation.unwrap();
+        assert!((explanation.total_score - bob.total_score).abs() < 1e-9);
+        assert!((explanation.breakdown().trading_score - b.trading_score).abs() < 1e-9);
+    }
+}
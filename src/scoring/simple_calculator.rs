@@ -2,12 +2,11 @@ use crate::{
     models::{Chain},
     chains::{ChainClient, EvmClient, SolanaClient},
     config::{RpcConfig, ScoringWeights},
-    scoring::algorithm::ScoringAlgorithm,
+    scoring::{algorithm::ScoringAlgorithm, percentile::PercentileEngine},
 };
-use std::sync::Arc;
+use std::sync::{Arc, PoisonError, RwLock};
 use anyhow::Result;
 use tracing::{info, warn};
-use chrono::Utc;
 
 pub struct ScoreCalculator {
     eth_client: Arc<EvmClient>,
@@ -16,6 +15,7 @@ pub struct ScoreCalculator {
     blast_client: Arc<EvmClient>,
     sol_client: Arc<SolanaClient>,
     algorithm: ScoringAlgorithm,
+    percentiles: RwLock<PercentileEngine>,
 }
 
 impl ScoreCalculator {
@@ -94,6 +94,7 @@ impl ScoreCalculator {
             blast_client,
             sol_client,
             algorithm: ScoringAlgorithm::new(ScoringWeights::default()),
+            percentiles: RwLock::new(PercentileEngine::new()),
         })
     }
     
@@ -157,22 +158,16 @@ impl ScoreCalculator {
         // Aggregate metrics
         let mut aggregated = crate::models::DegenMetrics::default();
         for metrics in &all_metrics {
-            aggregated.merge(&metrics.metrics);
+            aggregated.merge(&metrics.sourced_metrics());
         }
         
         // Calculate score
-        let score_result = self.algorithm.calculate_score(&aggregated);
-        let total_score = score_result.total_score;
+        let mut score = self.algorithm.calculate_score(&aggregated);
         
+        // Rank against everyone scored during this session
+        self.percentiles.write().unwrap_or_else(PoisonError::into_inner)
+            .rank(user_id, &mut score);
         
-        let tier = crate::models::ScoreTier::from_score(total_score);
-        
-        Ok(crate::models::DegenScore {
-            total_score,
-            percentile: 0.0, // Would calculate from database in production
-            breakdown: score_result.breakdown,
-            calculated_at: Utc::now(),
-            tier,
-        })
+        Ok(score)
     }
 }
\ No newline at end of file
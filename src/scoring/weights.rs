@@ -1,16 +1,31 @@
 use serde::{Deserialize, Serialize};
+use tracing::warn;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Points available for each scoring sub-component. Grouped by `ScoreBreakdown`
+/// category, they must sum to exactly 100.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "RawScoringWeights")]
 pub struct ScoringWeights {
+    // Trading
     pub trading_volume: f64,
     pub trading_count: f64,
+    // Gambling
     pub gambling_platforms: f64,
     pub casino_tokens: f64,
+    // DeFi activity
     pub defi_protocols: f64,
     pub token_diversity: f64,
-    pub nft_holdings: f64,
+    // NFT portfolio
+    pub nft_count: f64,
+    pub nft_value: f64,
+    // Longevity
     pub wallet_age: f64,
     pub activity_consistency: f64,
+    // Risk profile
+    pub risk_volatility: f64,
+    pub risk_multichain: f64,
+    pub risk_leverage: f64,
+    pub risk_battle_tested: f64,
 }
 
 impl Default for ScoringWeights {
@@ -22,48 +37,85 @@ impl Default for ScoringWeights {
             casino_tokens: 5.0,
             defi_protocols: 10.0,
             token_diversity: 5.0,
-            nft_holdings: 10.0,
+            nft_count: 5.0,
+            nft_value: 5.0,
             wallet_age: 10.0,
             activity_consistency: 10.0,
+            risk_volatility: 3.75,
+            risk_multichain: 3.75,
+            risk_leverage: 3.75,
+            risk_battle_tested: 3.75,
         }
     }
 }
 
 impl ScoringWeights {
+    pub fn trading(&self) -> f64 {
+        self.trading_volume + self.trading_count
+    }
+
+    pub fn gambling(&self) -> f64 {
+        self.gambling_platforms + self.casino_tokens
+    }
+
+    pub fn defi_activity(&self) -> f64 {
+        self.defi_protocols + self.token_diversity
+    }
+
+    pub fn nft_portfolio(&self) -> f64 {
+        self.nft_count + self.nft_value
+    }
+
+    pub fn longevity(&self) -> f64 {
+        self.wallet_age + self.activity_consistency
+    }
+
+    pub fn risk_profile(&self) -> f64 {
+        self.risk_volatility + self.risk_multichain + self.risk_leverage + self.risk_battle_tested
+    }
+
     pub fn total(&self) -> f64 {
-        self.trading_volume +
-        self.trading_count +
-        self.gambling_platforms +
-        self.casino_tokens +
-        self.defi_protocols +
-        self.token_diversity +
-        self.nft_holdings +
-        self.wallet_age +
-        self.activity_consistency
+        self.trading() +
+        self.gambling() +
+        self.defi_activity() +
+        self.nft_portfolio() +
+        self.longevity() +
+        self.risk_profile()
     }
-    
+
+    pub(crate) fn components(&self) -> [(&'static str, f64); 14] {
+        [
+            ("trading_volume", self.trading_volume),
+            ("trading_count", self.trading_count),
+            ("gambling_platforms", self.gambling_platforms),
+            ("casino_tokens", self.casino_tokens),
+            ("defi_protocols", self.defi_protocols),
+            ("token_diversity", self.token_diversity),
+            ("nft_count", self.nft_count),
+            ("nft_value", self.nft_value),
+            ("wallet_age", self.wallet_age),
+            ("activity_consistency", self.activity_consistency),
+            ("risk_volatility", self.risk_volatility),
+            ("risk_multichain", self.risk_multichain),
+            ("risk_leverage", self.risk_leverage),
+            ("risk_battle_tested", self.risk_battle_tested),
+        ]
+    }
+
     pub fn validate(&self) -> Result<(), String> {
+        // Check that all weights are non-negative
+        if let Some((name, _)) = self.components().iter().find(|(_, w)| !w.is_finite() || *w < 0.0) {
+            return Err(format!("Weight {} must be non-negative", name));
+        }
+
         let total = self.total();
         if (total - 100.0).abs() > 0.01 {
             return Err(format!("Weights must sum to 100, got {}", total));
         }
-        
-        // Check that all weights are non-negative
-        if self.trading_volume < 0.0 ||
-           self.trading_count < 0.0 ||
-           self.gambling_platforms < 0.0 ||
-           self.casino_tokens < 0.0 ||
-           self.defi_protocols < 0.0 ||
-           self.token_diversity < 0.0 ||
-           self.nft_holdings < 0.0 ||
-           self.wallet_age < 0.0 ||
-           self.activity_consistency < 0.0 {
-            return Err("All weights must be non-negative".to_string());
-        }
-        
+
         Ok(())
     }
-    
+
     pub fn normalize(&mut self) {
         let total = self.total();
         if total > 0.0 {
@@ -74,9 +126,143 @@ impl ScoringWeights {
             self.casino_tokens *= factor;
             self.defi_protocols *= factor;
             self.token_diversity *= factor;
-            self.nft_holdings *= factor;
+            self.nft_count *= factor;
+            self.nft_value *= factor;
             self.wallet_age *= factor;
             self.activity_consistency *= factor;
+            self.risk_volatility *= factor;
+            self.risk_multichain *= factor;
+            self.risk_leverage *= factor;
+            self.risk_battle_tested *= factor;
+        }
+    }
+}
+
+/// On-disk form of `[scoring.weights]`, accepting both the current layout and the
+/// legacy one (single `nft_holdings` weight, risk as a fixed 15-point bonus)
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RawScoringWeights {
+    pub trading_volume: Option<f64>,
+    pub trading_count: Option<f64>,
+    pub gambling_platforms: Option<f64>,
+    pub casino_tokens: Option<f64>,
+    pub defi_protocols: Option<f64>,
+    pub token_diversity: Option<f64>,
+    pub nft_holdings: Option<f64>,
+    pub nft_count: Option<f64>,
+    pub nft_value: Option<f64>,
+    pub wallet_age: Option<f64>,
+    pub activity_consistency: Option<f64>,
+    pub risk_volatility: Option<f64>,
+    pub risk_multichain: Option<f64>,
+    pub risk_leverage: Option<f64>,
+    pub risk_battle_tested: Option<f64>,
+}
+
+impl RawScoringWeights {
+    pub fn is_legacy(&self) -> bool {
+        self.nft_holdings.is_some()
+    }
+}
+
+impl TryFrom<RawScoringWeights> for ScoringWeights {
+    type Error = String;
+
+    fn try_from(raw: RawScoringWeights) -> Result<Self, Self::Error> {
+        let defaults = ScoringWeights::default();
+        let legacy = raw.is_legacy();
+        let required = |value: Option<f64>, name: &str| {
+            value.ok_or_else(|| format!("Missing scoring weight: {}", name))
+        };
+
+        // Legacy files split nft_holdings evenly between count and value,
+        // exactly as the old algorithm did
+        let (nft_count, nft_value) = match raw.nft_holdings {
+            Some(holdings) => (holdings / 2.0, holdings / 2.0),
+            None => (required(raw.nft_count, "nft_count")?, required(raw.nft_value, "nft_value")?),
+        };
+
+        let mut weights = ScoringWeights {
+            trading_volume: required(raw.trading_volume, "trading_volume")?,
+            trading_count: required(raw.trading_count, "trading_count")?,
+            gambling_platforms: required(raw.gambling_platforms, "gambling_platforms")?,
+            casino_tokens: required(raw.casino_tokens, "casino_tokens")?,
+            defi_protocols: required(raw.defi_protocols, "defi_protocols")?,
+            token_diversity: required(raw.token_diversity, "token_diversity")?,
+            nft_count,
+            nft_value,
+            wallet_age: required(raw.wallet_age, "wallet_age")?,
+            activity_consistency: required(raw.activity_consistency, "activity_consistency")?,
+            // Legacy risk was a fixed bonus of four 3.75-point signals
+            risk_volatility: raw.risk_volatility.unwrap_or(defaults.risk_volatility),
+            risk_multichain: raw.risk_multichain.unwrap_or(defaults.risk_multichain),
+            risk_leverage: raw.risk_leverage.unwrap_or(defaults.risk_leverage),
+            risk_battle_tested: raw.risk_battle_tested.unwrap_or(defaults.risk_battle_tested),
+        };
+
+        if legacy {
+            // The old algorithm only awarded half of each longevity weight
+            weights.wallet_age /= 2.0;
+            weights.activity_consistency /= 2.0;
+
+            let total = weights.total();
+            if (total - 100.0).abs() > 0.01 {
+                weights.normalize();
+            }
+            warn!(
+                "Legacy [scoring.weights] (nft_holdings, risk bonus) migrated in memory from a total of {}; \
+                 run `degen-scorer migrate-config` to update the file",
+                total
+            );
         }
+
+        Ok(weights)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_weights_cover_all_categories() {
+        let weights = ScoringWeights::default();
+        assert!(weights.validate().is_ok());
+        assert_eq!(weights.risk_profile(), 15.0);
+        assert_eq!(weights.nft_portfolio(), 10.0);
+
+        let mut skewed = ScoringWeights { risk_leverage: 20.0, ..ScoringWeights::default() };
+        assert!(skewed.validate().is_err());
+        skewed.normalize();
+        assert!(skewed.validate().is_ok());
+
+        let negative = ScoringWeights { nft_value: -5.0, nft_count: 15.0, ..ScoringWeights::default() };
+        assert!(negative.validate().unwrap_err().contains("nft_value"));
+    }
+
+    #[test]
+    fn test_legacy_weights_are_migrated() {
+        // The old default.toml: base weights summing to 100 plus the 15-point risk bonus
+        let legacy: ScoringWeights = serde_json::from_value(serde_json::json!({
+            "trading_volume": 15.0,
+            "trading_count": 10.0,
+            "gambling_platforms": 10.0,
+            "casino_tokens": 5.0,
+            "defi_protocols": 10.0,
+            "token_diversity": 5.0,
+            "nft_holdings": 10.0,
+            "wallet_age": 10.0,
+            "activity_consistency": 25.0,
+        })).unwrap();
+
+        assert!(legacy.validate().is_ok());
+        assert!((legacy.nft_count - legacy.nft_value).abs() < 1e-9);
+        // Effective points (longevity was halved) keep their proportions in the rescale to 100
+        assert!((legacy.activity_consistency / legacy.wallet_age - 2.5).abs() < 1e-9);
+        assert!((legacy.risk_profile() - 15.0 * 100.0 / 97.5).abs() < 1e-9);
+        assert!((legacy.longevity() - 17.5 * 100.0 / 97.5).abs() < 1e-9);
+
+        let missing = serde_json::from_value::<ScoringWeights>(serde_json::json!({ "trading_volume": 100.0 }));
+        assert!(missing.is_err());
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,679 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+use sqlx::{
+    sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions},
+    Row,
+};
+use std::str::FromStr;
+use std::time::Duration;
+use tracing::info;
+
+use crate::{
+    config::DatabaseSettings,
+    models::{
+        Chain, ChainMetrics, DegenMetrics, DegenScore, DegenScoreError, Result,
+        UserProfile, VerificationMethod, VerifiedAddress,
+    },
+    verification::{AddressEvent, AddressEventKind, AddressKey, AddressNamespace},
+};
+
+static MIGRATOR: sqlx::migrate::Migrator = sqlx::migrate!("./migrations");
+
+/// A stored score together with its row id
+#[derive(Debug, Clone)]
+pub struct ScoreRecord {
+    pub id: i64,
+    pub user_id: String,
+    pub score: DegenScore,
+}
+
+/// SQLite-backed persistence for users, verified addresses, metrics and scores
+#[derive(Debug, Clone)]
+pub struct ScoreStore {
+    pool: SqlitePool,
+}
+
+impl ScoreStore {
+    /// Open (creating if needed) the configured database and apply migrations
+    pub async fn connect(settings: &DatabaseSettings) -> Result<Self> {
+        let options = SqliteConnectOptions::from_str(&settings.url)?
+            .create_if_missing(true)
+            .foreign_keys(true);
+
+        let pool = SqlitePoolOptions::new()
+            .max_connections(settings.max_connections)
+            .min_connections(settings.min_connections)
+            .acquire_timeout(Duration::from_secs(settings.connect_timeout_seconds))
+            .connect_with(options)
+            .await?;
+
+        let store = Self { pool };
+        store.migrate().await?;
+
+        info!("Connected to score database at {}", settings.url);
+        Ok(store)
+    }
+
+    /// Open the configured database for reading only. Nothing is created or migrated,
+    /// so this fails when the database doesn't exist yet.
+    pub async fn connect_read_only(settings: &DatabaseSettings) -> Result<Self> {
+        let options = SqliteConnectOptions::from_str(&settings.url)?
+            .create_if_missing(false)
+            .read_only(true);
+
+        let pool = SqlitePoolOptions::new()
+            .max_connections(settings.max_connections)
+            .min_connections(settings.min_connections)
+            .acquire_timeout(Duration::from_secs(settings.connect_timeout_seconds))
+            .connect_with(options)
+            .await?;
+
+        info!("Opened score database at {} read-only", settings.url);
+        Ok(Self { pool })
+    }
+
+    /// Private in-memory database, mainly for tests and dry runs
+    pub async fn in_memory() -> Result<Self> {
+        let options = SqliteConnectOptions::from_str("sqlite::memory:")?
+            .foreign_keys(true);
+
+        // A single connection keeps every query on the same in-memory database
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(options)
+            .await?;
+
+        let store = Self { pool };
+        store.migrate().await?;
+        Ok(store)
+    }
+
+    pub async fn migrate(&self) -> Result<()> {
+        MIGRATOR.run(&self.pool).await.map_err(sqlx::Error::from)?;
+        Ok(())
+    }
+
+    pub fn pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+
+    // ---- Users ----
+
+    /// Insert or update a user along with all of its verified addresses
+    pub async fn upsert_user(&self, user: &UserProfile) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            "INSERT INTO users (id, aggregated_metrics, created_at, updated_at)
+             VALUES (?, ?, ?, ?)
+             ON CONFLICT(id) DO UPDATE SET
+                aggregated_metrics = excluded.aggregated_metrics,
+                updated_at = excluded.updated_at"
+        )
+            .bind(&user.id)
+            .bind(serde_json::to_string(&user.aggregated_metrics)?)
+            .bind(format_timestamp(&user.created_at))
+            .bind(format_timestamp(&user.updated_at))
+            .execute(&mut *tx)
+            .await?;
+
+        // Addresses dropped from the profile are removed, the rest inserted or updated
+        let stored = sqlx::query("SELECT chain, address FROM verified_addresses WHERE user_id = ?")
+            .bind(&user.id)
+            .fetch_all(&mut *tx)
+            .await?;
+        for row in &stored {
+            let chain: String = row.try_get("chain")?;
+            let address: String = row.try_get("address")?;
+            let kept = user.verified_addresses.iter()
+                .any(|verified| verified.chain.as_str() == chain && verified.address == address);
+            if !kept {
+                sqlx::query("DELETE FROM verified_addresses WHERE user_id = ? AND chain = ? AND address = ?")
+                    .bind(&user.id)
+                    .bind(&chain)
+                    .bind(&address)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+        }
+
+        for address in &user.verified_addresses {
+            insert_address(&mut tx, &user.id, address).await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Load a user with verified addresses and latest score
+    pub async fn get_user(&self, user_id: &str) -> Result<Option<UserProfile>> {
+        let row = sqlx::query(
+            "SELECT id, aggregated_metrics, created_at, updated_at FROM users WHERE id = ?"
+        )
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        let row = match row {
+            Some(row) => row,
+            None => return Ok(None),
+        };
+
+        let aggregated_metrics: DegenMetrics = serde_json::from_str(row.try_get("aggregated_metrics")?)?;
+
+        Ok(Some(UserProfile {
+            id: row.try_get("id")?,
+            verified_addresses: self.get_verified_addresses(user_id).await?,
+            aggregated_metrics,
+            degen_score: self.get_latest_score(user_id).await?,
+            created_at: parse_timestamp(row.try_get("created_at")?)?,
+            updated_at: parse_timestamp(row.try_get("updated_at")?)?,
+        }))
+    }
+
+    pub async fn list_user_ids(&self) -> Result<Vec<String>> {
+        let rows = sqlx::query("SELECT id FROM users ORDER BY id")
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter()
+            .map(|row| row.try_get("id").map_err(DegenScoreError::from))
+            .collect()
+    }
+
+    pub async fn list_users(&self) -> Result<Vec<UserProfile>> {
+        let mut users = Vec::new();
+        for id in self.list_user_ids().await? {
+            if let Some(user) = self.get_user(&id).await? {
+                users.push(user);
+            }
+        }
+        Ok(users)
+    }
+
+    /// Delete a user; addresses, metrics and scores cascade
+    pub async fn delete_user(&self, user_id: &str) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM users WHERE id = ?")
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    // ---- Verified addresses ----
+
+    pub async fn add_verified_address(&self, user_id: &str, address: &VerifiedAddress) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        insert_address(&mut tx, user_id, address).await?;
+
+        sqlx::query("UPDATE users SET updated_at = ? WHERE id = ?")
+            .bind(format_timestamp(&Utc::now()))
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn remove_verified_address(&self, user_id: &str, chain: &Chain, address: &str) -> Result<bool> {
+        let result = sqlx::query(
+            "DELETE FROM verified_addresses WHERE user_id = ? AND chain = ? AND address = ?"
+        )
+            .bind(user_id)
+            .bind(chain.as_str())
+            .bind(address)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn get_verified_addresses(&self, user_id: &str) -> Result<Vec<VerifiedAddress>> {
+        let rows = sqlx::query(
+            "SELECT address, chain, verification_method, verified_at, nonce
+             FROM verified_addresses WHERE user_id = ? ORDER BY id"
+        )
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter()
+            .map(|row| {
+                let chain_str: String = row.try_get("chain")?;
+                let method: VerificationMethod = serde_json::from_str(row.try_get("verification_method")?)?;
+
+                Ok(VerifiedAddress {
+                    address: row.try_get("address")?,
+                    chain: Chain::from_str(&chain_str).ok_or_else(|| {
+                        DegenScoreError::ChainNotSupported(chain_str.clone())
+                    })?,
+                    verification_method: method,
+                    verified_at: parse_timestamp(row.try_get("verified_at")?)?,
+                    nonce: row.try_get("nonce")?,
+                })
+            })
+            .collect()
+    }
+
+    // ---- Chain metrics ----
+
+    /// Record a per-chain metrics snapshot for a user
+    pub async fn save_chain_metrics(&self, user_id: &str, metrics: &ChainMetrics) -> Result<i64> {
+        let result = sqlx::query(
+            "INSERT INTO chain_metrics (user_id, chain, address, metrics, last_updated)
+             VALUES (?, ?, ?, ?, ?)"
+        )
+            .bind(user_id)
+            .bind(&metrics.chain)
+            .bind(&metrics.address)
+            .bind(serde_json::to_string(&metrics.metrics)?)
+            .bind(format_timestamp(&metrics.last_updated))
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    /// Most recent snapshot for each (chain, address) the user has
+    pub async fn get_latest_chain_metrics(&self, user_id: &str) -> Result<Vec<ChainMetrics>> {
+        let rows = sqlx::query(
+            "SELECT chain, address, metrics, last_updated FROM chain_metrics c
+             WHERE user_id = ? AND id = (
+                SELECT id FROM chain_metrics
+                WHERE user_id = c.user_id AND chain = c.chain AND address = c.address
+                ORDER BY last_updated DESC, id DESC LIMIT 1
+             )
+             ORDER BY chain, address"
+        )
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter().map(row_to_chain_metrics).collect()
+    }
+
+    pub async fn get_chain_metrics_history(
+        &self,
+        user_id: &str,
+        chain: &str,
+        address: &str,
+    ) -> Result<Vec<ChainMetrics>> {
+        let rows = sqlx::query(
+            "SELECT chain, address, metrics, last_updated FROM chain_metrics
+             WHERE user_id = ? AND chain = ? AND address = ?
+             ORDER BY last_updated DESC, id DESC"
+        )
+            .bind(user_id)
+            .bind(chain)
+            .bind(address)
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter().map(row_to_chain_metrics).collect()
+    }
+
+    // ---- Scores ----
+
+    /// Append a score to the user's history
+    pub async fn record_score(&self, user_id: &str, score: &DegenScore) -> Result<i64> {
+        let result = sqlx::query(
+            "INSERT INTO scores (user_id, total_score, tier, score, calculated_at)
+             VALUES (?, ?, ?, ?, ?)"
+        )
+            .bind(user_id)
+            .bind(score.total_score)
+            .bind(format!("{:?}", score.tier))
+            .bind(serde_json::to_string(score)?)
+            .bind(format_timestamp(&score.calculated_at))
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    pub async fn get_latest_score(&self, user_id: &str) -> Result<Option<DegenScore>> {
+        let row = sqlx::query(
+            "SELECT score FROM scores WHERE user_id = ?
+             ORDER BY calculated_at DESC, id DESC LIMIT 1"
+        )
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        match row {
+            Some(row) => Ok(Some(serde_json::from_str(row.try_get("score")?)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Score history for a user, newest first
+    pub async fn get_score_history(&self, user_id: &str, limit: u32) -> Result<Vec<ScoreRecord>> {
+        let rows = sqlx::query(
+            "SELECT id, user_id, score FROM scores WHERE user_id = ?
+             ORDER BY calculated_at DESC, id DESC LIMIT ?"
+        )
+            .bind(user_id)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter().map(row_to_score_record).collect()
+    }
+
+    /// Latest score of every user, in no particular order
+    pub async fn get_latest_scores(&self) -> Result<Vec<ScoreRecord>> {
+        let rows = sqlx::query(
+            "SELECT id, user_id, score FROM scores s
+             WHERE id = (
+                SELECT id FROM scores WHERE user_id = s.user_id
+                ORDER BY calculated_at DESC, id DESC LIMIT 1
+             )"
+        )
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter().map(row_to_score_record).collect()
+    }
+
+    /// Overwrite a stored score in place, e.g. after re-ranking percentiles
+    pub async fn update_score(&self, id: i64, score: &DegenScore) -> Result<()> {
+        sqlx::query("UPDATE scores SET total_score = ?, tier = ?, score = ? WHERE id = ?")
+            .bind(score.total_score)
+            .bind(format!("{:?}", score.tier))
+            .bind(serde_json::to_string(score)?)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Latest score of every user, highest first
+    pub async fn get_top_scores(&self, limit: u32) -> Result<Vec<ScoreRecord>> {
+        let rows = sqlx::query(
+            "SELECT id, user_id, score FROM scores s
+             WHERE id = (
+                SELECT id FROM scores WHERE user_id = s.user_id
+                ORDER BY calculated_at DESC, id DESC LIMIT 1
+             )
+             ORDER BY total_score DESC, user_id ASC LIMIT ?"
+        )
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter().map(row_to_score_record).collect()
+    }
+
+    // ---- Address registry ----
+
+    /// Append an ownership event to the address audit trail
+    pub async fn record_address_event(&self, event: &AddressEvent) -> Result<i64> {
+        let result = sqlx::query(
+            "INSERT INTO address_events (namespace, address, chain, user_id, event, detail, created_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?)"
+        )
+            .bind(event.key.namespace.as_str())
+            .bind(&event.key.address)
+            .bind(event.chain.as_str())
+            .bind(&event.user_id)
+            .bind(event.kind.as_str())
+            .bind(&event.detail)
+            .bind(format_timestamp(&event.at))
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    /// Whole audit trail in the order it was recorded
+    pub async fn get_address_events(&self) -> Result<Vec<AddressEvent>> {
+        let rows = sqlx::query(
+            "SELECT namespace, address, chain, user_id, event, detail, created_at
+             FROM address_events ORDER BY id"
+        )
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter().map(row_to_address_event).collect()
+    }
+}
+
+async fn insert_address(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    user_id: &str,
+    address: &VerifiedAddress,
+) -> Result<()> {
+    let kind = match &address.verification_method {
+        VerificationMethod::Signature { .. } => "signature",
+        VerificationMethod::MicroDeposit { .. } => "micro_deposit",
+    };
+
+    sqlx::query(
+        "INSERT INTO verified_addresses
+            (user_id, address, chain, verification_kind, verification_method, verified_at, nonce)
+         VALUES (?, ?, ?, ?, ?, ?, ?)
+         ON CONFLICT(user_id, chain, address) DO UPDATE SET
+            verification_kind = excluded.verification_kind,
+            verification_method = excluded.verification_method,
+            verified_at = excluded.verified_at,
+            nonce = excluded.nonce"
+    )
+        .bind(user_id)
+        .bind(&address.address)
+        .bind(address.chain.as_str())
+        .bind(kind)
+        .bind(serde_json::to_string(&address.verification_method)?)
+        .bind(format_timestamp(&address.verified_at))
+        .bind(&address.nonce)
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(())
+}
+
+fn row_to_chain_metrics(row: &sqlx::sqlite::SqliteRow) -> Result<ChainMetrics> {
+    Ok(ChainMetrics {
+        chain: row.try_get("chain")?,
+        address: row.try_get("address")?,
+        metrics: serde_json::from_str(row.try_get("metrics")?)?,
+        last_updated: parse_timestamp(row.try_get("last_updated")?)?,
+    })
+}
+
+fn row_to_address_event(row: &sqlx::sqlite::SqliteRow) -> Result<AddressEvent> {
+    let namespace: String = row.try_get("namespace")?;
+    let chain: String = row.try_get("chain")?;
+    let kind: String = row.try_get("event")?;
+
+    Ok(AddressEvent {
+        key: AddressKey {
+            namespace: AddressNamespace::parse(&namespace).ok_or_else(|| {
+                decode_error(format!("Unknown address namespace {}", namespace))
+            })?,
+            address: row.try_get("address")?,
+        },
+        chain: Chain::from_str(&chain).ok_or_else(|| DegenScoreError::ChainNotSupported(chain.clone()))?,
+        user_id: row.try_get("user_id")?,
+        kind: AddressEventKind::parse(&kind).ok_or_else(|| {
+            decode_error(format!("Unknown address event {}", kind))
+        })?,
+        detail: row.try_get("detail")?,
+        at: parse_timestamp(row.try_get("created_at")?)?,
+    })
+}
+
+fn row_to_score_record(row: &sqlx::sqlite::SqliteRow) -> Result<ScoreRecord> {
+    Ok(ScoreRecord {
+        id: row.try_get("id")?,
+        user_id: row.try_get("user_id")?,
+        score: serde_json::from_str(row.try_get("score")?)?,
+    })
+}
+
+// Fixed-width RFC 3339 so timestamps also sort correctly as text
+fn format_timestamp(ts: &DateTime<Utc>) -> String {
+    ts.to_rfc3339_opts(SecondsFormat::Micros, true)
+}
+
+fn parse_timestamp(value: &str) -> Result<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|ts| ts.with_timezone(&Utc))
+        .map_err(|e| decode_error(format!("Invalid stored timestamp {}: {}", value, e)))
+}
+
+// A stored value that doesn't decode is a database error, like a column of the wrong type
+fn decode_error(message: String) -> DegenScoreError {
+    DegenScoreError::DatabaseError(sqlx::Error::Decode(message.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::DegenScoreBuilder;
+    use chrono::Duration as ChronoDuration;
+    use rust_decimal::Decimal;
+
+    fn verified(address: &str, chain: Chain) -> VerifiedAddress {
+        VerifiedAddress {
+            address: address.to_string(),
+            chain,
+            verification_method: VerificationMethod::Signature {
+                message: "msg".to_string(),
+                signature: "sig".to_string(),
+            },
+            verified_at: Utc::now(),
+            nonce: "42".to_string(),
+        }
+    }
+
+    fn score(total: f64, at: DateTime<Utc>) -> DegenScore {
+        DegenScoreBuilder::new(total).trading(total).calculated_at(at).build()
+    }
+
+    #[tokio::test]
+    async fn test_read_only_connection_creates_nothing() {
+        let path = std::env::temp_dir().join(format!("read-only-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let settings = DatabaseSettings {
+            url: format!("sqlite://{}", path.display()),
+            max_connections: 1,
+            min_connections: 0,
+            connect_timeout_seconds: 5,
+        };
+
+        assert!(ScoreStore::connect_read_only(&settings).await.is_err());
+        assert!(!path.exists());
+
+        ScoreStore::connect(&settings).await.unwrap().upsert_user(&UserProfile::new("alice".to_string())).await.unwrap();
+        let store = ScoreStore::connect_read_only(&settings).await.unwrap();
+        assert_eq!(store.list_users().await.unwrap().len(), 1);
+        assert!(store.upsert_user(&UserProfile::new("bob".to_string())).await.is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_user_roundtrip() {
+        let store = ScoreStore::in_memory().await.unwrap();
+
+        let mut user = UserProfile::new("alice".to_string());
+        user.add_verified_address(verified("0x742d35Cc6634C0532925a3b844Bc9e7595f6e842", Chain::Ethereum));
+        user.add_verified_address(VerifiedAddress {
+            verification_method: VerificationMethod::MicroDeposit {
+                tx_hash: "0xabc".to_string(),
+                amount: "0.001".to_string(),
+            },
+            ..verified("7VXNe1r6nTqVw6TKyBzt1TNSSQqPqNcEYizv8TduLWpU", Chain::Solana)
+        });
+        user.aggregated_metrics.gmx_volume_usd = Decimal::from(1234);
+
+        store.upsert_user(&user).await.unwrap();
+
+        let loaded = store.get_user("alice").await.unwrap().unwrap();
+        assert_eq!(loaded.verified_addresses.len(), 2);
+        assert_eq!(loaded.verified_addresses[1].chain, Chain::Solana);
+        assert!(matches!(
+            loaded.verified_addresses[1].verification_method,
+            VerificationMethod::MicroDeposit { .. }
+        ));
+        assert_eq!(loaded.aggregated_metrics.gmx_volume_usd, Decimal::from(1234));
+
+        assert!(store.remove_verified_address("alice", &Chain::Solana, "7VXNe1r6nTqVw6TKyBzt1TNSSQqPqNcEYizv8TduLWpU").await.unwrap());
+        assert_eq!(store.get_verified_addresses("alice").await.unwrap().len(), 1);
+
+        // Upserting a profile without an address removes its row
+        user.verified_addresses.retain(|address| address.chain == Chain::Solana);
+        store.upsert_user(&user).await.unwrap();
+        let remaining = store.get_verified_addresses("alice").await.unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].chain, Chain::Solana);
+
+        assert!(store.delete_user("alice").await.unwrap());
+        assert!(store.get_user("alice").await.unwrap().is_none());
+        assert!(store.get_verified_addresses("alice").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_score_history_and_leaderboard() {
+        let store = ScoreStore::in_memory().await.unwrap();
+        store.upsert_user(&UserProfile::new("alice".to_string())).await.unwrap();
+        store.upsert_user(&UserProfile::new("bob".to_string())).await.unwrap();
+
+        let now = Utc::now();
+        store.record_score("alice", &score(80.0, now - ChronoDuration::hours(2))).await.unwrap();
+        store.record_score("alice", &score(30.0, now)).await.unwrap();
+        store.record_score("bob", &score(50.0, now)).await.unwrap();
+
+        let latest = store.get_latest_score("alice").await.unwrap().unwrap();
+        assert_eq!(latest.total_score, 30.0);
+
+        let history = store.get_score_history("alice", 10).await.unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[1].score.total_score, 80.0);
+
+        // Leaderboard only uses each user's latest score
+        let top = store.get_top_scores(10).await.unwrap();
+        assert_eq!(top.len(), 2);
+        assert_eq!(top[0].user_id, "bob");
+        assert_eq!(top[1].user_id, "alice");
+    }
+
+    #[tokio::test]
+    async fn test_chain_metrics_snapshots() {
+        let store = ScoreStore::in_memory().await.unwrap();
+        store.upsert_user(&UserProfile::new("alice".to_string())).await.unwrap();
+
+        let now = Utc::now();
+        for (i, age) in [100u32, 200].iter().enumerate() {
+            store.save_chain_metrics("alice", &ChainMetrics {
+                chain: "ethereum".to_string(),
+                address: "0xabc".to_string(),
+                metrics: DegenMetrics { wallet_age_days: *age, ..Default::default() },
+                last_updated: now + ChronoDuration::minutes(i as i64),
+            }).await.unwrap();
+        }
+
+        let latest = store.get_latest_chain_metrics("alice").await.unwrap();
+        assert_eq!(latest.len(), 1);
+        assert_eq!(latest[0].metrics.wallet_age_days, 200);
+
+        let history = store.get_chain_metrics_history("alice", "ethereum", "0xabc").await.unwrap();
+        assert_eq!(history.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_malformed_rows_are_database_errors() {
+        let store = ScoreStore::in_memory().await.unwrap();
+        sqlx::query(
+            "INSERT INTO address_events (namespace, address, chain, user_id, event, created_at)
+             VALUES ('evm', '0xabc', 'ethereum', 'alice', 'linked', 'yesterday')"
+        )
+            .execute(store.pool())
+            .await
+            .unwrap();
+
+        let error = store.get_address_events().await.unwrap_err();
+        assert!(matches!(error, DegenScoreError::DatabaseError(_)), "{:?}", error);
+    }
+}
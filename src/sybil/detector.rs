@@ -0,0 +1,543 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::Write;
+
+use crate::{
+    config::SybilSettings,
+    models::{ChainMetrics, EVMTokenTransfer, Result},
+};
+
+/// What the detector knows about one verified wallet of a scored user
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WalletActivity {
+    pub user_id: String,
+    pub chain: String,
+    pub address: String,
+    /// Sender of the wallet's earliest incoming transfer
+    pub funding_source: Option<String>,
+    pub first_transaction: Option<DateTime<Utc>>,
+    /// Protocols with at least one interaction
+    pub protocols: BTreeSet<String>,
+    pub transfers: Vec<EVMTokenTransfer>,
+}
+
+impl WalletActivity {
+    pub fn new(user_id: &str, chain: &str, address: &str) -> Self {
+        Self {
+            user_id: user_id.to_string(),
+            chain: chain.to_string(),
+            address: address.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// Seed from the wallet's stored metric snapshot
+    pub fn from_chain_metrics(user_id: &str, metrics: &ChainMetrics) -> Self {
+        Self {
+            first_transaction: metrics.metrics.first_transaction,
+            protocols: metrics.metrics.protocol_interaction_counts
+                .iter()
+                .filter(|(_, count)| **count > 0)
+                .map(|(protocol, _)| protocol.clone())
+                .collect(),
+            ..Self::new(user_id, &metrics.chain, &metrics.address)
+        }
+    }
+
+    /// Attach token transfers, deriving the funding source and (if unknown) the first
+    /// transaction time from them
+    pub fn with_transfers(mut self, transfers: Vec<EVMTokenTransfer>) -> Self {
+        let address = normalize(&self.address);
+
+        if self.funding_source.is_none() {
+            self.funding_source = transfers
+                .iter()
+                .filter(|t| normalize(&t.to) == address && normalize(&t.from) != address)
+                .min_by(|a, b| a.timestamp.cmp(&b.timestamp).then(a.log_index.cmp(&b.log_index)))
+                .map(|t| normalize(&t.from));
+        }
+        if self.first_transaction.is_none() {
+            self.first_transaction = transfers.iter().map(|t| t.timestamp).min();
+        }
+
+        self.transfers = transfers;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SybilSignalKind {
+    SharedFunding,
+    FirstTxTiming,
+    IdenticalProtocols,
+    CircularTransfers,
+}
+
+/// One piece of evidence linking wallets of different users
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SybilSignal {
+    pub kind: SybilSignalKind,
+    pub detail: String,
+    pub addresses: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SybilCluster {
+    pub id: usize,
+    pub users: Vec<String>,
+    pub addresses: Vec<String>,
+    pub signals: Vec<SybilSignal>,
+    /// Score multiplier per user, one `penalty_per_signal` for each signal kind the user's
+    /// own wallets take part in
+    pub penalties: BTreeMap<String, f64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SybilReport {
+    pub wallets_analyzed: usize,
+    pub users_analyzed: usize,
+    pub clusters: Vec<SybilCluster>,
+}
+
+impl SybilReport {
+    /// Score multiplier per flagged user
+    pub fn penalties(&self) -> HashMap<String, f64> {
+        let mut penalties = HashMap::new();
+        for cluster in &self.clusters {
+            for (user, multiplier) in &cluster.penalties {
+                let entry = penalties.entry(user.clone()).or_insert(1.0_f64);
+                *entry = entry.min(*multiplier);
+            }
+        }
+        penalties
+    }
+
+    pub fn flagged_users(&self) -> usize {
+        self.clusters.iter().map(|c| c.users.len()).sum()
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn render_text(&self) -> String {
+        let mut out = String::new();
+
+        let _ = writeln!(out, "Analyzed {} wallets of {} users: {} clusters, {} flagged users",
+            self.wallets_analyzed, self.users_analyzed, self.clusters.len(), self.flagged_users());
+
+        for cluster in &self.clusters {
+            let users: Vec<String> = cluster.penalties
+                .iter()
+                .map(|(user, multiplier)| format!("{} (x{:.2})", user, multiplier))
+                .collect();
+            let _ = writeln!(out, "\nCluster {}: {}", cluster.id, users.join(", "));
+            for signal in &cluster.signals {
+                let _ = writeln!(out, "  {:?}: {} [{}]", signal.kind, signal.detail, signal.addresses.join(", "));
+            }
+        }
+
+        out
+    }
+}
+
+/// Groups scored users whose wallets look like they are controlled by one person
+pub struct SybilDetector {
+    settings: SybilSettings,
+}
+
+impl SybilDetector {
+    pub fn new(settings: SybilSettings) -> Self {
+        Self { settings }
+    }
+
+    pub fn analyze(&self, wallets: &[WalletActivity]) -> SybilReport {
+        let mut groups: Vec<(SybilSignalKind, String, Vec<usize>)> = Vec::new();
+        groups.extend(self.shared_funding(wallets));
+        groups.extend(self.first_tx_timing(wallets));
+        groups.extend(self.identical_protocols(wallets));
+        groups.extend(circular_transfers(wallets));
+
+        // Only evidence that spans more than one user is interesting
+        groups.retain(|(_, _, members)| distinct_users(wallets, members) > 1);
+
+        let mut sets = DisjointSets::new(wallets.len());
+        for (_, _, members) in &groups {
+            for pair in members.windows(2) {
+                sets.union(pair[0], pair[1]);
+            }
+        }
+
+        let mut components: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+        for (_, _, members) in &groups {
+            let root = sets.find(members[0]);
+            components.entry(root).or_default();
+        }
+        for i in 0..wallets.len() {
+            let root = sets.find(i);
+            if let Some(members) = components.get_mut(&root) {
+                members.push(i);
+            }
+        }
+
+        let mut clusters: Vec<SybilCluster> = components
+            .into_iter()
+            .map(|(root, members)| {
+                let signals: Vec<SybilSignal> = groups
+                    .iter()
+                    .filter(|(_, _, m)| sets.find(m[0]) == root)
+                    .map(|(kind, detail, m)| SybilSignal {
+                        kind: *kind,
+                        detail: detail.clone(),
+                        addresses: m.iter().map(|i| wallets[*i].address.clone()).collect(),
+                    })
+                    .collect();
+                let users: BTreeSet<String> = members.iter().map(|i| wallets[*i].user_id.clone()).collect();
+                // A member is only penalized for the evidence its own wallets appear in,
+                // not for every signal that happens to share its component
+                let penalties = users
+                    .iter()
+                    .map(|user| {
+                        let kinds: BTreeSet<SybilSignalKind> = groups
+                            .iter()
+                            .filter(|(_, _, m)| m.iter().any(|i| &wallets[*i].user_id == user))
+                            .map(|(kind, _, _)| *kind)
+                            .collect();
+                        (user.clone(), self.settings.penalty_per_signal.powi(kinds.len() as i32))
+                    })
+                    .collect();
+
+                SybilCluster {
+                    id: 0,
+                    users: users.into_iter().collect(),
+                    addresses: members.iter().map(|i| wallets[*i].address.clone()).collect(),
+                    signals,
+                    penalties,
+                }
+            })
+            .collect();
+
+        clusters.sort_by(|a, b| a.users.cmp(&b.users));
+        for (i, cluster) in clusters.iter_mut().enumerate() {
+            cluster.id = i + 1;
+        }
+
+        SybilReport {
+            wallets_analyzed: wallets.len(),
+            users_analyzed: wallets.iter().map(|w| w.user_id.as_str()).collect::<BTreeSet<_>>().len(),
+            clusters,
+        }
+    }
+
+    fn shared_funding(&self, wallets: &[WalletActivity]) -> Vec<(SybilSignalKind, String, Vec<usize>)> {
+        let ignored: BTreeSet<String> = self.settings.ignored_funders.iter().map(|a| normalize(a)).collect();
+
+        let mut by_funder: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+        for (i, wallet) in wallets.iter().enumerate() {
+            if let Some(funder) = &wallet.funding_source {
+                let funder = normalize(funder);
+                if !ignored.contains(&funder) {
+                    by_funder.entry(funder).or_default().push(i);
+                }
+            }
+        }
+
+        by_funder
+            .into_iter()
+            .filter(|(_, members)| members.len() > 1)
+            .map(|(funder, members)| (SybilSignalKind::SharedFunding, format!("funded by {}", funder), members))
+            .collect()
+    }
+
+    fn first_tx_timing(&self, wallets: &[WalletActivity]) -> Vec<(SybilSignalKind, String, Vec<usize>)> {
+        let mut timed: Vec<(DateTime<Utc>, usize)> = wallets
+            .iter()
+            .enumerate()
+            .filter_map(|(i, w)| w.first_transaction.map(|t| (t, i)))
+            .collect();
+        timed.sort();
+
+        // Group wallets whose first transactions all fall within one window of the group's
+        // earliest, so a steady stream of sign-ups cannot chain into a single cohort
+        let window = self.settings.timing_window_seconds as i64;
+        let mut runs: Vec<Vec<(DateTime<Utc>, usize)>> = Vec::new();
+        for entry in timed {
+            match runs.last_mut() {
+                Some(run) if (entry.0 - run[0].0).num_seconds() <= window => run.push(entry),
+                _ => runs.push(vec![entry]),
+            }
+        }
+
+        runs.into_iter()
+            .filter(|run| run.len() > 1)
+            .map(|run| {
+                let detail = format!("first transactions between {} and {}",
+                    run[0].0.to_rfc3339(), run[run.len() - 1].0.to_rfc3339());
+                (SybilSignalKind::FirstTxTiming, detail, run.into_iter().map(|(_, i)| i).collect())
+            })
+            .collect()
+    }
+
+    fn identical_protocols(&self, wallets: &[WalletActivity]) -> Vec<(SybilSignalKind, String, Vec<usize>)> {
+        let mut by_set: BTreeMap<&BTreeSet<String>, Vec<usize>> = BTreeMap::new();
+        for (i, wallet) in wallets.iter().enumerate() {
+            if wallet.protocols.len() >= self.settings.min_shared_protocols {
+                by_set.entry(&wallet.protocols).or_default().push(i);
+            }
+        }
+
+        by_set
+            .into_iter()
+            .filter(|(_, members)| members.len() > 1)
+            .map(|(set, members)| {
+                let detail = format!("identical protocol set {{{}}}", set.iter().cloned().collect::<Vec<_>>().join(", "));
+                (SybilSignalKind::IdenticalProtocols, detail, members)
+            })
+            .collect()
+    }
+}
+
+/// Strongly connected groups in the transfer graph between analyzed wallets
+fn circular_transfers(wallets: &[WalletActivity]) -> Vec<(SybilSignalKind, String, Vec<usize>)> {
+    let index: HashMap<String, usize> = wallets
+        .iter()
+        .enumerate()
+        .map(|(i, w)| (normalize(&w.address), i))
+        .collect();
+
+    let mut edges: Vec<BTreeSet<usize>> = vec![BTreeSet::new(); wallets.len()];
+    for wallet in wallets {
+        for transfer in &wallet.transfers {
+            if let (Some(&from), Some(&to)) = (index.get(&normalize(&transfer.from)), index.get(&normalize(&transfer.to))) {
+                if from != to {
+                    edges[from].insert(to);
+                }
+            }
+        }
+    }
+
+    strongly_connected(&edges)
+        .into_iter()
+        .filter(|component| component.len() > 1)
+        .map(|component| {
+            let detail = format!("tokens cycled between {} wallets", component.len());
+            (SybilSignalKind::CircularTransfers, detail, component)
+        })
+        .collect()
+}
+
+// Tarjan's algorithm, iterative so long transfer chains cannot overflow the stack
+fn strongly_connected(edges: &[BTreeSet<usize>]) -> Vec<Vec<usize>> {
+    let n = edges.len();
+    let mut index = vec![usize::MAX; n];
+    let mut low = vec![0; n];
+    let mut on_stack = vec![false; n];
+    let mut stack = Vec::new();
+    let mut components = Vec::new();
+    let mut next = 0;
+
+    for start in 0..n {
+        if index[start] != usize::MAX {
+            continue;
+        }
+
+        let mut work: Vec<(usize, Vec<usize>)> = vec![(start, edges[start].iter().copied().collect())];
+        index[start] = next;
+        low[start] = next;
+        next += 1;
+        stack.push(start);
+        on_stack[start] = true;
+
+        while let Some((node, pending)) = work.last_mut() {
+            let node = *node;
+            if let Some(succ) = pending.pop() {
+                if index[succ] == usize::MAX {
+                    index[succ] = next;
+                    low[succ] = next;
+                    next += 1;
+                    stack.push(succ);
+                    on_stack[succ] = true;
+                    work.push((succ, edges[succ].iter().copied().collect()));
+                } else if on_stack[succ] {
+                    low[node] = low[node].min(index[succ]);
+                }
+                continue;
+            }
+
+            work.pop();
+            if let Some((parent, _)) = work.last() {
+                low[*parent] = low[*parent].min(low[node]);
+            }
+
+            if low[node] == index[node] {
+                let mut component = Vec::new();
+                while let Some(member) = stack.pop() {
+                    on_stack[member] = false;
+                    component.push(member);
+                    if member == node {
+                        break;
+                    }
+                }
+                component.sort_unstable();
+                components.push(component);
+            }
+        }
+    }
+
+    components
+}
+
+fn distinct_users(wallets: &[WalletActivity], members: &[usize]) -> usize {
+    members.iter().map(|i| wallets[*i].user_id.as_str()).collect::<BTreeSet<_>>().len()
+}
+
+// EVM addresses compare case-insensitively; Solana addresses are case-sensitive
+fn normalize(address: &str) -> String {
+    if address.starts_with("0x") || address.starts_with("0X") {
+        address.to_lowercase()
+    } else {
+        address.to_string()
+    }
+}
+
+struct DisjointSets {
+    parent: Vec<usize>,
+}
+
+impl DisjointSets {
+    fn new(n: usize) -> Self {
+        Self { parent: (0..n).collect() }
+    }
+
+    fn find(&mut self, mut i: usize) -> usize {
+        while self.parent[i] != i {
+            self.parent[i] = self.parent[self.parent[i]];
+            i = self.parent[i];
+        }
+        i
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a != b {
+            self.parent[a.max(b)] = a.min(b);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use ethers::types::U256;
+
+    fn transfer(from: &str, to: &str, minute: u32) -> EVMTokenTransfer {
+        EVMTokenTransfer {
+            token_address: "0xtoken".to_string(),
+            from: from.to_string(),
+            to: to.to_string(),
+            value: U256::from(1_000u64),
+            tx_hash: format!("0x{}{}{}", from, to, minute),
+            log_index: 0,
+            timestamp: Utc.with_ymd_and_hms(2024, 3, 1, 12, minute, 0).unwrap(),
+        }
+    }
+
+    fn wallet(user: &str, address: &str) -> WalletActivity {
+        WalletActivity::new(user, "ethereum", address)
+    }
+
+    fn detector() -> SybilDetector {
+        SybilDetector::new(SybilSettings::default())
+    }
+
+    #[test]
+    fn test_shared_funder_and_timing_form_one_cluster() {
+        let wallets = vec![
+            wallet("farm1", "0xA1").with_transfers(vec![transfer("0xF00D", "0xa1", 0), transfer("0xbeef", "0xa1", 30)]),
+            wallet("farm2", "0xA2").with_transfers(vec![transfer("0xf00d", "0xA2", 0)]),
+            // Same first-transaction minute as farm2, but a different funder
+            wallet("farm3", "0xA3").with_transfers(vec![transfer("0xcafe", "0xA3", 0)]),
+            wallet("honest", "0xB1").with_transfers(vec![transfer("0xcafe", "0xB1", 45)]),
+        ];
+        assert_eq!(wallets[0].funding_source.as_deref(), Some("0xf00d"));
+
+        // honest only shares a funder with farm3, which joins the cluster but is penalized once
+        let report = detector().analyze(&wallets);
+        assert_eq!(report.clusters.len(), 1);
+
+        let cluster = &report.clusters[0];
+        assert_eq!(cluster.users, vec!["farm1", "farm2", "farm3", "honest"]);
+        let kinds: BTreeSet<_> = cluster.signals.iter().map(|s| s.kind).collect();
+        assert_eq!(kinds.len(), 2);
+        assert_eq!(cluster.penalties["farm3"], 0.25);
+        assert_eq!(cluster.penalties["honest"], 0.5);
+
+        // ...unless that funder is an exchange hot wallet the campaign ignores
+        let ignoring = SybilDetector::new(SybilSettings {
+            ignored_funders: vec!["0xCAFE".to_string()],
+            ..SybilSettings::default()
+        });
+        let report = ignoring.analyze(&wallets);
+        assert!(!report.penalties().contains_key("honest"));
+        assert_eq!(report.penalties()["farm1"], 0.25);
+    }
+
+    #[test]
+    fn test_protocol_sets_and_circular_transfers() {
+        let protocols = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<BTreeSet<_>>();
+        let mut a = wallet("u1", "0x01");
+        let mut b = wallet("u2", "0x02");
+        let mut c = wallet("u3", "0x03");
+        a.protocols = protocols(&["GMX", "Aave", "Camelot"]);
+        b.protocols = protocols(&["GMX", "Aave", "Camelot"]);
+        // Too few protocols to be telling
+        c.protocols = protocols(&["GMX"]);
+
+        let loop_ = vec![transfer("0x03", "0x04", 1), transfer("0x04", "0x05", 2), transfer("0x05", "0x03", 3)];
+        let c = c.with_transfers(loop_.clone());
+        let d = wallet("u4", "0x04").with_transfers(loop_.clone());
+        let e = wallet("u5", "0x05").with_transfers(loop_);
+        // A one-way payment is not a cycle
+        let f = wallet("u6", "0x06").with_transfers(vec![transfer("0x06", "0x01", 40)]);
+
+        let report = SybilDetector::new(SybilSettings { timing_window_seconds: 0, ..SybilSettings::default() })
+            .analyze(&[a, b, c, d, e, f]);
+
+        assert_eq!(report.clusters.len(), 2);
+        assert_eq!(report.clusters[0].users, vec!["u1", "u2"]);
+        assert_eq!(report.clusters[0].signals[0].kind, SybilSignalKind::IdenticalProtocols);
+        assert_eq!(report.clusters[1].users, vec!["u3", "u4", "u5"]);
+        assert!(report.clusters[1].signals.iter().any(|s| s.kind == SybilSignalKind::CircularTransfers));
+        assert!(!report.penalties().contains_key("u6"));
+        assert!(report.render_text().contains("Cluster 2"));
+    }
+
+    #[test]
+    fn test_timing_groups_do_not_chain_past_the_window() {
+        // Sign-ups every 40s: with a 60s window, single linkage would chain all four
+        let wallets: Vec<WalletActivity> = (0..4)
+            .map(|i| {
+                let mut w = wallet(&format!("u{}", i), &format!("0x0{}", i));
+                w.first_transaction = Some(Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap() + chrono::Duration::seconds(40 * i));
+                w
+            })
+            .collect();
+
+        let report = detector().analyze(&wallets);
+        assert_eq!(report.clusters.len(), 2);
+        assert_eq!(report.clusters[0].users, vec!["u0", "u1"]);
+        assert_eq!(report.clusters[1].users, vec!["u2", "u3"]);
+    }
+
+    #[test]
+    fn test_wallets_of_one_user_are_not_a_cluster() {
+        let wallets = vec![
+            wallet("alice", "0xA1").with_transfers(vec![transfer("0xf00d", "0xA1", 0)]),
+            wallet("alice", "0xA2").with_transfers(vec![transfer("0xf00d", "0xA2", 0)]),
+        ];
+        assert!(detector().analyze(&wallets).clusters.is_empty());
+    }
+}
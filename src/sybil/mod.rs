@@ -0,0 +1,3 @@
+pub mod detector;
+
+pub use detector::{SybilCluster, SybilDetector, SybilReport, SybilSignal, SybilSignalKind, WalletActivity};
@@ -1,7 +1,9 @@
 pub mod signature;
 pub mod deposit;
 pub mod verifier;
+pub mod registry;
 
 pub use signature::{EvmSignatureVerifier, SolanaSignatureVerifier};
 pub use deposit::DepositVerifier;
-pub use verifier::WalletVerifier;
\ No newline at end of file
+pub use verifier::WalletVerifier;
+pub use registry::{AddressEvent, AddressEventKind, AddressKey, AddressNamespace, AddressRegistry};
\ No newline at end of file
@@ -0,0 +1,496 @@
+use chrono::{DateTime, Utc};
+use ethers::{types::Address, utils::to_checksum};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use tracing::{info, warn};
+
+use crate::{
+    config::ConflictPolicy,
+    models::{Chain, DegenScoreError, Result, UserProfile, VerifiedAddress},
+    storage::ScoreStore,
+};
+
+/// Address families that share ownership: one EVM key controls the same address on every EVM chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AddressNamespace {
+    Evm,
+    Solana,
+}
+
+impl AddressNamespace {
+    pub fn of(chain: &Chain) -> Self {
+        if chain.is_evm() { AddressNamespace::Evm } else { AddressNamespace::Solana }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AddressNamespace::Evm => "evm",
+            AddressNamespace::Solana => "solana",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "evm" => Some(AddressNamespace::Evm),
+            "solana" => Some(AddressNamespace::Solana),
+            _ => None,
+        }
+    }
+}
+
+/// Canonical identity of an address, independent of casing and chain within a namespace
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct AddressKey {
+    pub namespace: AddressNamespace,
+    /// Lowercase hex for EVM, base58 for Solana
+    pub address: String,
+}
+
+impl AddressKey {
+    /// Normalize an address for `chain`; EVM casing (checksummed or not) is ignored
+    pub fn normalize(chain: &Chain, address: &str) -> Result<Self> {
+        let address = address.trim();
+
+        if chain.is_evm() {
+            let parsed: Address = address.parse()
+                .map_err(|_| DegenScoreError::InvalidAddress(format!("Invalid EVM address: {}", address)))?;
+
+            return Ok(Self { namespace: AddressNamespace::Evm, address: format!("{:?}", parsed) });
+        }
+
+        let bytes = bs58::decode(address)
+            .into_vec()
+            .map_err(|_| DegenScoreError::InvalidAddress(format!("Invalid Solana address: {}", address)))?;
+        if bytes.len() != 32 {
+            return Err(DegenScoreError::InvalidAddress(format!("Invalid Solana address length: {}", address)));
+        }
+
+        Ok(Self { namespace: AddressNamespace::Solana, address: bs58::encode(bytes).into_string() })
+    }
+
+    /// Checksummed form for EVM, base58 for Solana
+    pub fn display(&self) -> String {
+        match self.namespace {
+            AddressNamespace::Evm => self.address.parse::<Address>()
+                .map(|a| to_checksum(&a, None))
+                .unwrap_or_else(|_| self.address.clone()),
+            AddressNamespace::Solana => self.address.clone(),
+        }
+    }
+}
+
+impl fmt::Display for AddressKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.namespace.as_str(), self.address)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AddressEventKind {
+    Linked,
+    Unlinked,
+    /// Link refused because another user owns the address
+    Rejected,
+    /// Link refused and queued for manual review
+    Escalated,
+    /// Ownership moved to another user by an operator
+    Reassigned,
+}
+
+impl AddressEventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AddressEventKind::Linked => "linked",
+            AddressEventKind::Unlinked => "unlinked",
+            AddressEventKind::Rejected => "rejected",
+            AddressEventKind::Escalated => "escalated",
+            AddressEventKind::Reassigned => "reassigned",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "linked" => Some(AddressEventKind::Linked),
+            "unlinked" => Some(AddressEventKind::Unlinked),
+            "rejected" => Some(AddressEventKind::Rejected),
+            "escalated" => Some(AddressEventKind::Escalated),
+            "reassigned" => Some(AddressEventKind::Reassigned),
+            _ => None,
+        }
+    }
+}
+
+/// One entry in the address audit trail
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AddressEvent {
+    pub key: AddressKey,
+    pub chain: Chain,
+    /// User the event acted for: the new owner, or the refused claimant
+    pub user_id: String,
+    pub kind: AddressEventKind,
+    pub detail: Option<String>,
+    pub at: DateTime<Utc>,
+}
+
+/// Global owner of every linked address, so one wallet can only count for one user
+#[derive(Debug, Clone)]
+pub struct AddressRegistry {
+    policy: ConflictPolicy,
+    owners: HashMap<AddressKey, String>,
+    events: Vec<AddressEvent>,
+    // Write-through persistence of the audit trail
+    store: Option<ScoreStore>,
+}
+
+impl AddressRegistry {
+    pub fn new(policy: ConflictPolicy) -> Self {
+        Self {
+            policy,
+            owners: HashMap::new(),
+            events: Vec::new(),
+            store: None,
+        }
+    }
+
+    /// Rebuild ownership by replaying an audit trail, oldest first
+    pub fn from_events(policy: ConflictPolicy, events: Vec<AddressEvent>) -> Self {
+        let mut registry = Self::new(policy);
+        for event in events {
+            registry.apply(event);
+        }
+        registry
+    }
+
+    /// Registry backed by the store's audit trail. Stored addresses that predate the
+    /// registry are linked to their user; ones already owned elsewhere are escalated and
+    /// dropped from the claimant's profile, which is saved, so no address counts twice.
+    pub async fn load(policy: ConflictPolicy, store: ScoreStore, users: &mut [UserProfile]) -> Result<Self> {
+        let mut registry = Self::from_events(policy, store.get_address_events().await?);
+        registry.store = Some(store.clone());
+
+        let mut backfilled = 0;
+        for user in users.iter_mut() {
+            let mut contested = Vec::new();
+            for verified in &user.verified_addresses {
+                let key = match AddressKey::normalize(&verified.chain, &verified.address) {
+                    Ok(key) => key,
+                    Err(e) => {
+                        warn!("Skipping unregistrable address of user {}: {}", user.id, e);
+                        continue;
+                    }
+                };
+
+                match registry.owners.get(&key) {
+                    Some(owner) if owner == &user.id => {}
+                    Some(_) if registry.events.iter().any(|e| {
+                        e.key == key && e.user_id == user.id && e.kind == AddressEventKind::Escalated
+                    }) => contested.push(key),
+                    Some(owner) => {
+                        warn!("Stored address {} of user {} is owned by {}", key, user.id, owner);
+                        let detail = format!("stored before registry; owned by {}", owner);
+                        registry.record(key.clone(), &verified.chain, &user.id, AddressEventKind::Escalated, Some(detail)).await?;
+                        contested.push(key);
+                    }
+                    None => {
+                        registry.record(key, &verified.chain, &user.id, AddressEventKind::Linked, Some("backfilled".to_string())).await?;
+                        backfilled += 1;
+                    }
+                }
+            }
+
+            if !contested.is_empty() {
+                user.verified_addresses.retain(|a| {
+                    AddressKey::normalize(&a.chain, &a.address).map_or(true, |k| !contested.contains(&k))
+                });
+                user.updated_at = Utc::now();
+                store.upsert_user(user).await?;
+            }
+        }
+
+        info!("Address registry holds {} addresses ({} backfilled)", registry.owners.len(), backfilled);
+        Ok(registry)
+    }
+
+    pub fn policy(&self) -> ConflictPolicy {
+        self.policy
+    }
+
+    pub fn len(&self) -> usize {
+        self.owners.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.owners.is_empty()
+    }
+
+    pub fn owner(&self, chain: &Chain, address: &str) -> Result<Option<&str>> {
+        let key = AddressKey::normalize(chain, address)?;
+        Ok(self.owners.get(&key).map(String::as_str))
+    }
+
+    /// Link an address to `user_id`. Re-linking an address the user already owns is a no-op.
+    /// An address owned by someone else is refused under `Reject` and queued for review
+    /// under `Escalate`; either way the attempt is recorded.
+    pub async fn link(&mut self, user_id: &str, chain: &Chain, address: &str) -> Result<AddressKey> {
+        let key = AddressKey::normalize(chain, address)?;
+
+        match self.owners.get(&key) {
+            Some(owner) if owner == user_id => Ok(key),
+            Some(owner) => {
+                let kind = match self.policy {
+                    ConflictPolicy::Reject => AddressEventKind::Rejected,
+                    ConflictPolicy::Escalate => AddressEventKind::Escalated,
+                };
+                let detail = format!("owned by {}", owner);
+                warn!("User {} tried to link {} ({})", user_id, key, detail);
+
+                let address = key.display();
+                self.record(key, chain, user_id, kind, Some(detail)).await?;
+                Err(match kind {
+                    AddressEventKind::Escalated => DegenScoreError::AddressEscalated { address },
+                    _ => DegenScoreError::AddressConflict { address, resolution: "rejected".to_string() },
+                })
+            }
+            None => {
+                self.record(key.clone(), chain, user_id, AddressEventKind::Linked, None).await?;
+                Ok(key)
+            }
+        }
+    }
+
+    /// Release an address owned by `user_id`; returns false if they did not own it
+    pub async fn unlink(&mut self, user_id: &str, chain: &Chain, address: &str) -> Result<bool> {
+        let key = AddressKey::normalize(chain, address)?;
+        if self.owners.get(&key).map(String::as_str) != Some(user_id) {
+            return Ok(false);
+        }
+
+        self.record(key, chain, user_id, AddressEventKind::Unlinked, None).await?;
+        Ok(true)
+    }
+
+    /// Operator override moving an address to `user_id`, e.g. to resolve an escalation.
+    /// If the previous owner is among `users`, their verified entries for the address move
+    /// to `user_id`'s profile, unless it already lists the address; returns the previous owner.
+    pub async fn reassign(
+        &mut self,
+        users: &mut HashMap<String, UserProfile>,
+        chain: &Chain,
+        address: &str,
+        user_id: &str,
+        reason: &str,
+    ) -> Result<Option<String>> {
+        let key = AddressKey::normalize(chain, address)?;
+        let previous = self.owners.get(&key).cloned();
+
+        let detail = match &previous {
+            Some(owner) => format!("from {}: {}", owner, reason),
+            None => reason.to_string(),
+        };
+        self.record(key.clone(), chain, user_id, AddressEventKind::Reassigned, Some(detail)).await?;
+
+        let matches_key = |a: &VerifiedAddress| AddressKey::normalize(&a.chain, &a.address).is_ok_and(|k| k == key);
+        let mut moved = Vec::new();
+        if let Some(profile) = previous.as_ref().filter(|owner| *owner != user_id).and_then(|owner| users.get_mut(owner)) {
+            // EVM ownership spans chains, so every chain's copy of the key goes
+            let (taken, kept) = profile.verified_addresses.drain(..).partition(&matches_key);
+            profile.verified_addresses = kept;
+            profile.updated_at = Utc::now();
+            moved = taken;
+        }
+
+        if let Some(profile) = users.get_mut(user_id) {
+            if !moved.is_empty() && !profile.verified_addresses.iter().any(&matches_key) {
+                profile.verified_addresses.extend(moved);
+                profile.updated_at = Utc::now();
+            }
+        }
+
+        Ok(previous)
+    }
+
+    /// Full audit trail, oldest first
+    pub fn events(&self) -> &[AddressEvent] {
+        &self.events
+    }
+
+    pub fn history(&self, chain: &Chain, address: &str) -> Result<Vec<&AddressEvent>> {
+        let key = AddressKey::normalize(chain, address)?;
+        Ok(self.events.iter().filter(|e| e.key == key).collect())
+    }
+
+    /// Escalated link attempts awaiting review
+    pub fn escalations(&self) -> Vec<&AddressEvent> {
+        self.events.iter().filter(|e| e.kind == AddressEventKind::Escalated).collect()
+    }
+
+    async fn record(
+        &mut self,
+        key: AddressKey,
+        chain: &Chain,
+        user_id: &str,
+        kind: AddressEventKind,
+        detail: Option<String>,
+    ) -> Result<()> {
+        let event = AddressEvent {
+            key,
+            chain: chain.clone(),
+            user_id: user_id.to_string(),
+            kind,
+            detail,
+            at: Utc::now(),
+        };
+
+        if let Some(store) = &self.store {
+            store.record_address_event(&event).await?;
+        }
+        self.apply(event);
+
+        Ok(())
+    }
+
+    fn apply(&mut self, event: AddressEvent) {
+        match event.kind {
+            AddressEventKind::Linked | AddressEventKind::Reassigned => {
+                self.owners.insert(event.key.clone(), event.user_id.clone());
+            }
+            AddressEventKind::Unlinked => {
+                self.owners.remove(&event.key);
+            }
+            AddressEventKind::Rejected | AddressEventKind::Escalated => {}
+        }
+        self.events.push(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EVM: &str = "0x742d35Cc6634C0532925a3b844Bc9e7595f6e842";
+    const SOL: &str = "7VXNe1r6nTqVw6TKyBzt1TNSSQqPqNcEYizv8TduLWpU";
+
+    fn verified(chain: Chain, address: &str) -> VerifiedAddress {
+        VerifiedAddress {
+            address: address.to_string(),
+            chain,
+            verification_method: crate::models::VerificationMethod::Signature {
+                message: "msg".to_string(),
+                signature: "sig".to_string(),
+            },
+            verified_at: Utc::now(),
+            nonce: "1".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_normalization() {
+        let checksummed = AddressKey::normalize(&Chain::Ethereum, EVM).unwrap();
+        let lower = AddressKey::normalize(&Chain::Arbitrum, &EVM.to_lowercase()).unwrap();
+        assert_eq!(checksummed, lower);
+        assert_eq!(lower.address, EVM.to_lowercase());
+        assert_eq!(lower.display(), to_checksum(&EVM.parse::<Address>().unwrap(), None));
+        assert!(AddressKey::normalize(&Chain::Ethereum, "0x742d").is_err());
+
+        let sol = AddressKey::normalize(&Chain::Solana, &format!(" {} ", SOL)).unwrap();
+        assert_eq!(sol.address, SOL);
+        assert!(AddressKey::normalize(&Chain::Solana, &SOL.to_lowercase()).is_err());
+        assert!(AddressKey::normalize(&Chain::Solana, "0OIl").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_conflicts_and_audit_trail() {
+        let mut registry = AddressRegistry::new(ConflictPolicy::Reject);
+
+        registry.link("alice", &Chain::Ethereum, EVM).await.unwrap();
+        registry.link("alice", &Chain::Optimism, &EVM.to_lowercase()).await.unwrap();
+        assert_eq!(registry.len(), 1);
+
+        // The same key on another EVM chain belongs to alice
+        let err = registry.link("bob", &Chain::Blast, &EVM.to_uppercase().replace("0X", "0x")).await.unwrap_err();
+        assert!(matches!(err, DegenScoreError::AddressConflict { .. }));
+        assert!(!err.to_string().contains("alice"));
+        assert_eq!(registry.owner(&Chain::Ethereum, EVM).unwrap(), Some("alice"));
+
+        assert!(!registry.unlink("bob", &Chain::Ethereum, EVM).await.unwrap());
+        assert!(registry.unlink("alice", &Chain::Ethereum, EVM).await.unwrap());
+        registry.link("bob", &Chain::Ethereum, EVM).await.unwrap();
+
+        let kinds: Vec<_> = registry.history(&Chain::Arbitrum, EVM).unwrap().iter().map(|e| e.kind).collect();
+        assert_eq!(kinds, vec![
+            AddressEventKind::Linked,
+            AddressEventKind::Rejected,
+            AddressEventKind::Unlinked,
+            AddressEventKind::Linked,
+        ]);
+
+        // Replaying the trail restores ownership
+        let replayed = AddressRegistry::from_events(ConflictPolicy::Reject, registry.events().to_vec());
+        assert_eq!(replayed.owner(&Chain::Ethereum, EVM).unwrap(), Some("bob"));
+    }
+
+    #[tokio::test]
+    async fn test_escalation_and_persistence() {
+        let store = ScoreStore::in_memory().await.unwrap();
+        let mut registry = AddressRegistry::load(ConflictPolicy::Escalate, store.clone(), &mut []).await.unwrap();
+
+        registry.link("alice", &Chain::Solana, SOL).await.unwrap();
+        let err = registry.link("bob", &Chain::Solana, SOL).await.unwrap_err();
+        assert!(matches!(err, DegenScoreError::AddressEscalated { .. }));
+        assert_eq!(registry.escalations().len(), 1);
+
+        registry.reassign(&mut HashMap::new(), &Chain::Solana, SOL, "bob", "support ticket").await.unwrap();
+
+        let reloaded = AddressRegistry::load(ConflictPolicy::Escalate, store, &mut []).await.unwrap();
+        assert_eq!(reloaded.owner(&Chain::Solana, SOL).unwrap(), Some("bob"));
+        assert_eq!(reloaded.events().len(), 3);
+        assert_eq!(reloaded.escalations()[0].user_id, "bob");
+    }
+
+    #[tokio::test]
+    async fn test_reassign_removes_address_from_previous_owner() {
+        let mut registry = AddressRegistry::new(ConflictPolicy::Escalate);
+        let mut users = HashMap::new();
+        let mut alice = UserProfile::new("alice".to_string());
+        for chain in [Chain::Ethereum, Chain::Arbitrum] {
+            alice.verified_addresses.push(verified(chain, EVM));
+        }
+        users.insert("alice".to_string(), alice);
+        users.insert("bob".to_string(), UserProfile::new("bob".to_string()));
+        registry.link("alice", &Chain::Ethereum, EVM).await.unwrap();
+
+        let previous = registry.reassign(&mut users, &Chain::Optimism, &EVM.to_lowercase(), "bob", "support ticket").await.unwrap();
+        assert_eq!(previous.as_deref(), Some("alice"));
+        assert!(users["alice"].verified_addresses.is_empty());
+        assert_eq!(users["bob"].verified_addresses.len(), 2);
+        assert_eq!(registry.owner(&Chain::Ethereum, EVM).unwrap(), Some("bob"));
+    }
+
+    #[tokio::test]
+    async fn test_load_drops_contested_addresses_from_the_claimant() {
+        let store = ScoreStore::in_memory().await.unwrap();
+        let mut users = Vec::new();
+        for id in ["alice", "bob"] {
+            let mut user = UserProfile::new(id.to_string());
+            user.verified_addresses.push(verified(Chain::Solana, SOL));
+            store.upsert_user(&user).await.unwrap();
+            users.push(user);
+        }
+        users[1].verified_addresses.push(verified(Chain::Ethereum, EVM));
+        store.upsert_user(&users[1]).await.unwrap();
+
+        let registry = AddressRegistry::load(ConflictPolicy::Escalate, store.clone(), &mut users).await.unwrap();
+        assert_eq!(registry.owner(&Chain::Solana, SOL).unwrap(), Some("alice"));
+        assert_eq!(registry.escalations().len(), 1);
+        assert_eq!(users[1].verified_addresses.len(), 1);
+        assert_eq!(store.get_verified_addresses("bob").await.unwrap().len(), 1);
+
+        // Reloading keeps the escalation without recording it again
+        let mut stored = store.list_users().await.unwrap();
+        let reloaded = AddressRegistry::load(ConflictPolicy::Escalate, store, &mut stored).await.unwrap();
+        assert_eq!(reloaded.events().len(), registry.events().len());
+    }
+}
@@ -3,7 +3,7 @@ use crate::{
         Result, DegenScoreError, Chain, UserProfile, VerifiedAddress, 
         VerificationMethod, AddressVerificationRequest
     },
-    verification::{EvmSignatureVerifier, SolanaSignatureVerifier, DepositVerifier},
+    verification::{EvmSignatureVerifier, SolanaSignatureVerifier, DepositVerifier, AddressKey, AddressRegistry},
     chains::{ChainClient, EvmClient, SolanaClient},
 };
 use chrono::Utc;
@@ -123,9 +123,10 @@ impl WalletVerifier {
         })
     }
     
-    /// Add a verified address to a user profile
-    pub fn add_verified_address_to_profile(
+    /// Add a verified address to a user profile, claiming it in the global registry
+    pub async fn add_verified_address_to_profile(
         &self,
+        registry: &mut AddressRegistry,
         user: &mut UserProfile,
         verified_address: VerifiedAddress,
     ) -> Result<()> {
@@ -138,12 +139,45 @@ impl WalletVerifier {
             ));
         }
         
+        // Fails if another user owns the address
+        registry.link(&user.id, &verified_address.chain, &verified_address.address).await?;
+        
         user.add_verified_address(verified_address);
         info!("Added verified address to user {}", user.id);
         
         Ok(())
     }
     
+    /// Remove an address from a user profile and release it in the registry
+    pub async fn remove_verified_address_from_profile(
+        &self,
+        registry: &mut AddressRegistry,
+        user: &mut UserProfile,
+        chain: &Chain,
+        address: &str,
+    ) -> Result<bool> {
+        let key = AddressKey::normalize(chain, address)?;
+        let before = user.verified_addresses.len();
+        user.verified_addresses.retain(|a| {
+            !(&a.chain == chain && AddressKey::normalize(&a.chain, &a.address).is_ok_and(|k| k == key))
+        });
+        if user.verified_addresses.len() == before {
+            return Ok(false);
+        }
+        user.updated_at = Utc::now();
+        
+        // EVM ownership spans chains, so keep it while another chain still uses the address
+        let still_used = user.verified_addresses.iter().any(|a| {
+            AddressKey::normalize(&a.chain, &a.address).is_ok_and(|k| k == key)
+        });
+        if !still_used {
+            registry.unlink(&user.id, chain, address).await?;
+        }
+        
+        info!("Removed {} address {} from user {}", chain.as_str(), address, user.id);
+        Ok(true)
+    }
+    
     /// Generate a new verification request
     pub fn create_verification_request(
         chain: Chain,